@@ -0,0 +1,149 @@
+//! The lint baseline: diagnostics known to exist in a project, which are
+//! not reported so only new ones are.
+//!
+//! Entries identify diagnostics by file, rule and message rather than by
+//! position, so editing a file does not invalidate them. A file reports a
+//! diagnostic only once there are more like it than the baseline counts.
+
+use ide::{Diagnostic, DiagnosticCode};
+use rustc_hash::FxHashMap;
+use serde::{Deserialize, Serialize};
+
+/// The file the baseline is read from and written to, in the root directory.
+pub(crate) const BASELINE_FILE: &str = ".cfls-baseline.json";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Baseline {
+    #[serde(default)]
+    diagnostics: Vec<BaselineEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+struct BaselineEntry {
+    /// The path of the file relative to the root directory, with `/`
+    /// separators.
+    file: String,
+    code: String,
+    message: String,
+    count: usize,
+}
+
+impl Baseline {
+    /// The baseline of the diagnostics of each file. Syntax errors are left
+    /// out, as they are always reported.
+    pub(crate) fn new<'a>(files: impl IntoIterator<Item = (String, &'a [Diagnostic])>) -> Self {
+        let mut counts: FxHashMap<(String, &str, &str), usize> = FxHashMap::default();
+        for (file, diagnostics) in files {
+            for diagnostic in diagnostics {
+                if diagnostic.code == DiagnosticCode::SYNTAX_ERROR {
+                    continue;
+                }
+                let key = (
+                    file.clone(),
+                    diagnostic.code.as_str(),
+                    diagnostic.message.as_str(),
+                );
+                *counts.entry(key).or_default() += 1;
+            }
+        }
+        let mut diagnostics: Vec<_> = counts
+            .into_iter()
+            .map(|((file, code, message), count)| BaselineEntry {
+                file,
+                code: code.to_owned(),
+                message: message.to_owned(),
+                count,
+            })
+            .collect();
+        diagnostics.sort();
+        Baseline { diagnostics }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.diagnostics.is_empty()
+    }
+
+    /// Drops the diagnostics of `file` the baseline accounts for, the first
+    /// ones in source order.
+    pub(crate) fn retain_new(&self, file: &str, diagnostics: &mut Vec<Diagnostic>) {
+        let mut counts: Vec<_> = self
+            .diagnostics
+            .iter()
+            .filter(|it| it.file == file)
+            .map(|it| (it.code.as_str(), it.message.as_str(), it.count))
+            .collect();
+        if counts.is_empty() {
+            return;
+        }
+        diagnostics.retain(|it| {
+            let count = counts
+                .iter_mut()
+                .find(|(code, message, _)| *code == it.code.as_str() && *message == it.message);
+            match count {
+                Some((_, _, count)) if *count > 0 => {
+                    *count -= 1;
+                    false
+                }
+                _ => true,
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ide::Severity;
+    use syntax::{TextRange, TextSize};
+
+    use super::*;
+
+    fn diagnostic(code: DiagnosticCode, message: &str, offset: u32) -> Diagnostic {
+        Diagnostic {
+            code,
+            message: message.to_owned(),
+            range: TextRange::empty(TextSize::from(offset)),
+            severity: Severity::Warning,
+            fixes: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn reports_only_new_diagnostics() {
+        let old = [
+            diagnostic(DiagnosticCode::MISSING_VAR, "`a` is not declared", 0),
+            diagnostic(DiagnosticCode::MISSING_VAR, "`a` is not declared", 10),
+            diagnostic(DiagnosticCode::SYNTAX_ERROR, "unclosed `<cfif>`", 20),
+        ];
+        let baseline = Baseline::new([("a.cfc".to_owned(), &old[..])]);
+        let json = serde_json::to_value(&baseline).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({ "diagnostics": [{
+                "file": "a.cfc",
+                "code": "MISSING_VAR",
+                "message": "`a` is not declared",
+                "count": 2,
+            }] })
+        );
+        let baseline: Baseline = serde_json::from_value(json).unwrap();
+
+        let mut new = vec![
+            diagnostic(DiagnosticCode::MISSING_VAR, "`a` is not declared", 5),
+            diagnostic(DiagnosticCode::MISSING_VAR, "`b` is not declared", 15),
+            diagnostic(DiagnosticCode::MISSING_VAR, "`a` is not declared", 25),
+            diagnostic(DiagnosticCode::MISSING_VAR, "`a` is not declared", 35),
+            diagnostic(DiagnosticCode::SYNTAX_ERROR, "unclosed `<cfif>`", 45),
+        ];
+        baseline.retain_new("a.cfc", &mut new);
+        let offsets: Vec<_> = new.iter().map(|it| u32::from(it.range.start())).collect();
+        assert_eq!(offsets, [15, 35, 45]);
+
+        let mut other = vec![diagnostic(
+            DiagnosticCode::MISSING_VAR,
+            "`a` is not declared",
+            0,
+        )];
+        baseline.retain_new("b.cfc", &mut other);
+        assert_eq!(other.len(), 1);
+    }
+}
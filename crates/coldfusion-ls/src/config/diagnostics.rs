@@ -0,0 +1,228 @@
+//! Settings of the lint rules: the `diagnostics.rules` configuration of the
+//! client and the `.cflintrc` file of a project, in CFLint's format.
+
+use ide::{find_rule, DiagnosticsConfig, Severity, TaintRule, RULES};
+use rustc_hash::FxHashMap;
+use serde::Deserialize;
+
+/// The setting of a rule in `diagnostics.rules`: either just its level, or
+/// its level and option values.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub(crate) enum RuleSetting {
+    Level(Level),
+    Table {
+        #[serde(default)]
+        severity: Option<Level>,
+        #[serde(flatten)]
+        options: FxHashMap<String, serde_json::Value>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Level {
+    Off,
+    Error,
+    Warning,
+    Info,
+    Hint,
+}
+
+/// A security rule of the project in `diagnostics.taint`.
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct TaintRuleSetting {
+    name: String,
+    #[serde(default)]
+    sources: Vec<String>,
+    sinks: Vec<String>,
+    #[serde(default)]
+    sanitizers: Vec<String>,
+}
+
+impl From<TaintRuleSetting> for TaintRule {
+    fn from(setting: TaintRuleSetting) -> Self {
+        TaintRule {
+            name: setting.name,
+            sources: setting.sources,
+            sinks: setting.sinks,
+            sanitizers: setting.sanitizers,
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct CflintRc {
+    includes: Vec<CflintRule>,
+    excludes: Vec<CflintRule>,
+    /// Option values keyed by `Checker.option`.
+    parameters: FxHashMap<String, serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CflintRule {
+    code: String,
+    #[serde(default)]
+    severity: Option<String>,
+}
+
+/// Parses a `.cflintrc` file. Rules it names that are not implemented are
+/// ignored, and including some rules disables the other CFLint rules.
+pub(crate) fn parse_cflintrc(text: &str) -> serde_json::Result<DiagnosticsConfig> {
+    let rc: CflintRc = serde_json::from_str(text)?;
+    let mut config = DiagnosticsConfig::default();
+    if !rc.includes.is_empty() {
+        for rule in RULES.iter().filter(|it| it.checker.is_some()) {
+            config.rules.entry(rule.code.as_str()).or_default().enabled = false;
+        }
+    }
+    for include in &rc.includes {
+        if let Some(rule) = find_rule(&include.code) {
+            let setting = config.rules.entry(rule.code.as_str()).or_default();
+            setting.enabled = true;
+            setting.severity = include.severity.as_deref().and_then(cflint_severity);
+        }
+    }
+    for exclude in &rc.excludes {
+        if let Some(rule) = find_rule(&exclude.code) {
+            config.rules.entry(rule.code.as_str()).or_default().enabled = false;
+        }
+    }
+    for (key, value) in &rc.parameters {
+        let Some((checker, option)) = key.rsplit_once('.') else {
+            continue;
+        };
+        for rule in RULES.iter().filter(|it| it.checker == Some(checker)) {
+            let Some(&(name, _)) = rule
+                .options
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(option))
+            else {
+                continue;
+            };
+            let setting = config.rules.entry(rule.code.as_str()).or_default();
+            setting.options.insert(name.to_owned(), option_value(value));
+        }
+    }
+    Ok(config)
+}
+
+/// Applies the settings of the client over `config`. Unknown rules are
+/// skipped; `Config::update` reports them.
+pub(crate) fn apply_settings(
+    config: &mut DiagnosticsConfig,
+    settings: &FxHashMap<String, RuleSetting>,
+) {
+    for (code, setting) in settings {
+        let Some(rule) = find_rule(code) else {
+            continue;
+        };
+        let rule_config = config.rules.entry(rule.code.as_str()).or_default();
+        let level = match setting {
+            RuleSetting::Level(level) => Some(*level),
+            RuleSetting::Table { severity, options } => {
+                for (name, value) in options {
+                    rule_config
+                        .options
+                        .insert(name.clone(), option_value(value));
+                }
+                *severity
+            }
+        };
+        match level {
+            Some(Level::Off) => rule_config.enabled = false,
+            Some(level) => {
+                rule_config.enabled = true;
+                rule_config.severity = Some(match level {
+                    Level::Error => Severity::Error,
+                    Level::Warning => Severity::Warning,
+                    Level::Info => Severity::Info,
+                    Level::Hint | Level::Off => Severity::Hint,
+                });
+            }
+            None => {}
+        }
+    }
+}
+
+fn cflint_severity(severity: &str) -> Option<Severity> {
+    let severity = match severity.to_ascii_uppercase().as_str() {
+        "FATAL" | "CRITICAL" | "ERROR" => Severity::Error,
+        "WARNING" | "CAUTION" => Severity::Warning,
+        "INFO" => Severity::Info,
+        "COSMETIC" => Severity::Hint,
+        _ => return None,
+    };
+    Some(severity)
+}
+
+fn option_value(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(it) => it.clone(),
+        it => it.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ide::{DiagnosticCode, RuleConfig};
+
+    use super::*;
+
+    #[test]
+    fn parses_cflintrc() {
+        let config = parse_cflintrc(
+            r#"{
+                "rule": [],
+                "includes": [
+                    { "code": "MISSING_VAR", "severity": "CAUTION" },
+                    { "code": "EXCESSIVE_FUNCTION_LENGTH" },
+                    { "code": "AVOID_USING_CFDUMP_TAG" },
+                    { "code": "NOT_PORTED" }
+                ],
+                "excludes": [{ "code": "AVOID_USING_CFDUMP_TAG" }],
+                "parameters": { "FunctionLengthChecker.length": 50 },
+                "inheritParent": false
+            }"#,
+        )
+        .unwrap();
+        let missing_var = &config.rules["MISSING_VAR"];
+        assert_eq!(
+            (missing_var.enabled, missing_var.severity),
+            (true, Some(Severity::Warning))
+        );
+        assert_eq!(
+            config.rules["EXCESSIVE_FUNCTION_LENGTH"].options["length"],
+            "50"
+        );
+        assert!(!config.is_enabled(DiagnosticCode::AVOID_USING_CFDUMP_TAG));
+        assert!(!config.is_enabled(DiagnosticCode::ARG_VAR_CONFLICT));
+        assert!(config.is_enabled(DiagnosticCode::SYNTAX_ERROR));
+        assert!(parse_cflintrc("\"rules\"").is_err());
+    }
+
+    #[test]
+    fn applies_settings_over_cflintrc() {
+        let mut config = parse_cflintrc(r#"{ "excludes": [{ "code": "MISSING_VAR" }] }"#).unwrap();
+        let settings: FxHashMap<String, RuleSetting> = serde_json::from_value(serde_json::json!({
+            "missing_var": "error",
+            "AVOID_USING_CFABORT_TAG": "off",
+            "EXCESSIVE_ARGUMENTS": { "severity": "hint", "maximum": 4 },
+        }))
+        .unwrap();
+        apply_settings(&mut config, &settings);
+        assert_eq!(
+            config.rules["MISSING_VAR"],
+            RuleConfig {
+                enabled: true,
+                severity: Some(Severity::Error),
+                options: FxHashMap::default(),
+            }
+        );
+        assert!(!config.is_enabled(DiagnosticCode::AVOID_USING_CFABORT_TAG));
+        let arguments = &config.rules["EXCESSIVE_ARGUMENTS"];
+        assert_eq!(arguments.severity, Some(Severity::Hint));
+        assert_eq!(arguments.options["maximum"], "4");
+    }
+}
@@ -0,0 +1,212 @@
+//! Settings of the formatter: the `.cfformat.json` file of a project, in
+//! the format of CommandBox's cfformat.
+
+use ide::{BlockSpacing, Casing, FormatOptions};
+use serde::Deserialize;
+
+/// The keys of `.cfformat.json` the formatter implements. cfformat has
+/// more, which are ignored.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct CfFormat {
+    indent_size: Option<usize>,
+    tab_indent: Option<bool>,
+    max_columns: Option<usize>,
+    #[serde(rename = "binary_operators.padding")]
+    binary_operators_padding: Option<bool>,
+    #[serde(rename = "keywords.spacing_to_group")]
+    keywords_spacing_to_group: Option<bool>,
+    #[serde(rename = "keywords.padding_inside_group")]
+    keywords_padding_inside_group: Option<bool>,
+    #[serde(rename = "keywords.group_to_block_spacing")]
+    keywords_group_to_block_spacing: Option<Spacing>,
+    #[serde(rename = "keywords.block_to_keyword_spacing")]
+    keywords_block_to_keyword_spacing: Option<Spacing>,
+    #[serde(rename = "function_declaration.group_to_block_spacing")]
+    function_declaration_group_to_block_spacing: Option<Spacing>,
+    #[serde(rename = "function_anonymous.group_to_block_spacing")]
+    function_anonymous_group_to_block_spacing: Option<Spacing>,
+    #[serde(rename = "function_declaration.padding")]
+    function_declaration_padding: Option<bool>,
+    #[serde(rename = "function_call.padding")]
+    function_call_padding: Option<bool>,
+    #[serde(rename = "function_call.casing.builtin")]
+    function_call_casing_builtin: Option<FunctionCasing>,
+    #[serde(rename = "function_call.casing.userdefined")]
+    function_call_casing_userdefined: Option<FunctionCasing>,
+    #[serde(rename = "parentheses.padding")]
+    parentheses_padding: Option<bool>,
+    #[serde(rename = "for_loop_semicolons.padding")]
+    for_loop_semicolons_padding: Option<bool>,
+    #[serde(rename = "struct.padding")]
+    struct_padding: Option<bool>,
+    #[serde(rename = "struct.empty_padding")]
+    struct_empty_padding: Option<bool>,
+    #[serde(rename = "struct.separator")]
+    struct_separator: Option<String>,
+    #[serde(rename = "struct.multiline.element_count")]
+    struct_multiline_element_count: Option<usize>,
+    #[serde(rename = "struct.multiline.min_length")]
+    struct_multiline_min_length: Option<usize>,
+    #[serde(rename = "array.padding")]
+    array_padding: Option<bool>,
+    #[serde(rename = "array.empty_padding")]
+    array_empty_padding: Option<bool>,
+    #[serde(rename = "array.multiline.element_count")]
+    array_multiline_element_count: Option<usize>,
+    #[serde(rename = "array.multiline.min_length")]
+    array_multiline_min_length: Option<usize>,
+    #[serde(rename = "tags.lowercase")]
+    tags_lowercase: Option<bool>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Spacing {
+    Spaced,
+    Compact,
+    Newline,
+}
+
+impl From<Spacing> for BlockSpacing {
+    fn from(spacing: Spacing) -> Self {
+        match spacing {
+            Spacing::Spaced => BlockSpacing::Spaced,
+            Spacing::Compact => BlockSpacing::Compact,
+            Spacing::Newline => BlockSpacing::Newline,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum FunctionCasing {
+    Ignore,
+    Cfdocs,
+    Camel,
+    Pascal,
+}
+
+impl From<FunctionCasing> for Casing {
+    fn from(casing: FunctionCasing) -> Self {
+        match casing {
+            FunctionCasing::Ignore => Casing::Ignore,
+            FunctionCasing::Cfdocs => Casing::Cfdocs,
+            FunctionCasing::Camel => Casing::Camel,
+            FunctionCasing::Pascal => Casing::Pascal,
+        }
+    }
+}
+
+/// Parses a `.cfformat.json` file. Settings it leaves out keep the
+/// defaults of cfformat.
+pub(crate) fn parse_cfformat(text: &str) -> serde_json::Result<FormatOptions> {
+    let rc: CfFormat = serde_json::from_str(text)?;
+    let defaults = FormatOptions::default();
+    Ok(FormatOptions {
+        indent_size: rc.indent_size.unwrap_or(defaults.indent_size),
+        tab_indent: rc.tab_indent.unwrap_or(defaults.tab_indent),
+        max_columns: rc.max_columns.unwrap_or(defaults.max_columns),
+        binary_operators_padding: rc
+            .binary_operators_padding
+            .unwrap_or(defaults.binary_operators_padding),
+        keywords_spacing_to_group: rc
+            .keywords_spacing_to_group
+            .unwrap_or(defaults.keywords_spacing_to_group),
+        keywords_padding_inside_group: rc
+            .keywords_padding_inside_group
+            .unwrap_or(defaults.keywords_padding_inside_group),
+        keywords_group_to_block_spacing: rc
+            .keywords_group_to_block_spacing
+            .map_or(defaults.keywords_group_to_block_spacing, BlockSpacing::from),
+        keywords_block_to_keyword_spacing: rc.keywords_block_to_keyword_spacing.map_or(
+            defaults.keywords_block_to_keyword_spacing,
+            BlockSpacing::from,
+        ),
+        function_declaration_group_to_block_spacing: rc
+            .function_declaration_group_to_block_spacing
+            .map_or(
+                defaults.function_declaration_group_to_block_spacing,
+                BlockSpacing::from,
+            ),
+        function_anonymous_group_to_block_spacing: rc
+            .function_anonymous_group_to_block_spacing
+            .map_or(
+                defaults.function_anonymous_group_to_block_spacing,
+                BlockSpacing::from,
+            ),
+        function_declaration_padding: rc
+            .function_declaration_padding
+            .unwrap_or(defaults.function_declaration_padding),
+        function_call_padding: rc
+            .function_call_padding
+            .unwrap_or(defaults.function_call_padding),
+        function_call_casing_builtin: rc
+            .function_call_casing_builtin
+            .map_or(defaults.function_call_casing_builtin, Casing::from),
+        function_call_casing_userdefined: rc
+            .function_call_casing_userdefined
+            .map_or(defaults.function_call_casing_userdefined, Casing::from),
+        parentheses_padding: rc
+            .parentheses_padding
+            .unwrap_or(defaults.parentheses_padding),
+        for_loop_semicolons_padding: rc
+            .for_loop_semicolons_padding
+            .unwrap_or(defaults.for_loop_semicolons_padding),
+        struct_padding: rc.struct_padding.unwrap_or(defaults.struct_padding),
+        struct_empty_padding: rc
+            .struct_empty_padding
+            .unwrap_or(defaults.struct_empty_padding),
+        struct_separator: rc.struct_separator.unwrap_or(defaults.struct_separator),
+        struct_multiline_element_count: rc
+            .struct_multiline_element_count
+            .unwrap_or(defaults.struct_multiline_element_count),
+        struct_multiline_min_length: rc
+            .struct_multiline_min_length
+            .unwrap_or(defaults.struct_multiline_min_length),
+        array_padding: rc.array_padding.unwrap_or(defaults.array_padding),
+        array_empty_padding: rc
+            .array_empty_padding
+            .unwrap_or(defaults.array_empty_padding),
+        array_multiline_element_count: rc
+            .array_multiline_element_count
+            .unwrap_or(defaults.array_multiline_element_count),
+        array_multiline_min_length: rc
+            .array_multiline_min_length
+            .unwrap_or(defaults.array_multiline_min_length),
+        tags_lowercase: rc.tags_lowercase.unwrap_or(defaults.tags_lowercase),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_cfformat() {
+        let options = parse_cfformat(
+            r#"{
+                "indent_size": 2,
+                "tab_indent": false,
+                "keywords.group_to_block_spacing": "newline",
+                "function_call.casing.userdefined": "pascal",
+                "struct.separator": " : ",
+                "struct.padding": true,
+                "comment.asterisks": "align"
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            options,
+            FormatOptions {
+                indent_size: 2,
+                keywords_group_to_block_spacing: BlockSpacing::Newline,
+                function_call_casing_userdefined: Casing::Pascal,
+                struct_separator: " : ".to_owned(),
+                struct_padding: true,
+                ..FormatOptions::default()
+            }
+        );
+        assert!(parse_cfformat(r#"{ "keywords.group_to_block_spacing": "allman" }"#).is_err());
+    }
+}
@@ -1,11 +1,22 @@
-use std::{iter, path::PathBuf};
+mod baseline;
+mod diagnostics;
+mod formatting;
+
+use std::{fmt, io, iter, path::PathBuf};
 use virtual_fs::AbsPathBuf;
 
-use serde::de::DeserializeOwned;
+use anyhow::Context;
+use rustc_hash::FxHashMap;
+use serde::de::{DeserializeOwned, Error as _};
+
+pub use self::baseline::Baseline;
+use self::diagnostics::{RuleSetting, TaintRuleSetting};
+#[allow(dead_code)]
 #[derive(Debug, Clone)]
 pub struct ManifestPath {
     file: AbsPathBuf,
 }
+#[allow(dead_code)]
 #[derive(Debug, Clone)]
 pub enum ProjectManifest {
     BoxJson(ManifestPath),
@@ -16,12 +27,38 @@ pub struct ConfigError {
     errors: Vec<(String, serde_json::Error)>,
 }
 
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let errors: Vec<_> = self
+            .errors
+            .iter()
+            .map(|(key, e)| format!("{key}: {e}"))
+            .collect();
+        write!(f, "invalid config values: {}", errors.join("; "))
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Config {
     root_path: AbsPathBuf,
     capabilities: lsp_types::ClientCapabilities,
     workspace_roots: Vec<AbsPathBuf>,
     detached_files: Vec<AbsPathBuf>,
+    workspace_symbol_search_limit: usize,
+    /// The rule settings of the project's `.cflintrc`.
+    cflintrc: ide::DiagnosticsConfig,
+    /// The rule settings of the client, by rule code.
+    diagnostics_rules: FxHashMap<String, RuleSetting>,
+    /// The project's own security rules.
+    diagnostics_taint: Vec<ide::TaintRule>,
+    /// The settings of the client applied over those of `.cflintrc`.
+    diagnostics: ide::DiagnosticsConfig,
+    baseline: Baseline,
+    /// Bumped whenever the baseline is replaced.
+    baseline_generation: u64,
+    /// The formatter settings of the project's `.cfformat.json`.
+    cfformat: ide::FormatOptions,
+    #[allow(dead_code)]
     discovered_projects: Vec<ProjectManifest>,
 }
 impl Config {
@@ -35,12 +72,20 @@ impl Config {
             capabilities,
             workspace_roots,
             detached_files: Vec::new(),
+            workspace_symbol_search_limit: 128,
+            cflintrc: ide::DiagnosticsConfig::default(),
+            diagnostics_rules: FxHashMap::default(),
+            diagnostics_taint: Vec::new(),
+            diagnostics: ide::DiagnosticsConfig::default(),
+            baseline: Baseline::default(),
+            baseline_generation: 0,
+            cfformat: ide::FormatOptions::default(),
             discovered_projects: Vec::new(),
         }
     }
 
     pub fn update(&mut self, mut json: serde_json::Value) -> Result<(), ConfigError> {
-        if json.is_null() || json.as_object().map_or(false, |it| it.is_empty()) {
+        if json.is_null() || json.as_object().is_some_and(|it| it.is_empty()) {
             return Ok(());
         }
 
@@ -50,6 +95,30 @@ impl Config {
                 .into_iter()
                 .map(AbsPathBuf::assert)
                 .collect();
+        self.workspace_symbol_search_limit = get_field(
+            &mut json,
+            &mut errors,
+            "workspaceSymbolSearchLimit",
+            None,
+            "128",
+        );
+        self.diagnostics_rules = get_field(&mut json, &mut errors, "diagnostics_rules", None, "{}");
+        for code in self.diagnostics_rules.keys() {
+            if ide::find_rule(code).is_none() {
+                let e = serde_json::Error::custom(format!("unknown rule `{code}`"));
+                errors.push((format!("/diagnostics/rules/{code}"), e));
+            }
+        }
+        let taint: Vec<TaintRuleSetting> =
+            get_field(&mut json, &mut errors, "diagnostics_taint", None, "[]");
+        self.diagnostics_taint = taint.into_iter().map(ide::TaintRule::from).collect();
+        for (i, rule) in self.diagnostics_taint.iter().enumerate() {
+            for sink in rule.invalid_sinks() {
+                let e = serde_json::Error::custom(format!("invalid sink `{sink}`"));
+                errors.push((format!("/diagnostics/taint/{i}/sinks"), e));
+            }
+        }
+        self.refresh_diagnostics();
 
         if errors.is_empty() {
             Ok(())
@@ -57,6 +126,176 @@ impl Config {
             Err(ConfigError { errors })
         }
     }
+
+    /// Reads the CFLint configuration of the project, `.cflintrc` in the
+    /// root directory, if there is one.
+    pub fn load_cflintrc(&mut self) -> anyhow::Result<()> {
+        let path = self.root_path.join(".cflintrc");
+        let text = match std::fs::read_to_string(&path) {
+            Ok(it) => it,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e).with_context(|| format!("failed to read {path}")),
+        };
+        self.cflintrc =
+            diagnostics::parse_cflintrc(&text).with_context(|| format!("invalid {path}"))?;
+        self.refresh_diagnostics();
+        Ok(())
+    }
+
+    /// Reads the lint baseline of the project, if there is one.
+    pub fn load_baseline(&mut self) -> anyhow::Result<()> {
+        let path = self.baseline_path();
+        let text = match std::fs::read_to_string(&path) {
+            Ok(it) => it,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e).with_context(|| format!("failed to read {path}")),
+        };
+        let baseline = serde_json::from_str(&text).with_context(|| format!("invalid {path}"))?;
+        self.set_baseline(baseline);
+        Ok(())
+    }
+
+    /// Reads the cfformat settings of the project, `.cfformat.json` in the
+    /// root directory, if there is one.
+    pub fn load_cfformat(&mut self) -> anyhow::Result<()> {
+        let path = self.root_path.join(".cfformat.json");
+        let text = match std::fs::read_to_string(&path) {
+            Ok(it) => it,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e).with_context(|| format!("failed to read {path}")),
+        };
+        self.cfformat =
+            formatting::parse_cfformat(&text).with_context(|| format!("invalid {path}"))?;
+        Ok(())
+    }
+
+    fn refresh_diagnostics(&mut self) {
+        self.diagnostics = self.cflintrc.clone();
+        diagnostics::apply_settings(&mut self.diagnostics, &self.diagnostics_rules);
+        self.diagnostics.taint_rules = self.diagnostics_taint.clone();
+    }
+
+    pub fn workspace_roots(&self) -> &[AbsPathBuf] {
+        &self.workspace_roots
+    }
+
+    pub fn detached_files(&self) -> &[AbsPathBuf] {
+        &self.detached_files
+    }
+
+    pub fn root_path(&self) -> &AbsPathBuf {
+        &self.root_path
+    }
+
+    pub fn baseline_path(&self) -> AbsPathBuf {
+        self.root_path.join(baseline::BASELINE_FILE)
+    }
+
+    /// The diagnostics not to report.
+    pub fn baseline(&self) -> &Baseline {
+        &self.baseline
+    }
+
+    /// Identifies the current baseline, so diagnostics computed with an
+    /// earlier one are known to be stale.
+    pub fn baseline_generation(&self) -> u64 {
+        self.baseline_generation
+    }
+
+    pub fn set_baseline(&mut self, baseline: Baseline) {
+        self.baseline = baseline;
+        self.baseline_generation += 1;
+    }
+
+    /// Whether the client can be asked to pull all diagnostics again, with
+    /// `workspace/diagnostic/refresh`.
+    pub fn diagnostics_refresh(&self) -> bool {
+        self.capabilities
+            .workspace
+            .as_ref()
+            .and_then(|it| it.diagnostic.as_ref())
+            .and_then(|it| it.refresh_support)
+            .unwrap_or(false)
+    }
+
+    /// Which lint rules are enabled, and their severities and options.
+    pub fn diagnostics(&self) -> &ide::DiagnosticsConfig {
+        &self.diagnostics
+    }
+
+    /// The maximum number of results of a `workspace/symbol` request.
+    pub fn workspace_symbol_search_limit(&self) -> usize {
+        self.workspace_symbol_search_limit
+    }
+
+    /// The formatter settings of the project's `.cfformat.json`.
+    pub fn formatting(&self) -> &ide::FormatOptions {
+        &self.cfformat
+    }
+
+    /// Whether the client can resolve the range of a workspace symbol
+    /// lazily, with `workspaceSymbol/resolve`.
+    pub fn workspace_symbol_resolve(&self) -> bool {
+        self.capabilities
+            .workspace
+            .as_ref()
+            .and_then(|it| it.symbol.as_ref())
+            .and_then(|it| it.resolve_support.as_ref())
+            .is_some_and(|it| it.properties.iter().any(|it| it == "location.range"))
+    }
+
+    /// Whether the client accepts `documentChanges` in a `WorkspaceEdit`.
+    pub fn document_changes(&self) -> bool {
+        self.workspace_edit_capabilities()
+            .and_then(|it| it.document_changes)
+            .unwrap_or(false)
+    }
+
+    /// Whether the client can rename files as part of a `WorkspaceEdit`.
+    pub fn resource_operations_rename(&self) -> bool {
+        self.document_changes()
+            && self
+                .workspace_edit_capabilities()
+                .and_then(|it| it.resource_operations.as_ref())
+                .is_some_and(|it| it.contains(&lsp_types::ResourceOperationKind::Rename))
+    }
+
+    /// Whether the client renders `DocumentSymbol` trees rather than flat
+    /// `SymbolInformation` lists.
+    pub fn hierarchical_symbols(&self) -> bool {
+        self.capabilities
+            .text_document
+            .as_ref()
+            .and_then(|it| it.document_symbol.as_ref())
+            .and_then(|it| it.hierarchical_document_symbol_support)
+            .unwrap_or(false)
+    }
+
+    pub fn line_folding_only(&self) -> bool {
+        self.capabilities
+            .text_document
+            .as_ref()
+            .and_then(|it| it.folding_range.as_ref())
+            .and_then(|it| it.line_folding_only)
+            .unwrap_or(false)
+    }
+
+    /// Whether the client pulls diagnostics with `textDocument/diagnostic`,
+    /// in which case none are pushed.
+    pub fn pull_diagnostics(&self) -> bool {
+        self.capabilities
+            .text_document
+            .as_ref()
+            .is_some_and(|it| it.diagnostic.is_some())
+    }
+
+    fn workspace_edit_capabilities(&self) -> Option<&lsp_types::WorkspaceEditClientCapabilities> {
+        self.capabilities
+            .workspace
+            .as_ref()?
+            .workspace_edit
+            .as_ref()
+    }
 }
 
 fn get_field<T: DeserializeOwned>(
@@ -170,6 +409,62 @@ mod tests {
         assert_eq!(config.detached_files.len(), 1);
     }
 
+    #[test]
+    fn test_config_update_workspace_symbol_search_limit() {
+        let mut config = Config::new(
+            AbsPathBuf::try_from("/tmp").unwrap(),
+            lsp_types::ClientCapabilities::default(),
+            vec![AbsPathBuf::try_from("/tmp").unwrap()],
+        );
+        assert_eq!(config.workspace_symbol_search_limit(), 128);
+        let json = serde_json::json!({
+            "workspaceSymbolSearchLimit": 20
+        });
+        assert!(config.update(json).is_ok());
+        assert_eq!(config.workspace_symbol_search_limit(), 20);
+    }
+
+    #[test]
+    fn test_config_update_diagnostics_rules() {
+        let mut config = Config::new(
+            AbsPathBuf::try_from("/tmp").unwrap(),
+            lsp_types::ClientCapabilities::default(),
+            vec![AbsPathBuf::try_from("/tmp").unwrap()],
+        );
+        let json = serde_json::json!({
+            "diagnostics": { "rules": { "MISSING_VAR": "off", "NO_SUCH_RULE": "error" } }
+        });
+        let e = config.update(json).unwrap_err();
+        assert_eq!(e.errors.len(), 1);
+        assert_eq!(e.errors[0].0, "/diagnostics/rules/NO_SUCH_RULE");
+        assert!(!config
+            .diagnostics()
+            .is_enabled(ide::DiagnosticCode::MISSING_VAR));
+    }
+
+    #[test]
+    fn test_config_update_diagnostics_taint() {
+        let mut config = Config::new(
+            AbsPathBuf::try_from("/tmp").unwrap(),
+            lsp_types::ClientCapabilities::default(),
+            vec![AbsPathBuf::try_from("/tmp").unwrap()],
+        );
+        let json = serde_json::json!({
+            "diagnostics": { "taint": [{
+                "name": "no-session-mail",
+                "sources": ["session"],
+                "sinks": ["cfmail.to", "sendMail"],
+            }] }
+        });
+        let e = config.update(json).unwrap_err();
+        assert_eq!(e.errors.len(), 1);
+        assert_eq!(e.errors[0].0, "/diagnostics/taint/0/sinks");
+        let rules = &config.diagnostics().taint_rules;
+        assert_eq!(rules.len(), 1);
+        assert_eq!(rules[0].sinks, ["cfmail.to", "sendMail"]);
+        assert!(rules[0].sanitizers.is_empty());
+    }
+
     #[test]
     fn test_get_field() {
         let mut json = serde_json::json!({
@@ -153,7 +153,7 @@ mod tests {
 
     #[test]
     fn test_request_dispatcher() {
-        let (sender, _) = crossbeam_channel::unbounded();
+        let (sender, _receiver) = crossbeam_channel::unbounded();
         let config = Config::new(
             AbsPathBuf::try_from("/test").unwrap(),
             lsp_types::ClientCapabilities::default(),
@@ -176,7 +176,7 @@ mod tests {
 
     #[test]
     fn test_notification_dispatcher() {
-        let (sender, _) = crossbeam_channel::unbounded();
+        let (sender, _receiver) = crossbeam_channel::unbounded();
         let config = Config::new(
             AbsPathBuf::try_from("/test").unwrap(),
             lsp_types::ClientCapabilities::default(),
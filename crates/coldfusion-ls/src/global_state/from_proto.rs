@@ -1,5 +1,11 @@
+use anyhow::format_err;
+use ide::{FilePosition, FileRange, LineColUtf16};
 use lsp_types::Url;
+use syntax::{TextRange, TextSize};
 use virtual_fs::{AbsPathBuf, VirtualFsPath};
+
+use crate::global_state::{line_index::LineIndex, GlobalStateSnapshot};
+
 pub(crate) fn abs_path(url: &Url) -> anyhow::Result<AbsPathBuf> {
     let path = url
         .to_file_path()
@@ -8,5 +14,52 @@ pub(crate) fn abs_path(url: &Url) -> anyhow::Result<AbsPathBuf> {
 }
 
 pub(crate) fn vfs_path(url: &Url) -> anyhow::Result<VirtualFsPath> {
-    abs_path(url).map(|it| VirtualFsPath::from(it))
+    abs_path(url).map(VirtualFsPath::from)
+}
+
+pub(crate) fn offset(
+    line_index: &LineIndex,
+    position: lsp_types::Position,
+) -> anyhow::Result<TextSize> {
+    let line_col = line_index.index.to_utf8(LineColUtf16 {
+        line: position.line,
+        col: position.character,
+    });
+    line_index
+        .index
+        .offset(line_col)
+        .ok_or_else(|| format_err!("Invalid offset {position:?}"))
+}
+
+pub(crate) fn file_position(
+    snap: &GlobalStateSnapshot,
+    params: lsp_types::TextDocumentPositionParams,
+) -> anyhow::Result<FilePosition> {
+    let file_id = snap.file_id(&params.text_document.uri)?;
+    let line_index = snap.file_line_index(file_id)?;
+    let offset = offset(&line_index, params.position)?;
+    Ok(FilePosition { file_id, offset })
+}
+
+pub(crate) fn text_range(
+    line_index: &LineIndex,
+    range: lsp_types::Range,
+) -> anyhow::Result<TextRange> {
+    let start = offset(line_index, range.start)?;
+    let end = offset(line_index, range.end)?;
+    if end < start {
+        return Err(format_err!("Invalid range {range:?}"));
+    }
+    Ok(TextRange::new(start, end))
+}
+
+pub(crate) fn file_range(
+    snap: &GlobalStateSnapshot,
+    text_document: &lsp_types::TextDocumentIdentifier,
+    range: lsp_types::Range,
+) -> anyhow::Result<FileRange> {
+    let file_id = snap.file_id(&text_document.uri)?;
+    let line_index = snap.file_line_index(file_id)?;
+    let range = text_range(&line_index, range)?;
+    Ok(FileRange { file_id, range })
 }
@@ -1,5 +1,15 @@
+use std::sync::Arc;
+
 use memchr::memmem;
 
+/// The line index of a file as seen by the client: offsets are computed on
+/// the normalized text, `endings` tells how to write it back.
+#[derive(Debug, Clone)]
+pub(crate) struct LineIndex {
+    pub(crate) index: Arc<ide::LineIndex>,
+    pub(crate) endings: LineEndings,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub(crate) enum LineEndings {
     Dos,
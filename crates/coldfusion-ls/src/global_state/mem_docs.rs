@@ -37,6 +37,7 @@ impl MemDocs {
         self.mem_docs.get_mut(path)
     }
 
+    #[allow(dead_code)]
     pub(crate) fn iter(&self) -> impl Iterator<Item = &VirtualFsPath> {
         self.mem_docs.keys()
     }
@@ -70,12 +71,12 @@ mod tests {
             "test".to_string(),
         )));
         let data = DocumentData::new(0, vec![]);
-        mem_docs.insert(path.clone(), data.clone());
+        mem_docs.insert(path.clone(), data.clone()).unwrap();
         assert!(mem_docs.get(&path).is_some());
-        assert_eq!(mem_docs.take_changes(), true);
-        assert_eq!(mem_docs.take_changes(), false);
-        mem_docs.remove(&path);
+        assert!(mem_docs.take_changes());
+        assert!(!mem_docs.take_changes());
+        mem_docs.remove(&path).unwrap();
         assert!(mem_docs.get(&path).is_none());
-        assert_eq!(mem_docs.take_changes(), true);
+        assert!(mem_docs.take_changes());
     }
 }
@@ -1,23 +1,29 @@
 use crossbeam_channel::Sender;
+use ide::{Analysis, AnalysisHost};
 use lsp_server::{Message, Request, Response};
 use lsp_types::Url;
 use nohash_hasher::IntMap;
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
+use rustc_hash::FxHashMap;
 use std::sync::Arc;
 use std::time::Instant;
-use virtual_fs::{FileId, VirtualFS};
+use virtual_fs::{FileId, VirtualFS, VirtualFsPath};
 
 use crate::config::Config;
-mod from_proto;
-mod line_index;
+pub(crate) mod from_proto;
+pub(crate) mod line_index;
 mod mem_docs;
+pub(crate) mod to_proto;
 
 type ReqHandler = fn(&mut GlobalState, lsp_server::Response);
 type ReqQueue = lsp_server::ReqQueue<(String, Instant), ReqHandler>;
 pub(crate) use mem_docs::DocumentData;
 pub use mem_docs::MemDocs;
 
-use self::line_index::LineEndings;
+use self::line_index::{LineEndings, LineIndex};
+
+/// Extensions of the files loaded from the workspace roots.
+const CFML_EXTENSIONS: &[&str] = &["cfc", "cfm", "cfml"];
 
 pub struct GlobalState {
     sender: Sender<Message>,
@@ -26,11 +32,21 @@ pub struct GlobalState {
     pub shutdown_requested: bool,
     mem_docs: MemDocs,
     vfs: Arc<RwLock<(VirtualFS, IntMap<FileId, LineEndings>)>>,
+    analysis_host: AnalysisHost,
+    /// The last semantic tokens sent for each document, the base of
+    /// `full/delta` responses.
+    pub(crate) semantic_tokens_cache: Arc<Mutex<FxHashMap<Url, lsp_types::SemanticTokens>>>,
+    /// Files with published diagnostics, and the diagnostics revision they
+    /// were computed in. They are republished when that revision changes,
+    /// even after the file was closed.
+    diagnostics_published: FxHashMap<FileId, u64>,
 }
 
 pub(crate) struct GlobalStateSnapshot {
     pub(crate) config: Arc<Config>,
+    pub(crate) analysis: Analysis,
     pub(crate) mem_docs: MemDocs,
+    pub(crate) semantic_tokens_cache: Arc<Mutex<FxHashMap<Url, lsp_types::SemanticTokens>>>,
     vfs: Arc<RwLock<(VirtualFS, IntMap<FileId, LineEndings>)>>,
 }
 
@@ -38,6 +54,12 @@ impl std::panic::UnwindSafe for GlobalStateSnapshot {}
 
 impl GlobalState {
     pub fn new(sender: Sender<Message>, config: Config) -> Self {
+        let mut analysis_host = AnalysisHost::new();
+        let mut change = ide::Change::new();
+        let roots = config.workspace_roots().iter().cloned();
+        change.set_roots(roots.map(VirtualFsPath::from).collect());
+        analysis_host.apply_change(change);
+
         GlobalState {
             sender,
             config: Arc::new(config.clone()),
@@ -45,9 +67,137 @@ impl GlobalState {
             shutdown_requested: false,
             mem_docs: MemDocs::default(),
             vfs: Arc::new(RwLock::new((VirtualFS::default(), IntMap::default()))),
+            analysis_host,
+            semantic_tokens_cache: Arc::default(),
+            diagnostics_published: FxHashMap::default(),
+        }
+    }
+
+    /// Reads every CFML file under the workspace roots into the vfs. Files
+    /// open in the editor keep their in-memory contents.
+    pub(crate) fn load_workspace(&mut self) {
+        let mut files = Vec::new();
+        for root in self.config.workspace_roots() {
+            collect_cfml_files(root.as_ref(), &mut files);
+        }
+        files.extend(self.config.detached_files().iter().cloned());
+        let vfs = &mut self.vfs.write().0;
+        for path in files {
+            let path = VirtualFsPath::from(path);
+            if self.mem_docs.contains(&path) {
+                continue;
+            }
+            let contents = std::fs::read(path.as_path().unwrap()).ok();
+            vfs.set_file_contents(path, contents);
+        }
+    }
+
+    /// Indexes the workspace for symbol search in the background, so the
+    /// first `workspace/symbol` request does not have to.
+    pub(crate) fn prime_caches(&self) {
+        let analysis = self.analysis_host.analysis();
+        std::thread::spawn(move || analysis.prime_symbol_index());
+    }
+
+    /// Moves the changes accumulated in the vfs into the analysis. Returns
+    /// whether anything changed.
+    pub(crate) fn process_changes(&mut self) -> bool {
+        let mut change = ide::Change::new();
+        let mut changed = Vec::new();
+        {
+            let (vfs, line_endings) = &mut *self.vfs.write();
+            let changed_files = vfs.take_changes();
+            let mem_docs_changed = self.mem_docs.take_changes();
+            if changed_files.is_empty() {
+                return mem_docs_changed;
+            }
+            for file in changed_files {
+                changed.push(file.file_id);
+                let path = vfs.file_path(file.file_id).clone();
+                let text = match file.change {
+                    virtual_fs::Change::Create(bytes) | virtual_fs::Change::Modify(bytes) => {
+                        String::from_utf8(bytes).ok().map(|text| {
+                            let (text, endings) = LineEndings::normalize(text);
+                            line_endings.insert(file.file_id, endings);
+                            Arc::from(text)
+                        })
+                    }
+                    virtual_fs::Change::Delete => None,
+                };
+                if text.is_none() {
+                    line_endings.remove(&file.file_id);
+                }
+                change.change_file(file.file_id, path, text);
+            }
+        }
+        self.analysis_host.apply_change(change);
+        self.publish_diagnostics(&changed, false);
+        true
+    }
+
+    /// Makes the client show the diagnostics of every file again, after a
+    /// change to which are reported rather than to the files.
+    pub(crate) fn refresh_diagnostics(&mut self) {
+        if !self.config.pull_diagnostics() {
+            self.publish_diagnostics(&[], true);
+        } else if self.config.diagnostics_refresh() {
+            self.send_request::<lsp_types::request::WorkspaceDiagnosticRefresh>((), |_, _| ());
+        }
+    }
+
+    /// Publishes the diagnostics of those of `files` that are open or had
+    /// diagnostics published before, and of any file with published
+    /// diagnostics whose dependencies changed. With `force`, diagnostics are
+    /// republished even if their dependencies did not change.
+    fn publish_diagnostics(&mut self, files: &[FileId], force: bool) {
+        if self.config.pull_diagnostics() {
+            return;
+        }
+        let snap = self.snapshot();
+        let mut files = files.to_vec();
+        files.extend(self.diagnostics_published.keys().copied());
+        files.sort_unstable_by_key(|it| it.0);
+        files.dedup();
+        for file_id in files {
+            let path = snap.vfs.read().0.file_path(file_id).clone();
+            let version = self.mem_docs.get(&path).map(|it| it.version);
+            let published = self.diagnostics_published.get(&file_id).copied();
+            if version.is_none() && published.is_none() {
+                continue;
+            }
+            let revision = snap.analysis.diagnostics_revision(file_id);
+            if !force && revision.is_some() && revision == published {
+                continue;
+            }
+            let Some(url) = path.as_path().and_then(|it| Url::from_file_path(it).ok()) else {
+                continue;
+            };
+            let diagnostics = snap.file_diagnostics(file_id);
+            match revision {
+                Some(revision) => self.diagnostics_published.insert(file_id, revision),
+                None => self.diagnostics_published.remove(&file_id),
+            };
+            self.send_notification::<lsp_types::notification::PublishDiagnostics>(
+                lsp_types::PublishDiagnosticsParams::new(url, diagnostics, version),
+            );
         }
     }
 
+    /// Clears the diagnostics of a document closed in the editor, unless it
+    /// belongs to the workspace and so stays checked.
+    pub(crate) fn clear_diagnostics_if_detached(&mut self, uri: &Url) {
+        let path = from_proto::vfs_path(uri).unwrap();
+        if self.config.pull_diagnostics() || is_workspace_file(&self.config, &path) {
+            return;
+        }
+        if let Some(file_id) = self.vfs.read().0.file_id(&path) {
+            self.diagnostics_published.remove(&file_id);
+        }
+        self.send_notification::<lsp_types::notification::PublishDiagnostics>(
+            lsp_types::PublishDiagnosticsParams::new(uri.clone(), Vec::new(), None),
+        );
+    }
+
     pub fn register_request(&mut self, request: &Request, request_received: Instant) {
         self.req_queue.incoming.register(
             request.id.clone(),
@@ -58,7 +208,9 @@ impl GlobalState {
     pub(crate) fn snapshot(&self) -> GlobalStateSnapshot {
         GlobalStateSnapshot {
             config: Arc::clone(&self.config),
+            analysis: self.analysis_host.analysis(),
             mem_docs: self.mem_docs.clone(),
+            semantic_tokens_cache: Arc::clone(&self.semantic_tokens_cache),
             vfs: Arc::clone(&self.vfs),
         }
     }
@@ -80,6 +232,40 @@ impl GlobalState {
         self.sender.send(message).unwrap()
     }
 
+    /// Streams part of the result of a request to the client, under the
+    /// `partialResultToken` of the request.
+    pub(crate) fn send_partial_result(
+        &self,
+        token: lsp_types::ProgressToken,
+        value: impl serde::Serialize,
+    ) {
+        use lsp_types::notification::{Notification, Progress};
+
+        let params = serde_json::json!({ "token": token, "value": value });
+        let notification = lsp_server::Notification::new(Progress::METHOD.to_owned(), params);
+        self.send(notification.into());
+    }
+
+    pub(crate) fn send_request<R: lsp_types::request::Request>(
+        &mut self,
+        params: R::Params,
+        handler: ReqHandler,
+    ) {
+        let request = self
+            .req_queue
+            .outgoing
+            .register(R::METHOD.to_owned(), params, handler);
+        self.send(request.into());
+    }
+
+    pub(crate) fn send_notification<N: lsp_types::notification::Notification>(
+        &self,
+        params: N::Params,
+    ) {
+        let notification = lsp_server::Notification::new(N::METHOD.to_owned(), params);
+        self.send(notification.into());
+    }
+
     pub fn cancel(&mut self, request_id: lsp_server::RequestId) {
         if let Some(response) = self.req_queue.incoming.cancel(request_id) {
             self.send(response.into());
@@ -129,4 +315,138 @@ impl GlobalState {
             .0
             .set_file_contents(path, Some(text.into_bytes()));
     }
+
+    /// Replaces the text of an open document, bumping its version.
+    pub(crate) fn update_document(&mut self, uri: &Url, text: String, version: i32) {
+        let path = from_proto::vfs_path(uri).unwrap();
+        if let Some(doc) = self.mem_docs.get_mut(&path) {
+            doc.version = version;
+            doc.data = text.clone().into_bytes();
+        }
+        self.add_changes_into_document(uri, text);
+    }
+
+    /// Makes the vfs reflect the file on disk again after its document was
+    /// closed, dropping it if it does not exist there.
+    pub(crate) fn reload_from_disk(&mut self, uri: &Url) {
+        let path = from_proto::vfs_path(uri).unwrap();
+        let contents = path.as_path().and_then(|it| std::fs::read(it).ok());
+        self.vfs.write().0.set_file_contents(path, contents);
+    }
+}
+
+impl GlobalStateSnapshot {
+    pub(crate) fn file_id(&self, url: &Url) -> anyhow::Result<FileId> {
+        let path = from_proto::vfs_path(url)?;
+        let vfs = &self.vfs.read().0;
+        vfs.file_id(&path)
+            .ok_or_else(|| anyhow::format_err!("file not found: {path}"))
+    }
+
+    pub(crate) fn file_id_to_url(&self, file_id: FileId) -> Url {
+        let vfs = &self.vfs.read().0;
+        let path = vfs.file_path(file_id);
+        Url::from_file_path(path.as_path().unwrap()).unwrap()
+    }
+
+    pub(crate) fn file_line_index(&self, file_id: FileId) -> anyhow::Result<LineIndex> {
+        let index = self.analysis.file_line_index(file_id);
+        let endings = self.vfs.read().1.get(&file_id).copied();
+        let endings = endings.ok_or_else(|| anyhow::format_err!("file not loaded: {file_id:?}"))?;
+        Ok(LineIndex { index, endings })
+    }
+
+    pub(crate) fn file_diagnostics(&self, file_id: FileId) -> Vec<lsp_types::Diagnostic> {
+        // Deleted files have no line index, and no diagnostics either.
+        let Ok(line_index) = self.file_line_index(file_id) else {
+            return Vec::new();
+        };
+        self.new_diagnostics(file_id)
+            .into_iter()
+            .map(|it| to_proto::diagnostic(&line_index, it))
+            .collect()
+    }
+
+    /// The diagnostics of a file that are not recorded in the baseline.
+    pub(crate) fn new_diagnostics(&self, file_id: FileId) -> Vec<ide::Diagnostic> {
+        let mut diagnostics = self
+            .analysis
+            .diagnostics(self.config.diagnostics(), file_id);
+        let baseline = self.config.baseline();
+        if let Some(path) = self.baseline_path(file_id).filter(|_| !baseline.is_empty()) {
+            baseline.retain_new(&path, &mut diagnostics);
+        }
+        diagnostics
+    }
+
+    /// The path of a file as the baseline records it: relative to the root
+    /// directory, with `/` separators. `None` for files outside of it.
+    pub(crate) fn baseline_path(&self, file_id: FileId) -> Option<String> {
+        let vfs = &self.vfs.read().0;
+        let path = vfs.file_path(file_id).as_path()?;
+        let relative = path.strip_prefix(self.config.root_path())?;
+        let components: Vec<_> = AsRef::<std::path::Path>::as_ref(relative)
+            .components()
+            .map(|it| it.as_os_str().to_string_lossy())
+            .collect();
+        Some(components.join("/"))
+    }
+
+    /// The files under the workspace roots, by id.
+    pub(crate) fn workspace_files(&self) -> Vec<(FileId, Url)> {
+        let vfs = &self.vfs.read().0;
+        vfs.iter()
+            .filter(|(_, path)| is_workspace_file(&self.config, path))
+            .filter_map(|(file_id, path)| {
+                let url = Url::from_file_path(path.as_path()?).ok()?;
+                Some((file_id, url))
+            })
+            .collect()
+    }
+
+    pub(crate) fn url_file_version(&self, url: &Url) -> Option<i32> {
+        let path = from_proto::vfs_path(url).ok()?;
+        Some(self.mem_docs.get(&path)?.version)
+    }
+}
+
+fn is_workspace_file(config: &Config, path: &VirtualFsPath) -> bool {
+    path.as_path().is_some_and(|path| {
+        config
+            .workspace_roots()
+            .iter()
+            .any(|root| path.starts_with(root))
+    })
+}
+
+fn collect_cfml_files(dir: &std::path::Path, acc: &mut Vec<virtual_fs::AbsPathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let hidden = path
+            .file_name()
+            .and_then(|it| it.to_str())
+            .is_some_and(|it| it.starts_with('.'));
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_dir() && !hidden {
+            collect_cfml_files(&path, acc);
+        } else if file_type.is_file() && is_cfml_file(&path) {
+            if let Ok(path) = virtual_fs::AbsPathBuf::try_from(path) {
+                acc.push(path);
+            }
+        }
+    }
+}
+
+fn is_cfml_file(path: &std::path::Path) -> bool {
+    let extension = path.extension().and_then(|it| it.to_str());
+    extension.is_some_and(|ext| {
+        CFML_EXTENSIONS
+            .iter()
+            .any(|it| it.eq_ignore_ascii_case(ext))
+    })
 }
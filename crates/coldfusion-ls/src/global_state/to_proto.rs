@@ -0,0 +1,371 @@
+use ide::{FileSystemEdit, Fold, FoldKind, HlMod, HlRange, HlTag, Indel, SourceChange, TextEdit};
+use lsp_types::Url;
+use syntax::{TextRange, TextSize};
+use virtual_fs::{AnchoredPathBuf, FileId};
+
+use crate::{
+    global_state::{
+        line_index::{LineEndings, LineIndex},
+        GlobalStateSnapshot,
+    },
+    lsp::{semantic_tokens, LspError},
+};
+
+pub(crate) fn position(line_index: &LineIndex, offset: TextSize) -> lsp_types::Position {
+    let line_col = line_index.index.to_utf16(line_index.index.line_col(offset));
+    lsp_types::Position::new(line_col.line, line_col.col)
+}
+
+pub(crate) fn range(line_index: &LineIndex, range: TextRange) -> lsp_types::Range {
+    lsp_types::Range::new(
+        position(line_index, range.start()),
+        position(line_index, range.end()),
+    )
+}
+
+pub(crate) fn symbol_kind(kind: ide::SymbolKind) -> lsp_types::SymbolKind {
+    match kind {
+        ide::SymbolKind::Component => lsp_types::SymbolKind::CLASS,
+        ide::SymbolKind::Interface => lsp_types::SymbolKind::INTERFACE,
+        ide::SymbolKind::Property => lsp_types::SymbolKind::PROPERTY,
+        ide::SymbolKind::Method => lsp_types::SymbolKind::METHOD,
+        ide::SymbolKind::Function | ide::SymbolKind::Closure => lsp_types::SymbolKind::FUNCTION,
+        ide::SymbolKind::Query | ide::SymbolKind::Variable => lsp_types::SymbolKind::VARIABLE,
+        ide::SymbolKind::Output => lsp_types::SymbolKind::NAMESPACE,
+    }
+}
+
+pub(crate) fn document_symbol(
+    line_index: &LineIndex,
+    symbol: ide::DocumentSymbol,
+) -> lsp_types::DocumentSymbol {
+    let children = symbol
+        .children
+        .into_iter()
+        .map(|it| document_symbol(line_index, it))
+        .collect();
+    #[allow(deprecated)]
+    lsp_types::DocumentSymbol {
+        name: symbol.name,
+        detail: symbol.detail,
+        kind: symbol_kind(symbol.kind),
+        tags: None,
+        deprecated: None,
+        range: range(line_index, symbol.range),
+        selection_range: range(line_index, symbol.selection_range),
+        children: Some(children),
+    }
+}
+
+pub(crate) fn diagnostic(
+    line_index: &LineIndex,
+    diagnostic: ide::Diagnostic,
+) -> lsp_types::Diagnostic {
+    let severity = match diagnostic.severity {
+        ide::Severity::Error => lsp_types::DiagnosticSeverity::ERROR,
+        ide::Severity::Warning => lsp_types::DiagnosticSeverity::WARNING,
+        ide::Severity::Info => lsp_types::DiagnosticSeverity::INFORMATION,
+        ide::Severity::Hint => lsp_types::DiagnosticSeverity::HINT,
+    };
+    lsp_types::Diagnostic {
+        range: range(line_index, diagnostic.range),
+        severity: Some(severity),
+        code: Some(lsp_types::NumberOrString::String(
+            diagnostic.code.as_str().to_owned(),
+        )),
+        source: Some("cfml".to_owned()),
+        message: diagnostic.message,
+        ..Default::default()
+    }
+}
+
+pub(crate) fn code_action_kind(kind: ide::AssistKind) -> lsp_types::CodeActionKind {
+    match kind {
+        ide::AssistKind::QuickFix => lsp_types::CodeActionKind::QUICKFIX,
+        ide::AssistKind::Generate => lsp_types::CodeActionKind::REFACTOR,
+        ide::AssistKind::RefactorExtract => lsp_types::CodeActionKind::REFACTOR_EXTRACT,
+        ide::AssistKind::RefactorRewrite => lsp_types::CodeActionKind::REFACTOR_REWRITE,
+    }
+}
+
+pub(crate) fn document_highlight_kind(
+    access: Option<ide::Access>,
+) -> lsp_types::DocumentHighlightKind {
+    match access {
+        Some(ide::Access::Read) => lsp_types::DocumentHighlightKind::READ,
+        Some(ide::Access::Write) => lsp_types::DocumentHighlightKind::WRITE,
+        None => lsp_types::DocumentHighlightKind::TEXT,
+    }
+}
+
+pub(crate) fn folding_range(
+    line_index: &LineIndex,
+    line_folding_only: bool,
+    fold: Fold,
+) -> lsp_types::FoldingRange {
+    let kind = match fold.kind {
+        FoldKind::Comment => Some(lsp_types::FoldingRangeKind::Comment),
+        FoldKind::Region => Some(lsp_types::FoldingRangeKind::Region),
+        FoldKind::Tag | FoldKind::Block => None,
+    };
+    let range = range(line_index, fold.range);
+    let (start_character, end_character) = match line_folding_only {
+        true => (None, None),
+        false => (Some(range.start.character), Some(range.end.character)),
+    };
+    lsp_types::FoldingRange {
+        start_line: range.start.line,
+        start_character,
+        end_line: range.end.line,
+        end_character,
+        kind,
+        collapsed_text: None,
+    }
+}
+
+pub(crate) fn text_edit(line_index: &LineIndex, indel: Indel) -> lsp_types::TextEdit {
+    let range = range(line_index, indel.delete);
+    let new_text = match line_index.endings {
+        LineEndings::Unix => indel.insert,
+        LineEndings::Dos => indel.insert.replace('\n', "\r\n"),
+    };
+    lsp_types::TextEdit { range, new_text }
+}
+
+pub(crate) fn text_edit_vec(
+    line_index: &LineIndex,
+    text_edit: TextEdit,
+) -> Vec<lsp_types::TextEdit> {
+    text_edit
+        .into_iter()
+        .map(|indel| self::text_edit(line_index, indel))
+        .collect()
+}
+
+pub(crate) fn url(snap: &GlobalStateSnapshot, file_id: FileId) -> Url {
+    snap.file_id_to_url(file_id)
+}
+
+/// The url of `path`, resolved against the directory of its anchor.
+pub(crate) fn anchored_path_url(snap: &GlobalStateSnapshot, path: &AnchoredPathBuf) -> Url {
+    let anchor = url(snap, path.anchor);
+    anchor.join(&path.path).unwrap()
+}
+
+pub(crate) fn text_document_edit(
+    snap: &GlobalStateSnapshot,
+    file_id: FileId,
+    edit: TextEdit,
+) -> anyhow::Result<lsp_types::TextDocumentEdit> {
+    let text_document = optional_versioned_text_document_identifier(snap, file_id);
+    let line_index = snap.file_line_index(file_id)?;
+    let edits = text_edit_vec(&line_index, edit)
+        .into_iter()
+        .map(lsp_types::OneOf::Left)
+        .collect();
+    Ok(lsp_types::TextDocumentEdit {
+        text_document,
+        edits,
+    })
+}
+
+fn optional_versioned_text_document_identifier(
+    snap: &GlobalStateSnapshot,
+    file_id: FileId,
+) -> lsp_types::OptionalVersionedTextDocumentIdentifier {
+    let url = url(snap, file_id);
+    let version = snap.url_file_version(&url);
+    lsp_types::OptionalVersionedTextDocumentIdentifier { uri: url, version }
+}
+
+fn resource_op(
+    snap: &GlobalStateSnapshot,
+    edit: FileSystemEdit,
+) -> lsp_types::DocumentChangeOperation {
+    let op = match edit {
+        FileSystemEdit::MoveFile { src, dst } => {
+            lsp_types::ResourceOp::Rename(lsp_types::RenameFile {
+                old_uri: url(snap, src),
+                new_uri: anchored_path_url(snap, &dst),
+                options: None,
+                annotation_id: None,
+            })
+        }
+    };
+    lsp_types::DocumentChangeOperation::Op(op)
+}
+
+pub(crate) fn workspace_edit(
+    snap: &GlobalStateSnapshot,
+    source_change: SourceChange,
+) -> anyhow::Result<lsp_types::WorkspaceEdit> {
+    let SourceChange {
+        source_file_edits,
+        file_system_edits,
+    } = source_change;
+
+    if !file_system_edits.is_empty() && !snap.config.resource_operations_rename() {
+        let message = "This rename moves a file, which the client does not support".to_owned();
+        return Err(LspError::new(lsp_server::ErrorCode::RequestFailed as i32, message).into());
+    }
+
+    let mut source_file_edits: Vec<_> = source_file_edits.into_iter().collect();
+    source_file_edits.sort_by_key(|(file_id, _)| *file_id);
+
+    if !snap.config.document_changes() {
+        let mut changes = std::collections::HashMap::new();
+        for (file_id, edit) in source_file_edits {
+            let line_index = snap.file_line_index(file_id)?;
+            changes.insert(url(snap, file_id), text_edit_vec(&line_index, edit));
+        }
+        return Ok(lsp_types::WorkspaceEdit {
+            changes: Some(changes),
+            document_changes: None,
+            change_annotations: None,
+        });
+    }
+
+    // Text edits come first: they address files by their old names.
+    let mut document_changes = Vec::new();
+    for (file_id, edit) in source_file_edits {
+        let edit = text_document_edit(snap, file_id, edit)?;
+        document_changes.push(lsp_types::DocumentChangeOperation::Edit(edit));
+    }
+    for edit in file_system_edits {
+        document_changes.push(resource_op(snap, edit));
+    }
+    Ok(lsp_types::WorkspaceEdit {
+        changes: None,
+        document_changes: Some(lsp_types::DocumentChanges::Operations(document_changes)),
+        change_annotations: None,
+    })
+}
+
+pub(crate) fn semantic_tokens(
+    text: &str,
+    line_index: &LineIndex,
+    highlights: Vec<HlRange>,
+) -> lsp_types::SemanticTokens {
+    let mut builder = semantic_tokens::SemanticTokensBuilder::new();
+    for HlRange { range, highlight } in highlights {
+        let token_type = semantic_tokens::type_index(&semantic_token_type(highlight.tag));
+        let modifiers: Vec<_> = highlight.mods.iter().map(semantic_token_modifier).collect();
+        let modifiers = semantic_tokens::modifier_set(&modifiers);
+        // Tokens may not span lines: split comments and SQL at line breaks.
+        let mut start = range.start();
+        for line in text[range].split_inclusive('\n') {
+            let len = TextSize::of(line.trim_end_matches(['\r', '\n']));
+            if len > TextSize::from(0) {
+                let range = self::range(line_index, TextRange::at(start, len));
+                builder.push(range, token_type, modifiers);
+            }
+            start += TextSize::of(line);
+        }
+    }
+    builder.build()
+}
+
+fn semantic_token_type(tag: HlTag) -> lsp_types::SemanticTokenType {
+    use lsp_types::SemanticTokenType;
+    match tag {
+        HlTag::Tag => semantic_tokens::TAG,
+        HlTag::Attribute => semantic_tokens::ATTRIBUTE,
+        HlTag::Scope => semantic_tokens::SCOPE,
+        HlTag::Keyword => SemanticTokenType::KEYWORD,
+        HlTag::Operator => SemanticTokenType::OPERATOR,
+        HlTag::Comment => SemanticTokenType::COMMENT,
+        HlTag::String => SemanticTokenType::STRING,
+        HlTag::Number => SemanticTokenType::NUMBER,
+        HlTag::Function => SemanticTokenType::FUNCTION,
+        HlTag::Method => SemanticTokenType::METHOD,
+        HlTag::Component => SemanticTokenType::CLASS,
+        HlTag::Type => SemanticTokenType::TYPE,
+        HlTag::Parameter => SemanticTokenType::PARAMETER,
+        HlTag::Variable => SemanticTokenType::VARIABLE,
+        HlTag::Property => SemanticTokenType::PROPERTY,
+    }
+}
+
+fn semantic_token_modifier(modifier: HlMod) -> lsp_types::SemanticTokenModifier {
+    use lsp_types::SemanticTokenModifier;
+    match modifier {
+        HlMod::Declaration => SemanticTokenModifier::DECLARATION,
+        HlMod::DefaultLibrary => SemanticTokenModifier::DEFAULT_LIBRARY,
+        HlMod::Deprecated => SemanticTokenModifier::DEPRECATED,
+        HlMod::Interpolated => semantic_tokens::INTERPOLATED,
+        HlMod::Sql => semantic_tokens::SQL,
+    }
+}
+
+pub(crate) fn semantic_token_delta(
+    previous: &lsp_types::SemanticTokens,
+    current: &lsp_types::SemanticTokens,
+) -> lsp_types::SemanticTokensDelta {
+    lsp_types::SemanticTokensDelta {
+        result_id: current.result_id.clone(),
+        edits: semantic_tokens::diff_tokens(&previous.data, &current.data),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+
+    #[test]
+    fn text_edit_restores_dos_line_endings() {
+        let line_index = LineIndex {
+            index: Arc::new(ide::LineIndex::new("a\nb\n")),
+            endings: LineEndings::Dos,
+        };
+        let indel = Indel::insert(TextSize::from(2), "x\ny\n".to_owned());
+        let edit = text_edit(&line_index, indel);
+        assert_eq!(
+            edit.range,
+            lsp_types::Range::new(
+                lsp_types::Position::new(1, 0),
+                lsp_types::Position::new(1, 0)
+            )
+        );
+        assert_eq!(edit.new_text, "x\r\ny\r\n");
+    }
+
+    #[test]
+    fn folding_range_honours_line_folding_only() {
+        let line_index = LineIndex {
+            index: Arc::new(ide::LineIndex::new("<cfif a>\n  x\n</cfif>")),
+            endings: LineEndings::Unix,
+        };
+        let fold = Fold {
+            range: TextRange::new(8.into(), 12.into()),
+            kind: FoldKind::Tag,
+        };
+        let range = folding_range(&line_index, false, fold);
+        assert_eq!((range.start_line, range.start_character), (0, Some(8)));
+        assert_eq!((range.end_line, range.end_character), (1, Some(3)));
+        let range = folding_range(&line_index, true, fold);
+        assert_eq!((range.start_line, range.end_line), (0, 1));
+        assert_eq!((range.start_character, range.end_character), (None, None));
+    }
+
+    #[test]
+    fn semantic_tokens_split_at_line_breaks() {
+        let text = "a <!--- x\n  y --->\nb";
+        let line_index = LineIndex {
+            index: Arc::new(ide::LineIndex::new(text)),
+            endings: LineEndings::Unix,
+        };
+        let comment = ide::HlRange {
+            range: TextRange::new(2.into(), 18.into()),
+            highlight: HlTag::Comment.into(),
+        };
+        let tokens = semantic_tokens(text, &line_index, vec![comment]);
+        let tokens: Vec<_> = tokens
+            .data
+            .iter()
+            .map(|it| (it.delta_line, it.delta_start, it.length))
+            .collect();
+        assert_eq!(tokens, vec![(0, 2, 7), (1, 0, 8)]);
+    }
+}
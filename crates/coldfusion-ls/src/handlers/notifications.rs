@@ -1,6 +1,7 @@
+use ide::{LineColUtf16, LineIndex};
 use lsp_types::{
     CancelParams, DidChangeTextDocumentParams, DidCloseTextDocumentParams,
-    DidOpenTextDocumentParams,
+    DidOpenTextDocumentParams, TextDocumentContentChangeEvent,
 };
 
 use crate::global_state::GlobalState;
@@ -43,6 +44,12 @@ pub(crate) fn handle_did_close_text_document(
     if state.remove_document(&text_document.uri).is_err() {
         tracing::error!("didClose notification for non-existing file: {:?}", text_document.uri);
     }
+    state.clear_diagnostics_if_detached(&text_document.uri);
+    state.reload_from_disk(&text_document.uri);
+    state
+        .semantic_tokens_cache
+        .lock()
+        .remove(&text_document.uri);
 
     Ok(())
 }
@@ -55,11 +62,122 @@ pub(crate) fn handle_did_change_text_document(
     let text_document = params.text_document;
     let content_changes = params.content_changes;
     let uri = text_document.uri;
-    let DocumentData { data, version } = if let Some(doc) = state.get_document(&uri) {
+    let DocumentData { data, .. } = if let Some(doc) = state.get_document(&uri) {
         doc
     } else {
         tracing::error!("didChange notification for non-existing file: {:?}", uri);
         return Ok(());
     };
+
+    let mut text = String::from_utf8_lossy(data).into_owned();
+    apply_document_changes(&mut text, content_changes);
+    state.update_document(&uri, text, text_document.version);
     Ok(())
 }
+
+/// Applies `content_changes` in order. Ranged changes are resolved against
+/// the text as left by the previous change.
+fn apply_document_changes(text: &mut String, content_changes: Vec<TextDocumentContentChangeEvent>) {
+    for change in content_changes {
+        let Some(range) = change.range else {
+            *text = change.text;
+            continue;
+        };
+        let line_index = LineIndex::new(text);
+        let offset = |position: lsp_types::Position| {
+            let line_col = line_index.to_utf8(LineColUtf16 {
+                line: position.line,
+                col: position.character,
+            });
+            line_index.offset(line_col).unwrap_or(line_index.len())
+        };
+        let start = usize::from(offset(range.start));
+        let end = usize::from(offset(range.end)).max(start);
+        text.replace_range(start..end, &change.text);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_server::Message;
+    use lsp_types::{
+        notification::{Notification, PublishDiagnostics},
+        PublishDiagnosticsParams, TextDocumentIdentifier, TextDocumentItem, Url,
+        VersionedTextDocumentIdentifier,
+    };
+    use virtual_fs::AbsPathBuf;
+
+    use super::*;
+    use crate::config::Config;
+
+    fn published(receiver: &crossbeam_channel::Receiver<Message>) -> PublishDiagnosticsParams {
+        match receiver.try_recv() {
+            Ok(Message::Notification(it)) if it.method == PublishDiagnostics::METHOD => {
+                serde_json::from_value(it.params).unwrap()
+            }
+            other => panic!("expected diagnostics, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn publishes_syntax_errors_until_closed() {
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        let root_path = AbsPathBuf::try_from("/tmp/ws").unwrap();
+        let capabilities = lsp_types::ClientCapabilities::default();
+        let workspace_roots = vec![root_path.clone()];
+        let config = Config::new(root_path, capabilities, workspace_roots);
+        let mut state = GlobalState::new(sender, config);
+
+        let uri = Url::parse("file:///tmp/detached.cfm").unwrap();
+        let params = DidOpenTextDocumentParams {
+            text_document: TextDocumentItem::new(
+                uri.clone(),
+                "cfml".to_owned(),
+                1,
+                "ok".to_owned(),
+            ),
+        };
+        handle_did_open_text_document(&mut state, params).unwrap();
+        state.process_changes();
+        let params = published(&receiver);
+        assert_eq!((params.version, params.diagnostics.len()), (Some(1), 0));
+
+        let params = DidChangeTextDocumentParams {
+            text_document: VersionedTextDocumentIdentifier::new(uri.clone(), 2),
+            content_changes: vec![TextDocumentContentChangeEvent {
+                range: None,
+                range_length: None,
+                text: "<cfif a>\r\n</cfoutput>".to_owned(),
+            }],
+        };
+        handle_did_change_text_document(&mut state, params).unwrap();
+        state.process_changes();
+        let params = published(&receiver);
+        assert_eq!(params.version, Some(2));
+        let ranges: Vec<_> = params
+            .diagnostics
+            .iter()
+            .map(|it| {
+                let lsp_types::Range { start, end } = it.range;
+                let range = (start.line, start.character, end.line, end.character);
+                (range, it.message.as_str())
+            })
+            .collect();
+        assert_eq!(
+            ranges,
+            [
+                ((0, 1, 0, 5), "unclosed `<cfif>`"),
+                ((1, 0, 1, 10), "unmatched `</cfoutput>`"),
+            ]
+        );
+
+        let params = DidCloseTextDocumentParams {
+            text_document: TextDocumentIdentifier::new(uri),
+        };
+        handle_did_close_text_document(&mut state, params).unwrap();
+        state.process_changes();
+        let params = published(&receiver);
+        assert_eq!((params.version, params.diagnostics.len()), (None, 0));
+        assert!(receiver.try_recv().is_err());
+    }
+}
@@ -1,26 +1,670 @@
-use crate::global_state::GlobalState;
-use lsp_types::{CompletionItemKind, CompletionParams};
+use crate::config::Baseline;
+use crate::global_state::{
+    from_proto, line_index::LineIndex, to_proto, GlobalState, GlobalStateSnapshot,
+};
+use crate::lsp::LspError;
+use anyhow::Context;
+use lsp_server::ErrorCode;
+use lsp_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionParams, CodeActionResponse,
+    CompletionItemKind, CompletionParams, DocumentDiagnosticParams, DocumentDiagnosticReport,
+    DocumentDiagnosticReportKind, DocumentDiagnosticReportResult, DocumentHighlight,
+    DocumentHighlightParams, DocumentSymbolParams, DocumentSymbolResponse, ExecuteCommandParams,
+    FoldingRange, FoldingRangeParams, FullDocumentDiagnosticReport, LinkedEditingRangeParams,
+    LinkedEditingRanges, Location, OneOf, PrepareRenameResponse,
+    RelatedFullDocumentDiagnosticReport, RelatedUnchangedDocumentDiagnosticReport, RenameParams,
+    SelectionRange, SelectionRangeParams, SemanticTokensDeltaParams, SemanticTokensFullDeltaResult,
+    SemanticTokensParams, SemanticTokensRangeParams, SemanticTokensRangeResult,
+    SemanticTokensResult, SymbolInformation, TextDocumentPositionParams,
+    UnchangedDocumentDiagnosticReport, WorkspaceDiagnosticParams, WorkspaceDiagnosticReport,
+    WorkspaceDiagnosticReportPartialResult, WorkspaceDiagnosticReportResult,
+    WorkspaceDocumentDiagnosticReport, WorkspaceEdit, WorkspaceFullDocumentDiagnosticReport,
+    WorkspaceSymbol, WorkspaceSymbolParams, WorkspaceSymbolResponse,
+    WorkspaceUnchangedDocumentDiagnosticReport,
+};
+use rustc_hash::FxHashMap;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use syntax::TextRange;
+use virtual_fs::FileId;
 
 pub fn handle_completion(
-    _snap: &mut GlobalState,
-    _params: CompletionParams,
+    state: &mut GlobalState,
+    params: CompletionParams,
 ) -> anyhow::Result<Option<lsp_types::CompletionResponse>> {
-    let completion_list = lsp_types::CompletionList {
-        is_incomplete: false,
-        items: vec![lsp_types::CompletionItem {
-            label: "Hello, World!".to_string(),
-            kind: Some(CompletionItemKind::TEXT),
-            detail: Some("This is a completion item".to_string()),
+    let snap = state.snapshot();
+    // Completion is requested while typing, also in files not loaded yet.
+    let Ok(position) = from_proto::file_position(&snap, params.text_document_position) else {
+        return Ok(None);
+    };
+    let line_index = snap.file_line_index(position.file_id)?;
+    let items = snap
+        .analysis
+        .completions(position)
+        .into_iter()
+        .map(|it| lsp_types::CompletionItem {
+            label: it.label.clone(),
+            kind: Some(CompletionItemKind::METHOD),
+            detail: Some(it.detail),
+            documentation: it
+                .documentation
+                .map(|it| lsp_types::Documentation::MarkupContent(markdown(it))),
+            text_edit: Some(lsp_types::CompletionTextEdit::Edit(lsp_types::TextEdit {
+                range: to_proto::range(&line_index, it.source_range),
+                new_text: it.label,
+            })),
             ..Default::default()
-        }],
+        })
+        .collect();
+    Ok(Some(lsp_types::CompletionResponse::Array(items)))
+}
+
+pub fn handle_hover(
+    state: &mut GlobalState,
+    params: lsp_types::HoverParams,
+) -> anyhow::Result<Option<lsp_types::Hover>> {
+    let snap = state.snapshot();
+    let position = from_proto::file_position(&snap, params.text_document_position_params)?;
+    let line_index = snap.file_line_index(position.file_id)?;
+    let Some(hover) = snap.analysis.hover(position) else {
+        return Ok(None);
+    };
+    Ok(Some(lsp_types::Hover {
+        contents: lsp_types::HoverContents::Markup(markdown(hover.markup)),
+        range: Some(to_proto::range(&line_index, hover.range)),
+    }))
+}
+
+pub fn handle_signature_help(
+    state: &mut GlobalState,
+    params: lsp_types::SignatureHelpParams,
+) -> anyhow::Result<Option<lsp_types::SignatureHelp>> {
+    let snap = state.snapshot();
+    let position = from_proto::file_position(&snap, params.text_document_position_params)?;
+    let Some(help) = snap.analysis.signature_help(position) else {
+        return Ok(None);
+    };
+    let parameters = help
+        .parameters
+        .into_iter()
+        .map(|it| lsp_types::ParameterInformation {
+            label: lsp_types::ParameterLabel::Simple(it.label),
+            documentation: it
+                .documentation
+                .map(|it| lsp_types::Documentation::MarkupContent(markdown(it))),
+        })
+        .collect();
+    let active_parameter = help.active_parameter.map(|it| it as u32);
+    let signature = lsp_types::SignatureInformation {
+        label: help.label,
+        documentation: help
+            .documentation
+            .map(|it| lsp_types::Documentation::MarkupContent(markdown(it))),
+        parameters: Some(parameters),
+        active_parameter,
+    };
+    Ok(Some(lsp_types::SignatureHelp {
+        signatures: vec![signature],
+        active_signature: Some(0),
+        active_parameter,
+    }))
+}
+
+fn markdown(value: String) -> lsp_types::MarkupContent {
+    lsp_types::MarkupContent {
+        kind: lsp_types::MarkupKind::Markdown,
+        value,
+    }
+}
+
+pub fn handle_document_symbol(
+    state: &mut GlobalState,
+    params: DocumentSymbolParams,
+) -> anyhow::Result<Option<DocumentSymbolResponse>> {
+    let snap = state.snapshot();
+    let file_id = snap.file_id(&params.text_document.uri)?;
+    let line_index = snap.file_line_index(file_id)?;
+    let symbols = snap.analysis.document_symbols(file_id);
+
+    if snap.config.hierarchical_symbols() {
+        let symbols = symbols
+            .into_iter()
+            .map(|it| to_proto::document_symbol(&line_index, it))
+            .collect();
+        return Ok(Some(DocumentSymbolResponse::Nested(symbols)));
+    }
+
+    let mut flat = Vec::new();
+    flatten_document_symbols(
+        &line_index,
+        &params.text_document.uri,
+        symbols,
+        None,
+        &mut flat,
+    );
+    Ok(Some(DocumentSymbolResponse::Flat(flat)))
+}
+
+fn flatten_document_symbols(
+    line_index: &LineIndex,
+    url: &lsp_types::Url,
+    symbols: Vec<ide::DocumentSymbol>,
+    container_name: Option<&str>,
+    acc: &mut Vec<SymbolInformation>,
+) {
+    for symbol in symbols {
+        #[allow(deprecated)]
+        acc.push(SymbolInformation {
+            name: symbol.name.clone(),
+            kind: to_proto::symbol_kind(symbol.kind),
+            tags: None,
+            deprecated: None,
+            location: lsp_types::Location::new(
+                url.clone(),
+                to_proto::range(line_index, symbol.range),
+            ),
+            container_name: container_name.map(str::to_owned),
+        });
+        flatten_document_symbols(line_index, url, symbol.children, Some(&symbol.name), acc);
+    }
+}
+
+pub fn handle_formatting(
+    state: &mut GlobalState,
+    params: lsp_types::DocumentFormattingParams,
+) -> anyhow::Result<Option<Vec<lsp_types::TextEdit>>> {
+    let snap = state.snapshot();
+    let file_id = snap.file_id(&params.text_document.uri)?;
+    format(&snap, file_id, None)
+}
+
+pub fn handle_range_formatting(
+    state: &mut GlobalState,
+    params: lsp_types::DocumentRangeFormattingParams,
+) -> anyhow::Result<Option<Vec<lsp_types::TextEdit>>> {
+    let snap = state.snapshot();
+    let range = from_proto::file_range(&snap, &params.text_document, params.range)?;
+    format(&snap, range.file_id, Some(range.range))
+}
+
+/// The edits formatting a file with the settings of the project, in its
+/// line endings.
+fn format(
+    snap: &GlobalStateSnapshot,
+    file_id: FileId,
+    range: Option<TextRange>,
+) -> anyhow::Result<Option<Vec<lsp_types::TextEdit>>> {
+    let line_index = snap.file_line_index(file_id)?;
+    let Some(edit) = snap
+        .analysis
+        .format(file_id, range, snap.config.formatting())
+    else {
+        return Ok(None);
+    };
+    Ok(Some(to_proto::text_edit_vec(&line_index, edit)))
+}
+
+pub fn handle_folding_range(
+    state: &mut GlobalState,
+    params: FoldingRangeParams,
+) -> anyhow::Result<Option<Vec<FoldingRange>>> {
+    let snap = state.snapshot();
+    let file_id = snap.file_id(&params.text_document.uri)?;
+    let line_index = snap.file_line_index(file_id)?;
+    let line_folding_only = snap.config.line_folding_only();
+    let folds = snap
+        .analysis
+        .folding_ranges(file_id)
+        .into_iter()
+        .map(|it| to_proto::folding_range(&line_index, line_folding_only, it))
+        .filter(|it| it.start_line < it.end_line)
+        .collect();
+    Ok(Some(folds))
+}
+
+pub fn handle_code_action(
+    state: &mut GlobalState,
+    params: CodeActionParams,
+) -> anyhow::Result<Option<CodeActionResponse>> {
+    let snap = state.snapshot();
+    let range = from_proto::file_range(&snap, &params.text_document, params.range)?;
+    let line_index = snap.file_line_index(range.file_id)?;
+    let only = params.context.only.as_deref();
+    let mut res = Vec::new();
+    for mut diagnostic in snap.new_diagnostics(range.file_id) {
+        if diagnostic.range.intersect(range.range).is_none() {
+            continue;
+        }
+        let fixes = std::mem::take(&mut diagnostic.fixes);
+        let diagnostic = to_proto::diagnostic(&line_index, diagnostic);
+        for (i, fix) in fixes.into_iter().enumerate() {
+            let kind = to_proto::code_action_kind(fix.kind);
+            if !is_kind_requested(only, &kind) {
+                continue;
+            }
+            res.push(CodeActionOrCommand::CodeAction(CodeAction {
+                title: fix.label,
+                kind: Some(kind),
+                diagnostics: Some(vec![diagnostic.clone()]),
+                edit: Some(to_proto::workspace_edit(&snap, fix.source_change)?),
+                is_preferred: Some(i == 0),
+                ..Default::default()
+            }));
+        }
+    }
+    for assist in snap.analysis.assists(range) {
+        let kind = to_proto::code_action_kind(assist.kind);
+        if !is_kind_requested(only, &kind) {
+            continue;
+        }
+        res.push(CodeActionOrCommand::CodeAction(CodeAction {
+            title: assist.label,
+            kind: Some(kind),
+            edit: Some(to_proto::workspace_edit(&snap, assist.source_change)?),
+            ..Default::default()
+        }));
+    }
+    Ok(Some(res))
+}
+
+/// The command recording the current diagnostics of the workspace in the
+/// baseline, so only new ones are reported.
+pub const WRITE_BASELINE_COMMAND: &str = "cfls.writeBaseline";
+
+pub fn handle_execute_command(
+    state: &mut GlobalState,
+    params: ExecuteCommandParams,
+) -> anyhow::Result<Option<serde_json::Value>> {
+    if params.command != WRITE_BASELINE_COMMAND {
+        let message = format!("unknown command `{}`", params.command);
+        return Err(LspError::new(ErrorCode::InvalidParams as i32, message).into());
+    }
+    let snap = state.snapshot();
+    let files: Vec<_> = snap
+        .workspace_files()
+        .into_iter()
+        .filter_map(|(file_id, _)| {
+            let path = snap.baseline_path(file_id)?;
+            Some((
+                path,
+                snap.analysis
+                    .diagnostics(snap.config.diagnostics(), file_id),
+            ))
+        })
+        .collect();
+    let baseline = Baseline::new(files.iter().map(|(path, it)| (path.clone(), it.as_slice())));
+    let path = snap.config.baseline_path();
+    let mut json = serde_json::to_string_pretty(&baseline)?;
+    json.push('\n');
+    std::fs::write(&path, json).with_context(|| format!("failed to write {path}"))?;
+    drop(snap);
+    Arc::make_mut(&mut state.config).set_baseline(baseline);
+    state.refresh_diagnostics();
+    Ok(None)
+}
+
+/// Whether `kind` is one of the kinds in `only`, or a sub-kind of one, as in
+/// `refactor.extract` for `refactor`.
+fn is_kind_requested(only: Option<&[CodeActionKind]>, kind: &CodeActionKind) -> bool {
+    let Some(only) = only else {
+        return true;
+    };
+    only.iter().any(|it| {
+        let prefix = it.as_str();
+        kind.as_str()
+            .strip_prefix(prefix)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+    })
+}
+
+pub fn handle_document_highlight(
+    state: &mut GlobalState,
+    params: DocumentHighlightParams,
+) -> anyhow::Result<Option<Vec<DocumentHighlight>>> {
+    let snap = state.snapshot();
+    let position = from_proto::file_position(&snap, params.text_document_position_params)?;
+    let line_index = snap.file_line_index(position.file_id)?;
+    let Some(highlights) = snap.analysis.document_highlight(position) else {
+        return Ok(None);
+    };
+    let res = highlights
+        .into_iter()
+        .map(|it| DocumentHighlight {
+            range: to_proto::range(&line_index, it.range),
+            kind: Some(to_proto::document_highlight_kind(it.access)),
+        })
+        .collect();
+    Ok(Some(res))
+}
+
+pub fn handle_linked_editing_range(
+    state: &mut GlobalState,
+    params: LinkedEditingRangeParams,
+) -> anyhow::Result<Option<LinkedEditingRanges>> {
+    let snap = state.snapshot();
+    let position = from_proto::file_position(&snap, params.text_document_position_params)?;
+    let line_index = snap.file_line_index(position.file_id)?;
+    let Some(ranges) = snap.analysis.linked_editing_ranges(position) else {
+        return Ok(None);
+    };
+    Ok(Some(LinkedEditingRanges {
+        ranges: ranges
+            .into_iter()
+            .map(|it| to_proto::range(&line_index, it))
+            .collect(),
+        word_pattern: Some(ide::TAG_NAME_PATTERN.to_owned()),
+    }))
+}
+
+pub fn handle_selection_range(
+    state: &mut GlobalState,
+    params: SelectionRangeParams,
+) -> anyhow::Result<Option<Vec<SelectionRange>>> {
+    let snap = state.snapshot();
+    let file_id = snap.file_id(&params.text_document.uri)?;
+    let line_index = snap.file_line_index(file_id)?;
+    let mut res = Vec::with_capacity(params.positions.len());
+    for position in params.positions {
+        let offset = from_proto::offset(&line_index, position)?;
+        let ranges = snap
+            .analysis
+            .selection_ranges(ide::FilePosition { file_id, offset });
+        let selection_range = ranges
+            .into_iter()
+            .rev()
+            .fold(None, |parent, range| {
+                Some(SelectionRange {
+                    range: to_proto::range(&line_index, range),
+                    parent: parent.map(Box::new),
+                })
+            })
+            .unwrap_or_else(|| SelectionRange {
+                range: lsp_types::Range::new(position, position),
+                parent: None,
+            });
+        res.push(selection_range);
+    }
+    Ok(Some(res))
+}
+
+/// What `workspaceSymbol/resolve` needs to compute the range of a symbol
+/// returned without one.
+#[derive(Debug, Serialize, Deserialize)]
+struct SymbolResolveData {
+    start: u32,
+    end: u32,
+}
+
+pub fn handle_workspace_symbol(
+    state: &mut GlobalState,
+    params: WorkspaceSymbolParams,
+) -> anyhow::Result<Option<WorkspaceSymbolResponse>> {
+    let snap = state.snapshot();
+    let mut query = ide::Query::new(&params.query);
+    query.limit(snap.config.workspace_symbol_search_limit());
+    let symbols = snap.analysis.symbol_search(query);
+
+    if snap.config.workspace_symbol_resolve() {
+        let symbols = symbols
+            .into_iter()
+            .map(|symbol| {
+                let data = SymbolResolveData {
+                    start: symbol.name_range.start().into(),
+                    end: symbol.name_range.end().into(),
+                };
+                WorkspaceSymbol {
+                    name: symbol.name,
+                    kind: to_proto::symbol_kind(symbol.kind),
+                    tags: None,
+                    container_name: symbol.container_name,
+                    location: OneOf::Right(lsp_types::WorkspaceLocation {
+                        uri: to_proto::url(&snap, symbol.file_id),
+                    }),
+                    data: serde_json::to_value(data).ok(),
+                }
+            })
+            .collect();
+        return Ok(Some(WorkspaceSymbolResponse::Nested(symbols)));
+    }
+
+    let mut res = Vec::new();
+    for symbol in symbols {
+        let line_index = snap.file_line_index(symbol.file_id)?;
+        let uri = to_proto::url(&snap, symbol.file_id);
+        #[allow(deprecated)]
+        res.push(SymbolInformation {
+            name: symbol.name,
+            kind: to_proto::symbol_kind(symbol.kind),
+            tags: None,
+            deprecated: None,
+            location: Location::new(uri, to_proto::range(&line_index, symbol.name_range)),
+            container_name: symbol.container_name,
+        });
+    }
+    Ok(Some(WorkspaceSymbolResponse::Flat(res)))
+}
+
+pub fn handle_workspace_symbol_resolve(
+    state: &mut GlobalState,
+    mut symbol: WorkspaceSymbol,
+) -> anyhow::Result<WorkspaceSymbol> {
+    let snap = state.snapshot();
+    let uri = match &symbol.location {
+        OneOf::Left(_) => return Ok(symbol),
+        OneOf::Right(it) => it.uri.clone(),
     };
-    Ok(Some(completion_list.into()))
+    let Some(data) = symbol.data.take() else {
+        return Ok(symbol);
+    };
+    let data: SymbolResolveData = serde_json::from_value(data)?;
+    let file_id = snap.file_id(&uri)?;
+    let line_index = snap.file_line_index(file_id)?;
+    let range = TextRange::new(data.start.into(), data.end.into());
+    symbol.location = OneOf::Left(Location::new(uri, to_proto::range(&line_index, range)));
+    Ok(symbol)
+}
+
+pub fn handle_prepare_rename(
+    state: &mut GlobalState,
+    params: TextDocumentPositionParams,
+) -> anyhow::Result<Option<PrepareRenameResponse>> {
+    let snap = state.snapshot();
+    let position = from_proto::file_position(&snap, params)?;
+    let (range, placeholder) = snap
+        .analysis
+        .prepare_rename(position)
+        .map_err(to_rename_error)?;
+
+    let line_index = snap.file_line_index(position.file_id)?;
+    let range = to_proto::range(&line_index, range);
+    Ok(Some(PrepareRenameResponse::RangeWithPlaceholder {
+        range,
+        placeholder,
+    }))
+}
+
+pub fn handle_rename(
+    state: &mut GlobalState,
+    params: RenameParams,
+) -> anyhow::Result<Option<WorkspaceEdit>> {
+    let snap = state.snapshot();
+    let position = from_proto::file_position(&snap, params.text_document_position)?;
+    let change = snap
+        .analysis
+        .rename(position, &params.new_name)
+        .map_err(to_rename_error)?;
+
+    let workspace_edit = to_proto::workspace_edit(&snap, change)?;
+    Ok(Some(workspace_edit))
+}
+
+pub fn handle_semantic_tokens_full(
+    state: &mut GlobalState,
+    params: SemanticTokensParams,
+) -> anyhow::Result<Option<SemanticTokensResult>> {
+    let snap = state.snapshot();
+    let file_id = snap.file_id(&params.text_document.uri)?;
+    let text = snap.analysis.file_text(file_id);
+    let line_index = snap.file_line_index(file_id)?;
+
+    let highlights = snap.analysis.highlight(file_id);
+    let tokens = to_proto::semantic_tokens(&text, &line_index, highlights);
+
+    snap.semantic_tokens_cache
+        .lock()
+        .insert(params.text_document.uri, tokens.clone());
+    Ok(Some(tokens.into()))
+}
+
+pub fn handle_semantic_tokens_full_delta(
+    state: &mut GlobalState,
+    params: SemanticTokensDeltaParams,
+) -> anyhow::Result<Option<SemanticTokensFullDeltaResult>> {
+    let snap = state.snapshot();
+    let file_id = snap.file_id(&params.text_document.uri)?;
+    let text = snap.analysis.file_text(file_id);
+    let line_index = snap.file_line_index(file_id)?;
+
+    let highlights = snap.analysis.highlight(file_id);
+    let tokens = to_proto::semantic_tokens(&text, &line_index, highlights);
+
+    let mut cache = snap.semantic_tokens_cache.lock();
+    let cached = cache.entry(params.text_document.uri).or_default();
+    if cached.result_id.as_ref() == Some(&params.previous_result_id) {
+        let delta = to_proto::semantic_token_delta(cached, &tokens);
+        *cached = tokens;
+        return Ok(Some(delta.into()));
+    }
+    *cached = tokens.clone();
+    Ok(Some(tokens.into()))
+}
+
+pub fn handle_semantic_tokens_range(
+    state: &mut GlobalState,
+    params: SemanticTokensRangeParams,
+) -> anyhow::Result<Option<SemanticTokensRangeResult>> {
+    let snap = state.snapshot();
+    let frange = from_proto::file_range(&snap, &params.text_document, params.range)?;
+    let text = snap.analysis.file_text(frange.file_id);
+    let line_index = snap.file_line_index(frange.file_id)?;
+
+    let highlights = snap.analysis.highlight_range(frange);
+    let tokens = to_proto::semantic_tokens(&text, &line_index, highlights);
+    Ok(Some(tokens.into()))
+}
+
+pub fn handle_document_diagnostic(
+    state: &mut GlobalState,
+    params: DocumentDiagnosticParams,
+) -> anyhow::Result<DocumentDiagnosticReportResult> {
+    let snap = state.snapshot();
+    let file_id = snap.file_id(&params.text_document.uri)?;
+    let report = match diagnostic_report(&snap, file_id, params.previous_result_id.as_deref()) {
+        DocumentDiagnosticReportKind::Full(report) => {
+            DocumentDiagnosticReport::Full(RelatedFullDocumentDiagnosticReport {
+                related_documents: None,
+                full_document_diagnostic_report: report,
+            })
+        }
+        DocumentDiagnosticReportKind::Unchanged(report) => {
+            DocumentDiagnosticReport::Unchanged(RelatedUnchangedDocumentDiagnosticReport {
+                related_documents: None,
+                unchanged_document_diagnostic_report: report,
+            })
+        }
+    };
+    Ok(report.into())
+}
+
+/// How many file reports each partial result of `workspace/diagnostic`
+/// carries.
+const WORKSPACE_DIAGNOSTIC_BATCH: usize = 64;
+
+pub fn handle_workspace_diagnostic(
+    state: &mut GlobalState,
+    params: WorkspaceDiagnosticParams,
+) -> anyhow::Result<WorkspaceDiagnosticReportResult> {
+    let snap = state.snapshot();
+    let previous: FxHashMap<_, _> = params
+        .previous_result_ids
+        .into_iter()
+        .map(|it| (it.uri, it.value))
+        .collect();
+    let mut items = Vec::new();
+    for (file_id, uri) in snap.workspace_files() {
+        let previous = previous.get(&uri).map(String::as_str);
+        let version = snap.url_file_version(&uri).map(i64::from);
+        let item = match diagnostic_report(&snap, file_id, previous) {
+            DocumentDiagnosticReportKind::Full(report) => {
+                WorkspaceDocumentDiagnosticReport::Full(WorkspaceFullDocumentDiagnosticReport {
+                    uri,
+                    version,
+                    full_document_diagnostic_report: report,
+                })
+            }
+            DocumentDiagnosticReportKind::Unchanged(report) => {
+                WorkspaceDocumentDiagnosticReport::Unchanged(
+                    WorkspaceUnchangedDocumentDiagnosticReport {
+                        uri,
+                        version,
+                        unchanged_document_diagnostic_report: report,
+                    },
+                )
+            }
+        };
+        items.push(item);
+
+        if let Some(token) = &params.partial_result_params.partial_result_token {
+            if items.len() == WORKSPACE_DIAGNOSTIC_BATCH {
+                let partial = WorkspaceDiagnosticReportPartialResult {
+                    items: std::mem::take(&mut items),
+                };
+                state.send_partial_result(token.clone(), partial);
+            }
+        }
+    }
+    // With partial results, the response itself must not carry any items.
+    if let Some(token) = params.partial_result_params.partial_result_token {
+        if !items.is_empty() {
+            state.send_partial_result(token, WorkspaceDiagnosticReportPartialResult { items });
+        }
+        return Ok(WorkspaceDiagnosticReport::default().into());
+    }
+    Ok(WorkspaceDiagnosticReport { items }.into())
+}
+
+/// The diagnostics of a file, or just their result ID if it is the one the
+/// client already has. Result IDs are the diagnostics revisions of the
+/// file, with the generation of the baseline.
+fn diagnostic_report(
+    snap: &GlobalStateSnapshot,
+    file_id: FileId,
+    previous_result_id: Option<&str>,
+) -> DocumentDiagnosticReportKind {
+    let result_id = snap
+        .analysis
+        .diagnostics_revision(file_id)
+        .map(|it| format!("{it}.{}", snap.config.baseline_generation()));
+    if let Some(result_id) = result_id.clone() {
+        if previous_result_id == Some(result_id.as_str()) {
+            return DocumentDiagnosticReportKind::Unchanged(UnchangedDocumentDiagnosticReport {
+                result_id,
+            });
+        }
+    }
+    let items = snap.file_diagnostics(file_id);
+    DocumentDiagnosticReportKind::Full(FullDocumentDiagnosticReport { result_id, items })
+}
+
+fn to_rename_error(err: ide::RenameError) -> LspError {
+    LspError::new(ErrorCode::InvalidParams as i32, err.to_string())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::config::Config;
+    use lsp_server::Message;
     use lsp_types::CompletionParams;
     use lsp_types::Position;
     use lsp_types::TextDocumentIdentifier;
@@ -31,7 +675,7 @@ mod tests {
 
     #[test]
     fn test_handle_completion() {
-        let (sender, _) = crossbeam_channel::unbounded();
+        let (sender, _receiver) = crossbeam_channel::unbounded();
         let root_path = AbsPathBuf::try_from("/tmp").unwrap();
         let capabilities = lsp_types::ClientCapabilities::default();
         let workspace_roots = vec![AbsPathBuf::try_from("/tmp").unwrap()];
@@ -58,4 +702,435 @@ mod tests {
         let result = handle_completion(&mut snap, params);
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_handle_completion_of_accessors() {
+        let (mut state, _receiver) = test_state("/tmp", Default::default());
+
+        let uri = Url::parse("file:///tmp/User.cfc").unwrap();
+        let text = "component accessors=true {\r\n    property name;\r\n    function f() { this.ge }\r\n}\r\n";
+        state.add_document(&uri, text.to_owned(), 1).unwrap();
+        state.add_changes_into_document(&uri, text.to_owned());
+        state.process_changes();
+
+        let params = CompletionParams {
+            text_document_position: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri },
+                position: Position::new(2, 26),
+            },
+            context: None,
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: lsp_types::PartialResultParams::default(),
+        };
+        let Some(lsp_types::CompletionResponse::Array(items)) =
+            handle_completion(&mut state, params).unwrap()
+        else {
+            panic!("expected completion items");
+        };
+        let labels: Vec<_> = items.iter().map(|it| it.label.as_str()).collect();
+        assert_eq!(labels, ["f", "getName", "setName"]);
+        let Some(lsp_types::CompletionTextEdit::Edit(edit)) = &items[1].text_edit else {
+            panic!("expected a text edit");
+        };
+        let range = lsp_types::Range::new(Position::new(2, 24), Position::new(2, 26));
+        assert_eq!(edit.range, range);
+    }
+
+    #[test]
+    fn test_handle_hover() {
+        let (mut state, _receiver) = test_state("/tmp", Default::default());
+
+        let uri = Url::parse("file:///tmp/Greeter.cfc").unwrap();
+        let text = "component {\r\n    /** Greets */\r\n    function greet() { greet(); }\r\n}\r\n";
+        state.add_document(&uri, text.to_owned(), 1).unwrap();
+        state.add_changes_into_document(&uri, text.to_owned());
+        state.process_changes();
+
+        let params = lsp_types::HoverParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri },
+                position: Position::new(2, 25),
+            },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+        };
+        let hover = handle_hover(&mut state, params).unwrap().unwrap();
+        let lsp_types::HoverContents::Markup(contents) = hover.contents else {
+            panic!("expected markup");
+        };
+        assert_eq!(contents.value, "```cfml\nany greet()\n```\n\n---\n\nGreets");
+        let range = lsp_types::Range::new(Position::new(2, 23), Position::new(2, 28));
+        assert_eq!(hover.range, Some(range));
+    }
+
+    #[test]
+    fn test_handle_code_action() {
+        let (mut state, _receiver) = test_state("/tmp", Default::default());
+
+        let uri = Url::parse("file:///tmp/Counter.cfc").unwrap();
+        let text = "component {\r\n    function inc() { count = 1; }\r\n}\r\n";
+        state.add_document(&uri, text.to_owned(), 1).unwrap();
+        state.add_changes_into_document(&uri, text.to_owned());
+        state.process_changes();
+
+        let params = |only: Option<Vec<CodeActionKind>>| CodeActionParams {
+            text_document: TextDocumentIdentifier { uri: uri.clone() },
+            range: lsp_types::Range::new(Position::new(1, 23), Position::new(1, 23)),
+            context: lsp_types::CodeActionContext {
+                diagnostics: Vec::new(),
+                only,
+                trigger_kind: None,
+            },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: lsp_types::PartialResultParams::default(),
+        };
+        let actions = handle_code_action(&mut state, params(None))
+            .unwrap()
+            .unwrap();
+        let actions: Vec<_> = actions
+            .into_iter()
+            .map(|it| match it {
+                CodeActionOrCommand::CodeAction(it) => it,
+                CodeActionOrCommand::Command(it) => panic!("unexpected command {it:?}"),
+            })
+            .collect();
+        let titles: Vec<_> = actions.iter().map(|it| it.title.as_str()).collect();
+        assert_eq!(
+            titles,
+            ["Declare `count` with `var`", "Prefix `count` with `local.`"]
+        );
+        let action = &actions[0];
+        assert_eq!(action.kind, Some(CodeActionKind::QUICKFIX));
+        assert_eq!(action.is_preferred, Some(true));
+        let diagnostic = &action.diagnostics.as_ref().unwrap()[0];
+        assert_eq!(
+            diagnostic.code,
+            Some(lsp_types::NumberOrString::String("MISSING_VAR".to_owned()))
+        );
+        let edits = &action.edit.as_ref().unwrap().changes.as_ref().unwrap()[&uri];
+        assert_eq!(edits.len(), 1);
+        assert_eq!(edits[0].range.start, Position::new(1, 21));
+        assert_eq!(edits[0].new_text, "var ");
+
+        let only = Some(vec![CodeActionKind::REFACTOR]);
+        let actions = handle_code_action(&mut state, params(only))
+            .unwrap()
+            .unwrap();
+        assert!(actions.is_empty());
+
+        let text = "component {\r\n    function inc() { application.count = 1; }\r\n}\r\n";
+        state.add_changes_into_document(&uri, text.to_owned());
+        state.process_changes();
+        let only = Some(vec![CodeActionKind::REFACTOR]);
+        let actions = handle_code_action(&mut state, params(only))
+            .unwrap()
+            .unwrap();
+        let [CodeActionOrCommand::CodeAction(action)] = &actions[..] else {
+            panic!("expected one refactoring, got {actions:?}");
+        };
+        assert_eq!(action.title, "Wrap in an exclusive `application` lock");
+        assert_eq!(action.kind, Some(CodeActionKind::REFACTOR_REWRITE));
+        let edits = &action.edit.as_ref().unwrap().changes.as_ref().unwrap()[&uri];
+        assert_eq!(
+            edits[0].new_text,
+            "lock scope=\"application\" type=\"exclusive\" timeout=\"10\" {\r\n        application.count = 1;\r\n    }"
+        );
+    }
+
+    #[test]
+    fn test_handle_code_action_skips_baselined_problems() {
+        let (mut state, _receiver) = pull_diagnostics_state("/tmp/baselined");
+        let uri = Url::parse("file:///tmp/baselined/Counter.cfc").unwrap();
+        let text = "component {\n    function inc() { count = 1; }\n}\n";
+        state.add_document(&uri, text.to_owned(), 1).unwrap();
+        state.add_changes_into_document(&uri, text.to_owned());
+        state.process_changes();
+
+        let snap = state.snapshot();
+        let file_id = snap.file_id(&uri).unwrap();
+        let diagnostics = snap
+            .analysis
+            .diagnostics(snap.config.diagnostics(), file_id);
+        drop(snap);
+        let baseline = Baseline::new([("Counter.cfc".to_owned(), diagnostics.as_slice())]);
+        Arc::make_mut(&mut state.config).set_baseline(baseline);
+
+        let params = CodeActionParams {
+            text_document: TextDocumentIdentifier { uri: uri.clone() },
+            range: lsp_types::Range::new(Position::new(1, 23), Position::new(1, 23)),
+            context: lsp_types::CodeActionContext {
+                diagnostics: Vec::new(),
+                only: Some(vec![CodeActionKind::QUICKFIX]),
+                trigger_kind: None,
+            },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: lsp_types::PartialResultParams::default(),
+        };
+        let actions = handle_code_action(&mut state, params).unwrap().unwrap();
+        assert!(actions.is_empty(), "{actions:?}");
+    }
+
+    #[test]
+    fn test_handle_formatting() {
+        let (mut state, _receiver) = test_state("/tmp", Default::default());
+
+        let uri = Url::parse("file:///tmp/Greeter.cfc").unwrap();
+        let text = "component {\r\n  function greet( ) {return 1;}\r\n}\r\n";
+        state.add_document(&uri, text.to_owned(), 1).unwrap();
+        state.add_changes_into_document(&uri, text.to_owned());
+        state.process_changes();
+
+        let params = lsp_types::DocumentFormattingParams {
+            text_document: TextDocumentIdentifier { uri: uri.clone() },
+            options: lsp_types::FormattingOptions::default(),
+            work_done_progress_params: WorkDoneProgressParams::default(),
+        };
+        let edits = handle_formatting(&mut state, params).unwrap().unwrap();
+        let new_texts: Vec<_> = edits.iter().map(|it| it.new_text.as_str()).collect();
+        assert_eq!(new_texts, ["\r\n    ", "", "\r\n        ", "\r\n    "]);
+        let range = lsp_types::Range::new(Position::new(0, 11), Position::new(1, 2));
+        assert_eq!(edits[0].range, range);
+
+        let params = lsp_types::DocumentRangeFormattingParams {
+            text_document: TextDocumentIdentifier { uri },
+            range: lsp_types::Range::new(Position::new(1, 20), Position::new(1, 20)),
+            options: lsp_types::FormattingOptions::default(),
+            work_done_progress_params: WorkDoneProgressParams::default(),
+        };
+        let range_edits = handle_range_formatting(&mut state, params)
+            .unwrap()
+            .unwrap();
+        assert_eq!(range_edits, edits);
+    }
+
+    #[test]
+    fn test_handle_rename() {
+        let (mut state, _receiver) = test_state("/tmp", Default::default());
+
+        let uri = Url::parse("file:///tmp/Greeter.cfc").unwrap();
+        let text = "component {\r\n    function greet(name) { return name; }\r\n}\r\n";
+        state.add_document(&uri, text.to_owned(), 1).unwrap();
+        state.add_changes_into_document(&uri, text.to_owned());
+        state.process_changes();
+
+        let params = RenameParams {
+            text_document_position: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri: uri.clone() },
+                position: Position::new(1, 20),
+            },
+            new_name: "who".to_owned(),
+            work_done_progress_params: WorkDoneProgressParams {
+                work_done_token: None,
+            },
+        };
+        let edit = handle_rename(&mut state, params).unwrap().unwrap();
+        let edits = &edit.changes.unwrap()[&uri];
+        let ranges: Vec<_> = edits
+            .iter()
+            .map(|it| (it.range.start, it.range.end))
+            .collect();
+        assert_eq!(
+            ranges,
+            vec![
+                (Position::new(1, 19), Position::new(1, 23)),
+                (Position::new(1, 34), Position::new(1, 38)),
+            ]
+        );
+        assert!(edits.iter().all(|it| it.new_text == "who"));
+    }
+
+    #[test]
+    fn test_handle_semantic_tokens_delta() {
+        let (mut state, _receiver) = test_state("/tmp", Default::default());
+
+        let uri = Url::parse("file:///tmp/tokens.cfm").unwrap();
+        let text = "<cfset x = len(y)>\n<cfset z = 1>\n";
+        state.add_document(&uri, text.to_owned(), 1).unwrap();
+        state.add_changes_into_document(&uri, text.to_owned());
+        state.process_changes();
+
+        let text_document = TextDocumentIdentifier { uri: uri.clone() };
+        let params = SemanticTokensParams {
+            text_document: text_document.clone(),
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: lsp_types::PartialResultParams::default(),
+        };
+        let Some(SemanticTokensResult::Tokens(full)) =
+            handle_semantic_tokens_full(&mut state, params).unwrap()
+        else {
+            panic!("expected full tokens");
+        };
+
+        let text = "<cfset x = len(y)>\n<cfset z = 12>\n";
+        state.update_document(&uri, text.to_owned(), 2);
+        state.process_changes();
+        let params = SemanticTokensDeltaParams {
+            text_document,
+            previous_result_id: full.result_id.clone().unwrap(),
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: lsp_types::PartialResultParams::default(),
+        };
+        let Some(SemanticTokensFullDeltaResult::TokensDelta(delta)) =
+            handle_semantic_tokens_full_delta(&mut state, params).unwrap()
+        else {
+            panic!("expected a delta");
+        };
+        assert_ne!(delta.result_id, full.result_id);
+        let [edit] = delta.edits.as_slice() else {
+            panic!("expected one edit: {:?}", delta.edits);
+        };
+        let last = full.data.len() as u32 - 1;
+        assert_eq!((edit.start, edit.delete_count), (5 * last, 5));
+        assert_eq!(edit.data.as_ref().unwrap()[0].length, 2);
+    }
+
+    /// A server with the workspace `root`, for a client with
+    /// `capabilities`, and the receiver of the messages it sends.
+    fn test_state(
+        root: &str,
+        capabilities: lsp_types::ClientCapabilities,
+    ) -> (GlobalState, crossbeam_channel::Receiver<Message>) {
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        let root_path = AbsPathBuf::try_from(root).unwrap();
+        let workspace_roots = vec![root_path.clone()];
+        let config = Config::new(root_path, capabilities, workspace_roots);
+        (GlobalState::new(sender, config), receiver)
+    }
+
+    fn pull_diagnostics_state(root: &str) -> (GlobalState, crossbeam_channel::Receiver<Message>) {
+        let capabilities = lsp_types::ClientCapabilities {
+            text_document: Some(lsp_types::TextDocumentClientCapabilities {
+                diagnostic: Some(Default::default()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        test_state(root, capabilities)
+    }
+
+    #[test]
+    fn test_handle_document_diagnostic() {
+        let (mut state, receiver) = pull_diagnostics_state("/tmp/pull");
+        let uri = Url::parse("file:///tmp/pull/page.cfm").unwrap();
+        let text = "<cfif a>\n";
+        state.add_document(&uri, text.to_owned(), 1).unwrap();
+        state.add_changes_into_document(&uri, text.to_owned());
+        state.process_changes();
+        assert!(
+            receiver.try_recv().is_err(),
+            "pulling clients get no pushes"
+        );
+
+        let params = |previous_result_id| DocumentDiagnosticParams {
+            text_document: TextDocumentIdentifier { uri: uri.clone() },
+            identifier: None,
+            previous_result_id,
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: lsp_types::PartialResultParams::default(),
+        };
+        let DocumentDiagnosticReportResult::Report(DocumentDiagnosticReport::Full(full)) =
+            handle_document_diagnostic(&mut state, params(None)).unwrap()
+        else {
+            panic!("expected a full report");
+        };
+        let full = full.full_document_diagnostic_report;
+        assert_eq!(full.items.len(), 1);
+
+        let DocumentDiagnosticReportResult::Report(DocumentDiagnosticReport::Unchanged(_)) =
+            handle_document_diagnostic(&mut state, params(full.result_id.clone())).unwrap()
+        else {
+            panic!("expected an unchanged report");
+        };
+
+        state.update_document(&uri, "<cfif a></cfif>\n".to_owned(), 2);
+        state.process_changes();
+        let DocumentDiagnosticReportResult::Report(DocumentDiagnosticReport::Full(changed)) =
+            handle_document_diagnostic(&mut state, params(full.result_id.clone())).unwrap()
+        else {
+            panic!("expected a full report");
+        };
+        let changed = changed.full_document_diagnostic_report;
+        assert_ne!(changed.result_id, full.result_id);
+        assert!(changed.items.is_empty());
+    }
+
+    #[test]
+    fn test_handle_execute_command_write_baseline() {
+        let root = std::env::temp_dir().join(format!("cfls-baseline-{}", std::process::id()));
+        std::fs::create_dir_all(&root).unwrap();
+        let (mut state, _receiver) = pull_diagnostics_state(root.to_str().unwrap());
+        let uri = Url::from_file_path(root.join("page.cfm")).unwrap();
+        let text = "<cfdump var=\"#a#\">\n";
+        state.add_document(&uri, text.to_owned(), 1).unwrap();
+        state.add_changes_into_document(&uri, text.to_owned());
+        state.process_changes();
+
+        let params = |previous_result_id| DocumentDiagnosticParams {
+            text_document: TextDocumentIdentifier { uri: uri.clone() },
+            identifier: None,
+            previous_result_id,
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: lsp_types::PartialResultParams::default(),
+        };
+        let DocumentDiagnosticReportResult::Report(DocumentDiagnosticReport::Full(before)) =
+            handle_document_diagnostic(&mut state, params(None)).unwrap()
+        else {
+            panic!("expected a full report");
+        };
+        let before = before.full_document_diagnostic_report;
+        assert_eq!(before.items.len(), 1);
+
+        let command = ExecuteCommandParams {
+            command: WRITE_BASELINE_COMMAND.to_owned(),
+            arguments: Vec::new(),
+            work_done_progress_params: WorkDoneProgressParams::default(),
+        };
+        handle_execute_command(&mut state, command).unwrap();
+        let written = std::fs::read_to_string(root.join(".cfls-baseline.json")).unwrap();
+        std::fs::remove_dir_all(&root).unwrap();
+        let written: serde_json::Value = serde_json::from_str(&written).unwrap();
+        assert_eq!(written["diagnostics"][0]["file"], "page.cfm");
+        assert_eq!(written["diagnostics"][0]["code"], "AVOID_USING_CFDUMP_TAG");
+
+        let DocumentDiagnosticReportResult::Report(DocumentDiagnosticReport::Full(after)) =
+            handle_document_diagnostic(&mut state, params(before.result_id.clone())).unwrap()
+        else {
+            panic!("expected a full report");
+        };
+        assert!(after.full_document_diagnostic_report.items.is_empty());
+    }
+
+    #[test]
+    fn test_handle_workspace_diagnostic_streams_partial_results() {
+        let (mut state, receiver) = pull_diagnostics_state("/tmp/pull-ws");
+        for idx in 0..70 {
+            let uri = Url::parse(&format!("file:///tmp/pull-ws/page{idx}.cfm")).unwrap();
+            state.add_changes_into_document(&uri, "<cfset x = >".to_owned());
+        }
+        state.process_changes();
+
+        let token = lsp_types::NumberOrString::String("partial".to_owned());
+        let params = WorkspaceDiagnosticParams {
+            identifier: None,
+            previous_result_ids: Vec::new(),
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: lsp_types::PartialResultParams {
+                partial_result_token: Some(token.clone()),
+            },
+        };
+        let WorkspaceDiagnosticReportResult::Report(report) =
+            handle_workspace_diagnostic(&mut state, params).unwrap()
+        else {
+            panic!("expected a report");
+        };
+        assert!(report.items.is_empty());
+
+        let mut batches = Vec::new();
+        while let Ok(Message::Notification(it)) = receiver.try_recv() {
+            assert_eq!(it.params["token"], "partial");
+            let partial: WorkspaceDiagnosticReportPartialResult =
+                serde_json::from_value(it.params["value"].clone()).unwrap();
+            batches.push(partial.items.len());
+        }
+        assert_eq!(batches, [64, 6]);
+    }
 }
@@ -1,8 +1,11 @@
+pub(crate) mod semantic_tokens;
+
 use std::{
     fmt,
     panic::{self, UnwindSafe},
 };
 
+#[allow(dead_code)]
 #[derive(Debug, Clone, Copy)]
 pub enum Cancelled {
     /// The query was operating on revision R, but there is a pending write to move to revision R+1.
@@ -14,6 +17,7 @@ pub enum Cancelled {
     PropagatedPanic,
 }
 
+#[allow(dead_code)]
 impl Cancelled {
     fn throw(self) -> ! {
         // We use resume and not panic here to avoid running the panic
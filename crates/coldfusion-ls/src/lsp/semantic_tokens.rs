@@ -0,0 +1,166 @@
+//! The semantic tokens legend and the encoding of tokens as relative
+//! positions, plus the diff used to answer `full/delta` requests.
+
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use lsp_types::{
+    Range, SemanticToken, SemanticTokenModifier, SemanticTokenType, SemanticTokens,
+    SemanticTokensEdit, SemanticTokensLegend,
+};
+
+pub(crate) const TAG: SemanticTokenType = SemanticTokenType::new("tag");
+pub(crate) const ATTRIBUTE: SemanticTokenType = SemanticTokenType::new("attribute");
+pub(crate) const SCOPE: SemanticTokenType = SemanticTokenType::new("scope");
+
+pub(crate) const INTERPOLATED: SemanticTokenModifier = SemanticTokenModifier::new("interpolated");
+pub(crate) const SQL: SemanticTokenModifier = SemanticTokenModifier::new("sql");
+
+pub(crate) const SUPPORTED_TYPES: &[SemanticTokenType] = &[
+    TAG,
+    ATTRIBUTE,
+    SCOPE,
+    SemanticTokenType::KEYWORD,
+    SemanticTokenType::OPERATOR,
+    SemanticTokenType::COMMENT,
+    SemanticTokenType::STRING,
+    SemanticTokenType::NUMBER,
+    SemanticTokenType::FUNCTION,
+    SemanticTokenType::METHOD,
+    SemanticTokenType::CLASS,
+    SemanticTokenType::TYPE,
+    SemanticTokenType::PARAMETER,
+    SemanticTokenType::VARIABLE,
+    SemanticTokenType::PROPERTY,
+];
+
+pub(crate) const SUPPORTED_MODIFIERS: &[SemanticTokenModifier] = &[
+    SemanticTokenModifier::DECLARATION,
+    SemanticTokenModifier::DEFAULT_LIBRARY,
+    SemanticTokenModifier::DEPRECATED,
+    INTERPOLATED,
+    SQL,
+];
+
+pub(crate) fn legend() -> SemanticTokensLegend {
+    SemanticTokensLegend {
+        token_types: SUPPORTED_TYPES.to_vec(),
+        token_modifiers: SUPPORTED_MODIFIERS.to_vec(),
+    }
+}
+
+pub(crate) fn type_index(ty: &SemanticTokenType) -> u32 {
+    SUPPORTED_TYPES.iter().position(|it| it == ty).unwrap() as u32
+}
+
+/// The bitset of `modifiers`, by their position in the legend.
+pub(crate) fn modifier_set(modifiers: &[SemanticTokenModifier]) -> u32 {
+    modifiers.iter().fold(0, |acc, modifier| {
+        let idx = SUPPORTED_MODIFIERS
+            .iter()
+            .position(|it| it == modifier)
+            .unwrap();
+        acc | 1 << idx
+    })
+}
+
+/// Builds the relative encoding of tokens pushed in document order. Tokens
+/// must not span lines.
+pub(crate) struct SemanticTokensBuilder {
+    id: String,
+    prev_line: u32,
+    prev_char: u32,
+    data: Vec<SemanticToken>,
+}
+
+impl SemanticTokensBuilder {
+    pub(crate) fn new() -> Self {
+        static NEXT_ID: AtomicU32 = AtomicU32::new(1);
+        SemanticTokensBuilder {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed).to_string(),
+            prev_line: 0,
+            prev_char: 0,
+            data: Vec::new(),
+        }
+    }
+
+    pub(crate) fn push(&mut self, range: Range, token_type: u32, token_modifiers: u32) {
+        let mut push_line = range.start.line;
+        let mut push_char = range.start.character;
+        if !self.data.is_empty() {
+            push_line -= self.prev_line;
+            if push_line == 0 {
+                push_char -= self.prev_char;
+            }
+        }
+        self.data.push(SemanticToken {
+            delta_line: push_line,
+            delta_start: push_char,
+            length: range.end.character - range.start.character,
+            token_type,
+            token_modifiers_bitset: token_modifiers,
+        });
+        self.prev_line = range.start.line;
+        self.prev_char = range.start.character;
+    }
+
+    pub(crate) fn build(self) -> SemanticTokens {
+        SemanticTokens {
+            result_id: Some(self.id),
+            data: self.data,
+        }
+    }
+}
+
+/// The single edit turning `old` into `new`: everything between their
+/// common prefix and suffix is replaced. Offsets count integers, five per
+/// token.
+pub(crate) fn diff_tokens(old: &[SemanticToken], new: &[SemanticToken]) -> Vec<SemanticTokensEdit> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let (old, new) = (&old[prefix..], &new[prefix..]);
+    let suffix = old
+        .iter()
+        .rev()
+        .zip(new.iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let (old, new) = (&old[..old.len() - suffix], &new[..new.len() - suffix]);
+    if old.is_empty() && new.is_empty() {
+        return Vec::new();
+    }
+    vec![SemanticTokensEdit {
+        start: 5 * prefix as u32,
+        delete_count: 5 * old.len() as u32,
+        data: Some(new.to_vec()),
+    }]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(delta_line: u32, delta_start: u32) -> SemanticToken {
+        SemanticToken {
+            delta_line,
+            delta_start,
+            length: 1,
+            token_type: 0,
+            token_modifiers_bitset: 0,
+        }
+    }
+
+    #[test]
+    fn diff_replaces_the_changed_middle() {
+        let old = [token(0, 1), token(0, 2), token(1, 0), token(2, 3)];
+        let new = [token(0, 1), token(0, 4), token(0, 5), token(2, 3)];
+        let edits = diff_tokens(&old, &new);
+        assert_eq!(
+            edits,
+            vec![SemanticTokensEdit {
+                start: 5,
+                delete_count: 10,
+                data: Some(vec![token(0, 4), token(0, 5)]),
+            }]
+        );
+        assert!(diff_tokens(&old, &old).is_empty());
+    }
+}
@@ -1,7 +1,12 @@
 use crossbeam_channel::{select, Receiver};
 use lsp_server::{Connection, ErrorCode, Message, Notification, Request, Response};
 use lsp_types::{
-    CompletionOptions, ServerCapabilities, TextDocumentSyncCapability, TextDocumentSyncKind,
+    CodeActionKind, CodeActionOptions, CodeActionProviderCapability, CompletionOptions,
+    DiagnosticOptions, DiagnosticServerCapabilities, ExecuteCommandOptions,
+    FoldingRangeProviderCapability, HoverProviderCapability, LinkedEditingRangeServerCapabilities,
+    OneOf, RenameOptions, SelectionRangeProviderCapability, SemanticTokensFullOptions,
+    SemanticTokensOptions, SemanticTokensServerCapabilities, ServerCapabilities,
+    SignatureHelpOptions, TextDocumentSyncCapability, TextDocumentSyncKind, WorkspaceSymbolOptions,
 };
 use serde::de::DeserializeOwned;
 use std::path::PathBuf;
@@ -78,34 +83,82 @@ fn main() -> anyhow::Result<()> {
 
     if let Some(json) = initialization_options {
         if let Err(e) = config.update(json) {
-            use lsp_types::{
-                notification::{Notification, ShowMessage},
-                MessageType, ShowMessageParams,
-            };
-
-            let notification = lsp_server::Notification::new(
-                ShowMessage::METHOD.to_owned(),
-                ShowMessageParams {
-                    typ: MessageType::WARNING,
-                    message: format!("Failed to update configuration: {:?}", e),
-                },
-            );
-            connection
-                .sender
-                .send(Message::Notification(notification))
-                .unwrap();
+            show_warning(&connection, format!("Failed to update configuration: {e}"));
         }
     }
+    if let Err(e) = config.load_cflintrc() {
+        show_warning(&connection, format!("Failed to load `.cflintrc`: {e:#}"));
+    }
+    if let Err(e) = config.load_cfformat() {
+        show_warning(
+            &connection,
+            format!("Failed to load `.cfformat.json`: {e:#}"),
+        );
+    }
+    if let Err(e) = config.load_baseline() {
+        show_warning(&connection, format!("Failed to load the baseline: {e:#}"));
+    }
 
     let server_capabilities = ServerCapabilities {
         text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        code_action_provider: Some(CodeActionProviderCapability::Options(CodeActionOptions {
+            code_action_kinds: Some(vec![
+                CodeActionKind::QUICKFIX,
+                CodeActionKind::REFACTOR,
+                CodeActionKind::REFACTOR_EXTRACT,
+                CodeActionKind::REFACTOR_REWRITE,
+            ]),
+            work_done_progress_options: Default::default(),
+            resolve_provider: None,
+        })),
         completion_provider: Some(CompletionOptions {
-            resolve_provider: Some(true),
+            resolve_provider: None,
             trigger_characters: Some(vec![".".to_string()]),
             work_done_progress_options: Default::default(),
             all_commit_characters: None,
             completion_item: None,
         }),
+        diagnostic_provider: config.pull_diagnostics().then(|| {
+            DiagnosticServerCapabilities::Options(DiagnosticOptions {
+                identifier: Some("cfml".to_owned()),
+                inter_file_dependencies: true,
+                workspace_diagnostics: true,
+                work_done_progress_options: Default::default(),
+            })
+        }),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        signature_help_provider: Some(SignatureHelpOptions {
+            trigger_characters: Some(vec!["(".to_owned(), ",".to_owned()]),
+            retrigger_characters: None,
+            work_done_progress_options: Default::default(),
+        }),
+        document_formatting_provider: Some(OneOf::Left(true)),
+        document_highlight_provider: Some(OneOf::Left(true)),
+        document_range_formatting_provider: Some(OneOf::Left(true)),
+        document_symbol_provider: Some(OneOf::Left(true)),
+        execute_command_provider: Some(ExecuteCommandOptions {
+            commands: vec![handlers::request::WRITE_BASELINE_COMMAND.to_owned()],
+            work_done_progress_options: Default::default(),
+        }),
+        workspace_symbol_provider: Some(OneOf::Right(WorkspaceSymbolOptions {
+            resolve_provider: Some(true),
+            work_done_progress_options: Default::default(),
+        })),
+        rename_provider: Some(OneOf::Right(RenameOptions {
+            prepare_provider: Some(true),
+            work_done_progress_options: Default::default(),
+        })),
+        folding_range_provider: Some(FoldingRangeProviderCapability::Simple(true)),
+        linked_editing_range_provider: Some(LinkedEditingRangeServerCapabilities::Simple(true)),
+        selection_range_provider: Some(SelectionRangeProviderCapability::Simple(true)),
+        semantic_tokens_provider: Some(SemanticTokensServerCapabilities::SemanticTokensOptions(
+            SemanticTokensOptions {
+                legend: lsp::semantic_tokens::legend(),
+                range: Some(true),
+                full: Some(SemanticTokensFullOptions::Delta { delta: Some(true) }),
+                work_done_progress_options: Default::default(),
+            },
+        )),
         ..ServerCapabilities::default()
     };
 
@@ -132,6 +185,25 @@ fn main() -> anyhow::Result<()> {
     Ok(())
 }
 
+fn show_warning(connection: &Connection, message: String) {
+    use lsp_types::{
+        notification::{Notification, ShowMessage},
+        MessageType, ShowMessageParams,
+    };
+
+    let notification = lsp_server::Notification::new(
+        ShowMessage::METHOD.to_owned(),
+        ShowMessageParams {
+            typ: MessageType::WARNING,
+            message,
+        },
+    );
+    connection
+        .sender
+        .send(Message::Notification(notification))
+        .unwrap();
+}
+
 fn run(config: Config, connection: Connection) -> anyhow::Result<()> {
     #[cfg(windows)]
     unsafe {
@@ -146,6 +218,10 @@ fn run(config: Config, connection: Connection) -> anyhow::Result<()> {
 
 impl GlobalState {
     fn run(mut self, inbox: Receiver<Message>) -> anyhow::Result<()> {
+        self.load_workspace();
+        self.process_changes();
+        self.prime_caches();
+
         while let Some(event) = self.next_event(&inbox) {
             if matches!(
                 &event,
@@ -172,7 +248,10 @@ impl GlobalState {
         match event {
             Event::Lsp(msg) => match msg {
                 Message::Request(req) => self.on_new_request(loop_start, req),
-                Message::Notification(notification) => self.on_notification(notification)?,
+                Message::Notification(notification) => {
+                    self.on_notification(notification)?;
+                    self.process_changes();
+                }
                 Message::Response(resp) => self.complete_request(resp),
             },
         }
@@ -216,7 +295,41 @@ impl GlobalState {
         use lsp_types::request as lsp_request;
 
         dispatcher
+            .on_sync_mut::<lsp_request::CodeActionRequest>(handlers::handle_code_action)
             .on_sync_mut::<lsp_request::Completion>(handlers::handle_completion)
+            .on_sync_mut::<lsp_request::DocumentDiagnosticRequest>(
+                handlers::handle_document_diagnostic,
+            )
+            .on_sync_mut::<lsp_request::WorkspaceDiagnosticRequest>(
+                handlers::handle_workspace_diagnostic,
+            )
+            .on_sync_mut::<lsp_request::ExecuteCommand>(handlers::handle_execute_command)
+            .on_sync_mut::<lsp_request::DocumentHighlightRequest>(
+                handlers::handle_document_highlight,
+            )
+            .on_sync_mut::<lsp_request::DocumentSymbolRequest>(handlers::handle_document_symbol)
+            .on_sync_mut::<lsp_request::Formatting>(handlers::handle_formatting)
+            .on_sync_mut::<lsp_request::RangeFormatting>(handlers::handle_range_formatting)
+            .on_sync_mut::<lsp_request::WorkspaceSymbolRequest>(handlers::handle_workspace_symbol)
+            .on_sync_mut::<lsp_request::WorkspaceSymbolResolve>(
+                handlers::handle_workspace_symbol_resolve,
+            )
+            .on_sync_mut::<lsp_request::HoverRequest>(handlers::handle_hover)
+            .on_sync_mut::<lsp_request::SignatureHelpRequest>(handlers::handle_signature_help)
+            .on_sync_mut::<lsp_request::FoldingRangeRequest>(handlers::handle_folding_range)
+            .on_sync_mut::<lsp_request::LinkedEditingRange>(handlers::handle_linked_editing_range)
+            .on_sync_mut::<lsp_request::SelectionRangeRequest>(handlers::handle_selection_range)
+            .on_sync_mut::<lsp_request::PrepareRenameRequest>(handlers::handle_prepare_rename)
+            .on_sync_mut::<lsp_request::Rename>(handlers::handle_rename)
+            .on_sync_mut::<lsp_request::SemanticTokensFullRequest>(
+                handlers::handle_semantic_tokens_full,
+            )
+            .on_sync_mut::<lsp_request::SemanticTokensFullDeltaRequest>(
+                handlers::handle_semantic_tokens_full_delta,
+            )
+            .on_sync_mut::<lsp_request::SemanticTokensRangeRequest>(
+                handlers::handle_semantic_tokens_range,
+            )
             .finish();
     }
 
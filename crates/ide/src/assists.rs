@@ -0,0 +1,168 @@
+//! Code actions: edits offered for a place in a file, such as the fixes of
+//! a diagnostic.
+
+use syntax::{
+    ast::{self, AstNode},
+    NodeOrToken,
+    SyntaxKind::*,
+    TextRange, TextSize,
+};
+
+use crate::{
+    db::RootDatabase,
+    docs, extract, inheritance,
+    items::{ComponentInfo, FunctionNode},
+    locking, properties, script_to_tags,
+    source_change::SourceChange,
+    tag_to_script,
+    text_edit::TextEdit,
+    FileRange,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AssistKind {
+    /// Fixes the problem reported by a diagnostic.
+    QuickFix,
+    /// Adds new code, like the methods of an interface.
+    Generate,
+    /// Moves code into a new function or variable.
+    RefactorExtract,
+    /// Rewrites code without changing what it does.
+    RefactorRewrite,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Assist {
+    /// A short description, shown in the editor's menu.
+    pub label: String,
+    pub kind: AssistKind,
+    pub source_change: SourceChange,
+}
+
+/// The refactorings applying to the selection `frange`.
+pub(crate) fn assists(db: &RootDatabase, frange: FileRange) -> Vec<Assist> {
+    [
+        extract::extract_function(db, frange),
+        extract::extract_variable(db, frange),
+        locking::wrap_in_lock(db, frange),
+        tag_to_script::convert_to_script(db, frange),
+        script_to_tags::convert_to_tags(db, frange),
+        docs::generate_docblock(db, frange),
+    ]
+    .into_iter()
+    .flatten()
+    .chain(properties::generate_from_properties(db, frange))
+    .chain(inheritance::override_methods(db, frange))
+    .collect()
+}
+
+/// The line ending used by `text`.
+pub(crate) fn line_ending(text: &str) -> &'static str {
+    if text.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    }
+}
+
+/// One level of indentation in `text`: a tab, or the leading spaces of
+/// the first indented line, four if there is none.
+pub(crate) fn indent_unit(text: &str) -> String {
+    let indent = text
+        .lines()
+        .filter(|it| !it.trim().is_empty())
+        .map(|it| &it[..it.len() - it.trim_start_matches([' ', '\t']).len()])
+        .find(|it| !it.is_empty());
+    match indent {
+        Some(it) if it.starts_with('\t') => "\t".to_owned(),
+        Some(it) => it.to_owned(),
+        None => "    ".to_owned(),
+    }
+}
+
+/// The indentation of the line containing `offset`.
+pub(crate) fn line_indent(text: &str, offset: TextSize) -> &str {
+    let offset = usize::from(offset);
+    let line_start = text[..offset].rfind('\n').map_or(0, |it| it + 1);
+    let line = &text[line_start..];
+    &line[..line.len() - line.trim_start_matches([' ', '\t']).len()]
+}
+
+/// Whether `range` lies in `component` outside of its functions, where
+/// members can be declared.
+pub(crate) fn at_member_level(
+    file: &ast::SourceFile,
+    component: &ComponentInfo,
+    range: TextRange,
+) -> bool {
+    let in_function = file
+        .syntax()
+        .descendants()
+        .filter_map(FunctionNode::cast)
+        .any(|it| it.syntax().text_range().contains_range(range));
+    component.range.contains_range(range) && !in_function
+}
+
+/// An edit adding `members` at the end of the body of the component or
+/// interface of `file`. Each member is written with `\n` line breaks and
+/// indented as if the body were not.
+pub(crate) fn add_members(
+    file: &ast::SourceFile,
+    text: &str,
+    members: &[String],
+) -> Option<TextEdit> {
+    let (body, open, close) = match file.component() {
+        Some(component) => {
+            let body = component.body()?.syntax().clone();
+            let open = body.first_token()?.text_range();
+            let close = body
+                .last_token()
+                .filter(|it| it.kind() == R_CURLY)?
+                .text_range();
+            (body, open, close)
+        }
+        None => {
+            let tag = file
+                .tags()
+                .find(|it| matches!(it.name().as_str(), "cfcomponent" | "cfinterface"))?;
+            let open = tag.start_tag()?.syntax().text_range();
+            let close = tag.end_tag()?.syntax().text_range();
+            (tag.syntax().clone(), open, close)
+        }
+    };
+    let last = body
+        .children_with_tokens()
+        .filter(|it| {
+            open.end() <= it.text_range().start() && it.text_range().end() <= close.start()
+        })
+        .filter(|it| match it {
+            NodeOrToken::Token(it) => !it.text().trim().is_empty(),
+            NodeOrToken::Node(_) => true,
+        })
+        .last();
+    let offset = last.as_ref().map_or(open.end(), |it| it.text_range().end());
+
+    let newline = line_ending(text);
+    let outer = line_indent(text, body.text_range().start());
+    let indent = format!("{outer}{}", indent_unit(text));
+    let mut insert = String::new();
+    for (i, member) in members.iter().enumerate() {
+        insert.push_str(newline);
+        if i > 0 || last.is_some() {
+            insert.push_str(newline);
+        }
+        let lines: Vec<_> = member
+            .lines()
+            .map(|line| match line.is_empty() {
+                true => String::new(),
+                false => format!("{indent}{line}"),
+            })
+            .collect();
+        insert.push_str(&lines.join(newline));
+    }
+    if !text[usize::from(offset)..usize::from(close.start())].contains('\n') {
+        insert.push_str(newline);
+        insert.push_str(outer);
+    }
+    Some(TextEdit::insert(offset, insert))
+}
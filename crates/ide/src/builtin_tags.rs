@@ -0,0 +1,179 @@
+//! The catalog of built-in CFML tags: their attributes, which of them are
+//! required and which only take one of a fixed set of values.
+//!
+//! Attributes are written as a comma-separated list of names; a trailing
+//! `?` marks an optional attribute and `=a|b|c` lists the values it
+//! accepts. Attributes only required in some forms of a tag, like `file` of
+//! `<cffile>`, are listed as optional, as are attributes only one of Adobe
+//! ColdFusion and Lucee requires; values either of them accepts are
+//! listed. Only the attributes worth checking are listed; tags accept
+//! others.
+
+use std::sync::OnceLock;
+
+use rustc_hash::FxHashMap;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuiltinTag {
+    /// The lowercase tag name, with its `cf` prefix.
+    pub name: &'static str,
+    pub attributes: Vec<TagAttribute>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagAttribute {
+    pub name: &'static str,
+    pub required: bool,
+    /// The accepted values, in lowercase; empty if any value is.
+    pub values: Vec<&'static str>,
+}
+
+impl BuiltinTag {
+    pub fn attribute(&self, name: &str) -> Option<&TagAttribute> {
+        self.attributes
+            .iter()
+            .find(|it| it.name.eq_ignore_ascii_case(name))
+    }
+}
+
+impl TagAttribute {
+    /// Whether `value` is one of the accepted values, ignoring case. SQL
+    /// types may omit their `cf_sql_` prefix.
+    pub fn accepts(&self, value: &str) -> bool {
+        if self.values.is_empty() {
+            return true;
+        }
+        let value = value.trim().to_ascii_lowercase();
+        self.values
+            .iter()
+            .any(|it| *it == value || it.strip_prefix("cf_sql_") == Some(value.as_str()))
+    }
+}
+
+/// Looks a built-in tag up by its name, ignoring case.
+pub fn builtin_tag(name: &str) -> Option<&'static BuiltinTag> {
+    static CATALOG: OnceLock<FxHashMap<&'static str, BuiltinTag>> = OnceLock::new();
+    let catalog = CATALOG.get_or_init(|| {
+        TAGS.iter()
+            .map(|&(name, attributes)| (name, parse_attributes(name, attributes)))
+            .collect()
+    });
+    catalog.get(name.to_ascii_lowercase().as_str())
+}
+
+fn parse_attributes(name: &'static str, attributes: &'static str) -> BuiltinTag {
+    let attributes = attributes
+        .split(',')
+        .map(str::trim)
+        .filter(|it| !it.is_empty())
+        .map(|attribute| {
+            let (attribute, values) = match attribute.split_once('=') {
+                Some((attribute, values)) => (attribute, values.split('|').collect()),
+                None => (attribute, Vec::new()),
+            };
+            let (name, required) = match attribute.strip_suffix('?') {
+                Some(name) => (name, false),
+                None => (attribute, true),
+            };
+            TagAttribute {
+                name,
+                required,
+                values,
+            }
+        })
+        .collect();
+    BuiltinTag { name, attributes }
+}
+
+/// The values of `cfsqltype`, as a literal for `concat!`.
+macro_rules! sql_types {
+    () => {
+        "cf_sql_array|cf_sql_bigint|cf_sql_binary|cf_sql_bit|cf_sql_blob|cf_sql_boolean|cf_sql_char|cf_sql_clob|cf_sql_date|cf_sql_decimal|cf_sql_double|cf_sql_float|cf_sql_idstamp|cf_sql_integer|cf_sql_longnvarchar|cf_sql_longvarbinary|cf_sql_longvarchar|cf_sql_money|cf_sql_money4|cf_sql_nchar|cf_sql_nclob|cf_sql_null|cf_sql_numeric|cf_sql_nvarchar|cf_sql_other|cf_sql_real|cf_sql_refcursor|cf_sql_smallint|cf_sql_sqlxml|cf_sql_struct|cf_sql_time|cf_sql_timestamp|cf_sql_tinyint|cf_sql_varbinary|cf_sql_varchar"
+    };
+}
+
+const TAGS: &[(&str, &str)] = &[
+    ("cfabort", "showerror?"),
+    ("cfargument", "name, type?, required?, default?, hint?, displayname?"),
+    ("cfcase", "value, delimiters?"),
+    ("cfcatch", "type?"),
+    ("cfcomponent", "extends?, implements?, output?, accessors?, persistent?, displayname?, hint?"),
+    ("cfcontent", "type?, file?, variable?, reset?, deletefile?"),
+    ("cfcookie", "name, value?, expires?, secure?, path?, domain?, httponly?, samesite?=strict|lax|none"),
+    ("cfdbinfo", "type=columns|dbnames|foreignkeys|index|procedures|procedure_columns|tables|terms|users|version, name, datasource?, dbname?, table?, pattern?, procedure?"),
+    ("cfdirectory", "action?=list|create|delete|rename, directory, name?, filter?, recurse?, sort?, listinfo?=all|name, newdirectory?, type?=all|dir|file, mode?"),
+    ("cfdump", "var?, label?, expand?, format?=html|text|simple|classic|console, output?, abort?, top?, keys?, show?, hide?"),
+    ("cfexecute", "name, arguments?, timeout?, variable?, outputfile?, errorvariable?, errorfile?"),
+    ("cfexit", "method?=exittag|exittemplate|loop"),
+    ("cffile", "action=append|copy|delete|move|read|readbinary|rename|upload|uploadall|write|touch|info, file?, destination?, source?, output?, variable?, filefield?, nameconflict?=error|skip|overwrite|makeunique|forceunique, charset?, accept?, mode?, result?, addnewline?, attributes?, fixnewline?, strict?, allowedextensions?"),
+    ("cfflush", "interval?"),
+    ("cffunction", "name, access?=private|package|public|remote, returntype?, output?, roles?, hint?, displayname?, description?, returnformat?, securejson?, verifyclient?"),
+    ("cfheader", "name?, value?, statuscode?, statustext?, charset?"),
+    ("cfhtmlhead", "text?, action?=append|read|reset|write|flush"),
+    ("cfhttp", "url, method?=get|post|put|delete|head|trace|options|patch, port?, result?, timeout?, username?, password?, useragent?, charset?, resolveurl?, throwonerror?, redirect?, getasbinary?=auto|yes|no|never|true|false, proxyserver?, proxyport?, proxyuser?, proxypassword?, name?, columns?, firstrowasheaders?, delimiter?, textqualifier?, file?, path?, multipart?, multiparttype?=form-data|related, clientcert?, clientcertpassword?, compression?, authtype?=basic|ntlm, encodeurl?, cachedwithin?"),
+    ("cfhttpparam", "type=header|body|xml|cgi|file|url|formfield|cookie, name?, value?, file?, encoded?, mimetype?"),
+    ("cfimport", "prefix?, taglib?, path?"),
+    ("cfinclude", "template, runonce?"),
+    ("cfinterface", "extends?, displayname?, hint?"),
+    ("cfinvoke", "method, component?, returnvariable?, argumentcollection?, webservice?, timeout?, username?, password?"),
+    ("cfinvokeargument", "name, value?, omit?"),
+    ("cflocation", "url, addtoken?, statuscode?=300|301|302|303|304|305|307|308, encode?"),
+    ("cflock", "timeout?, name?, scope?=application|request|server|session, type?=readonly|exclusive, throwontimeout?"),
+    ("cflog", "text, type?=information|warning|error|fatal|debug|trace|info|warn, log?, file?, application?"),
+    ("cfmail", "to, from, subject, cc?, bcc?, replyto?, type?=text|plain|html, server?, port?, username?, password?, charset?, mailerid?, priority?, query?, group?, failto?, spoolenable?, timeout?, usessl?, usetls?, wraptext?"),
+    ("cfmailparam", "name?, value?, file?, disposition?=inline|attachment, contentid?, type?, remove?, content?"),
+    ("cfmailpart", "type, charset?, wraptext?"),
+    ("cfmodule", "template?, name?, attributecollection?"),
+    ("cfobject", "name, type?=component|java|com|corba|dotnet|webservice, component?, class?, action?=create|connect, webservice?"),
+    ("cfoutput", "query?, group?, groupcasesensitive?, startrow?, maxrows?, encodefor?"),
+    ("cfparam", "name, type?, default?, min?, max?, pattern?"),
+    ("cfprocessingdirective", "pageencoding?, suppresswhitespace?"),
+    ("cfprocparam", concat!("type?=in|out|inout, cfsqltype?=", sql_types!(), ", value?, variable?, null?, maxlength?, scale?, dbvarname?")),
+    ("cfprocresult", "name, resultset?, maxrows?"),
+    ("cfproperty", "name, type?, default?, required?, hint?, displayname?, getter?, setter?"),
+    ("cfquery", "name?, datasource?, dbtype?=query|hql, result?, maxrows?, timeout?, cachedwithin?, cachedafter?, username?, password?, blockfactor?, debug?, returntype?=query|array|struct|array_of_entity, columnkey?"),
+    ("cfqueryparam", concat!("value?, cfsqltype?=", sql_types!(), ", sqltype?=", sql_types!(), ", maxlength?, scale?, null?, list?, separator?")),
+    ("cfsavecontent", "variable, trim?, append?"),
+    ("cfschedule", "action=create|delete|list|modify|pause|pauseall|resume|resumeall|run|update, task?, operation?, url?, startdate?, starttime?, interval?"),
+    ("cfsetting", "enablecfoutputonly?, showdebugoutput?, requesttimeout?"),
+    ("cfstoredproc", "procedure, datasource?, result?, returncode?, username?, password?, blockfactor?, debug?"),
+    ("cfswitch", "expression"),
+    ("cfthread", "action?=join|run|sleep|terminate, name?, priority?=high|low|normal, timeout?, duration?"),
+    ("cfthrow", "message?, type?, detail?, errorcode?, extendedinfo?, object?"),
+    ("cftimer", "label?, type?=inline|outline|comment|debug"),
+    ("cftransaction", "action?=begin|commit|rollback|setsavepoint, isolation?=read_uncommitted|read_committed|repeatable_read|serializable, savepoint?, nested?"),
+    ("cfwddx", "action=cfml2wddx|wddx2cfml|cfml2js|wddx2js, input, output?, toplevelvariable?, usetimezoneinfo?, validate?"),
+    ("cfzip", "action?=zip|unzip|delete|list|read|readbinary, file, destination?, source?, filter?, name?, overwrite?, prefix?, recurse?, storepath?, variable?"),
+    ("cfzipparam", "source?, content?, entrypath?, filter?, prefix?, recurse?"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn looks_up_attributes() {
+        let mail = builtin_tag("CFMAIL").unwrap();
+        let required: Vec<_> = mail
+            .attributes
+            .iter()
+            .filter(|it| it.required)
+            .map(|it| it.name)
+            .collect();
+        assert_eq!(required, ["to", "from", "subject"]);
+        let scope = builtin_tag("cflock").unwrap().attribute("Scope").unwrap();
+        assert!(scope.accepts("Session") && !scope.accepts("sesion"));
+        assert!(builtin_tag("cflock")
+            .unwrap()
+            .attribute("name")
+            .unwrap()
+            .accepts("any"));
+        let sql_type = builtin_tag("cfqueryparam")
+            .unwrap()
+            .attribute("cfsqltype")
+            .unwrap();
+        assert!(sql_type.accepts("cf_sql_varchar") && sql_type.accepts("VARCHAR"));
+        assert!(!sql_type.accepts("cf_sql_string"));
+        assert!(builtin_tag("cf_panel").is_none());
+    }
+}
@@ -0,0 +1,535 @@
+//! The catalog of built-in CFML functions: their parameters and whether
+//! they are deprecated.
+//!
+//! Signatures are written as comma-separated parameter names; a trailing
+//! `?` marks an optional parameter and a final `...` accepts any number of
+//! further arguments. Where Adobe ColdFusion and Lucee disagree on whether a
+//! parameter is required, it is listed as optional.
+
+use std::sync::OnceLock;
+
+use rustc_hash::FxHashMap;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuiltinFunction {
+    pub name: &'static str,
+    pub params: Vec<BuiltinParam>,
+    /// Whether arguments beyond `params` are accepted.
+    pub variadic: bool,
+    /// The function to use instead, for deprecated functions.
+    pub deprecated: Option<&'static str>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuiltinParam {
+    pub name: &'static str,
+    pub required: bool,
+}
+
+impl BuiltinFunction {
+    pub fn min_args(&self) -> usize {
+        self.params.iter().filter(|it| it.required).count()
+    }
+
+    /// The maximum number of arguments, `None` if unbounded.
+    pub fn max_args(&self) -> Option<usize> {
+        (!self.variadic).then_some(self.params.len())
+    }
+}
+
+/// Looks a built-in function up, ignoring case.
+pub fn builtin_function(name: &str) -> Option<&'static BuiltinFunction> {
+    static CATALOG: OnceLock<FxHashMap<String, BuiltinFunction>> = OnceLock::new();
+    let catalog = CATALOG.get_or_init(|| {
+        FUNCTIONS
+            .iter()
+            .map(|&(name, signature)| {
+                let function = parse_signature(name, signature);
+                (name.to_ascii_lowercase(), function)
+            })
+            .collect()
+    });
+    catalog.get(&name.to_ascii_lowercase())
+}
+
+fn parse_signature(name: &'static str, signature: &'static str) -> BuiltinFunction {
+    let mut params = Vec::new();
+    let mut variadic = false;
+    for param in signature
+        .split(',')
+        .map(str::trim)
+        .filter(|it| !it.is_empty())
+    {
+        if param == "..." {
+            variadic = true;
+        } else if let Some(name) = param.strip_suffix('?') {
+            params.push(BuiltinParam {
+                name,
+                required: false,
+            });
+        } else {
+            params.push(BuiltinParam {
+                name: param,
+                required: true,
+            });
+        }
+    }
+    let deprecated = DEPRECATED
+        .iter()
+        .find(|(it, _)| it.eq_ignore_ascii_case(name))
+        .map(|&(_, replacement)| replacement);
+    BuiltinFunction {
+        name,
+        params,
+        variadic,
+        deprecated,
+    }
+}
+
+/// Deprecated functions and what to use instead.
+const DEPRECATED: &[(&str, &str)] = &[
+    ("authenticatedContext", "getAuthUser"),
+    ("authenticatedUser", "getAuthUser"),
+    ("getK2ServerCollections", "cfcollection"),
+    ("getK2ServerDocCount", "cfcollection"),
+    ("getK2ServerDocCountLimit", "cfcollection"),
+    ("getTemplatePath", "getBaseTemplatePath"),
+    ("htmlCodeFormat", "encodeForHTML"),
+    ("htmlEditFormat", "encodeForHTML"),
+    ("isAuthenticated", "isUserLoggedIn"),
+    ("isAuthorized", "isUserInRole"),
+    ("isK2ServerABroker", "cfcollection"),
+    ("isK2ServerDocCountExceeded", "cfcollection"),
+    ("isK2ServerOnline", "cfcollection"),
+    ("parameterExists", "isDefined"),
+];
+
+const FUNCTIONS: &[(&str, &str)] = &[
+    // Arrays
+    ("arrayAppend", "array, value, merge?"),
+    ("arrayAvg", "array"),
+    ("arrayClear", "array"),
+    ("arrayContains", "array, value"),
+    ("arrayContainsNoCase", "array, value"),
+    ("arrayDelete", "array, value, scope?"),
+    ("arrayDeleteAt", "array, index"),
+    ("arrayDeleteNoCase", "array, value"),
+    ("arrayEach", "array, callback, parallel?, maxThreadCount?"),
+    ("arrayEvery", "array, callback, parallel?, maxThreadCount?"),
+    ("arrayFilter", "array, callback, parallel?, maxThreadCount?"),
+    ("arrayFind", "array, value"),
+    ("arrayFindAll", "array, value"),
+    ("arrayFindAllNoCase", "array, value"),
+    ("arrayFindNoCase", "array, value"),
+    ("arrayFirst", "array"),
+    ("arrayGetMetadata", "array"),
+    ("arrayInsertAt", "array, position, value"),
+    ("arrayIsDefined", "array, index"),
+    ("arrayIsEmpty", "array"),
+    ("arrayLast", "array"),
+    ("arrayLen", "array"),
+    ("arrayMap", "array, callback, parallel?, maxThreadCount?"),
+    ("arrayMax", "array"),
+    ("arrayMid", "array, start, length?"),
+    ("arrayMin", "array"),
+    ("arrayNew", "dimension?, isSynchronized?"),
+    ("arrayPop", "array"),
+    ("arrayPrepend", "array, value"),
+    ("arrayPush", "array, value"),
+    ("arrayReduce", "array, callback, initialValue?"),
+    ("arrayReduceRight", "array, callback, initialValue?"),
+    ("arrayResize", "array, size"),
+    ("arrayReverse", "array"),
+    ("arraySet", "array, start, end, value"),
+    ("arrayShift", "array"),
+    ("arraySlice", "array, offset, length?"),
+    ("arraySome", "array, callback, parallel?, maxThreadCount?"),
+    ("arraySort", "array, sortType, sortOrder?, localeSensitive?"),
+    ("arraySplice", "array, index, replaceCount?, replacements?"),
+    ("arraySum", "array"),
+    ("arraySwap", "array, position1, position2"),
+    ("arrayToList", "array, delimiter?"),
+    ("arrayUnshift", "array, value"),
+    ("isArray", "value, number?"),
+    // Structs
+    ("isStruct", "variable"),
+    ("structAppend", "struct1, struct2, overwriteFlag?"),
+    ("structClear", "struct"),
+    ("structCopy", "struct"),
+    ("structCount", "struct"),
+    ("structDelete", "struct, key, indicateNotExisting?"),
+    ("structEach", "struct, callback, parallel?, maxThreadCount?"),
+    ("structEvery", "struct, callback, parallel?, maxThreadCount?"),
+    ("structFilter", "struct, callback, parallel?, maxThreadCount?"),
+    ("structFind", "struct, key, defaultValue?"),
+    ("structFindKey", "top, value, scope?"),
+    ("structFindValue", "top, value, scope?"),
+    ("structGet", "path"),
+    ("structInsert", "struct, key, value, allowOverwrite?"),
+    ("structIsEmpty", "struct"),
+    ("structKeyArray", "struct"),
+    ("structKeyExists", "struct, key"),
+    ("structKeyList", "struct, delimiter?"),
+    ("structMap", "struct, callback, parallel?, maxThreadCount?"),
+    ("structNew", "type?, sortType?, sortOrder?, localeSensitive?"),
+    ("structReduce", "struct, callback, initialValue?"),
+    ("structSome", "struct, callback, parallel?, maxThreadCount?"),
+    ("structSort", "base, sortType?, sortOrder?, pathToSubElement?"),
+    ("structUpdate", "struct, key, value"),
+    ("structValueArray", "struct"),
+    // Strings
+    ("asc", "string"),
+    ("canonicalize", "string, restrictMultiple?, restrictMixed?, throwOnError?"),
+    ("charsetDecode", "string, encoding"),
+    ("charsetEncode", "binaryObject, encoding"),
+    ("chr", "number"),
+    ("cJustify", "string, length"),
+    ("compare", "string1, string2"),
+    ("compareNoCase", "string1, string2"),
+    ("decimalFormat", "number"),
+    ("dollarFormat", "number"),
+    ("encodeFor", "type, string, canonicalize?"),
+    ("encodeForCSS", "string, canonicalize?"),
+    ("encodeForDN", "string, canonicalize?"),
+    ("encodeForHTML", "string, canonicalize?"),
+    ("encodeForHTMLAttribute", "string, canonicalize?"),
+    ("encodeForJavaScript", "string, canonicalize?"),
+    ("encodeForLDAP", "string, canonicalize?"),
+    ("encodeForURL", "string, canonicalize?"),
+    ("encodeForXML", "string, canonicalize?"),
+    ("encodeForXMLAttribute", "string, canonicalize?"),
+    ("encodeForXPath", "string, canonicalize?"),
+    ("find", "substring, string, start?"),
+    ("findNoCase", "substring, string, start?"),
+    ("findOneOf", "set, string, start?"),
+    ("formatBaseN", "number, radix"),
+    ("getSafeHTML", "input, policyFile?, throwOnError?"),
+    ("getToken", "string, index, delimiters?"),
+    ("htmlCodeFormat", "string, version?"),
+    ("htmlEditFormat", "string, version?"),
+    ("insert", "substring, string, position"),
+    ("isSafeHTML", "input, policyFile?"),
+    ("jsStringFormat", "string"),
+    ("lCase", "string"),
+    ("left", "string, count"),
+    ("len", "value"),
+    ("lJustify", "string, length"),
+    ("lsCurrencyFormat", "number, type?, locale?"),
+    ("lsNumberFormat", "number, mask?, locale?"),
+    ("lsParseCurrency", "string, locale?"),
+    ("lsParseNumber", "string, locale?"),
+    ("lTrim", "string"),
+    ("mid", "string, start, count?"),
+    ("numberFormat", "number, mask?"),
+    ("paragraphFormat", "string"),
+    ("reFind", "regex, string, start?, returnSubExpressions?, scope?"),
+    ("reFindNoCase", "regex, string, start?, returnSubExpressions?, scope?"),
+    ("reMatch", "regex, string"),
+    ("reMatchNoCase", "regex, string"),
+    ("removeChars", "string, start, count"),
+    ("repeatString", "string, count"),
+    ("replace", "string, substring1, substring2, scope?"),
+    ("replaceList", "string, list1, list2, delimiter?"),
+    ("replaceNoCase", "string, substring1, substring2, scope?"),
+    ("reReplace", "string, regex, substring, scope?"),
+    ("reReplaceNoCase", "string, regex, substring, scope?"),
+    ("reverse", "string"),
+    ("right", "string, count"),
+    ("rJustify", "string, length"),
+    ("rTrim", "string"),
+    ("spanExcluding", "string, set"),
+    ("spanIncluding", "string, set"),
+    ("stripCR", "string"),
+    ("toBase64", "value, encoding?"),
+    ("toBinary", "value"),
+    ("toString", "value, encoding?"),
+    ("trim", "string"),
+    ("uCase", "string"),
+    ("ucFirst", "string, doAll?, doLowerIfAllUppercase?"),
+    ("urlDecode", "string, charset?"),
+    ("urlEncodedFormat", "string, charset?"),
+    ("val", "string"),
+    ("wrap", "string, limit, strip?"),
+    ("xmlFormat", "string, escapeChars?"),
+    // Lists
+    ("listAppend", "list, value, delimiters?, includeEmptyFields?"),
+    ("listChangeDelims", "list, newDelimiter, delimiters?, includeEmptyFields?"),
+    ("listContains", "list, substring, delimiters?, includeEmptyFields?"),
+    ("listContainsNoCase", "list, substring, delimiters?, includeEmptyFields?"),
+    ("listDeleteAt", "list, position, delimiters?, includeEmptyFields?"),
+    ("listEach", "list, callback, delimiters?, includeEmptyFields?"),
+    ("listFilter", "list, callback, delimiters?, includeEmptyFields?"),
+    ("listFind", "list, value, delimiters?, includeEmptyFields?"),
+    ("listFindNoCase", "list, value, delimiters?, includeEmptyFields?"),
+    ("listFirst", "list, delimiters?, includeEmptyFields?"),
+    ("listGetAt", "list, position, delimiters?, includeEmptyFields?"),
+    ("listInsertAt", "list, position, value, delimiters?, includeEmptyFields?"),
+    ("listLast", "list, delimiters?, includeEmptyFields?"),
+    ("listLen", "list, delimiters?, includeEmptyFields?"),
+    ("listMap", "list, callback, delimiters?, includeEmptyFields?"),
+    ("listPrepend", "list, value, delimiters?, includeEmptyFields?"),
+    ("listQualify", "list, qualifier, delimiters?, elements?, includeEmptyFields?"),
+    ("listReduce", "list, callback, initialValue?, delimiters?, includeEmptyFields?"),
+    ("listRemoveDuplicates", "list, delimiter?, ignoreCase?"),
+    ("listRest", "list, delimiters?, includeEmptyFields?"),
+    ("listSetAt", "list, position, value, delimiters?, includeEmptyFields?"),
+    ("listSort", "list, sortType, sortOrder?, delimiters?, localeSensitive?"),
+    ("listToArray", "list, delimiters?, includeEmptyFields?, multiCharacterDelimiter?"),
+    ("listValueCount", "list, value, delimiters?"),
+    ("listValueCountNoCase", "list, value, delimiters?"),
+    // Decisions
+    ("isBinary", "value"),
+    ("isBoolean", "value"),
+    ("isClosure", "value"),
+    ("isCustomFunction", "value"),
+    ("isDate", "value"),
+    ("isDebugMode", ""),
+    ("isDefined", "variableName"),
+    ("isInstanceOf", "object, typeName"),
+    ("isJSON", "value"),
+    ("isLeapYear", "year"),
+    ("isNull", "value"),
+    ("isNumeric", "value"),
+    ("isNumericDate", "number"),
+    ("isObject", "value"),
+    ("isQuery", "value"),
+    ("isSimpleValue", "value"),
+    ("isUserInAnyRole", "roles"),
+    ("isUserInRole", "roles"),
+    ("isUserLoggedIn", ""),
+    ("isValid", "type, value, min?, max?"),
+    ("isXML", "value"),
+    ("isXmlDoc", "value"),
+    ("isXmlElem", "value"),
+    ("isXmlNode", "value"),
+    ("isXmlRoot", "value"),
+    // Math
+    ("abs", "number"),
+    ("aCos", "number"),
+    ("aSin", "number"),
+    ("atn", "number"),
+    ("bitAnd", "number1, number2"),
+    ("bitMaskClear", "number, start, length"),
+    ("bitMaskRead", "number, start, length"),
+    ("bitMaskSet", "number, mask, start, length"),
+    ("bitNot", "number"),
+    ("bitOr", "number1, number2"),
+    ("bitSHLN", "number, count"),
+    ("bitSHRN", "number, count"),
+    ("bitXor", "number1, number2"),
+    ("ceiling", "number"),
+    ("cos", "number"),
+    ("decrementValue", "number"),
+    ("exp", "number"),
+    ("fix", "number"),
+    ("incrementValue", "number"),
+    ("inputBaseN", "string, radix"),
+    ("int", "number"),
+    ("log", "number"),
+    ("log10", "number"),
+    ("max", "number1, number2"),
+    ("min", "number1, number2"),
+    ("pi", ""),
+    ("precisionEvaluate", "expression, ..."),
+    ("rand", "algorithm?"),
+    ("randomize", "seed, algorithm?"),
+    ("randRange", "number1, number2, algorithm?"),
+    ("round", "number, precision?"),
+    ("sgn", "number"),
+    ("sin", "number"),
+    ("sqr", "number"),
+    ("tan", "number"),
+    // Dates
+    ("createDate", "year, month, day"),
+    ("createDateTime", "year, month?, day?, hour?, minute?, second?, millisecond?"),
+    ("createODBCDate", "date"),
+    ("createODBCDateTime", "date"),
+    ("createODBCTime", "date"),
+    ("createTime", "hour, minute?, second?, millisecond?"),
+    ("createTimeSpan", "days, hours, minutes, seconds"),
+    ("dateAdd", "datePart, number, date"),
+    ("dateCompare", "date1, date2, datePart?"),
+    ("dateConvert", "conversionType, date"),
+    ("dateDiff", "datePart, date1, date2"),
+    ("dateFormat", "date, mask?"),
+    ("datePart", "datePart, date"),
+    ("dateTimeFormat", "date, mask?, timeZone?"),
+    ("day", "date"),
+    ("dayOfWeek", "date"),
+    ("dayOfWeekAsString", "dayOfWeek, locale?"),
+    ("dayOfYear", "date"),
+    ("daysInMonth", "date"),
+    ("daysInYear", "date"),
+    ("firstDayOfMonth", "date"),
+    ("getHttpTimeString", "date?"),
+    ("getTickCount", ""),
+    ("getTimeZoneInfo", "timeZone?, locale?"),
+    ("hour", "date"),
+    ("lsDateFormat", "date, mask?, locale?"),
+    ("lsDateTimeFormat", "date, mask?, locale?, timeZone?"),
+    ("lsIsDate", "date, locale?"),
+    ("lsParseDateTime", "date, locale?, format?"),
+    ("lsTimeFormat", "time, mask?, locale?"),
+    ("minute", "date"),
+    ("month", "date"),
+    ("monthAsString", "month, locale?"),
+    ("now", ""),
+    ("parseDateTime", "date, format?, timeZone?"),
+    ("quarter", "date"),
+    ("second", "date"),
+    ("timeFormat", "time, mask?"),
+    ("week", "date"),
+    ("year", "date"),
+    // Queries
+    ("preserveSingleQuotes", "variable"),
+    ("queryAddColumn", "query, column, dataType?, values?"),
+    ("queryAddRow", "query, rowData?"),
+    ("queryAppend", "query1, query2"),
+    ("queryDeleteRow", "query, row"),
+    ("queryEach", "query, callback, parallel?, maxThreadCount?"),
+    ("queryExecute", "sql, params?, options?"),
+    ("queryFilter", "query, callback, parallel?, maxThreadCount?"),
+    ("queryGetRow", "query, row"),
+    ("queryKeyExists", "query, key"),
+    ("queryMap", "query, callback, parallel?, maxThreadCount?"),
+    ("queryNew", "columnList, columnTypeList?, rowData?"),
+    ("queryRecordCount", "query"),
+    ("queryReduce", "query, callback, initialValue?"),
+    ("querySetCell", "query, column, value, row?"),
+    ("querySort", "query, callback, sortOrder?"),
+    ("quotedValueList", "column, delimiter?"),
+    ("valueList", "column, delimiter?"),
+    // Files and directories
+    ("directoryCreate", "path, createPath?, ignoreExists?"),
+    ("directoryDelete", "path, recurse?"),
+    ("directoryExists", "path"),
+    ("directoryList", "path, recurse?, listInfo?, filter?, sort?, type?"),
+    ("directoryRename", "oldPath, newPath, createPath?"),
+    ("expandPath", "path"),
+    ("fileAppend", "file, data, charset?"),
+    ("fileClose", "file"),
+    ("fileCopy", "source, destination"),
+    ("fileDelete", "file"),
+    ("fileExists", "path"),
+    ("fileGetMimeType", "file, strict?"),
+    ("fileIsEOF", "file"),
+    ("fileMove", "source, destination"),
+    ("fileOpen", "path, mode?, charset?, seekable?"),
+    ("fileRead", "file, charsetOrBufferSize?"),
+    ("fileReadBinary", "path"),
+    ("fileReadLine", "file"),
+    ("fileSetLastModified", "path, date"),
+    ("fileWrite", "file, data, charset?"),
+    ("fileWriteLine", "file, data"),
+    ("getCurrentTemplatePath", ""),
+    ("getBaseTemplatePath", ""),
+    ("getDirectoryFromPath", "path"),
+    ("getFileFromPath", "path"),
+    ("getFileInfo", "path"),
+    ("getTempDirectory", ""),
+    ("getTempFile", "directory, prefix"),
+    ("getTemplatePath", ""),
+    // JSON, XML and encoding
+    ("binaryDecode", "string, encoding"),
+    ("binaryEncode", "binary, encoding"),
+    ("decrypt", "string, key, algorithm?, encoding?, iv?, iterations?"),
+    ("deserializeJSON", "json, strictMapping?, useCustomSerializer?"),
+    ("encrypt", "string, key, algorithm?, encoding?, iv?, iterations?"),
+    ("generateSecretKey", "algorithm?, keySize?"),
+    ("hash", "string, algorithm?, encoding?, iterations?"),
+    ("hmac", "message, key, algorithm?, encoding?"),
+    ("serializeJSON", "value, serializeQueryByColumns?, useSecureJSONPrefix?, useCustomSerializer?"),
+    ("xmlElemNew", "xmlDoc, namespace?, childName?"),
+    ("xmlNew", "caseSensitive?"),
+    ("xmlParse", "xml, caseSensitive?, validator?"),
+    ("xmlSearch", "xmlDoc, xPath, params?"),
+    ("xmlTransform", "xml, xsl, parameters?"),
+    ("xmlValidate", "xmlDoc, validator?"),
+    // System and runtime
+    ("abort", "showError?"),
+    ("applicationStop", ""),
+    ("cacheClear", "filter?, region?"),
+    ("cacheGet", "id, region?"),
+    ("cachePut", "id, value, timeSpan?, idleTime?, region?, throwOnError?"),
+    ("cacheRemove", "ids, throwOnError?, region?, exact?"),
+    ("callStackDump", "output?"),
+    ("callStackGet", ""),
+    ("createGUID", ""),
+    ("createObject", "type, name?, context?, ..."),
+    ("createUUID", ""),
+    ("de", "string"),
+    ("dump", "var?, ..."),
+    ("duplicate", "value"),
+    ("echo", "string"),
+    ("entityDelete", "entity"),
+    ("entityLoad", "name, ..."),
+    ("entityLoadByPK", "name, id, ..."),
+    ("entityNew", "name, properties?"),
+    ("entitySave", "entity, forceInsert?"),
+    ("evaluate", "expression, ..."),
+    ("getApplicationMetadata", ""),
+    ("getAuthUser", ""),
+    ("getComponentMetadata", "path"),
+    ("getEncoding", "scope"),
+    ("getFunctionCalledName", ""),
+    ("getHttpRequestData", "includeBody?"),
+    ("getLocale", ""),
+    ("getMetadata", "value"),
+    ("getPageContext", ""),
+    ("iif", "condition, expression1, expression2"),
+    ("invoke", "object, method, arguments?"),
+    ("javaCast", "type, value"),
+    ("location", "url, addToken?, statusCode?"),
+    ("ormExecuteQuery", "hql, ..."),
+    ("rethrow", ""),
+    ("runAsync", "function, timeout?"),
+    ("sessionInvalidate", ""),
+    ("sessionRotate", ""),
+    ("setEncoding", "scope, charset"),
+    ("setLocale", "locale"),
+    ("setVariable", "name, value"),
+    ("sleep", "duration"),
+    ("threadJoin", "name?, timeout?"),
+    ("threadTerminate", "name"),
+    ("throw", "message?, type?, detail?, errorCode?, extendedInfo?, object?"),
+    ("transactionCommit", ""),
+    ("transactionRollback", "savepoint?"),
+    ("transactionSetSavepoint", "savepoint?"),
+    ("writeDump", "var, output?, format?, abort?, label?, metainfo?, top?, show?, hide?, keys?, expand?, showUDFs?"),
+    ("writeLog", "text, type?, application?, file?, log?"),
+    ("writeOutput", "string"),
+    // Security, deprecated
+    ("authenticatedContext", ""),
+    ("authenticatedUser", ""),
+    ("getK2ServerCollections", ""),
+    ("getK2ServerDocCount", ""),
+    ("getK2ServerDocCountLimit", ""),
+    ("isAuthenticated", "securityContext?"),
+    ("isAuthorized", "securityContext?, resourceType?, resourceName?, action?"),
+    ("isK2ServerABroker", ""),
+    ("isK2ServerDocCountExceeded", ""),
+    ("isK2ServerOnline", ""),
+    ("parameterExists", "parameter"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn looks_up_signatures() {
+        let mid = builtin_function("MID").unwrap();
+        assert_eq!((mid.min_args(), mid.max_args()), (2, Some(3)));
+        let evaluate = builtin_function("evaluate").unwrap();
+        assert_eq!((evaluate.min_args(), evaluate.max_args()), (1, None));
+        assert_eq!(builtin_function("now").unwrap().max_args(), Some(0));
+        assert_eq!(
+            builtin_function("htmlEditFormat").unwrap().deprecated,
+            Some("encodeForHTML")
+        );
+        assert!(builtin_function("myHelper").is_none());
+        assert!(DEPRECATED
+            .iter()
+            .all(|(name, _)| builtin_function(name).is_some()));
+    }
+}
@@ -0,0 +1,173 @@
+//! Completion of the methods of components after a dot: `user.|`, including
+//! the implicit accessors of their properties.
+
+use rustc_hash::FxHashSet;
+use syntax::TextRange;
+
+use crate::{db::RootDatabase, usages::Receiver, FilePosition};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompletionItem {
+    /// The name of the method, which is also the inserted text.
+    pub label: String,
+    /// The signature of the method: `string getName()`.
+    pub detail: String,
+    /// The documentation of the method, in Markdown.
+    pub documentation: Option<String>,
+    /// The range the inserted text replaces.
+    pub source_range: TextRange,
+}
+
+/// The methods that can follow the dot before `position`, nearest
+/// declarations first. Only public and remote methods are offered on
+/// values other than `this` and `super`.
+pub(crate) fn completions(db: &RootDatabase, position: FilePosition) -> Vec<CompletionItem> {
+    let usages = db.usages(position.file_id);
+    let Some(member) = usages.member_at(position.offset) else {
+        return Vec::new();
+    };
+    let (target, public_only) = match &member.receiver {
+        Receiver::None | Receiver::This => (Some(position.file_id), false),
+        Receiver::Super => (db.parent_component(position.file_id), false),
+        Receiver::Component(path) => (db.resolve_component(position.file_id, path), true),
+        Receiver::Unknown => (None, false),
+    };
+    let Some(target) = target else {
+        return Vec::new();
+    };
+
+    let mut seen = FxHashSet::default();
+    let mut res = Vec::new();
+    for file_id in db.component_chain(target) {
+        let items = db.items(file_id);
+        let accessors = items
+            .component
+            .as_ref()
+            .map(|it| it.accessors())
+            .unwrap_or_default();
+        for function in items.functions.iter().chain(&accessors) {
+            let is_public = function
+                .access
+                .as_deref()
+                .is_none_or(|it| matches!(it, "public" | "remote"));
+            if function.is_static
+                || (public_only && !is_public)
+                || !seen.insert(function.name.to_ascii_lowercase())
+            {
+                continue;
+            }
+            res.push(CompletionItem {
+                label: function.name.clone(),
+                detail: function.signature(),
+                documentation: function.doc.markdown(),
+                source_range: member.name_range,
+            });
+        }
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use virtual_fs::FileId;
+
+    use crate::{fixture, FilePosition};
+
+    fn check(fixture: &str, expect: &[(&str, &str)]) {
+        let (fixture, range) = fixture::selection(fixture);
+        let analysis = fixture::parse(&fixture).analysis;
+        let items = analysis.completions(FilePosition {
+            file_id: FileId(0),
+            offset: range.start(),
+        });
+        let items: Vec<_> = items
+            .iter()
+            .map(|it| (it.label.as_str(), it.detail.as_str()))
+            .collect();
+        assert_eq!(items, expect);
+    }
+
+    #[test]
+    fn completes_implicit_accessors() {
+        check(
+            r#"
+//- /handlers/Main.cfc
+component {
+    function index() {
+        var user = new models.User();
+        user.$0
+    }
+}
+//- /models/User.cfc
+component accessors="true" extends="Entity" {
+    property string name;
+
+    function getName() {
+        return ucase(variables.name);
+    }
+
+    private function hash() {}
+}
+//- /models/Entity.cfc
+component accessors="true" {
+    property numeric id;
+
+    function save() {}
+}
+"#,
+            &[
+                ("getName", "any getName()"),
+                ("setName", "any setName(string name)"),
+                ("save", "any save()"),
+                ("getId", "numeric getId()"),
+                ("setId", "any setId(numeric id)"),
+            ],
+        );
+    }
+
+    #[test]
+    fn completes_partial_names_on_this() {
+        check(
+            r#"
+//- /User.cfc
+component accessors="true" {
+    property email;
+
+    private function validate() {
+        this.get$0
+    }
+}
+"#,
+            &[
+                ("validate", "any validate()"),
+                ("getEmail", "any getEmail()"),
+                ("setEmail", "any setEmail(email)"),
+            ],
+        );
+    }
+
+    #[test]
+    fn documents_completions() {
+        let (fixture, range) = fixture::selection(
+            r#"
+//- /page.cfm
+<cfset new Users().$0>
+//- /Users.cfc
+component {
+    /**
+     * Finds a user.
+     * @return The user
+     */
+    function find() {}
+}
+"#,
+        );
+        let analysis = fixture::parse(&fixture).analysis;
+        let items = analysis.completions(FilePosition {
+            file_id: FileId(0),
+            offset: range.start(),
+        });
+        let docs: Vec<_> = items.iter().map(|it| it.documentation.as_deref()).collect();
+        assert_eq!(docs, [Some("Finds a user.\n\n**Returns** The user")]);
+    }
+}
@@ -0,0 +1,247 @@
+//! The files known to the analysis and the facts derived from them.
+//!
+//! Derived data (syntax trees, declarations, usages) is computed lazily and
+//! cached per file; changing a file replaces its entry, dropping the caches.
+//! Each entry records the revision of the database it was set in.
+
+use std::sync::{Arc, OnceLock};
+
+use rustc_hash::{FxHashMap, FxHashSet};
+use syntax::{ast::SourceFile, Parse};
+use virtual_fs::{FileId, VirtualFsPath};
+
+use crate::{
+    items::{FileItems, FunctionInfo},
+    line_index::LineIndex,
+    symbol_index::{self, IndexedSymbol},
+    usages::FileUsages,
+};
+
+#[derive(Debug)]
+pub(crate) struct FileData {
+    pub(crate) path: VirtualFsPath,
+    pub(crate) text: Arc<str>,
+    pub(crate) revision: u64,
+    parse: OnceLock<Parse>,
+    line_index: OnceLock<Arc<LineIndex>>,
+    items: OnceLock<Arc<FileItems>>,
+    usages: OnceLock<Arc<FileUsages>>,
+    symbols: OnceLock<Arc<[IndexedSymbol]>>,
+}
+
+impl FileData {
+    fn new(path: VirtualFsPath, text: Arc<str>, revision: u64) -> FileData {
+        FileData {
+            path,
+            text,
+            revision,
+            parse: OnceLock::new(),
+            line_index: OnceLock::new(),
+            items: OnceLock::new(),
+            usages: OnceLock::new(),
+            symbols: OnceLock::new(),
+        }
+    }
+}
+
+/// An immutable view of all files. Cloning is cheap; the host copies the
+/// file table on write only if a snapshot is still alive.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct RootDatabase {
+    files: Arc<FxHashMap<FileId, Arc<FileData>>>,
+    /// Lowercase path of every file, for case-insensitive resolution.
+    paths: Arc<FxHashMap<String, FileId>>,
+    /// The directories component paths are resolved against.
+    roots: Arc<Vec<VirtualFsPath>>,
+    /// Bumped on every file change.
+    revision: u64,
+    /// The revision in which a file was last added or removed, or the roots
+    /// changed: the last time any path may have started or stopped
+    /// resolving.
+    layout_revision: u64,
+}
+
+impl RootDatabase {
+    pub(crate) fn set_file_text(
+        &mut self,
+        file_id: FileId,
+        path: VirtualFsPath,
+        text: Option<Arc<str>>,
+    ) {
+        self.revision += 1;
+        let files = Arc::make_mut(&mut self.files);
+        let paths = Arc::make_mut(&mut self.paths);
+        let old = files.remove(&file_id);
+        if let Some(old) = &old {
+            paths.remove(&path_key(&old.path));
+        }
+        if old.is_some() != text.is_some() {
+            self.layout_revision = self.revision;
+        }
+        if let Some(text) = text {
+            paths.insert(path_key(&path), file_id);
+            let data = FileData::new(path, text, self.revision);
+            files.insert(file_id, Arc::new(data));
+        }
+    }
+
+    pub(crate) fn set_roots(&mut self, roots: Vec<VirtualFsPath>) {
+        self.revision += 1;
+        self.layout_revision = self.revision;
+        self.roots = Arc::new(roots);
+    }
+
+    pub(crate) fn layout_revision(&self) -> u64 {
+        self.layout_revision
+    }
+
+    pub(crate) fn file(&self, file_id: FileId) -> Option<&FileData> {
+        self.files.get(&file_id).map(|it| &**it)
+    }
+
+    /// All files, in a stable order.
+    pub(crate) fn files(&self) -> Vec<FileId> {
+        let mut files: Vec<_> = self.files.keys().copied().collect();
+        files.sort();
+        files
+    }
+
+    pub(crate) fn file_text(&self, file_id: FileId) -> Arc<str> {
+        self.file(file_id)
+            .map_or_else(|| Arc::from(""), |it| it.text.clone())
+    }
+
+    pub(crate) fn file_path(&self, file_id: FileId) -> Option<&VirtualFsPath> {
+        self.file(file_id).map(|it| &it.path)
+    }
+
+    pub(crate) fn parse(&self, file_id: FileId) -> Parse {
+        match self.file(file_id) {
+            Some(file) => file
+                .parse
+                .get_or_init(|| SourceFile::parse(&file.text))
+                .clone(),
+            None => SourceFile::parse(""),
+        }
+    }
+
+    pub(crate) fn line_index(&self, file_id: FileId) -> Arc<LineIndex> {
+        match self.file(file_id) {
+            Some(file) => file
+                .line_index
+                .get_or_init(|| Arc::new(LineIndex::new(&file.text)))
+                .clone(),
+            None => Arc::new(LineIndex::new("")),
+        }
+    }
+
+    pub(crate) fn items(&self, file_id: FileId) -> Arc<FileItems> {
+        let compute = || Arc::new(FileItems::new(&self.parse(file_id).tree()));
+        match self.file(file_id) {
+            Some(file) => file.items.get_or_init(compute).clone(),
+            None => compute(),
+        }
+    }
+
+    pub(crate) fn usages(&self, file_id: FileId) -> Arc<FileUsages> {
+        let compute = || Arc::new(FileUsages::new(&self.parse(file_id).tree()));
+        match self.file(file_id) {
+            Some(file) => file.usages.get_or_init(compute).clone(),
+            None => compute(),
+        }
+    }
+
+    pub(crate) fn file_symbols(&self, file_id: FileId) -> Arc<[IndexedSymbol]> {
+        let compute = || symbol_index::file_symbols(self, file_id);
+        match self.file(file_id) {
+            Some(file) => file.symbols.get_or_init(compute).clone(),
+            None => compute(),
+        }
+    }
+
+    /// The name of the component defined by `file_id`: its file name
+    /// without the `.cfc` extension.
+    pub(crate) fn component_name(&self, file_id: FileId) -> Option<String> {
+        match self.file_path(file_id)?.name_and_extension()? {
+            (name, Some(ext)) if ext.eq_ignore_ascii_case("cfc") => Some(name.to_owned()),
+            _ => None,
+        }
+    }
+
+    /// Resolves a dotted component path as written in `from`: first relative
+    /// to the directory of `from`, then relative to each root.
+    pub(crate) fn resolve_component(&self, from: FileId, path: &str) -> Option<FileId> {
+        let path = path.trim().trim_start_matches('/');
+        if path.is_empty() || path.split('.').any(|it| it.is_empty()) {
+            return None;
+        }
+        let rel = format!("{}.cfc", path.replace('.', "/"));
+        let dir = self.file_path(from).and_then(VirtualFsPath::parent);
+        dir.iter()
+            .chain(self.roots.iter())
+            .find_map(|base| self.paths.get(&path_key(&base.join(&rel)?)).copied())
+    }
+
+    /// The component extended by `file_id`.
+    pub(crate) fn parent_component(&self, file_id: FileId) -> Option<FileId> {
+        let items = self.items(file_id);
+        let extends = items.component.as_ref()?.extends.as_ref()?;
+        self.resolve_component(file_id, &extends.path)
+    }
+
+    /// `file_id` followed by the components it extends, nearest first.
+    pub(crate) fn component_chain(&self, file_id: FileId) -> Vec<FileId> {
+        let mut chain = vec![file_id];
+        let mut seen = FxHashSet::default();
+        seen.insert(file_id);
+        while let Some(parent) = self.parent_component(*chain.last().unwrap()) {
+            if !seen.insert(parent) {
+                break;
+            }
+            chain.push(parent);
+        }
+        chain
+    }
+
+    /// Looks `name` up among the functions of `file_id` and the components
+    /// it extends, counting the implicit accessors of their properties. A
+    /// function declared in a component wins over its implicit accessor.
+    pub(crate) fn find_function(
+        &self,
+        file_id: FileId,
+        name: &str,
+    ) -> Option<(FileId, FunctionInfo)> {
+        self.component_chain(file_id)
+            .into_iter()
+            .find_map(|file_id| {
+                let items = self.items(file_id);
+                let function = match items.function(name) {
+                    Some(it) => it.clone(),
+                    None => items.component.as_ref()?.accessor(name)?,
+                };
+                Some((file_id, function))
+            })
+    }
+
+    /// Files whose text contains `needle`, ignoring ASCII case. Used to
+    /// narrow down workspace-wide searches before parsing.
+    pub(crate) fn files_containing(&self, needle: &str) -> Vec<FileId> {
+        let needle = needle.to_ascii_lowercase();
+        self.files()
+            .into_iter()
+            .filter(|&file_id| {
+                let text = &self.files[&file_id].text;
+                text.to_ascii_lowercase().contains(&needle)
+            })
+            .collect()
+    }
+
+    /// Whether a file exists at `path`, ignoring case.
+    pub(crate) fn file_exists(&self, path: &VirtualFsPath) -> bool {
+        self.paths.contains_key(&path_key(path))
+    }
+}
+
+fn path_key(path: &VirtualFsPath) -> String {
+    path.to_string().to_lowercase()
+}
@@ -0,0 +1,943 @@
+//! Problems reported for a file: syntax errors, calls to functions that do
+//! not exist or with the wrong arguments, misused built-in tags, component
+//! paths that do not resolve, and the lints of [`crate::lints`].
+//!
+//! Every kind of diagnostic is a [`Rule`] that can be turned off, given
+//! another severity or, for some, tuned with options.
+//!
+//! The semantic checks stay quiet whenever the file may see names they
+//! cannot: when it includes other templates, imports component paths, or
+//! extends a component that cannot be found.
+
+use std::str::FromStr;
+
+use rustc_hash::{FxHashMap, FxHashSet};
+use syntax::{
+    ast::{self, AstNode},
+    SyntaxKind::*,
+    TextRange,
+};
+use virtual_fs::FileId;
+
+use crate::{
+    assists::{Assist, AssistKind},
+    builtin_tags::builtin_tag,
+    builtins::builtin_function,
+    db::RootDatabase,
+    inheritance,
+    items::{FunctionInfo, FunctionNode},
+    lints, locking,
+    references::resolve_call,
+    script_to_tags,
+    security::{self, TaintRule},
+    source_change::SourceChange,
+    suppressions, taint,
+    text_edit::TextEdit,
+    usages::{CallSite, Receiver},
+    xss,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Severity {
+    Error,
+    Warning,
+    Info,
+    Hint,
+}
+
+/// A stable identifier of the kind of a diagnostic, shown to users and used
+/// to configure it. Lints ported from CFLint keep their CFLint names.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DiagnosticCode(pub &'static str);
+
+impl DiagnosticCode {
+    pub const SYNTAX_ERROR: DiagnosticCode = DiagnosticCode("syntax-error");
+    pub const UNRESOLVED_FUNCTION: DiagnosticCode = DiagnosticCode("unresolved-function");
+    pub const MISSING_ARGUMENT: DiagnosticCode = DiagnosticCode("missing-argument");
+    pub const TOO_MANY_ARGUMENTS: DiagnosticCode = DiagnosticCode("too-many-arguments");
+    pub const UNKNOWN_NAMED_ARGUMENT: DiagnosticCode = DiagnosticCode("unknown-named-argument");
+    pub const MISSING_ATTRIBUTE: DiagnosticCode = DiagnosticCode("missing-attribute");
+    pub const INVALID_ATTRIBUTE_VALUE: DiagnosticCode = DiagnosticCode("invalid-attribute-value");
+    pub const UNRESOLVED_COMPONENT: DiagnosticCode = DiagnosticCode("unresolved-component");
+    pub const MISSING_INTERFACE_METHOD: DiagnosticCode = DiagnosticCode("missing-interface-method");
+    pub const UNENCODED_OUTPUT: DiagnosticCode = DiagnosticCode("unencoded-output");
+    pub const TAINTED_CODE: DiagnosticCode = DiagnosticCode("tainted-code");
+    pub const TAINTED_FILE_PATH: DiagnosticCode = DiagnosticCode("tainted-file-path");
+    pub const TAINTED_COMMAND: DiagnosticCode = DiagnosticCode("tainted-command");
+    pub const TAINTED_REDIRECT: DiagnosticCode = DiagnosticCode("tainted-redirect");
+    pub const TAINTED_DATA: DiagnosticCode = DiagnosticCode("tainted-data");
+    pub const UNLOCKED_SCOPE_WRITE: DiagnosticCode = DiagnosticCode("unlocked-scope-write");
+    pub const UNLOCKED_SCOPE_READ: DiagnosticCode = DiagnosticCode("unlocked-scope-read");
+    pub const NO_TAG_EQUIVALENT: DiagnosticCode = DiagnosticCode("no-tag-equivalent");
+    pub const MISSING_VAR: DiagnosticCode = DiagnosticCode("MISSING_VAR");
+    pub const ARG_VAR_CONFLICT: DiagnosticCode = DiagnosticCode("ARG_VAR_CONFLICT");
+    pub const CFQUERYPARAM_REQ: DiagnosticCode = DiagnosticCode("CFQUERYPARAM_REQ");
+    pub const QUERYPARAM_REQ: DiagnosticCode = DiagnosticCode("QUERYPARAM_REQ");
+    pub const AVOID_USING_CFDUMP_TAG: DiagnosticCode = DiagnosticCode("AVOID_USING_CFDUMP_TAG");
+    pub const AVOID_USING_WRITEDUMP: DiagnosticCode = DiagnosticCode("AVOID_USING_WRITEDUMP");
+    pub const AVOID_USING_CFABORT_TAG: DiagnosticCode = DiagnosticCode("AVOID_USING_CFABORT_TAG");
+    pub const AVOID_USING_ABORT: DiagnosticCode = DiagnosticCode("AVOID_USING_ABORT");
+    pub const AVOID_USING_CFEXECUTE_TAG: DiagnosticCode =
+        DiagnosticCode("AVOID_USING_CFEXECUTE_TAG");
+    pub const EXCESSIVE_FUNCTION_LENGTH: DiagnosticCode =
+        DiagnosticCode("EXCESSIVE_FUNCTION_LENGTH");
+    pub const EXCESSIVE_ARGUMENTS: DiagnosticCode = DiagnosticCode("EXCESSIVE_ARGUMENTS");
+
+    pub fn as_str(&self) -> &'static str {
+        self.0
+    }
+}
+
+/// A kind of diagnostic and its defaults.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rule {
+    pub code: DiagnosticCode,
+    pub severity: Severity,
+    pub description: &'static str,
+    /// The CFLint checker the rule is ported from. Its name prefixes the
+    /// rule's parameters in a `.cflintrc`.
+    pub checker: Option<&'static str>,
+    /// The options of the rule and their default values.
+    pub options: &'static [(&'static str, &'static str)],
+}
+
+const fn rule(code: DiagnosticCode, severity: Severity, description: &'static str) -> Rule {
+    Rule {
+        code,
+        severity,
+        description,
+        checker: None,
+        options: &[],
+    }
+}
+
+const fn cflint(
+    code: DiagnosticCode,
+    severity: Severity,
+    checker: &'static str,
+    description: &'static str,
+) -> Rule {
+    Rule {
+        checker: Some(checker),
+        ..rule(code, severity, description)
+    }
+}
+
+pub const RULES: &[Rule] = &[
+    rule(
+        DiagnosticCode::SYNTAX_ERROR,
+        Severity::Error,
+        "The file does not parse.",
+    ),
+    rule(
+        DiagnosticCode::UNRESOLVED_FUNCTION,
+        Severity::Warning,
+        "A function that is neither built in nor declared is called.",
+    ),
+    rule(
+        DiagnosticCode::MISSING_ARGUMENT,
+        Severity::Error,
+        "A call leaves out a required argument.",
+    ),
+    rule(
+        DiagnosticCode::TOO_MANY_ARGUMENTS,
+        Severity::Error,
+        "A built-in function is called with more arguments than it takes.",
+    ),
+    rule(
+        DiagnosticCode::UNKNOWN_NAMED_ARGUMENT,
+        Severity::Warning,
+        "A call names an argument the function does not declare.",
+    ),
+    rule(
+        DiagnosticCode::MISSING_ATTRIBUTE,
+        Severity::Error,
+        "A built-in tag lacks a required attribute.",
+    ),
+    rule(
+        DiagnosticCode::INVALID_ATTRIBUTE_VALUE,
+        Severity::Error,
+        "A tag attribute has a value it does not accept.",
+    ),
+    rule(
+        DiagnosticCode::UNRESOLVED_COMPONENT,
+        Severity::Warning,
+        "A component that is extended or created does not exist.",
+    ),
+    rule(
+        DiagnosticCode::MISSING_INTERFACE_METHOD,
+        Severity::Error,
+        "A component lacks methods of an interface it implements.",
+    ),
+    Rule {
+        options: &[
+            ("sources", taint::SOURCES),
+            ("functions", taint::SAFE_FUNCTIONS),
+        ],
+        ..rule(
+            DiagnosticCode::UNENCODED_OUTPUT,
+            Severity::Warning,
+            "A value from `sources` or a query is output without the encoder for its HTML context, other than the results of `functions`.",
+        )
+    },
+    Rule {
+        options: &[
+            ("sources", taint::SOURCES),
+            ("sinks", security::CODE_SINKS),
+            ("sanitizers", taint::SAFE_FUNCTIONS),
+        ],
+        ..rule(
+            DiagnosticCode::TAINTED_CODE,
+            Severity::Error,
+            "A value from `sources` is evaluated as code by one of `sinks`, without passing through one of `sanitizers`.",
+        )
+    },
+    Rule {
+        options: &[
+            ("sources", taint::SOURCES),
+            ("sinks", security::FILE_PATH_SINKS),
+            ("sanitizers", taint::SAFE_FUNCTIONS),
+        ],
+        ..rule(
+            DiagnosticCode::TAINTED_FILE_PATH,
+            Severity::Error,
+            "A value from `sources` ends up in the path of a file written by one of `sinks`, without passing through one of `sanitizers`.",
+        )
+    },
+    Rule {
+        options: &[
+            ("sources", taint::SOURCES),
+            ("sinks", security::COMMAND_SINKS),
+            ("sanitizers", taint::SAFE_FUNCTIONS),
+        ],
+        ..rule(
+            DiagnosticCode::TAINTED_COMMAND,
+            Severity::Error,
+            "A value from `sources` ends up in a command run by one of `sinks`, without passing through one of `sanitizers`.",
+        )
+    },
+    Rule {
+        options: &[
+            ("sources", taint::SOURCES),
+            ("sinks", security::REDIRECT_SINKS),
+            ("sanitizers", taint::SAFE_FUNCTIONS),
+        ],
+        ..rule(
+            DiagnosticCode::TAINTED_REDIRECT,
+            Severity::Error,
+            "A value from `sources` ends up in the URL one of `sinks` redirects to, without passing through one of `sanitizers`.",
+        )
+    },
+    rule(
+        DiagnosticCode::TAINTED_DATA,
+        Severity::Error,
+        "A value reaches a sink of one of the project's own security rules.",
+    ),
+    rule(
+        DiagnosticCode::UNLOCKED_SCOPE_WRITE,
+        Severity::Warning,
+        "The `application`, `session` or `server` scope is written outside a lock on it.",
+    ),
+    rule(
+        DiagnosticCode::UNLOCKED_SCOPE_READ,
+        Severity::Warning,
+        "Part of a shared scope value is read outside a lock while it is written under one.",
+    ),
+    rule(
+        DiagnosticCode::NO_TAG_EQUIVALENT,
+        Severity::Hint,
+        "A statement in a `<cfscript>` block has no tag equivalent, like a closure.",
+    ),
+    cflint(
+        DiagnosticCode::MISSING_VAR,
+        Severity::Warning,
+        "VarScoper",
+        "A function assigns a variable without declaring it with `var` or `local`.",
+    ),
+    cflint(
+        DiagnosticCode::ARG_VAR_CONFLICT,
+        Severity::Error,
+        "ArgVarChecker",
+        "A function declares a local variable with the name of one of its arguments.",
+    ),
+    Rule {
+        options: &[
+            ("sources", taint::SOURCES),
+            ("functions", taint::SAFE_FUNCTIONS),
+        ],
+        ..cflint(
+            DiagnosticCode::CFQUERYPARAM_REQ,
+            Severity::Warning,
+            "QueryParamChecker",
+            "A `<cfquery>` interpolates a value instead of using `<cfqueryparam>`, other than the results of `functions`.",
+        )
+    },
+    Rule {
+        options: &[
+            ("sources", taint::SOURCES),
+            ("functions", taint::SAFE_FUNCTIONS),
+        ],
+        ..cflint(
+            DiagnosticCode::QUERYPARAM_REQ,
+            Severity::Warning,
+            "QueryParamChecker",
+            "The SQL passed to `queryExecute` interpolates a value instead of using a parameter, other than the results of `functions`.",
+        )
+    },
+    cflint(
+        DiagnosticCode::AVOID_USING_CFDUMP_TAG,
+        Severity::Warning,
+        "CFDumpChecker",
+        "A `<cfdump>` tag is left in the code.",
+    ),
+    cflint(
+        DiagnosticCode::AVOID_USING_WRITEDUMP,
+        Severity::Info,
+        "WriteDumpChecker",
+        "A `writeDump` call is left in the code.",
+    ),
+    cflint(
+        DiagnosticCode::AVOID_USING_CFABORT_TAG,
+        Severity::Info,
+        "AbortChecker",
+        "A `<cfabort>` tag is left in the code.",
+    ),
+    cflint(
+        DiagnosticCode::AVOID_USING_ABORT,
+        Severity::Info,
+        "AbortChecker",
+        "An `abort` statement is left in the code.",
+    ),
+    cflint(
+        DiagnosticCode::AVOID_USING_CFEXECUTE_TAG,
+        Severity::Warning,
+        "CFExecuteChecker",
+        "A `<cfexecute>` tag runs an external program.",
+    ),
+    Rule {
+        options: &[("length", "100")],
+        ..cflint(
+            DiagnosticCode::EXCESSIVE_FUNCTION_LENGTH,
+            Severity::Info,
+            "FunctionLengthChecker",
+            "A function has more lines than `length`.",
+        )
+    },
+    Rule {
+        options: &[("maximum", "10")],
+        ..cflint(
+            DiagnosticCode::EXCESSIVE_ARGUMENTS,
+            Severity::Info,
+            "TooManyArgumentsChecker",
+            "A function has more arguments than `maximum`.",
+        )
+    },
+];
+
+/// Looks a rule up by its code, ignoring case.
+pub fn find_rule(code: &str) -> Option<&'static Rule> {
+    RULES.iter().find(|it| it.code.0.eq_ignore_ascii_case(code))
+}
+
+/// How rules are configured; rules without settings keep their defaults.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DiagnosticsConfig {
+    /// Settings by rule code.
+    pub rules: FxHashMap<&'static str, RuleConfig>,
+    /// The project's own security rules, reported as `tainted-data`.
+    pub taint_rules: Vec<TaintRule>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleConfig {
+    pub enabled: bool,
+    /// The severity to report with instead of the rule's default.
+    pub severity: Option<Severity>,
+    /// Option values by name, overriding the defaults.
+    pub options: FxHashMap<String, String>,
+}
+
+impl Default for RuleConfig {
+    fn default() -> Self {
+        RuleConfig {
+            enabled: true,
+            severity: None,
+            options: FxHashMap::default(),
+        }
+    }
+}
+
+impl DiagnosticsConfig {
+    pub fn is_enabled(&self, code: DiagnosticCode) -> bool {
+        self.rules.get(code.0).is_none_or(|it| it.enabled)
+    }
+
+    /// The value of an option of a rule, or its default if it is not set or
+    /// does not parse.
+    pub(crate) fn option<T: FromStr>(&self, code: DiagnosticCode, name: &str) -> Option<T> {
+        let configured = self.rules.get(code.0).and_then(|it| it.options.get(name));
+        if let Some(value) = configured.and_then(|it| it.trim().parse().ok()) {
+            return Some(value);
+        }
+        let (_, default) = find_rule(code.0)?
+            .options
+            .iter()
+            .find(|(it, _)| *it == name)?;
+        default.parse().ok()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub code: DiagnosticCode,
+    pub message: String,
+    pub range: TextRange,
+    pub severity: Severity,
+    /// Edits that make the problem go away.
+    pub fixes: Vec<Assist>,
+}
+
+impl Diagnostic {
+    /// A diagnostic with the default severity of its rule.
+    pub(crate) fn new(code: DiagnosticCode, range: TextRange, message: String) -> Self {
+        let severity = find_rule(code.0).map_or(Severity::Warning, |it| it.severity);
+        Diagnostic {
+            code,
+            message,
+            range,
+            severity,
+            fixes: Vec::new(),
+        }
+    }
+
+    pub(crate) fn with_fixes(mut self, fixes: Vec<Assist>) -> Self {
+        self.fixes = fixes;
+        self
+    }
+}
+
+pub(crate) fn fix(file_id: FileId, label: String, edit: TextEdit) -> Assist {
+    Assist {
+        label,
+        kind: AssistKind::QuickFix,
+        source_change: SourceChange::from_text_edit(file_id, edit),
+    }
+}
+
+/// The diagnostics of a file, in source order, as configured.
+pub(crate) fn diagnostics(
+    db: &RootDatabase,
+    config: &DiagnosticsConfig,
+    file_id: FileId,
+) -> Vec<Diagnostic> {
+    let mut res: Vec<_> = db
+        .parse(file_id)
+        .errors()
+        .iter()
+        .map(|it| {
+            let message = it.message().to_owned();
+            Diagnostic::new(DiagnosticCode::SYNTAX_ERROR, it.range(), message)
+        })
+        .collect();
+    let file = db.parse(file_id).tree();
+    let opaque = Opaque::new(db, file_id, &file);
+    calls(db, file_id, &opaque, &mut res);
+    tags(&file, &mut res);
+    if !opaque.imports {
+        component_paths(db, file_id, &file, &mut res);
+    }
+    inheritance::missing_interface_methods(db, file_id, &file, &mut res);
+    lints::lints(db, config, file_id, &file, &mut res);
+    xss::unencoded_output(db, config, file_id, &file, &mut res);
+    security::tainted_sinks(db, config, file_id, &file, &mut res);
+    locking::unlocked_scopes(db, file_id, &file, &mut res);
+    script_to_tags::no_tag_equivalent(&file, &mut res);
+
+    res.retain_mut(|it| {
+        let Some(rule) = config.rules.get(it.code.0) else {
+            return true;
+        };
+        if let Some(severity) = rule.severity {
+            it.severity = severity;
+        }
+        rule.enabled
+    });
+    suppressions::retain_unsuppressed(&file, &db.line_index(file_id), &mut res);
+    res.sort_by_key(|it| it.range.start());
+    res
+}
+
+/// The revision the diagnostics of `file_id` were computed in: the latest
+/// revision of the file, the components it extends or refers to, and the
+/// set of files. `None` if the file does not exist.
+pub(crate) fn revision(db: &RootDatabase, file_id: FileId) -> Option<u64> {
+    let revision = db.file(file_id)?.revision;
+    let mut files: FxHashSet<_> = db.component_chain(file_id).into_iter().collect();
+    for path in &db.usages(file_id).component_refs {
+        if let Some(component) = db.resolve_component(file_id, &path.path) {
+            files.extend(db.component_chain(component));
+        }
+    }
+    let latest = files
+        .into_iter()
+        .filter_map(|it| Some(db.file(it)?.revision))
+        .max();
+    Some(revision.max(latest.unwrap_or(0)).max(db.layout_revision()))
+}
+
+/// What the file can see that the analysis cannot.
+struct Opaque {
+    /// Whether the file includes other templates, which may declare
+    /// functions.
+    includes: bool,
+    /// Whether the file imports component paths, which may be used unqualified.
+    imports: bool,
+    /// Whether a component in the chain extends one that cannot be found.
+    unknown_parent: bool,
+    /// Lowercase names that may hold a function: variables and nested
+    /// functions.
+    names: FxHashSet<String>,
+}
+
+impl Opaque {
+    fn new(db: &RootDatabase, file_id: FileId, file: &ast::SourceFile) -> Opaque {
+        let mut includes = false;
+        let mut imports = false;
+        for node in file.syntax().descendants() {
+            match node.kind() {
+                IMPORT_STMT => imports = true,
+                SCRIPT_TAG_STMT => {
+                    includes |=
+                        ast::ScriptTagStmt::cast(node).is_some_and(|it| it.name() == "include")
+                }
+                TAG => {
+                    let Some(tag) = ast::Tag::cast(node) else {
+                        continue;
+                    };
+                    match tag.name().as_str() {
+                        "cfinclude" => includes = true,
+                        "cfimport" => imports |= tag.attribute("path").is_some(),
+                        _ => (),
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        let chain = db.component_chain(file_id);
+        let unknown_parent = chain.last().is_some_and(|&last| {
+            let items = db.items(last);
+            items
+                .component
+                .as_ref()
+                .is_some_and(|it| it.extends.is_some())
+        });
+
+        let mut names: FxHashSet<_> = db
+            .usages(file_id)
+            .vars
+            .iter()
+            .map(|it| it.name.to_ascii_lowercase())
+            .collect();
+        names.extend(
+            file.syntax()
+                .descendants()
+                .filter_map(FunctionNode::cast)
+                .filter_map(|it| Some(it.name()?.0.to_ascii_lowercase())),
+        );
+        Opaque {
+            includes,
+            imports,
+            unknown_parent,
+            names,
+        }
+    }
+}
+
+fn calls(db: &RootDatabase, file_id: FileId, opaque: &Opaque, res: &mut Vec<Diagnostic>) {
+    for call in &db.usages(file_id).calls {
+        if let Some((_, function)) = resolve_call(db, file_id, call) {
+            user_call_args(call, &function, res);
+            continue;
+        }
+        if call.receiver != Receiver::None {
+            continue;
+        }
+        // `cfhttp(url = "...")` is the script form of a tag, not a function.
+        if builtin_function(&call.name).is_some() {
+            builtin_call_args(call, res);
+        } else if builtin_tag(&call.name).is_none()
+            && !opaque.includes
+            && !opaque.unknown_parent
+            && !opaque.names.contains(&call.name.to_ascii_lowercase())
+        {
+            res.push(Diagnostic::new(
+                DiagnosticCode::UNRESOLVED_FUNCTION,
+                call.name_range,
+                format!("unresolved function `{}`", call.name),
+            ));
+        }
+    }
+}
+
+/// Checks the arguments of a call to a built-in function. Only positional
+/// arguments are counted, as engines disagree on parameter names.
+fn builtin_call_args(call: &CallSite, res: &mut Vec<Diagnostic>) {
+    let Some(function) = builtin_function(&call.name) else {
+        return;
+    };
+    if call.args.iter().any(|it| it.name.is_some()) {
+        return;
+    }
+    let count = call.args.len();
+    let missing = function.params.iter().skip(count).filter(|it| it.required);
+    for param in missing {
+        res.push(missing_argument(call, param.name, function.name));
+    }
+    if let Some(max) = function.max_args().filter(|&max| count > max) {
+        let range = TextRange::new(
+            call.args[max].range.start(),
+            call.args[count - 1].range.end(),
+        );
+        res.push(Diagnostic::new(
+            DiagnosticCode::TOO_MANY_ARGUMENTS,
+            range,
+            format!(
+                "`{}` takes at most {max} argument{}, found {count}",
+                function.name,
+                if max == 1 { "" } else { "s" },
+            ),
+        ));
+    }
+}
+
+/// Checks the arguments of a call to a user-defined function. Calls mixing
+/// positional and named arguments, or passing `argumentCollection`, are not
+/// checked; extra positional arguments are allowed.
+fn user_call_args(call: &CallSite, function: &FunctionInfo, res: &mut Vec<Diagnostic>) {
+    let named: Vec<_> = call.args.iter().filter_map(|it| it.name.as_ref()).collect();
+    if named
+        .iter()
+        .any(|(name, _)| name.eq_ignore_ascii_case("argumentCollection"))
+    {
+        return;
+    }
+    if named.is_empty() {
+        let missing = function
+            .params
+            .iter()
+            .skip(call.args.len())
+            .filter(|it| it.required);
+        for param in missing {
+            res.push(missing_argument(call, &param.name, &function.name));
+        }
+    } else if named.len() == call.args.len() {
+        for (name, range) in &named {
+            if function.param(name).is_none() {
+                res.push(Diagnostic::new(
+                    DiagnosticCode::UNKNOWN_NAMED_ARGUMENT,
+                    *range,
+                    format!("`{}` has no parameter `{name}`", function.name),
+                ));
+            }
+        }
+        let missing = function.params.iter().filter(|param| {
+            param.required
+                && !named
+                    .iter()
+                    .any(|(name, _)| name.eq_ignore_ascii_case(&param.name))
+        });
+        for param in missing {
+            res.push(missing_argument(call, &param.name, &function.name));
+        }
+    }
+}
+
+fn missing_argument(call: &CallSite, param: &str, function: &str) -> Diagnostic {
+    Diagnostic::new(
+        DiagnosticCode::MISSING_ARGUMENT,
+        call.range,
+        format!("missing argument `{param}` to `{function}`"),
+    )
+}
+
+/// Checks built-in tags for missing required attributes and literal values
+/// outside the ones an attribute accepts.
+fn tags(file: &ast::SourceFile, res: &mut Vec<Diagnostic>) {
+    for tag in file.syntax().descendants().filter_map(ast::Tag::cast) {
+        let Some(builtin) = builtin_tag(&tag.name()) else {
+            continue;
+        };
+        let Some(name_token) = tag.name_token() else {
+            continue;
+        };
+        if tag.attribute("attributecollection").is_none() {
+            for attribute in builtin.attributes.iter().filter(|it| it.required) {
+                if tag.attribute(attribute.name).is_none() {
+                    res.push(Diagnostic::new(
+                        DiagnosticCode::MISSING_ATTRIBUTE,
+                        name_token.text_range(),
+                        format!(
+                            "missing attribute `{}` of `<{}>`",
+                            attribute.name, builtin.name
+                        ),
+                    ));
+                }
+            }
+        }
+        for attribute in tag.attributes() {
+            let Some(expected) = builtin.attribute(&attribute.name()) else {
+                continue;
+            };
+            let (Some(value), Some(range)) = (attribute.value_text(), attribute.value_range())
+            else {
+                continue;
+            };
+            if expected.accepts(&value) {
+                continue;
+            }
+            let mut message = format!(
+                "invalid value `{value}` for `{}` of `<{}>`",
+                expected.name, builtin.name
+            );
+            if expected.values.len() <= 10 {
+                let values: Vec<_> = expected.values.iter().map(|it| format!("`{it}`")).collect();
+                message.push_str(&format!(", expected one of {}", values.join(", ")));
+            }
+            res.push(Diagnostic::new(
+                DiagnosticCode::INVALID_ATTRIBUTE_VALUE,
+                range,
+                message,
+            ));
+        }
+    }
+}
+
+/// Checks the component a file extends and the ones it instantiates with
+/// `new`. Paths with a `:` prefix, like `java:`, are not components.
+fn component_paths(
+    db: &RootDatabase,
+    file_id: FileId,
+    file: &ast::SourceFile,
+    res: &mut Vec<Diagnostic>,
+) {
+    let mut paths = Vec::new();
+    if let Some(extends) = db
+        .items(file_id)
+        .component
+        .as_ref()
+        .and_then(|it| it.extends.clone())
+    {
+        paths.push((extends.path, extends.range));
+    }
+    for new_expr in file.syntax().descendants().filter_map(ast::NewExpr::cast) {
+        if let Some(path) = new_expr.path() {
+            let segments: Vec<_> = path.segments().collect();
+            if let (Some(first), Some(last)) = (segments.first(), segments.last()) {
+                let range = TextRange::new(first.text_range().start(), last.text_range().end());
+                paths.push((path.text(), range));
+            }
+        } else if let Some(string) = new_expr.path_string() {
+            if let Some(value) = string.value().filter(|it| !it.contains('#')) {
+                paths.push((value, string.contents_range()));
+            }
+        }
+    }
+    for (path, range) in paths {
+        if path.contains(':') || db.resolve_component(file_id, &path).is_some() {
+            continue;
+        }
+        res.push(Diagnostic::new(
+            DiagnosticCode::UNRESOLVED_COMPONENT,
+            range,
+            format!("unresolved component `{}`", path.trim()),
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use virtual_fs::{FileId, VirtualFsPath};
+
+    use crate::{fixture, AnalysisHost, Change, DiagnosticsConfig};
+
+    fn check(fixture: &str, expect: &[(&str, &str)]) {
+        let fixture = fixture::parse(fixture);
+        let analysis = fixture.analysis;
+        let file_id = FileId(0);
+        let text = analysis.file_text(file_id);
+        let actual: Vec<_> = analysis
+            .diagnostics(&DiagnosticsConfig::default(), file_id)
+            .into_iter()
+            .map(|it| (&text[it.range], it.message))
+            .collect();
+        let expect: Vec<_> = expect.iter().map(|(r, m)| (*r, m.to_string())).collect();
+        assert_eq!(actual, expect);
+    }
+
+    #[test]
+    fn syntax_errors() {
+        check(
+            r#"
+//- /page.cfm
+<cfoutput>
+  <cfif a>x</cfoutput>
+</cfif>
+<cfoutput>100 #</cfoutput>
+<cfset s = "open>
+"#,
+            &[
+                ("cfif", "unclosed `<cfif>`"),
+                ("</cfif", "unmatched `</cfif>`"),
+                ("#", "stray `#`, use `##` for a literal `#`"),
+                ("\"", "unterminated string"),
+                ("", "expected `>`"),
+            ],
+        );
+    }
+
+    #[test]
+    fn calls() {
+        check(
+            r#"
+//- /Service.cfc
+component extends="Base" {
+    function run(required a, b) {}
+    function go(callback) {
+        run();
+        run(b = 1, c = 2);
+        run(argumentCollection = {});
+        inherited();
+        getName();
+        setName();
+        callback();
+        var each = function() {};
+        each();
+        nope();
+        len("a", "b");
+        listFind("a");
+        dateFormat(date = now());
+        cfhttp(url = "http://x", result = "local.res");
+    }
+}
+//- /Base.cfc
+component accessors="true" {
+    property name="name";
+    function inherited() {}
+}
+"#,
+            &[
+                ("run()", "missing argument `a` to `run`"),
+                ("run(b = 1, c = 2)", "missing argument `a` to `run`"),
+                ("c", "`run` has no parameter `c`"),
+                ("setName()", "missing argument `name` to `setName`"),
+                ("nope", "unresolved function `nope`"),
+                ("\"b\"", "`len` takes at most 1 argument, found 2"),
+                ("listFind(\"a\")", "missing argument `value` to `listFind`"),
+            ],
+        );
+    }
+
+    #[test]
+    fn unresolved_functions_are_not_reported_when_names_may_be_hidden() {
+        check(
+            r#"
+//- /page.cfm
+<cfinclude template="helpers.cfm">
+<cfset helper()>
+"#,
+            &[],
+        );
+        check(
+            r#"
+//- /Child.cfc
+component extends="Missing" {
+    function go() { helper(); }
+}
+"#,
+            &[("Missing", "unresolved component `Missing`")],
+        );
+    }
+
+    #[test]
+    fn tags() {
+        check(
+            r##"
+//- /page.cfm
+<cfmail to="a@b.c" subject="Hi">x</cfmail>
+<cfmail attributeCollection="#args#">x</cfmail>
+<cflock scope="sesion" timeout="1">x</cflock>
+<cflock scope="#scope#" timeout="1">x</cflock>
+<cfquery name="q"><cfqueryparam value="1" cfsqltype="integer"><cfqueryparam value="1" cfsqltype="cf_sql_int"></cfquery>
+"##,
+            &[
+                ("cfmail", "missing attribute `from` of `<cfmail>`"),
+                (
+                    "sesion",
+                    "invalid value `sesion` for `scope` of `<cflock>`, expected one of `application`, `request`, `server`, `session`",
+                ),
+                ("cf_sql_int", "invalid value `cf_sql_int` for `cfsqltype` of `<cfqueryparam>`"),
+            ],
+        );
+    }
+
+    #[test]
+    fn component_paths() {
+        check(
+            r##"
+//- /models/User.cfc
+component extends="models.Base" {
+    function make() {
+        new models.Base();
+        new Base();
+        new "models.Role"();
+        new models.Missing();
+        new "#name#"();
+        new "java:java.util.HashMap"();
+    }
+}
+//- /models/Base.cfc
+component {}
+"##,
+            &[
+                ("models.Role", "unresolved component `models.Role`"),
+                ("models.Missing", "unresolved component `models.Missing`"),
+            ],
+        );
+    }
+
+    #[test]
+    fn revision_follows_dependencies() {
+        let path = |it: &str| VirtualFsPath::new_virtual_path(format!("/ws/{it}"));
+        let mut host = AnalysisHost::new();
+        let mut change = Change::new();
+        change.set_roots(vec![VirtualFsPath::new_virtual_path("/ws".to_owned())]);
+        for (id, name, text) in [
+            (0, "Child.cfc", "component extends=\"Base\" {}"),
+            (1, "Base.cfc", "component {}"),
+            (2, "Other.cfc", "component {}"),
+        ] {
+            change.change_file(FileId(id), path(name), Some(Arc::from(text)));
+        }
+        host.apply_change(change);
+        let revision = |host: &AnalysisHost| host.analysis().diagnostics_revision(FileId(0));
+        let before = revision(&host);
+
+        let mut change = Change::new();
+        change.change_file(
+            FileId(2),
+            path("Other.cfc"),
+            Some(Arc::from("component {}")),
+        );
+        host.apply_change(change);
+        assert_eq!(revision(&host), before);
+
+        let mut change = Change::new();
+        let text = "component { function f() {} }";
+        change.change_file(FileId(1), path("Base.cfc"), Some(Arc::from(text)));
+        host.apply_change(change);
+        assert!(revision(&host) > before);
+    }
+}
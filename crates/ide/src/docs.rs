@@ -0,0 +1,376 @@
+//! Documentation of functions and components: JavaDoc-style docblocks
+//! before script declarations and `hint` attributes.
+//!
+//! In a docblock, `@param name text` and `@name text` describe the
+//! parameter `name`, `@return text` the returned value and `@hint text`
+//! the declaration. Any other `@key value` is metadata, which CFML reads
+//! like an attribute of the declaration: `@output false`.
+
+use syntax::{
+    ast::{self, AstNode},
+    SyntaxKind::*,
+    SyntaxNode, SyntaxToken, TextRange,
+};
+
+use crate::{
+    assists::{line_ending, line_indent, Assist, AssistKind},
+    db::RootDatabase,
+    items::FunctionNode,
+    source_change::SourceChange,
+    text_edit::TextEdit,
+    FileRange,
+};
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Documentation {
+    /// The description, its paragraphs separated by blank lines.
+    pub description: String,
+    /// The descriptions of parameters, by name.
+    pub params: Vec<(String, String)>,
+    pub returns: Option<String>,
+    /// The other docblock tags, by name, in source order.
+    pub metadata: Vec<(String, String)>,
+}
+
+impl Documentation {
+    pub fn is_empty(&self) -> bool {
+        self.description.is_empty()
+            && self.params.is_empty()
+            && self.returns.is_none()
+            && self.metadata.is_empty()
+    }
+
+    pub fn param(&self, name: &str) -> Option<&str> {
+        let (_, text) = self
+            .params
+            .iter()
+            .find(|(it, _)| it.eq_ignore_ascii_case(name))?;
+        Some(text)
+    }
+
+    pub fn metadata(&self, key: &str) -> Option<&str> {
+        let (_, value) = self
+            .metadata
+            .iter()
+            .find(|(it, _)| it.eq_ignore_ascii_case(key))?;
+        Some(value)
+    }
+
+    /// The description, parameters and returned value as Markdown, `None`
+    /// if there are none.
+    pub fn markdown(&self) -> Option<String> {
+        let mut sections = Vec::new();
+        if !self.description.is_empty() {
+            sections.push(self.description.clone());
+        }
+        let params: Vec<_> = self
+            .params
+            .iter()
+            .map(|(name, text)| format!("- `{name}`: {text}"))
+            .collect();
+        if !params.is_empty() {
+            sections.push(format!("**Parameters**\n\n{}", params.join("\n")));
+        }
+        if let Some(returns) = &self.returns {
+            sections.push(format!("**Returns** {returns}"));
+        }
+        (!sections.is_empty()).then(|| sections.join("\n\n"))
+    }
+}
+
+/// Parses the text of a `/** ... */` comment. Lines after a tag continue
+/// its text; untagged lines before the first tag form the description.
+pub(crate) fn parse_docblock(text: &str) -> Documentation {
+    let text = text.trim_start_matches("/**").trim_end_matches("*/");
+    let mut paragraphs: Vec<String> = vec![String::new()];
+    let mut tags: Vec<(String, String)> = Vec::new();
+    for line in text.lines() {
+        let line = line.trim();
+        let line = line.strip_prefix('*').unwrap_or(line).trim();
+        if let Some(tag) = line.strip_prefix('@') {
+            let (name, text) = tag.split_once(char::is_whitespace).unwrap_or((tag, ""));
+            tags.push((name.to_owned(), text.trim().to_owned()));
+            continue;
+        }
+        let text = match tags.last_mut() {
+            Some((_, text)) => text,
+            None if line.is_empty() => {
+                if paragraphs.last().is_some_and(|it| !it.is_empty()) {
+                    paragraphs.push(String::new());
+                }
+                continue;
+            }
+            None => paragraphs.last_mut().unwrap(),
+        };
+        if !line.is_empty() {
+            if !text.is_empty() {
+                text.push(' ');
+            }
+            text.push_str(line);
+        }
+    }
+
+    let mut doc = Documentation {
+        description: paragraphs
+            .into_iter()
+            .filter(|it| !it.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n\n"),
+        ..Documentation::default()
+    };
+    for (name, text) in tags {
+        match name.to_ascii_lowercase().as_str() {
+            "param" | "arg" | "argument" => {
+                let (param, text) = text.split_once(char::is_whitespace).unwrap_or((&text, ""));
+                if !param.is_empty() {
+                    doc.params.push((param.to_owned(), text.trim().to_owned()));
+                }
+            }
+            "return" | "returns" => doc.returns = Some(text),
+            "hint" if doc.description.is_empty() => doc.description = text,
+            _ => doc.metadata.push((name, text)),
+        }
+    }
+    doc
+}
+
+/// The documentation of a function: its docblock and the `hint`s of the
+/// function and its parameters. Docblock tags named after a parameter
+/// describe it.
+pub(crate) fn function_docs(function: &FunctionNode) -> Documentation {
+    match function {
+        FunctionNode::Script(decl) => {
+            let mut doc = docblock(decl.syntax())
+                .map(|it| parse_docblock(it.text()))
+                .unwrap_or_default();
+            let params: Vec<_> = decl.params().collect();
+            let (params_meta, metadata) = doc.metadata.into_iter().partition(|(key, _)| {
+                params
+                    .iter()
+                    .filter_map(|it| it.name_token())
+                    .any(|it| it.text().eq_ignore_ascii_case(key))
+            });
+            doc.metadata = metadata;
+            doc.params.extend::<Vec<_>>(params_meta);
+            if let Some(hint) = decl
+                .attribute_value("hint")
+                .filter(|_| doc.description.is_empty())
+            {
+                doc.description = hint;
+            }
+            for param in params {
+                let (Some(name), Some(hint)) = (param.name_token(), param.attribute_value("hint"))
+                else {
+                    continue;
+                };
+                if doc.param(name.text()).is_none() {
+                    doc.params.push((name.text().to_owned(), hint));
+                }
+            }
+            doc
+        }
+        FunctionNode::Tag(tag) => Documentation {
+            description: tag.attribute_value("hint").unwrap_or_default(),
+            params: function
+                .argument_tags()
+                .iter()
+                .filter_map(|it| Some((it.attribute_value("name")?, it.attribute_value("hint")?)))
+                .collect(),
+            ..Documentation::default()
+        },
+    }
+}
+
+/// The documentation of the component or interface of `file`.
+pub(crate) fn component_docs(file: &ast::SourceFile) -> Documentation {
+    if let Some(component) = file.component() {
+        let mut doc = docblock(component.syntax())
+            .map(|it| parse_docblock(it.text()))
+            .unwrap_or_default();
+        if let Some(hint) = component
+            .attribute_value("hint")
+            .filter(|_| doc.description.is_empty())
+        {
+            doc.description = hint;
+        }
+        return doc;
+    }
+    let tag = file
+        .tags()
+        .find(|it| matches!(it.name().as_str(), "cfcomponent" | "cfinterface"));
+    Documentation {
+        description: tag
+            .and_then(|it| it.attribute_value("hint"))
+            .unwrap_or_default(),
+        ..Documentation::default()
+    }
+}
+
+/// Offers to write a docblock skeleton for the script function whose
+/// signature contains `frange`, with its parameters and returned value.
+pub(crate) fn generate_docblock(db: &RootDatabase, frange: FileRange) -> Option<Assist> {
+    let file = db.parse(frange.file_id).tree();
+    let text = db.file_text(frange.file_id);
+    let decl = file
+        .syntax()
+        .descendants()
+        .filter_map(ast::FunctionDecl::cast)
+        .filter(|it| {
+            let start = it.syntax().text_range().start();
+            let end = it
+                .body()
+                .map_or(start, |it| it.syntax().text_range().start());
+            TextRange::new(start, end).contains_range(frange.range) && frange.range.end() < end
+        })
+        .last()?;
+    if docblock(decl.syntax()).is_some() {
+        return None;
+    }
+
+    let start = decl.syntax().text_range().start();
+    let newline = line_ending(&text);
+    let indent = line_indent(&text, start);
+    let mut lines = vec!["/**".to_owned(), " *".to_owned()];
+    lines.extend(
+        decl.params()
+            .filter_map(|it| it.name())
+            .map(|it| format!(" * @param {it}")),
+    );
+    let returns = decl
+        .return_type()
+        .is_none_or(|it| !it.text().eq_ignore_ascii_case("void"));
+    if returns {
+        lines.push(" * @return".to_owned());
+    }
+    lines.push(" */".to_owned());
+    let mut insert = lines.join(&format!("{newline}{indent}"));
+    insert.push_str(newline);
+    insert.push_str(indent);
+    Some(Assist {
+        label: "Generate docblock".to_owned(),
+        kind: AssistKind::Generate,
+        source_change: SourceChange::from_text_edit(
+            frange.file_id,
+            TextEdit::insert(start, insert),
+        ),
+    })
+}
+
+/// The `/** */` comment right before `node`, with only whitespace between.
+pub(crate) fn docblock(node: &SyntaxNode) -> Option<SyntaxToken> {
+    let mut prev = node.prev_sibling_or_token();
+    while let Some(element) = prev {
+        match element.kind() {
+            WHITESPACE => prev = element.prev_sibling_or_token(),
+            BLOCK_COMMENT => {
+                let token = element.into_token()?;
+                return token.text().starts_with("/**").then_some(token);
+            }
+            _ => return None,
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use virtual_fs::FileId;
+
+    use super::*;
+    use crate::fixture;
+
+    #[test]
+    fn parses_docblocks() {
+        let doc = parse_docblock(
+            "/**
+     * Finds a user
+     * by id.
+     *
+     * Cached for a minute.
+     * @param id The id
+     *     of the user
+     * @return The user, or null
+     * @output false
+     * @role The role
+     */",
+        );
+        assert_eq!(
+            doc,
+            Documentation {
+                description: "Finds a user by id.\n\nCached for a minute.".to_owned(),
+                params: vec![("id".to_owned(), "The id of the user".to_owned())],
+                returns: Some("The user, or null".to_owned()),
+                metadata: vec![
+                    ("output".to_owned(), "false".to_owned()),
+                    ("role".to_owned(), "The role".to_owned()),
+                ],
+            }
+        );
+        assert_eq!(doc.metadata("OUTPUT"), Some("false"));
+    }
+
+    #[test]
+    fn generates_docblocks() {
+        let (fixture, range) = fixture::selection(
+            "//- /Users.cfc\ncomponent {\n    public query function fi$0nd(required numeric id, role) {}\n\n    void function reset() {}\n}\n",
+        );
+        let analysis = fixture::parse(&fixture).analysis;
+        let frange = FileRange {
+            file_id: FileId(0),
+            range,
+        };
+        let assists = analysis.assists(frange);
+        let Some(assist) = assists.iter().find(|it| it.label == "Generate docblock") else {
+            panic!("no docblock assist in {assists:?}");
+        };
+        let mut text = analysis.file_text(FileId(0)).to_string();
+        assist.source_change.source_file_edits[&FileId(0)].apply(&mut text);
+        assert_eq!(
+            text,
+            "component {\n    /**\n     *\n     * @param id\n     * @param role\n     * @return\n     */\n    public query function find(required numeric id, role) {}\n\n    void function reset() {}\n}\n"
+        );
+
+        let analysis = fixture::parse(&format!("//- /Users.cfc\n{text}")).analysis;
+        let assists = analysis.assists(frange);
+        assert!(assists.iter().all(|it| it.label != "Generate docblock"));
+    }
+
+    #[test]
+    fn documents_functions_from_docblocks_and_hints() {
+        let file = ast::SourceFile::parse(
+            r#"/** @accessors true */
+component hint="Users" {
+    /**
+     * Saves
+     * @force Whether to skip validation
+     * @output false
+     */
+    function save(boolean force, string reason hint="Why") {}
+
+    function load(id) hint="Loads" {}
+}
+"#,
+        )
+        .tree();
+        let docs: Vec<_> = file
+            .syntax()
+            .descendants()
+            .filter_map(FunctionNode::cast)
+            .map(|it| function_docs(&it))
+            .collect();
+        assert_eq!(docs[0].description, "Saves");
+        assert_eq!(
+            docs[0].params,
+            [
+                ("force".to_owned(), "Whether to skip validation".to_owned()),
+                ("reason".to_owned(), "Why".to_owned()),
+            ]
+        );
+        assert_eq!(docs[0].metadata("output"), Some("false"));
+        assert_eq!(docs[1].description, "Loads");
+
+        let doc = component_docs(&file);
+        assert_eq!(doc.description, "Users");
+        assert_eq!(doc.metadata("accessors"), Some("true"));
+    }
+}
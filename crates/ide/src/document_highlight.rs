@@ -0,0 +1,145 @@
+//! Highlights related to the name under the cursor within one file: the
+//! reads and writes of a variable in its scope, or the start and end tag
+//! names of a tag, including the `cfelseif`/`cfelse` branches of a `cfif`.
+
+use syntax::{
+    ast::{self, AstNode},
+    tags,
+    SyntaxKind::*,
+    SyntaxToken, TextRange,
+};
+
+use crate::{db::RootDatabase, usages::Access, FilePosition};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HighlightedRange {
+    pub range: TextRange,
+    /// How a variable is accessed; `None` for tag names.
+    pub access: Option<Access>,
+}
+
+pub(crate) fn document_highlight(
+    db: &RootDatabase,
+    position: FilePosition,
+) -> Option<Vec<HighlightedRange>> {
+    let FilePosition { file_id, offset } = position;
+    let file = db.parse(file_id).tree();
+    if let Some(token) = file
+        .syntax()
+        .token_at_offset(offset)
+        .find(|it| it.kind() == TAG_NAME)
+    {
+        return tag_highlights(&token);
+    }
+
+    let usages = db.usages(file_id);
+    let var = usages.var_at(offset)?;
+    let res = usages
+        .vars
+        .iter()
+        .filter(|it| it.scope == var.scope && it.name.eq_ignore_ascii_case(&var.name))
+        .map(|it| HighlightedRange {
+            range: it.range,
+            access: Some(it.access),
+        })
+        .collect();
+    Some(res)
+}
+
+/// The names of the start and end tags of the tag named by `token`. On any
+/// tag of a `cfif` chain, the names of all its branches too.
+fn tag_highlights(token: &SyntaxToken) -> Option<Vec<HighlightedRange>> {
+    let tag = token.parent()?.parent().and_then(ast::Tag::cast)?;
+    let tag = match tag.syntax().parent().and_then(ast::Tag::cast) {
+        Some(parent) if tags::is_if_branch(&tag.name()) => parent,
+        _ => tag,
+    };
+    let branches = tag
+        .child_tags()
+        .filter(|it| tags::is_if_branch(&it.name()))
+        .filter_map(|it| it.name_token());
+    let res = tag
+        .name_token()
+        .into_iter()
+        .chain(branches)
+        .chain(tag.end_tag().and_then(|it| it.name_token()))
+        .map(|it| HighlightedRange {
+            range: it.text_range(),
+            access: None,
+        })
+        .collect();
+    Some(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixture;
+
+    use super::*;
+
+    fn check(fixture: &str, expect: &[(&str, Option<Access>)]) {
+        let (analysis, position) = fixture::position(fixture);
+        let text = analysis.file_text(position.file_id);
+        let highlights = analysis.document_highlight(position).unwrap();
+        let actual: Vec<_> = highlights
+            .iter()
+            .map(|it| (&text[it.range], it.access))
+            .collect();
+        assert_eq!(actual, expect);
+    }
+
+    #[test]
+    fn variable_reads_and_writes_in_scope() {
+        check(
+            r#"
+//- /Cart.cfc
+component {
+    function sum(items) {
+        var total = 0;
+        for (var item in items) { local.total += item; }
+        return to$0tal;
+    }
+    function other() { return total; }
+}
+"#,
+            &[
+                ("total", Some(Access::Write)),
+                ("total", Some(Access::Write)),
+                ("total", Some(Access::Read)),
+            ],
+        );
+    }
+
+    #[test]
+    fn cfif_chain() {
+        check(
+            r#"
+//- /page.cfm
+<cfif a>
+  <cfif b>x</cfif>
+<cfelseif c>
+  y
+<$0cfelse>
+  z
+</cfif>
+"#,
+            &[
+                ("cfif", None),
+                ("cfelseif", None),
+                ("cfelse", None),
+                ("cfif", None),
+            ],
+        );
+    }
+
+    #[test]
+    fn tag_pair() {
+        check(
+            r#"
+//- /page.cfm
+<cfoutput><cfloop from="1" to="3" index="i">#i#</CFLOOP$0></cfoutput>
+"#,
+            &[("cfloop", None), ("CFLOOP", None)],
+        );
+    }
+}
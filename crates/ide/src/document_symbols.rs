@@ -0,0 +1,354 @@
+//! The outline of a file: its component, properties and functions with the
+//! closures nested in them, and for templates the queries, output blocks
+//! and page variables.
+
+use rustc_hash::FxHashSet;
+use syntax::{
+    ast::{self, AstNode},
+    SyntaxKind::*,
+    SyntaxNode, TextRange,
+};
+
+use crate::items::FunctionNode;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SymbolKind {
+    Component,
+    Interface,
+    Property,
+    /// A function of a component.
+    Method,
+    /// A function of a template.
+    Function,
+    Closure,
+    /// A `<cfquery>` result.
+    Query,
+    /// A `<cfoutput>` block.
+    Output,
+    /// A page variable set with `<cfset>`.
+    Variable,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocumentSymbol {
+    pub name: String,
+    pub detail: Option<String>,
+    pub kind: SymbolKind,
+    /// The whole declaration.
+    pub range: TextRange,
+    /// The name of the symbol, or the tag name for anonymous blocks.
+    pub selection_range: TextRange,
+    pub children: Vec<DocumentSymbol>,
+}
+
+/// `component_name` names the component symbol; without it the keyword
+/// or tag name is used.
+pub(crate) fn document_symbols(
+    file: &ast::SourceFile,
+    component_name: Option<String>,
+) -> Vec<DocumentSymbol> {
+    let mut collector = Collector {
+        component_name,
+        ..Collector::default()
+    };
+    let mut symbols = Vec::new();
+    collector.walk(file.syntax(), &mut symbols);
+    symbols
+}
+
+#[derive(Default)]
+struct Collector {
+    component_name: Option<String>,
+    in_component: bool,
+    /// Nesting depth of functions and closures.
+    depth: usize,
+    /// Lowercase names of page variables already listed.
+    variables: FxHashSet<String>,
+}
+
+impl Collector {
+    fn walk(&mut self, node: &SyntaxNode, acc: &mut Vec<DocumentSymbol>) {
+        for child in node.children() {
+            match self.symbol(&child) {
+                Some(mut symbol) => {
+                    let is_function = matches!(
+                        symbol.kind,
+                        SymbolKind::Method | SymbolKind::Function | SymbolKind::Closure
+                    );
+                    let was_in_component = self.in_component;
+                    self.in_component |=
+                        matches!(symbol.kind, SymbolKind::Component | SymbolKind::Interface);
+                    self.depth += usize::from(is_function);
+                    self.walk(&child, &mut symbol.children);
+                    self.depth -= usize::from(is_function);
+                    self.in_component = was_in_component;
+                    acc.push(symbol);
+                }
+                None => self.walk(&child, acc),
+            }
+        }
+    }
+
+    fn symbol(&mut self, node: &SyntaxNode) -> Option<DocumentSymbol> {
+        let range = node.text_range();
+        if let Some(function) = FunctionNode::cast(node.clone()) {
+            let (name, selection_range) = function.name()?;
+            let kind = match self.in_component && self.depth == 0 {
+                true => SymbolKind::Method,
+                false => SymbolKind::Function,
+            };
+            let params: Vec<_> = function.params().into_iter().map(|it| it.name).collect();
+            return Some(DocumentSymbol {
+                name,
+                detail: Some(format!("({})", params.join(", "))),
+                kind,
+                range,
+                selection_range,
+                children: Vec::new(),
+            });
+        }
+
+        match node.kind() {
+            COMPONENT => {
+                let component = ast::Component::cast(node.clone())?;
+                let keyword = component.keyword()?;
+                let kind = match component.is_interface() {
+                    true => SymbolKind::Interface,
+                    false => SymbolKind::Component,
+                };
+                let name = self.component_name.as_deref().unwrap_or(keyword.text());
+                Some(symbol(name, kind, range, keyword.text_range()))
+            }
+            PROPERTY_DECL => {
+                let property = ast::PropertyDecl::cast(node.clone())?;
+                let (name, selection_range) = match property.attribute("name") {
+                    Some(it) => (it.value_text()?, it.value_range()?),
+                    None => {
+                        let token = property.name_token()?;
+                        (token.text().to_owned(), token.text_range())
+                    }
+                };
+                let detail = property
+                    .type_ref()
+                    .map(|it| it.text())
+                    .or_else(|| property.attribute("type")?.value_text());
+                let mut symbol = symbol(&name, SymbolKind::Property, range, selection_range);
+                symbol.detail = detail;
+                Some(symbol)
+            }
+            CLOSURE_EXPR | ARROW_EXPR => {
+                let (name, selection_range) =
+                    closure_name(node).unwrap_or_else(|| ("<function>".to_owned(), range));
+                Some(symbol(&name, SymbolKind::Closure, range, selection_range))
+            }
+            TAG => self.tag_symbol(ast::Tag::cast(node.clone())?),
+            _ => None,
+        }
+    }
+
+    fn tag_symbol(&mut self, tag: ast::Tag) -> Option<DocumentSymbol> {
+        let range = tag.syntax().text_range();
+        let tag_name = tag.name_token()?;
+        match tag.name().as_str() {
+            "cfcomponent" | "cfinterface" => {
+                let kind = match tag.name() == "cfinterface" {
+                    true => SymbolKind::Interface,
+                    false => SymbolKind::Component,
+                };
+                let name = self.component_name.as_deref().unwrap_or(tag_name.text());
+                Some(symbol(name, kind, range, tag_name.text_range()))
+            }
+            "cfproperty" => {
+                let name = tag.attribute("name")?;
+                let mut symbol = symbol(
+                    &name.value_text()?,
+                    SymbolKind::Property,
+                    range,
+                    name.value_range()?,
+                );
+                symbol.detail = tag.attribute_value("type");
+                Some(symbol)
+            }
+            "cfquery" => {
+                let name = tag.attribute("name")?;
+                let mut symbol = symbol(
+                    &name.value_text()?,
+                    SymbolKind::Query,
+                    range,
+                    name.value_range()?,
+                );
+                symbol.detail = tag.attribute_value("datasource");
+                Some(symbol)
+            }
+            "cfoutput" => {
+                let mut symbol = symbol(
+                    tag_name.text(),
+                    SymbolKind::Output,
+                    range,
+                    tag_name.text_range(),
+                );
+                symbol.detail = tag.attribute_value("query");
+                Some(symbol)
+            }
+            "cfset" if self.depth == 0 => {
+                let ast::Expr::AssignExpr(assign) = tag.expression()? else {
+                    return None;
+                };
+                let target = assign.target()?;
+                let name = page_variable_name(&target)?;
+                if !self.variables.insert(name.to_ascii_lowercase()) {
+                    return None;
+                }
+                let selection_range = target.syntax().text_range();
+                Some(symbol(&name, SymbolKind::Variable, range, selection_range))
+            }
+            _ => None,
+        }
+    }
+}
+
+fn symbol(
+    name: &str,
+    kind: SymbolKind,
+    range: TextRange,
+    selection_range: TextRange,
+) -> DocumentSymbol {
+    DocumentSymbol {
+        name: name.to_owned(),
+        detail: None,
+        kind,
+        range,
+        selection_range,
+        children: Vec::new(),
+    }
+}
+
+/// `x` for `x = 1` and `variables.x = 1`; other scopes keep their prefix,
+/// as in `request.x`.
+fn page_variable_name(target: &ast::Expr) -> Option<String> {
+    match target {
+        ast::Expr::NameRef(name) => Some(name.name()),
+        ast::Expr::MemberExpr(member) => {
+            let text = member.syntax().text().to_string();
+            let (scope, name) = text.split_once('.')?;
+            match scope.eq_ignore_ascii_case("variables") {
+                true => Some(name.to_owned()),
+                false => Some(text),
+            }
+        }
+        _ => None,
+    }
+}
+
+/// The variable, struct key or argument a closure is assigned to.
+fn closure_name(closure: &SyntaxNode) -> Option<(String, TextRange)> {
+    let parent = closure.parent()?;
+    match parent.kind() {
+        VAR_DECL => {
+            let token = ast::VarDecl::cast(parent)?.name_token()?;
+            Some((token.text().to_owned(), token.text_range()))
+        }
+        ASSIGN_EXPR => {
+            let target = ast::AssignExpr::cast(parent)?.target()?;
+            let target = target.syntax();
+            Some((target.text().to_string(), target.text_range()))
+        }
+        STRUCT_ENTRY => {
+            let token = ast::StructEntry::cast(parent)?.key_token()?;
+            Some((token.text().to_owned(), token.text_range()))
+        }
+        NAMED_ARG => {
+            let token = ast::NamedArg::cast(parent)?.name_token()?;
+            Some((token.text().to_owned(), token.text_range()))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use syntax::ast::SourceFile;
+
+    use super::*;
+
+    fn check(text: &str, expect: &str) {
+        let symbols = document_symbols(&SourceFile::parse(text).tree(), Some("User".to_owned()));
+        let mut buf = String::new();
+        render(&symbols, 0, &mut buf);
+        assert_eq!(buf.trim(), expect.trim());
+    }
+
+    fn render(symbols: &[DocumentSymbol], indent: usize, buf: &mut String) {
+        for symbol in symbols {
+            buf.push_str(&format!("{:indent$}{:?} {}", "", symbol.kind, symbol.name));
+            if let Some(detail) = &symbol.detail {
+                buf.push_str(&format!(" {detail}"));
+            }
+            buf.push('\n');
+            render(&symbol.children, indent + 2, buf);
+        }
+    }
+
+    #[test]
+    fn script_component() {
+        check(
+            r#"component {
+    property name="id" type="numeric";
+    property models.Role role;
+    function init() { return this; }
+    function each(items) {
+        var visit = function(item) {
+            var inner = (x) => x;
+        };
+        items.each(callback = function(it) {});
+        arrayMap(items, function(it) {});
+    }
+}"#,
+            r#"
+Component User
+  Property id numeric
+  Property role models.Role
+  Method init ()
+  Method each (items)
+    Closure visit
+      Closure inner
+    Closure callback
+    Closure <function>
+"#,
+        );
+    }
+
+    #[test]
+    fn template() {
+        check(
+            r##"<cfset title = "Users">
+<cfset variables.count = 0>
+<cfset request.start = now()>
+<cfset title = "Other">
+<cfquery name="users" datasource="app">
+    SELECT * FROM users
+</cfquery>
+<cfoutput query="users">
+    #name#
+</cfoutput>
+<cffunction name="format">
+    <cfargument name="value">
+    <cfset result = value>
+    <cfquery name="lookup">SELECT 1</cfquery>
+</cffunction>
+<cfscript>
+    function helper(a, b) {}
+</cfscript>"##,
+            r#"
+Variable title
+Variable count
+Variable request.start
+Query users app
+Output cfoutput users
+Function format (value)
+  Query lookup
+Function helper (a, b)
+"#,
+        );
+    }
+}
@@ -0,0 +1,803 @@
+//! Extracting statements into a new function, and an expression into a
+//! variable, in script or tags.
+//!
+//! An extracted function takes the local variables and arguments the
+//! statements read before assigning as parameters, and returns the one
+//! they assign that is read afterwards. Statements assigning more than
+//! one such variable, or leaving the function with `return` or a loop
+//! with `break`, are not extracted.
+
+use rustc_hash::FxHashSet;
+use syntax::{
+    ast::{self, AstNode},
+    NodeOrToken, SyntaxElement,
+    SyntaxKind::*,
+    SyntaxNode, TextRange, TextSize,
+};
+
+use crate::{
+    assists::{indent_unit, line_ending, line_indent, Assist, AssistKind},
+    db::RootDatabase,
+    items::{is_scope_owner, FunctionNode},
+    source_change::SourceChange,
+    text_edit::TextEdit,
+    usages::{Access, VarOccurrence, VarScope},
+    FileRange,
+};
+
+/// Tags that only make sense in the tag around them.
+const FIXED_TAGS: &[&str] = &[
+    "cfargument",
+    "cfcase",
+    "cfcatch",
+    "cfdefaultcase",
+    "cfelse",
+    "cfelseif",
+    "cffinally",
+    "cffunction",
+];
+
+/// Moves the statements selected by `frange` into a new private function
+/// declared after the one they are in, and calls it in their place.
+pub(crate) fn extract_function(db: &RootDatabase, frange: FileRange) -> Option<Assist> {
+    let file = db.parse(frange.file_id).tree();
+    let text = db.file_text(frange.file_id);
+    let range = trim(&text, frange.range);
+    if range.is_empty() {
+        return None;
+    }
+    let covering = match file.syntax().covering_element(range) {
+        NodeOrToken::Node(it) => it,
+        NodeOrToken::Token(it) => it.parent()?,
+    };
+    let (container, selected) = covering
+        .ancestors()
+        .find_map(|it| Some((it.clone(), selected_statements(&it, range)?)))?;
+    let function = FunctionNode::cast(container.ancestors().find(is_scope_owner)?)?;
+    let is_script = matches!(function, FunctionNode::Script(_));
+    if is_script != (container.kind() == BLOCK) || leaves_selection(&function, &selected, range) {
+        return None;
+    }
+
+    let owner = function.syntax().text_range();
+    let usages = db.usages(frange.file_id);
+    let vars: Vec<&VarOccurrence> = usages
+        .vars
+        .iter()
+        .filter(|it| match &it.scope {
+            VarScope::Local { owner: it } | VarScope::Arguments { owner: it } => *it == owner,
+            _ => false,
+        })
+        .collect();
+    let declared: FxHashSet<String> = vars
+        .iter()
+        .filter(|it| it.is_decl && range.contains_range(it.range))
+        .map(|it| it.name.to_ascii_lowercase())
+        .collect();
+    let mut selected_vars: Vec<_> = vars
+        .iter()
+        .filter(|it| range.contains_range(it.range))
+        .collect();
+    selected_vars.sort_by_key(|it| it.range.start());
+    // Variables first assigned in the statements are their own; the rest
+    // are read before and passed in.
+    let mut params: Vec<&str> = Vec::new();
+    let mut assigned: Vec<&VarOccurrence> = Vec::new();
+    for var in &selected_vars {
+        let is_seen = params
+            .iter()
+            .copied()
+            .chain(assigned.iter().map(|it| it.name.as_str()))
+            .any(|it| it.eq_ignore_ascii_case(&var.name));
+        if is_seen || declared.contains(&var.name.to_ascii_lowercase()) {
+            continue;
+        }
+        let read_first = |value: TextRange| {
+            selected_vars.iter().any(|it| {
+                it.access == Access::Read
+                    && value.contains_range(it.range)
+                    && it.name.eq_ignore_ascii_case(&var.name)
+            })
+        };
+        let overwritten = match var.access {
+            Access::Write => overwrites(file.syntax(), var.range),
+            Access::Read => None,
+        };
+        match overwritten {
+            Some(value) if !read_first(value) => assigned.push(var),
+            _ => params.push(&var.name),
+        }
+    }
+
+    // Reads later in the function, or anywhere in a loop around the
+    // statements, see what they assign.
+    let enclosing_loop = container
+        .ancestors()
+        .take_while(|it| it != function.syntax())
+        .filter(|it| match it.kind() {
+            FOR_STMT | FOR_IN_STMT | WHILE_STMT | DO_WHILE_STMT => true,
+            TAG => ast::Tag::cast(it.clone()).is_some_and(|it| it.name() == "cfloop"),
+            _ => false,
+        })
+        .last()
+        .map(|it| it.text_range());
+    let is_later = |it: TextRange| {
+        !range.contains_range(it)
+            && (it.start() >= range.end() || enclosing_loop.is_some_and(|l| l.contains_range(it)))
+    };
+    let mut outputs: Vec<&str> = Vec::new();
+    for var in &selected_vars {
+        let is_written = var.access == Access::Write || var.is_decl;
+        let is_read_later = vars.iter().any(|it| {
+            it.access == Access::Read
+                && is_later(it.range)
+                && it.name.eq_ignore_ascii_case(&var.name)
+        });
+        let is_output = outputs.iter().any(|it| it.eq_ignore_ascii_case(&var.name));
+        if is_written && is_read_later && !is_output {
+            outputs.push(&var.name);
+        }
+    }
+    let output = match outputs[..] {
+        [] => None,
+        [it] => Some(it),
+        _ => return None,
+    };
+
+    let items = db.items(frange.file_id);
+    let name = unique_name("extracted", |name| {
+        items
+            .functions
+            .iter()
+            .any(|it| it.name.eq_ignore_ascii_case(name))
+    });
+
+    // `local.x` would not find a local variable passed as an argument, and
+    // an unscoped variable assigned in the new function would not be local
+    // to it.
+    let mut renames: Vec<_> = vars
+        .iter()
+        .filter(|it| it.scoped && matches!(it.scope, VarScope::Local { .. }))
+        .filter(|it| range.contains_range(it.range))
+        .filter(|it| params.iter().any(|p| p.eq_ignore_ascii_case(&it.name)))
+        .filter_map(|it| {
+            let prefix = TextRange::at(it.range.start() - TextSize::of("local."), 5.into());
+            text.get(std::ops::Range::<usize>::from(prefix))?
+                .eq_ignore_ascii_case("local")
+                .then_some((prefix - range.start(), "arguments"))
+        })
+        .chain(
+            assigned
+                .iter()
+                .filter(|it| !it.scoped && matches!(it.scope, VarScope::Local { .. }))
+                .map(|it| (TextRange::empty(it.range.start() - range.start()), "local.")),
+        )
+        .collect();
+    renames.sort_by_key(|(it, _)| std::cmp::Reverse(it.start()));
+    let mut body = text[range].to_owned();
+    for (prefix, replacement) in renames {
+        body.replace_range(std::ops::Range::<usize>::from(prefix), replacement);
+    }
+
+    let newline = line_ending(&text);
+    let function_indent = line_indent(&text, owner.start());
+    let body_indent = format!("{function_indent}{}", indent_unit(&text));
+    let old_indent = line_indent(&text, range.start());
+    let mut lines = Vec::new();
+    for (i, line) in body.lines().enumerate() {
+        let line = match i {
+            0 => line,
+            _ if line.trim().is_empty() => "",
+            _ => line.strip_prefix(old_indent).unwrap_or(line.trim_start()),
+        };
+        match line.is_empty() {
+            true => lines.push(String::new()),
+            false => lines.push(format!("{body_indent}{line}")),
+        }
+    }
+    let body = lines.join(newline);
+
+    let args = params.join(", ");
+    let is_new = |name: &str| {
+        let is_assigned = assigned.iter().any(|it| it.name.eq_ignore_ascii_case(name));
+        let is_used_before = vars
+            .iter()
+            .any(|it| it.range.end() <= range.start() && it.name.eq_ignore_ascii_case(name));
+        declared.contains(&name.to_ascii_lowercase()) || (is_assigned && !is_used_before)
+    };
+    let assign = match output {
+        Some(it) if is_new(it) => format!("var {it} = "),
+        Some(it) => format!("{it} = "),
+        None => String::new(),
+    };
+    let (declaration, call) = if is_script {
+        let ret = output.map_or_else(String::new, |it| {
+            format!("{newline}{body_indent}return {it};")
+        });
+        let declaration = format!(
+            "{newline}{newline}{function_indent}private function {name}({args}) {{{newline}\
+             {body}{ret}{newline}{function_indent}}}"
+        );
+        (declaration, format!("{assign}{name}({args});"))
+    } else {
+        let arguments: String = params
+            .iter()
+            .map(|it| format!("{body_indent}<cfargument name=\"{it}\">{newline}"))
+            .collect();
+        let ret = output.map_or_else(String::new, |it| {
+            format!("{newline}{body_indent}<cfreturn {it}>")
+        });
+        let declaration = format!(
+            "{newline}{newline}{function_indent}<cffunction name=\"{name}\" access=\"private\">\
+             {newline}{arguments}{body}{ret}{newline}{function_indent}</cffunction>"
+        );
+        (declaration, format!("<cfset {assign}{name}({args})>"))
+    };
+
+    let mut edit = TextEdit::builder();
+    edit.replace(range, call);
+    edit.insert(owner.end(), declaration);
+    Some(Assist {
+        label: "Extract into function".to_owned(),
+        kind: AssistKind::RefactorExtract,
+        source_change: SourceChange::from_text_edit(frange.file_id, edit.finish()),
+    })
+}
+
+/// Assigns the expression selected by `frange` to a new variable before
+/// the statement or tag it is in, and uses the variable in its place.
+pub(crate) fn extract_variable(db: &RootDatabase, frange: FileRange) -> Option<Assist> {
+    let file = db.parse(frange.file_id).tree();
+    let text = db.file_text(frange.file_id);
+    let range = trim(&text, frange.range);
+    if range.is_empty() {
+        return None;
+    }
+    let covering = match file.syntax().covering_element(range) {
+        NodeOrToken::Node(it) => it,
+        NodeOrToken::Token(it) => it.parent()?,
+    };
+    let expr = covering
+        .ancestors()
+        .take_while(|it| it.text_range() == range)
+        .filter_map(ast::Expr::cast)
+        .last()?;
+    if matches!(expr, ast::Expr::NameRef(_) | ast::Expr::AssignExpr(_)) {
+        return None;
+    }
+    let parent = expr.syntax().parent()?;
+    let is_target = ast::AssignExpr::cast(parent.clone())
+        .and_then(|it| it.target())
+        .is_some_and(|it| it.syntax() == expr.syntax());
+    // The whole expression of a statement or `<cfset>` has no value used.
+    let is_cfset = parent.kind() == START_TAG
+        && parent
+            .parent()
+            .and_then(ast::Tag::cast)
+            .is_some_and(|it| it.name() == "cfset");
+    if parent.kind() == EXPR_STMT || is_cfset || is_target {
+        return None;
+    }
+
+    // The statement or tag evaluating the expression once, where the
+    // variable can be assigned just before it.
+    let mut child = expr.syntax().clone();
+    let (anchor, is_script) = loop {
+        let parent = child.parent()?;
+        let is_header = ast::Expr::can_cast(child.kind()) || child.kind() == VAR_DECL;
+        match parent.kind() {
+            BLOCK | SCRIPT if ast::Stmt::can_cast(child.kind()) => break (child, true),
+            CLOSURE_EXPR | ARROW_EXPR | PARAM | CASE_CLAUSE | ELSE_BRANCH => return None,
+            FOR_STMT | WHILE_STMT | DO_WHILE_STMT if is_header => return None,
+            IF_STMT if is_header && parent.parent()?.kind() == ELSE_BRANCH => return None,
+            START_TAG => {
+                let tag = ast::Tag::cast(parent.parent()?)?;
+                if FIXED_TAGS.contains(&tag.name().as_str()) {
+                    return None;
+                }
+                break (tag.syntax().clone(), false);
+            }
+            _ if ast::Stmt::can_cast(child.kind()) => return None,
+            TAG => return None,
+            _ => (),
+        }
+        child = parent;
+    };
+
+    let usages = db.usages(frange.file_id);
+    let name = unique_name("value", |name| {
+        usages
+            .vars
+            .iter()
+            .any(|it| it.name.eq_ignore_ascii_case(name))
+    });
+    let in_function = match is_script {
+        true => anchor.ancestors().any(|it| is_scope_owner(&it)),
+        false => anchor
+            .ancestors()
+            .any(|it| FunctionNode::cast(it).is_some()),
+    };
+    let var = if in_function { "var " } else { "" };
+    let value = &text[range];
+    let declaration = match is_script {
+        true => format!("{var}{name} = {value};"),
+        false => format!("<cfset {var}{name} = {value}>"),
+    };
+    let start = anchor.text_range().start();
+    let indent = line_indent(&text, start);
+
+    let mut edit = TextEdit::builder();
+    edit.insert(
+        start,
+        format!("{declaration}{}{indent}", line_ending(&text)),
+    );
+    edit.replace(range, name);
+    Some(Assist {
+        label: "Extract into variable".to_owned(),
+        kind: AssistKind::RefactorExtract,
+        source_change: SourceChange::from_text_edit(frange.file_id, edit.finish()),
+    })
+}
+
+/// `range` without the whitespace at its ends.
+fn trim(text: &str, range: TextRange) -> TextRange {
+    let selected = &text[range];
+    let start =
+        range.start() + TextSize::of(&selected[..selected.len() - selected.trim_start().len()]);
+    let end = range.end() - TextSize::of(&selected[selected.trim_end().len()..]);
+    TextRange::new(start, end.max(start))
+}
+
+/// The statements, tags and comments of `container` spanning exactly
+/// `range`.
+fn selected_statements(container: &SyntaxNode, range: TextRange) -> Option<Vec<SyntaxElement>> {
+    if !matches!(container.kind(), BLOCK | TAG) {
+        return None;
+    }
+    let mut selected = Vec::new();
+    for element in container.children_with_tokens() {
+        let element_range = element.text_range();
+        if element_range.end() <= range.start() || element_range.start() >= range.end() {
+            continue;
+        }
+        let is_blank = match &element {
+            NodeOrToken::Token(it) => {
+                it.kind() == WHITESPACE || it.kind() == TEXT && it.text().trim().is_empty()
+            }
+            NodeOrToken::Node(_) => false,
+        };
+        if is_blank {
+            continue;
+        }
+        if !range.contains_range(element_range) {
+            return None;
+        }
+        match &element {
+            NodeOrToken::Token(it)
+                if matches!(it.kind(), LINE_COMMENT | BLOCK_COMMENT | TAG_COMMENT) => {}
+            NodeOrToken::Node(it) if it.kind() == TAG => {
+                let tag = ast::Tag::cast(it.clone())?;
+                if FIXED_TAGS.contains(&tag.name().as_str()) {
+                    return None;
+                }
+            }
+            NodeOrToken::Node(it)
+                if container.kind() == BLOCK
+                    && ast::Stmt::can_cast(it.kind())
+                    && it.kind() != FUNCTION_DECL => {}
+            _ => return None,
+        }
+        selected.push(element);
+    }
+    let first = selected.first()?.text_range();
+    let last = selected.last()?.text_range();
+    let has_statement = selected.iter().any(|it| it.as_node().is_some());
+    (first.start() == range.start() && last.end() == range.end() && has_statement)
+        .then_some(selected)
+}
+
+/// Whether the statements `return` from `function`, or `break` or
+/// `continue` a loop around `range`.
+fn leaves_selection(function: &FunctionNode, selected: &[SyntaxElement], range: TextRange) -> bool {
+    let nodes = selected.iter().filter_map(|it| it.as_node());
+    nodes.flat_map(|it| it.descendants()).any(|node| {
+        let tag = ast::Tag::cast(node.clone()).map(|it| it.name());
+        let target = match (node.kind(), tag.as_deref()) {
+            (RETURN_STMT, _) | (_, Some("cfreturn")) => {
+                return node.ancestors().find(is_scope_owner).as_ref() == Some(function.syntax());
+            }
+            (BREAK_STMT, _) => node.ancestors().find(|it| {
+                matches!(
+                    it.kind(),
+                    FOR_STMT | FOR_IN_STMT | WHILE_STMT | DO_WHILE_STMT | SWITCH_STMT
+                )
+            }),
+            (CONTINUE_STMT, _) => node.ancestors().find(|it| {
+                matches!(
+                    it.kind(),
+                    FOR_STMT | FOR_IN_STMT | WHILE_STMT | DO_WHILE_STMT
+                )
+            }),
+            (_, Some("cfbreak" | "cfcontinue")) => node
+                .ancestors()
+                .skip(1)
+                .find(|it| ast::Tag::cast(it.clone()).is_some_and(|it| it.name() == "cfloop")),
+            _ => return false,
+        };
+        target.is_none_or(|it| !range.contains_range(it.text_range()))
+    })
+}
+
+/// If the variable written at `range` is given a new value, rather than
+/// updated from its old one as by `+=` or `++`, the range of the value,
+/// evaluated before the variable is written.
+fn overwrites(root: &SyntaxNode, range: TextRange) -> Option<TextRange> {
+    let mut target = root
+        .token_at_offset(range.start())
+        .right_biased()?
+        .parent()?;
+    while let Some(parent) = target.parent() {
+        let is_path = parent.kind() == NAME_REF || ast::Expr::can_cast(parent.kind());
+        if !is_path || parent.text_range().end() != range.end() {
+            break;
+        }
+        if matches!(parent.kind(), ASSIGN_EXPR | PREFIX_EXPR | POSTFIX_EXPR) {
+            break;
+        }
+        target = parent;
+    }
+    let Some(parent) = target.parent() else {
+        return Some(TextRange::empty(range.start()));
+    };
+    match parent.kind() {
+        ASSIGN_EXPR => {
+            let assign = ast::AssignExpr::cast(parent)?;
+            if assign.target()?.syntax() != &target || assign.op_token()?.kind() != EQ {
+                return None;
+            }
+            let value = assign.value().map(|it| it.syntax().text_range());
+            Some(value.unwrap_or(TextRange::empty(range.end())))
+        }
+        PREFIX_EXPR | POSTFIX_EXPR => None,
+        _ => Some(TextRange::empty(range.start())),
+    }
+}
+
+/// `base`, or `base` numbered from 2 if it is `taken`.
+fn unique_name(base: &str, taken: impl Fn(&str) -> bool) -> String {
+    if !taken(base) {
+        return base.to_owned();
+    }
+    (2..)
+        .map(|i| format!("{base}{i}"))
+        .find(|it| !taken(it))
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use virtual_fs::FileId;
+
+    use crate::{fixture, FileRange};
+
+    /// The first file after the assist labelled `label` at the selection
+    /// between the `$0` markers, or `None` if it is not offered.
+    fn apply(label: &str, fixture: &str) -> Option<String> {
+        let (fixture, range) = fixture::selection(fixture);
+        let analysis = fixture::parse(&fixture).analysis;
+        let mut text = analysis.file_text(FileId(0)).to_string();
+        let assist = analysis
+            .assists(FileRange {
+                file_id: FileId(0),
+                range,
+            })
+            .into_iter()
+            .find(|it| it.label == label)?;
+        assist.source_change.source_file_edits[&FileId(0)].apply(&mut text);
+        Some(text)
+    }
+
+    fn check(label: &str, fixture: &str, expect: &str) {
+        assert_eq!(apply(label, fixture).as_deref(), Some(expect));
+    }
+
+    #[test]
+    fn extracts_script_function() {
+        check(
+            "Extract into function",
+            r#"
+//- /Cart.cfc
+component {
+    function total(items, rate) {
+        var sum = 0;
+        local.count = 0;
+        $0for (var item in items) {
+            sum += item.price;
+            local.count++;
+        }
+        var taxed = sum * rate;$0
+        return taxed / arrayLen(items);
+    }
+}
+"#,
+            r#"component {
+    function total(items, rate) {
+        var sum = 0;
+        local.count = 0;
+        var taxed = extracted(items, sum, count, rate);
+        return taxed / arrayLen(items);
+    }
+
+    private function extracted(items, sum, count, rate) {
+        for (var item in items) {
+            sum += item.price;
+            arguments.count++;
+        }
+        var taxed = sum * rate;
+        return taxed;
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn extracts_tag_function() {
+        check(
+            "Extract into function",
+            r##"
+//- /Cart.cfc
+<cfcomponent>
+    <cffunction name="extracted">
+    </cffunction>
+    <cffunction name="total">
+        <cfargument name="items">
+        <cfset var sum = 0>
+        $0<cfloop array="#items#" index="item">
+            <cfset sum += item>
+        </cfloop>$0
+        <cfreturn sum>
+    </cffunction>
+</cfcomponent>
+"##,
+            r##"<cfcomponent>
+    <cffunction name="extracted">
+    </cffunction>
+    <cffunction name="total">
+        <cfargument name="items">
+        <cfset var sum = 0>
+        <cfset sum = extracted2(items, sum)>
+        <cfreturn sum>
+    </cffunction>
+
+    <cffunction name="extracted2" access="private">
+        <cfargument name="items">
+        <cfargument name="sum">
+        <cfloop array="#items#" index="item">
+            <cfset sum += item>
+        </cfloop>
+        <cfreturn sum>
+    </cffunction>
+</cfcomponent>
+"##,
+        );
+    }
+
+    #[test]
+    fn assigned_variables_not_passed() {
+        check(
+            "Extract into function",
+            r#"
+//- /a.cfc
+component {
+    function f() {
+        var total = 0;
+        $0local.tmp = 5;
+        total = total + local.tmp;$0
+        return total;
+    }
+}
+"#,
+            r#"component {
+    function f() {
+        var total = 0;
+        total = extracted(total);
+        return total;
+    }
+
+    private function extracted(total) {
+        local.tmp = 5;
+        total = total + local.tmp;
+        return total;
+    }
+}
+"#,
+        );
+        check(
+            "Extract into function",
+            r##"
+//- /a.cfc
+<cfcomponent>
+    <cffunction name="f">
+        <cfargument name="items">
+        <cfset var total = 0>
+        $0<cfloop array="#items#" index="local.item">
+            <cfset total += item>
+        </cfloop>$0
+        <cfreturn total>
+    </cffunction>
+</cfcomponent>
+"##,
+            r##"<cfcomponent>
+    <cffunction name="f">
+        <cfargument name="items">
+        <cfset var total = 0>
+        <cfset total = extracted(items, total)>
+        <cfreturn total>
+    </cffunction>
+
+    <cffunction name="extracted" access="private">
+        <cfargument name="items">
+        <cfargument name="total">
+        <cfloop array="#items#" index="local.item">
+            <cfset total += item>
+        </cfloop>
+        <cfreturn total>
+    </cffunction>
+</cfcomponent>
+"##,
+        );
+        check(
+            "Extract into function",
+            r##"
+//- /a.cfc
+<cfcomponent>
+    <cffunction name="f">
+        <cfset var item = "">
+        $0<cfloop list="a,b" index="item">
+            <cfoutput>#item#</cfoutput>
+        </cfloop>$0
+    </cffunction>
+</cfcomponent>
+"##,
+            r##"<cfcomponent>
+    <cffunction name="f">
+        <cfset var item = "">
+        <cfset extracted()>
+    </cffunction>
+
+    <cffunction name="extracted" access="private">
+        <cfloop list="a,b" index="local.item">
+            <cfoutput>#item#</cfoutput>
+        </cfloop>
+    </cffunction>
+</cfcomponent>
+"##,
+        );
+    }
+
+    #[test]
+    fn function_not_extracted() {
+        let not_extracted = [
+            // Partial statements.
+            "function f() { $0a = 1; b$0 = 2; }",
+            // Leaving the function or the loop.
+            "function f() { $0if (a) { return 1; }$0 }",
+            "function f() { while (true) { $0if (a) { break; }$0 } }",
+            // Assigning two variables read afterwards.
+            "function f() { var a = 0; var b = 0; $0a = 1; b = 2;$0 return a + b; }",
+        ];
+        for body in not_extracted {
+            let fixture = format!("//- /a.cfc\ncomponent {{ {body} }}");
+            assert_eq!(apply("Extract into function", &fixture), None, "{body}");
+        }
+        let fixture = r#"
+//- /a.cfc
+<cfcomponent><cffunction name="f">$0<cfargument name="a">$0</cffunction></cfcomponent>
+"#;
+        assert_eq!(apply("Extract into function", fixture), None);
+    }
+
+    #[test]
+    fn extracts_variable() {
+        check(
+            "Extract into variable",
+            r#"
+//- /a.cfc
+component {
+    function f(items) {
+        if (arrayLen(items) > 0) {
+            return $0items[1].price * 2$0;
+        }
+    }
+}
+"#,
+            r#"component {
+    function f(items) {
+        if (arrayLen(items) > 0) {
+            var value = items[1].price * 2;
+            return value;
+        }
+    }
+}
+"#,
+        );
+        check(
+            "Extract into variable",
+            r#"
+//- /page.cfm
+<cfset value = 1>
+<cfif $0len(trim(form.name))$0 gt 10>
+</cfif>
+"#,
+            r#"<cfset value = 1>
+<cfset value2 = len(trim(form.name))>
+<cfif value2 gt 10>
+</cfif>
+"#,
+        );
+    }
+
+    #[test]
+    fn extracts_tag_function_variable() {
+        check(
+            "Extract into variable",
+            r##"
+//- /a.cfc
+<cfcomponent>
+    <cffunction name="f">
+        <cfargument name="a">
+        <cfset var x = $0a + 1$0>
+        <cfreturn x * 2>
+    </cffunction>
+</cfcomponent>
+"##,
+            r##"<cfcomponent>
+    <cffunction name="f">
+        <cfargument name="a">
+        <cfset var value = a + 1>
+        <cfset var x = value>
+        <cfreturn x * 2>
+    </cffunction>
+</cfcomponent>
+"##,
+        );
+        check(
+            "Extract into variable",
+            r##"
+//- /a.cfc
+<cfcomponent>
+    <cffunction name="f">
+        <cfargument name="a">
+        <cfreturn $0a * 2$0>
+    </cffunction>
+</cfcomponent>
+"##,
+            r##"<cfcomponent>
+    <cffunction name="f">
+        <cfargument name="a">
+        <cfset var value = a * 2>
+        <cfreturn value>
+    </cffunction>
+</cfcomponent>
+"##,
+        );
+    }
+
+    #[test]
+    fn variable_not_extracted() {
+        let not_extracted = [
+            "while ($0f(i)$0) {}",
+            "if (a) {} else if ($0f(i)$0) {}",
+            "x = $0y$0;",
+            "$0f(i)$0;",
+            "x = arrayMap(a, (it) => $0it * 2$0);",
+        ];
+        for body in not_extracted {
+            let fixture = format!("//- /a.cfc\ncomponent {{ function f() {{ {body} }} }}");
+            assert_eq!(apply("Extract into variable", &fixture), None, "{body}");
+        }
+        let fixture = "//- /page.cfm\n<cfif a><cfelseif $0f(b)$0></cfif>";
+        assert_eq!(apply("Extract into variable", fixture), None);
+    }
+}
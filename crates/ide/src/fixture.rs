@@ -0,0 +1,119 @@
+//! Multi-file test fixtures.
+//!
+//! A fixture is a list of files, each introduced by a `//- /path` line.
+//! Paths are relative to a single workspace root. A `$0` marker in the text
+//! sets the cursor position.
+
+use std::sync::Arc;
+
+use syntax::{TextRange, TextSize};
+use virtual_fs::{FileId, VirtualFsPath};
+
+use crate::{Analysis, AnalysisHost, Change, FilePosition, FileSystemEdit, SourceChange};
+
+const ROOT: &str = "/ws";
+const CURSOR: &str = "$0";
+
+pub(crate) struct Fixture {
+    pub(crate) analysis: Analysis,
+    pub(crate) position: Option<FilePosition>,
+}
+
+pub(crate) fn parse(fixture: &str) -> Fixture {
+    let mut host = AnalysisHost::default();
+    let mut change = Change::new();
+    change.set_roots(vec![VirtualFsPath::new_virtual_path(ROOT.to_owned())]);
+
+    let mut files = Vec::new();
+    let mut position = None;
+    let mut current: Option<(String, String)> = None;
+    let mut flush = |current: Option<(String, String)>, files: &mut Vec<FileId>| {
+        let Some((path, mut text)) = current else {
+            return;
+        };
+        let file_id = FileId(files.len() as u32);
+        if let Some(offset) = text.find(CURSOR) {
+            text.replace_range(offset..offset + CURSOR.len(), "");
+            position = Some(FilePosition {
+                file_id,
+                offset: TextSize::from(offset as u32),
+            });
+        }
+        let vfs_path = VirtualFsPath::new_virtual_path(format!("{ROOT}{path}"));
+        change.change_file(file_id, vfs_path, Some(Arc::from(text)));
+        files.push(file_id);
+    };
+
+    for line in fixture.lines() {
+        if let Some(path) = line.strip_prefix("//- ") {
+            flush(current.take(), &mut files);
+            current = Some((path.trim().to_owned(), String::new()));
+        } else if let Some((_, text)) = &mut current {
+            text.push_str(line);
+            text.push('\n');
+        }
+    }
+    flush(current.take(), &mut files);
+
+    host.apply_change(change);
+    Fixture {
+        analysis: host.analysis(),
+        position,
+    }
+}
+
+/// A fixture with a `$0` cursor.
+pub(crate) fn position(fixture: &str) -> (Analysis, FilePosition) {
+    let fixture = parse(fixture);
+    (
+        fixture.analysis,
+        fixture.position.expect("fixture has no `$0` cursor"),
+    )
+}
+
+/// A fixture with a selection between two `$0` markers, or an empty one at
+/// a single marker, without the markers. The range is relative to the file
+/// the selection is in.
+pub(crate) fn selection(fixture: &str) -> (String, TextRange) {
+    let start = fixture.find(CURSOR).expect("fixture has no `$0` marker");
+    let fixture = fixture.replacen(CURSOR, "", 1);
+    let end = fixture[start..].find(CURSOR).map_or(start, |it| start + it);
+    let fixture = fixture.replacen(CURSOR, "", 1);
+    let file_start = fixture[..start]
+        .rfind("//- ")
+        .map_or(0, |it| it + fixture[it..].find('\n').unwrap() + 1);
+    let range = TextRange::new(
+        TextSize::from((start - file_start) as u32),
+        TextSize::from((end - file_start) as u32),
+    );
+    (fixture, range)
+}
+
+/// Renders the files touched by `change`, after applying it, sorted by path,
+/// followed by its file system edits.
+pub(crate) fn render_change(analysis: &Analysis, change: &SourceChange) -> String {
+    let path = |file_id: FileId| {
+        analysis
+            .db
+            .file_path(file_id)
+            .map(|it| it.to_string().trim_start_matches(ROOT).to_owned())
+            .unwrap_or_default()
+    };
+
+    let mut files: Vec<_> = change.source_file_edits.iter().collect();
+    files.sort_by_key(|(file_id, _)| path(**file_id));
+    let mut buf = String::new();
+    for (file_id, edit) in files {
+        let mut text = analysis.file_text(*file_id).to_string();
+        edit.apply(&mut text);
+        buf.push_str(&format!("//- {}\n{text}", path(*file_id)));
+    }
+    for edit in &change.file_system_edits {
+        match edit {
+            FileSystemEdit::MoveFile { src, dst } => {
+                buf.push_str(&format!("move {} -> {}\n", path(*src), dst.path));
+            }
+        }
+    }
+    buf
+}
@@ -0,0 +1,232 @@
+//! Folding ranges: the bodies of tag pairs and of each `cfif` branch,
+//! cfscript blocks and literals, multi-line comments, runs of `//`
+//! comments and `// region` ... `// endregion` markers.
+//!
+//! Folds cover the contents between the delimiters with the trailing
+//! whitespace trimmed, so a client folding whole lines keeps the closing
+//! delimiter visible.
+
+use rustc_hash::FxHashSet;
+use syntax::{
+    ast::{self, AstNode},
+    tags, NodeOrToken,
+    SyntaxKind::*,
+    SyntaxNode, SyntaxToken, TextRange, TextSize,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FoldKind {
+    Comment,
+    Region,
+    /// The body of a tag pair or of a `cfif` branch.
+    Tag,
+    /// A cfscript block, struct or array literal.
+    Block,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fold {
+    pub range: TextRange,
+    pub kind: FoldKind,
+}
+
+pub(crate) fn folding_ranges(file: &ast::SourceFile) -> Vec<Fold> {
+    let mut folds = Vec::new();
+    let mut grouped_comments = FxHashSet::default();
+    let mut region_starts = Vec::new();
+
+    for element in file.syntax().descendants_with_tokens() {
+        match element {
+            NodeOrToken::Token(token) if token.kind().is_comment() => match region_marker(&token) {
+                Some(RegionMarker::Start) => region_starts.push(token.text_range().start()),
+                Some(RegionMarker::End) => {
+                    if let Some(start) = region_starts.pop() {
+                        let range = TextRange::new(start, token.text_range().end());
+                        folds.push(Fold {
+                            range,
+                            kind: FoldKind::Region,
+                        });
+                    }
+                }
+                None if token.kind() == LINE_COMMENT => {
+                    if let Some(range) = line_comment_run(&token, &mut grouped_comments) {
+                        folds.push(Fold {
+                            range,
+                            kind: FoldKind::Comment,
+                        });
+                    }
+                }
+                None if token.text().contains('\n') => folds.push(Fold {
+                    range: token.text_range(),
+                    kind: FoldKind::Comment,
+                }),
+                None => (),
+            },
+            NodeOrToken::Token(_) => (),
+            NodeOrToken::Node(node) => {
+                let fold = match node.kind() {
+                    TAG => tag_body(&node).map(|range| Fold {
+                        range,
+                        kind: FoldKind::Tag,
+                    }),
+                    BLOCK | STRUCT_LIT | ARRAY_LIT => delimited_body(&node).map(|range| Fold {
+                        range,
+                        kind: FoldKind::Block,
+                    }),
+                    _ => None,
+                };
+                folds.extend(fold.filter(|it| spans_lines(&node, it.range)));
+            }
+        }
+    }
+    folds.sort_by_key(|it| (it.range.start(), std::cmp::Reverse(it.range.end())));
+    folds
+}
+
+enum RegionMarker {
+    Start,
+    End,
+}
+
+/// `// region`, `// #region`, `/* region */` and `<!--- region --->`, and
+/// their `endregion` counterparts.
+fn region_marker(comment: &SyntaxToken) -> Option<RegionMarker> {
+    let text = comment.text();
+    let text = text
+        .strip_prefix("//")
+        .or_else(|| text.strip_prefix("/*")?.strip_suffix("*/"))
+        .or_else(|| text.strip_prefix("<!---")?.strip_suffix("--->"))?;
+    let text = text.trim().trim_start_matches('#').to_ascii_lowercase();
+    let is_word = |word: &str| {
+        text.strip_prefix(word)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with(char::is_whitespace))
+    };
+    if is_word("region") {
+        Some(RegionMarker::Start)
+    } else if is_word("endregion") {
+        Some(RegionMarker::End)
+    } else {
+        None
+    }
+}
+
+/// The range of the run of `//` comments starting at `first`, each on its
+/// own line, if there are at least two.
+fn line_comment_run(
+    first: &SyntaxToken,
+    grouped: &mut FxHashSet<SyntaxToken>,
+) -> Option<TextRange> {
+    if grouped.contains(first) {
+        return None;
+    }
+    let mut last = first.clone();
+    let mut next = first.next_token();
+    while let Some(token) = next {
+        match token.kind() {
+            WHITESPACE if token.text().matches('\n').count() <= 1 => (),
+            LINE_COMMENT if region_marker(&token).is_none() => {
+                grouped.insert(token.clone());
+                last = token.clone();
+            }
+            _ => break,
+        }
+        next = token.next_token();
+    }
+    (last != *first).then(|| TextRange::new(first.text_range().start(), last.text_range().end()))
+}
+
+/// The body of a tag up to its end tag, or for `cfif`, up to its first
+/// `cfelseif`/`cfelse` branch. Branches fold up to the next branch.
+fn tag_body(node: &SyntaxNode) -> Option<TextRange> {
+    let tag = ast::Tag::cast(node.clone())?;
+    let start = tag.start_tag()?.syntax().text_range().end();
+    let end = node
+        .children()
+        .find(|it| {
+            ast::Tag::cast(it.clone()).is_some_and(|it| tags::is_if_branch(&it.name()))
+                || it.kind() == END_TAG
+        })
+        .map(|it| it.text_range().start());
+    let end = match end {
+        Some(end) => end,
+        None if tags::is_if_branch(&tag.name()) => node.text_range().end(),
+        None => return None,
+    };
+    trimmed(node, TextRange::new(start, end))
+}
+
+/// The contents between the first and the last token of a `{ }` or `[ ]`.
+fn delimited_body(node: &SyntaxNode) -> Option<TextRange> {
+    let open = node.first_token()?;
+    let close = node.last_token()?;
+    if !matches!(open.kind(), L_CURLY | L_BRACK) || !matches!(close.kind(), R_CURLY | R_BRACK) {
+        return None;
+    }
+    trimmed(
+        node,
+        TextRange::new(open.text_range().end(), close.text_range().start()),
+    )
+}
+
+/// `range` without its trailing whitespace.
+fn trimmed(node: &SyntaxNode, range: TextRange) -> Option<TextRange> {
+    let root = node.ancestors().last()?;
+    let text = root.text().slice(range).to_string();
+    let len = TextSize::of(text.trim_end());
+    Some(TextRange::at(range.start(), len))
+}
+
+fn spans_lines(node: &SyntaxNode, range: TextRange) -> bool {
+    let root = node.ancestors().last().unwrap();
+    root.text().slice(range).contains_char('\n')
+}
+
+#[cfg(test)]
+mod tests {
+    use syntax::ast::SourceFile;
+
+    use super::*;
+
+    fn check(text: &str, expect: &[(&str, FoldKind)]) {
+        let folds = folding_ranges(&SourceFile::parse(text).tree());
+        let actual: Vec<_> = folds.iter().map(|it| (&text[it.range], it.kind)).collect();
+        assert_eq!(actual, expect);
+    }
+
+    #[test]
+    fn tags_and_if_branches() {
+        check(
+            "<cffunction name=\"f\">\n  <cfif a>\n    x\n  <cfelseif b>\n    y\n  <cfelse>\n    z\n  </cfif>\n</cffunction>\n<!--- one --->\n<!---\n  two\n--->",
+            &[
+                (
+                    "\n  <cfif a>\n    x\n  <cfelseif b>\n    y\n  <cfelse>\n    z\n  </cfif>",
+                    FoldKind::Tag,
+                ),
+                ("\n    x", FoldKind::Tag),
+                ("\n    y", FoldKind::Tag),
+                ("\n    z", FoldKind::Tag),
+                ("<!---\n  two\n--->", FoldKind::Comment),
+            ],
+        );
+    }
+
+    #[test]
+    fn script_blocks_comments_and_regions() {
+        check(
+            "component {\n  // region helpers\n  // one\n  // two\n\n  // alone\n  function f() {\n    var xs = [\n      1\n    ];\n  }\n  // endregion\n}",
+            &[
+                (
+                    "\n  // region helpers\n  // one\n  // two\n\n  // alone\n  function f() {\n    var xs = [\n      1\n    ];\n  }\n  // endregion",
+                    FoldKind::Block,
+                ),
+                (
+                    "// region helpers\n  // one\n  // two\n\n  // alone\n  function f() {\n    var xs = [\n      1\n    ];\n  }\n  // endregion",
+                    FoldKind::Region,
+                ),
+                ("// one\n  // two", FoldKind::Comment),
+                ("\n    var xs = [\n      1\n    ];", FoldKind::Block),
+                ("\n      1", FoldKind::Block),
+            ],
+        );
+    }
+}
@@ -0,0 +1,959 @@
+//! Document formatting in the style of CommandBox's cfformat, configured
+//! by the settings of its `.cfformat.json`.
+//!
+//! Script, in cfscript blocks and script components, is re-indented and
+//! re-spaced token by token over the lossless tree, breaking struct and
+//! array literals over lines past the configured lengths. Line breaks that
+//! no setting decides are kept, up to one blank line. Tag markup keeps its
+//! layout; only the case of tag names and of called functions changes.
+//! Files with syntax errors are left alone.
+
+use rustc_hash::FxHashSet;
+use syntax::{
+    ast::AstNode,
+    NodeOrToken, SyntaxElement,
+    SyntaxKind::{self, *},
+    SyntaxNode, SyntaxToken, TextRange, TextSize, WalkEvent,
+};
+use virtual_fs::FileId;
+
+use crate::{
+    assists::line_indent, builtins::builtin_function, db::RootDatabase, text_edit::TextEdit,
+};
+
+/// The settings of the formatter, each named after its `.cfformat.json`
+/// key. The defaults are those of cfformat.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormatOptions {
+    /// `indent_size`: the columns of one level of indentation.
+    pub indent_size: usize,
+    /// `tab_indent`: whether to indent with tabs.
+    pub tab_indent: bool,
+    /// `max_columns`: the width past which struct and array literals are
+    /// broken over lines.
+    pub max_columns: usize,
+    /// `binary_operators.padding`: `a + b` rather than `a+b`.
+    pub binary_operators_padding: bool,
+    /// `keywords.spacing_to_group`: `if (` rather than `if(`.
+    pub keywords_spacing_to_group: bool,
+    /// `keywords.padding_inside_group`: `if ( a )`.
+    pub keywords_padding_inside_group: bool,
+    /// `keywords.group_to_block_spacing`: the brace of the blocks of `if`,
+    /// `for`, `try` and other statements.
+    pub keywords_group_to_block_spacing: BlockSpacing,
+    /// `keywords.block_to_keyword_spacing`: between `}` and `else`,
+    /// `catch`, `finally` or the `while` of `do`.
+    pub keywords_block_to_keyword_spacing: BlockSpacing,
+    /// `function_declaration.group_to_block_spacing`: the brace of
+    /// function bodies.
+    pub function_declaration_group_to_block_spacing: BlockSpacing,
+    /// `function_anonymous.group_to_block_spacing`: the brace of closure
+    /// bodies.
+    pub function_anonymous_group_to_block_spacing: BlockSpacing,
+    /// `function_declaration.padding`: `function find( id )`.
+    pub function_declaration_padding: bool,
+    /// `function_call.padding`: `find( id )`.
+    pub function_call_padding: bool,
+    /// `function_call.casing.builtin`: the case of built-in functions.
+    pub function_call_casing_builtin: Casing,
+    /// `function_call.casing.userdefined`: the case of other functions.
+    pub function_call_casing_userdefined: Casing,
+    /// `parentheses.padding`: `( a + b )`.
+    pub parentheses_padding: bool,
+    /// `for_loop_semicolons.padding`: `for (i = 1; i < n; i++)`.
+    pub for_loop_semicolons_padding: bool,
+    /// `struct.padding`: `{ a: 1 }`.
+    pub struct_padding: bool,
+    /// `struct.empty_padding`: `{ }`.
+    pub struct_empty_padding: bool,
+    /// `struct.separator`: between the keys and values of entries.
+    pub struct_separator: String,
+    /// `struct.multiline.element_count`: the number of entries from which
+    /// a struct at least `struct.multiline.min_length` wide is broken over
+    /// lines.
+    pub struct_multiline_element_count: usize,
+    /// `struct.multiline.min_length`
+    pub struct_multiline_min_length: usize,
+    /// `array.padding`: `[ 1, 2 ]`.
+    pub array_padding: bool,
+    /// `array.empty_padding`: `[ ]`.
+    pub array_empty_padding: bool,
+    /// `array.multiline.element_count`, like its struct counterpart.
+    pub array_multiline_element_count: usize,
+    /// `array.multiline.min_length`
+    pub array_multiline_min_length: usize,
+    /// `tags.lowercase`: `<cfset>` rather than `<CFSET>`.
+    pub tags_lowercase: bool,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        FormatOptions {
+            indent_size: 4,
+            tab_indent: false,
+            max_columns: 120,
+            binary_operators_padding: true,
+            keywords_spacing_to_group: true,
+            keywords_padding_inside_group: false,
+            keywords_group_to_block_spacing: BlockSpacing::Spaced,
+            keywords_block_to_keyword_spacing: BlockSpacing::Spaced,
+            function_declaration_group_to_block_spacing: BlockSpacing::Spaced,
+            function_anonymous_group_to_block_spacing: BlockSpacing::Spaced,
+            function_declaration_padding: false,
+            function_call_padding: false,
+            function_call_casing_builtin: Casing::Cfdocs,
+            function_call_casing_userdefined: Casing::Camel,
+            parentheses_padding: false,
+            for_loop_semicolons_padding: true,
+            struct_padding: false,
+            struct_empty_padding: false,
+            struct_separator: ": ".to_owned(),
+            struct_multiline_element_count: 4,
+            struct_multiline_min_length: 40,
+            array_padding: false,
+            array_empty_padding: false,
+            array_multiline_element_count: 4,
+            array_multiline_min_length: 40,
+            tags_lowercase: true,
+        }
+    }
+}
+
+/// Where an opening brace goes after what precedes it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockSpacing {
+    /// `) {`
+    Spaced,
+    /// `){`
+    Compact,
+    /// On a line of its own.
+    Newline,
+}
+
+/// How the names of called functions are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Casing {
+    /// As they are.
+    Ignore,
+    /// As in the CFML documentation, `arrayAppend`; only for built-in
+    /// functions.
+    Cfdocs,
+    /// With a lower case first letter.
+    Camel,
+    /// With an upper case first letter.
+    Pascal,
+}
+
+/// The edits formatting the file, or only the lines `range` touches.
+/// `None` if the file has syntax errors.
+pub(crate) fn format(
+    db: &RootDatabase,
+    file_id: FileId,
+    range: Option<TextRange>,
+    options: &FormatOptions,
+) -> Option<TextEdit> {
+    let parse = db.parse(file_id);
+    if !parse.errors().is_empty() {
+        return None;
+    }
+    let text = db.file_text(file_id);
+    let file = parse.tree();
+    let mut formatter = Formatter {
+        options,
+        text: &text,
+        base: String::new(),
+        edits: Vec::new(),
+    };
+    if file.component().is_some() {
+        formatter.format_script(file.syntax(), 0);
+    } else {
+        for script in file.syntax().descendants().filter(|it| it.kind() == SCRIPT) {
+            formatter.base = line_indent(&text, script.text_range().start()).to_owned();
+            formatter.format_script(&script, 1);
+        }
+    }
+    if options.tags_lowercase {
+        let is_cfml_tag = |name: &str| {
+            let name = name.to_ascii_lowercase();
+            name.starts_with("cf") && !name.starts_with("cf_")
+        };
+        for token in file
+            .syntax()
+            .descendants_with_tokens()
+            .filter_map(|it| it.into_token())
+            .filter(|it| it.kind() == TAG_NAME && is_cfml_tag(it.text()))
+        {
+            let name = token.text().to_ascii_lowercase();
+            formatter.replace(token.text_range(), name);
+        }
+    }
+
+    // Edits end where the text they change ends, so the one ending on the
+    // first line re-indents it while the one after the last line is left.
+    let range = range.map(|range| {
+        let start = text[..usize::from(range.start())]
+            .rfind('\n')
+            .map_or(0, |it| it + 1);
+        let end = text[usize::from(range.end())..]
+            .find('\n')
+            .map_or(text.len(), |it| usize::from(range.end()) + it);
+        TextRange::new(TextSize::from(start as u32), TextSize::from(end as u32))
+    });
+    let mut builder = TextEdit::builder();
+    for (edit, insert) in formatter.edits {
+        if range.is_none_or(|it| it.start() <= edit.end() && edit.end() <= it.end()) {
+            builder.replace(edit, insert);
+        }
+    }
+    Some(builder.finish())
+}
+
+/// The whitespace between two tokens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Gap {
+    /// Left as it is.
+    Keep,
+    None,
+    Space,
+    /// Line breaks, then the indentation of the next line.
+    Lines(usize),
+}
+
+struct Formatter<'a> {
+    options: &'a FormatOptions,
+    text: &'a str,
+    /// The indentation of the `<cfscript>` tag of the script formatted.
+    base: String,
+    edits: Vec<(TextRange, String)>,
+}
+
+impl Formatter<'_> {
+    /// Formats the script of `node`, whose statements are indented by
+    /// `level`: 1 in cfscript blocks, whose end tag is indented like their
+    /// start tag.
+    fn format_script(&mut self, node: &SyntaxNode, level: usize) {
+        // The levels of the lines inside the open brackets and `case`s.
+        let mut stack: Vec<usize> = Vec::new();
+        let mut line = level.saturating_sub(1);
+        let mut multiline = FxHashSet::default();
+        let mut prev: Option<SyntaxElement> = None;
+        let mut start = node.text_range().start();
+        for atom in atoms(node) {
+            let gap = TextRange::new(start, atom.text_range().start());
+            let newlines = self.text[gap].matches('\n').count();
+            let layout = match &prev {
+                Some(prev) => self.layout(prev, &atom, newlines, &multiline),
+                None if newlines > 0 => Gap::Lines(newlines.min(2)),
+                None => Gap::Keep,
+            };
+            if let Gap::Lines(_) = layout {
+                line = match is_closer(atom.kind()) {
+                    true => stack.last().map_or(level, |it| it.saturating_sub(1)),
+                    false => {
+                        let continues = prev.as_ref().is_some_and(|it| continues(it, &atom));
+                        stack.last().copied().unwrap_or(level) + usize::from(continues)
+                    }
+                };
+            }
+            self.gap(gap, layout, line);
+
+            match &atom {
+                NodeOrToken::Token(token) if token.kind() == BLOCK_COMMENT => {
+                    let at_line_start = matches!(layout, Gap::Lines(_))
+                        || self.text[..usize::from(gap.end())].ends_with('\n')
+                        || gap.end() == 0.into();
+                    if at_line_start {
+                        self.align_comment(token, line);
+                    }
+                }
+                NodeOrToken::Token(token) if token.kind() == IDENT => {
+                    if let Some(name) = self.cased_call(token) {
+                        self.replace(token.text_range(), name);
+                    }
+                }
+                _ => {}
+            }
+            if is_opener(atom.kind()) || is_case_colon(&atom) {
+                stack.push(line + 1);
+                if let Some(literal) = literal(&atom).filter(|it| self.breaks(it)) {
+                    multiline.insert(literal.text_range());
+                }
+            }
+            if is_closer(atom.kind()) {
+                stack.pop();
+            }
+            let end = atom.text_range().end();
+            let clauses = ancestors(&atom)
+                .take_while(|it| it.text_range().end() == end)
+                .filter(|it| it.kind() == CASE_CLAUSE);
+            for _ in clauses {
+                stack.pop();
+            }
+            start = end;
+            prev = Some(atom);
+        }
+
+        let gap = TextRange::new(start, node.text_range().end());
+        let newlines = self.text[gap].matches('\n').count();
+        if node.kind() == SCRIPT && newlines > 0 {
+            self.gap(gap, Gap::Lines(newlines.min(2)), 0);
+        }
+    }
+
+    /// The whitespace between `prev` and `next`, which `newlines` line
+    /// breaks separate.
+    fn layout(
+        &self,
+        prev: &SyntaxElement,
+        next: &SyntaxElement,
+        newlines: usize,
+        multiline: &FxHashSet<TextRange>,
+    ) -> Gap {
+        let options = self.options;
+        let lines = Gap::Lines(newlines.clamp(1, 2));
+        if prev.kind() == LINE_COMMENT {
+            return lines;
+        }
+        if next.kind().is_comment() && newlines == 0 {
+            return Gap::Space;
+        }
+        if is_block_brace(prev, R_CURLY)
+            && (matches!(next.kind(), ELSE_KW | CATCH_KW | FINALLY_KW)
+                || next.kind() == WHILE_KW && parent_kind(next) == Some(DO_WHILE_STMT))
+        {
+            return block_gap(options.keywords_block_to_keyword_spacing, prev);
+        }
+        if is_block_brace(next, L_CURLY) && !starts(next, is_statement) {
+            let owner = next
+                .parent()
+                .filter(|it| it.kind() == BLOCK)
+                .and_then(|it| it.parent());
+            let spacing = match owner.map(|it| it.kind()) {
+                Some(FUNCTION_DECL) => options.function_declaration_group_to_block_spacing,
+                Some(CLOSURE_EXPR | ARROW_EXPR) => {
+                    options.function_anonymous_group_to_block_spacing
+                }
+                _ => options.keywords_group_to_block_spacing,
+            };
+            return block_gap(spacing, prev);
+        }
+        if is_block_brace(prev, L_CURLY) {
+            let is_empty = is_block_brace(next, R_CURLY) && next.parent() == prev.parent();
+            return match is_empty {
+                true if newlines == 0 => Gap::None,
+                true => Gap::Lines(1),
+                false => lines,
+            };
+        }
+        if is_block_brace(next, R_CURLY) {
+            return lines;
+        }
+
+        let literal = match (prev.kind(), next.kind()) {
+            (L_CURLY | L_BRACK | COMMA, _) => literal(prev),
+            (_, R_CURLY | R_BRACK) => literal(next),
+            _ => None,
+        };
+        if let Some(literal) = literal {
+            if multiline.contains(&literal.text_range()) {
+                return lines;
+            }
+        } else {
+            if is_case_colon(prev) || ends(prev, is_statement) {
+                return lines;
+            }
+            if newlines > 0 {
+                return Gap::Lines(newlines.min(2));
+            }
+        }
+        let had_space = prev.text_range().end() < next.text_range().start();
+        match self.space(prev, next, had_space) {
+            true => Gap::Space,
+            false => Gap::None,
+        }
+    }
+
+    /// Whether a space separates `prev` and `next` on a line.
+    fn space(&self, prev: &SyntaxElement, next: &SyntaxElement, had_space: bool) -> bool {
+        let space = self.spacing_rule(prev, next);
+        match space {
+            Some(false)
+                if had_space
+                    && glues(&self.text[prev.text_range()], &self.text[next.text_range()]) =>
+            {
+                true
+            }
+            Some(space) => space,
+            None => had_space,
+        }
+    }
+
+    /// The spacing the settings decide between `prev` and `next`, `None`
+    /// where they decide none.
+    fn spacing_rule(&self, prev: &SyntaxElement, next: &SyntaxElement) -> Option<bool> {
+        let options = self.options;
+        let (p, n) = (prev.kind(), next.kind());
+        if matches!(n, COMMA | SEMICOLON) || (p == SEMICOLON && n == R_PAREN) {
+            return Some(false);
+        }
+        if p == SEMICOLON {
+            return Some(
+                parent_kind(prev) != Some(FOR_STMT) || options.for_loop_semicolons_padding,
+            );
+        }
+        if p == COMMA {
+            return Some(true);
+        }
+        if matches!(p, DOT | QUESTION_DOT | COLON2) || matches!(n, DOT | QUESTION_DOT | COLON2) {
+            return Some(false);
+        }
+        if is_opener(p) || is_closer(n) {
+            let empty = is_opener(p) && is_closer(n);
+            let group = match is_opener(p) {
+                true => prev.parent()?,
+                false => next.parent()?,
+            };
+            return Some(match group.kind() {
+                ARG_LIST => !empty && options.function_call_padding,
+                PARAM_LIST => !empty && options.function_declaration_padding,
+                PAREN_EXPR => options.parentheses_padding,
+                IF_STMT | WHILE_STMT | DO_WHILE_STMT | FOR_STMT | FOR_IN_STMT | SWITCH_STMT
+                | CATCH_CLAUSE => !empty && options.keywords_padding_inside_group,
+                STRUCT_LIT if empty => options.struct_empty_padding,
+                STRUCT_LIT => options.struct_padding,
+                ARRAY_LIT if empty => options.array_empty_padding,
+                ARRAY_LIT => options.array_padding,
+                INDEX_EXPR | BLOCK => false,
+                _ => return None,
+            });
+        }
+        match (n, parent_kind(next)) {
+            (L_PAREN, Some(ARG_LIST)) | (L_BRACK, Some(INDEX_EXPR)) => return Some(false),
+            (L_PAREN, Some(PARAM_LIST))
+                if next
+                    .parent()
+                    .and_then(|it| it.parent())
+                    .is_some_and(|it| matches!(it.kind(), FUNCTION_DECL | CLOSURE_EXPR)) =>
+            {
+                return Some(false)
+            }
+            (
+                L_PAREN,
+                Some(
+                    IF_STMT | WHILE_STMT | DO_WHILE_STMT | FOR_STMT | FOR_IN_STMT | SWITCH_STMT
+                    | CATCH_CLAUSE,
+                ),
+            ) => return Some(options.keywords_spacing_to_group),
+            _ => {}
+        }
+        if is_separator(next) {
+            return Some(options.struct_separator.starts_with(' '));
+        }
+        if is_separator(prev) {
+            return Some(options.struct_separator.ends_with(' '));
+        }
+        if matches!(p, WORD_OP | FAT_ARROW) || matches!(n, WORD_OP | FAT_ARROW) {
+            return Some(true);
+        }
+        if p == EQ && parent_kind(prev) == Some(SCRIPT_ATTRIBUTE)
+            || n == EQ && parent_kind(next) == Some(SCRIPT_ATTRIBUTE)
+        {
+            return Some(false);
+        }
+        if is_operator(p) && parent_kind(prev) == Some(PREFIX_EXPR)
+            || is_operator(n) && parent_kind(next) == Some(POSTFIX_EXPR)
+        {
+            return Some(false);
+        }
+        if is_binary(prev) || is_binary(next) {
+            return Some(options.binary_operators_padding);
+        }
+        if is_word(p) && is_word(n) {
+            return Some(true);
+        }
+        None
+    }
+
+    /// Whether `literal` is broken over lines: when it has enough entries
+    /// and is wide enough, or would not fit on its line.
+    fn breaks(&self, literal: &SyntaxNode) -> bool {
+        let options = self.options;
+        let (element_count, min_length) = match literal.kind() {
+            STRUCT_LIT => (
+                options.struct_multiline_element_count,
+                options.struct_multiline_min_length,
+            ),
+            _ => (
+                options.array_multiline_element_count,
+                options.array_multiline_min_length,
+            ),
+        };
+        let count = literal.children().count();
+        if count == 0 {
+            return false;
+        }
+        if literal
+            .descendants_with_tokens()
+            .any(|it| it.kind().is_comment())
+        {
+            return true;
+        }
+        let width: usize = literal
+            .descendants_with_tokens()
+            .filter_map(|it| it.into_token())
+            .map(|it| match it.kind() {
+                WHITESPACE => 1,
+                _ => it.text().len(),
+            })
+            .sum();
+        let offset = usize::from(literal.text_range().start());
+        let column = offset - self.text[..offset].rfind('\n').map_or(0, |it| it + 1);
+        count >= element_count && width >= min_length || column + width > options.max_columns
+    }
+
+    /// The name of the function `token` calls in the configured case, if
+    /// that changes it.
+    fn cased_call(&self, token: &SyntaxToken) -> Option<String> {
+        let callee = token.parent()?;
+        let builtin = match callee.kind() {
+            NAME_REF => builtin_function(token.text()),
+            MEMBER_EXPR if callee.last_token().as_ref() == Some(token) => None,
+            _ => return None,
+        };
+        let call = callee.parent().filter(|it| it.kind() == CALL_EXPR)?;
+        if call.first_child().as_ref() != Some(&callee) {
+            return None;
+        }
+        let name = token.text();
+        let casing = match builtin {
+            Some(_) => self.options.function_call_casing_builtin,
+            None => self.options.function_call_casing_userdefined,
+        };
+        let cased = match casing {
+            Casing::Ignore => return None,
+            Casing::Cfdocs => builtin?.name.to_owned(),
+            Casing::Camel => name[..1].to_ascii_lowercase() + &name[1..],
+            Casing::Pascal => name[..1].to_ascii_uppercase() + &name[1..],
+        };
+        (cased != name).then_some(cased)
+    }
+
+    /// Re-indents the lines of a `/* */` comment that start with `*`, so
+    /// that the asterisks line up under its first line.
+    fn align_comment(&mut self, token: &SyntaxToken, level: usize) {
+        let mut lines = token.text().split('\n');
+        let first = lines.next().unwrap_or_default();
+        let rest: Vec<_> = lines.map(str::trim_start).collect();
+        if rest.is_empty() || !rest.iter().all(|it| it.starts_with('*')) {
+            return;
+        }
+        let indent = self.indent(level);
+        let mut text = first.to_owned();
+        for line in rest {
+            text.push('\n');
+            text.push_str(&indent);
+            text.push(' ');
+            text.push_str(line);
+        }
+        self.replace(token.text_range(), text);
+    }
+
+    fn gap(&mut self, range: TextRange, gap: Gap, level: usize) {
+        let text = match gap {
+            Gap::Keep => return,
+            Gap::None => String::new(),
+            Gap::Space => " ".to_owned(),
+            Gap::Lines(n) => "\n".repeat(n) + &self.indent(level),
+        };
+        self.replace(range, text);
+    }
+
+    fn replace(&mut self, range: TextRange, text: String) {
+        if self.text[range] != text {
+            self.edits.push((range, text));
+        }
+    }
+
+    fn indent(&self, level: usize) -> String {
+        let unit = match self.options.tab_indent {
+            true => "\t".to_owned(),
+            false => " ".repeat(self.options.indent_size),
+        };
+        self.base.clone() + &unit.repeat(level)
+    }
+}
+
+/// The tokens of `node` other than whitespace, with strings as a whole.
+fn atoms(node: &SyntaxNode) -> Vec<SyntaxElement> {
+    let mut res = Vec::new();
+    let mut preorder = node.preorder_with_tokens();
+    while let Some(event) = preorder.next() {
+        let WalkEvent::Enter(element) = event else {
+            continue;
+        };
+        match element {
+            NodeOrToken::Node(it) if matches!(it.kind(), STRING | HASH_EXPR) => {
+                preorder.skip_subtree();
+                res.push(NodeOrToken::Node(it));
+            }
+            NodeOrToken::Token(it) if it.kind() != WHITESPACE => res.push(NodeOrToken::Token(it)),
+            _ => {}
+        }
+    }
+    res
+}
+
+fn block_gap(spacing: BlockSpacing, prev: &SyntaxElement) -> Gap {
+    match spacing {
+        BlockSpacing::Spaced => Gap::Space,
+        BlockSpacing::Compact if prev.kind() == R_PAREN => Gap::None,
+        BlockSpacing::Compact => Gap::Space,
+        BlockSpacing::Newline => Gap::Lines(1),
+    }
+}
+
+/// Whether the line starting with `next` continues the statement or
+/// entry of the line before, and is indented one level more.
+fn continues(prev: &SyntaxElement, next: &SyntaxElement) -> bool {
+    let is_keyword_after_block = matches!(next.kind(), ELSE_KW | CATCH_KW | FINALLY_KW)
+        || next.kind() == WHILE_KW && parent_kind(next) == Some(DO_WHILE_STMT);
+    if is_closer(next.kind()) || is_keyword_after_block || is_block_brace(next, L_CURLY) {
+        return false;
+    }
+    if prev.kind().is_comment()
+        || is_opener(prev.kind())
+        || prev.kind() == COMMA
+        || is_case_colon(prev)
+    {
+        return false;
+    }
+    !ends(prev, is_unit) && !starts(next, is_unit)
+}
+
+/// A statement of a block, script or `case`, or a member of a component.
+fn is_statement(node: &SyntaxNode) -> bool {
+    let Some(parent) = node.parent() else {
+        return false;
+    };
+    match parent.kind() {
+        BLOCK | SCRIPT | SOURCE_FILE => true,
+        SWITCH_STMT => node.kind() == CASE_CLAUSE,
+        CASE_CLAUSE => parent
+            .children_with_tokens()
+            .take_while(|it| it.as_node() != Some(node))
+            .any(|it| it.kind() == COLON),
+        _ => false,
+    }
+}
+
+/// A statement, or an element of a list: an argument, parameter, struct
+/// entry or array element.
+fn is_unit(node: &SyntaxNode) -> bool {
+    is_statement(node)
+        || matches!(node.kind(), STRUCT_ENTRY | ARG | NAMED_ARG | PARAM)
+        || node.parent().is_some_and(|it| it.kind() == ARRAY_LIT)
+}
+
+fn starts(element: &SyntaxElement, unit: fn(&SyntaxNode) -> bool) -> bool {
+    let start = element.text_range().start();
+    with_ancestors(element)
+        .take_while(|it| it.text_range().start() == start)
+        .any(|it| unit(&it))
+}
+
+fn ends(element: &SyntaxElement, unit: fn(&SyntaxNode) -> bool) -> bool {
+    let end = element.text_range().end();
+    with_ancestors(element)
+        .take_while(|it| it.text_range().end() == end)
+        .any(|it| unit(&it))
+}
+
+/// `element` if it is a node, then its ancestors.
+fn with_ancestors(element: &SyntaxElement) -> impl Iterator<Item = SyntaxNode> {
+    let node = match element {
+        NodeOrToken::Node(it) => Some(it.clone()),
+        NodeOrToken::Token(it) => it.parent(),
+    };
+    node.into_iter().flat_map(|it| it.ancestors())
+}
+
+fn ancestors(element: &SyntaxElement) -> impl Iterator<Item = SyntaxNode> {
+    element.parent().into_iter().flat_map(|it| it.ancestors())
+}
+
+fn parent_kind(element: &SyntaxElement) -> Option<SyntaxKind> {
+    element.parent().map(|it| it.kind())
+}
+
+fn is_opener(kind: SyntaxKind) -> bool {
+    matches!(kind, L_PAREN | L_BRACK | L_CURLY)
+}
+
+fn is_closer(kind: SyntaxKind) -> bool {
+    matches!(kind, R_PAREN | R_BRACK | R_CURLY)
+}
+
+/// Whether `element` is the brace `kind` of a block or `switch`.
+fn is_block_brace(element: &SyntaxElement, kind: SyntaxKind) -> bool {
+    element.kind() == kind && matches!(parent_kind(element), Some(BLOCK | SWITCH_STMT))
+}
+
+fn is_case_colon(element: &SyntaxElement) -> bool {
+    element.kind() == COLON && parent_kind(element) == Some(CASE_CLAUSE)
+}
+
+/// The colon between the key and value of a struct entry or named
+/// argument.
+fn is_separator(element: &SyntaxElement) -> bool {
+    element.kind() == COLON && matches!(parent_kind(element), Some(STRUCT_ENTRY | NAMED_ARG))
+}
+
+/// The struct or array literal whose bracket or comma `element` is.
+fn literal(element: &SyntaxElement) -> Option<SyntaxNode> {
+    let parent = element.parent()?;
+    let is_literal = match element.kind() {
+        L_CURLY | R_CURLY => parent.kind() == STRUCT_LIT,
+        L_BRACK | R_BRACK => parent.kind() == ARRAY_LIT,
+        COMMA => matches!(parent.kind(), STRUCT_LIT | ARRAY_LIT),
+        _ => false,
+    };
+    is_literal.then_some(parent)
+}
+
+fn is_operator(kind: SyntaxKind) -> bool {
+    kind.is_punct()
+        && !matches!(
+            kind,
+            L_PAREN | R_PAREN | L_CURLY | R_CURLY | L_BRACK | R_BRACK
+        )
+}
+
+fn is_binary(element: &SyntaxElement) -> bool {
+    is_operator(element.kind())
+        && matches!(
+            parent_kind(element),
+            Some(
+                BIN_EXPR | ASSIGN_EXPR | TERNARY_EXPR | VAR_DECL | NAMED_ARG | PARAM | STRUCT_ENTRY
+            )
+        )
+}
+
+fn is_word(kind: SyntaxKind) -> bool {
+    matches!(kind, IDENT | NUMBER | STRING | HASH_EXPR) || kind.is_keyword()
+}
+
+/// Whether `prev` and `next` would read as one token without a space.
+fn glues(prev: &str, next: &str) -> bool {
+    let (Some(last), Some(first)) = (prev.chars().last(), next.chars().next()) else {
+        return false;
+    };
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    let is_operator = |c: char| "+-*/%&|<>=!?:.^".contains(c);
+    is_word(last) && is_word(first) || is_operator(last) && is_operator(first)
+}
+
+#[cfg(test)]
+mod tests {
+    use virtual_fs::FileId;
+
+    use super::*;
+    use crate::fixture;
+
+    fn formatted(path: &str, text: &str, options: &FormatOptions) -> String {
+        let analysis = fixture::parse(&format!("//- {path}\n{text}")).analysis;
+        let edit = analysis.format(FileId(0), None, options).unwrap();
+        let mut text = analysis.file_text(FileId(0)).to_string();
+        edit.apply(&mut text);
+        text
+    }
+
+    #[test]
+    fn formats_script_components() {
+        let text = r#"/**
+   * Users.
+      */
+component extends="Base"{
+    property name="email"  type="string";
+  function find(id,role="admin"){
+        var filter={id:arguments.id,"role"=[role]};
+        for(var i=1;i<=2;i++){count=!found;}
+        switch(role){case "admin": grant();break; default: deny();}
+        if(found){ }else if(id) load(); else {id = -1;}
+        try{save();}catch(any e){// ignore
+        }finally{}
+        users.each(function(user) {
+    writeOutput(user);   
+        });
+        var total = a
+            + b;
+
+
+        return new User( id = 1, name : 2 ) & LEN( "x" ) & GetUser();
+    }
+}
+"#;
+        let expect = r#"/**
+ * Users.
+ */
+component extends="Base" {
+    property name="email" type="string";
+    function find(id, role = "admin") {
+        var filter = {id: arguments.id, "role" = [role]};
+        for (var i = 1; i <= 2; i++) {
+            count = !found;
+        }
+        switch (role) {
+            case "admin":
+                grant();
+                break;
+            default:
+                deny();
+        }
+        if (found) {} else if (id) load(); else {
+            id = -1;
+        }
+        try {
+            save();
+        } catch (any e) { // ignore
+        } finally {}
+        users.each(function(user) {
+            writeOutput(user);
+        });
+        var total = a
+            + b;
+
+        return new User(id = 1, name: 2) & len("x") & getUser();
+    }
+}
+"#;
+        assert_eq!(
+            formatted("/Users.cfc", text, &FormatOptions::default()),
+            expect
+        );
+        assert_eq!(
+            formatted("/Users.cfc", expect, &FormatOptions::default()),
+            expect
+        );
+    }
+
+    #[test]
+    fn breaks_long_literals() {
+        let text = r#"component {
+    user = {name: "alice", email: "alice@example.com", age: 30, role: "admin"};
+    short = {
+        a: 1, b: 2, c: 3, d: 4
+    };
+    ids = [1000000, 2000000, 3000000, 4000000, 5000000, 6000000];
+}
+"#;
+        assert_eq!(
+            formatted("/Users.cfc", text, &FormatOptions::default()),
+            r#"component {
+    user = {
+        name: "alice",
+        email: "alice@example.com",
+        age: 30,
+        role: "admin"
+    };
+    short = {a: 1, b: 2, c: 3, d: 4};
+    ids = [
+        1000000,
+        2000000,
+        3000000,
+        4000000,
+        5000000,
+        6000000
+    ];
+}
+"#
+        );
+        let options = FormatOptions {
+            max_columns: 30,
+            array_multiline_element_count: 10,
+            ..FormatOptions::default()
+        };
+        assert_eq!(
+            formatted("/Users.cfc", "component {\n    ids = [1, 2];\n    names = [\"alice\", \"bob\", \"carol\"];\n}\n", &options),
+            "component {\n    ids = [1, 2];\n    names = [\n        \"alice\",\n        \"bob\",\n        \"carol\"\n    ];\n}\n"
+        );
+    }
+
+    #[test]
+    fn applies_cfformat_settings() {
+        let text = r#"<CFOUTPUT>
+  <div>
+    <cfscript>
+    x = {a:1};  y = [ 1,2 ];
+      if (x.a EQ 1) { WriteOutput( "a" ); } else { z = x - -1; }
+    list.each( function(i){ Foo(i); } );
+    </cfscript>
+  </div>
+  <CFSET x=1>
+</CFOUTPUT>
+"#;
+        let options = FormatOptions {
+            tab_indent: true,
+            keywords_group_to_block_spacing: BlockSpacing::Newline,
+            keywords_block_to_keyword_spacing: BlockSpacing::Newline,
+            function_anonymous_group_to_block_spacing: BlockSpacing::Compact,
+            keywords_spacing_to_group: false,
+            keywords_padding_inside_group: true,
+            binary_operators_padding: false,
+            struct_padding: true,
+            array_padding: true,
+            struct_separator: " : ".to_owned(),
+            function_call_casing_userdefined: Casing::Ignore,
+            ..FormatOptions::default()
+        };
+        assert_eq!(
+            formatted("/page.cfm", text, &options),
+            "<cfoutput>
+  <div>
+    <cfscript>
+    \tx={ a : 1 };
+    \ty=[ 1, 2 ];
+    \tif( x.a EQ 1 )
+    \t{
+    \t\twriteOutput(\"a\");
+    \t}
+    \telse
+    \t{
+    \t\tz=x- -1;
+    \t}
+    \tlist.each(function(i){
+    \t\tFoo(i);
+    \t});
+    </cfscript>
+  </div>
+  <cfset x=1>
+</cfoutput>
+"
+        );
+    }
+
+    #[test]
+    fn formats_only_the_lines_of_ranges() {
+        let (fixture, range) = fixture::selection(
+            "//- /page.cfm\n<cfscript>\nx=1;\n  $0y=2;\n z=3;$0\nw=4;\n</cfscript>\n",
+        );
+        let analysis = fixture::parse(&fixture).analysis;
+        let edit = analysis
+            .format(FileId(0), Some(range), &FormatOptions::default())
+            .unwrap();
+        let mut text = analysis.file_text(FileId(0)).to_string();
+        edit.apply(&mut text);
+        assert_eq!(
+            text,
+            "<cfscript>\nx=1;\n    y = 2;\n    z = 3;\nw=4;\n</cfscript>\n"
+        );
+    }
+
+    #[test]
+    fn leaves_files_with_syntax_errors() {
+        let analysis = fixture::parse("//- /page.cfm\n<cfscript>x = ;</cfscript>\n").analysis;
+        assert_eq!(
+            analysis.format(FileId(0), None, &FormatOptions::default()),
+            None
+        );
+    }
+}
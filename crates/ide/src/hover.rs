@@ -0,0 +1,144 @@
+//! Hover information for functions, their parameters and components: the
+//! signature and the documentation from docblocks and `hint`s.
+
+use syntax::TextRange;
+
+use crate::{
+    db::RootDatabase,
+    references::{classify, Definition},
+    FilePosition,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HoverResult {
+    /// The name hovered.
+    pub range: TextRange,
+    /// The hover text, in Markdown.
+    pub markup: String,
+}
+
+pub(crate) fn hover(db: &RootDatabase, position: FilePosition) -> Option<HoverResult> {
+    let (definition, range) = classify(db, position)?;
+    let (signature, doc) = match definition {
+        Definition::Function { file_id, name } => {
+            let (_, function) = db.find_function(file_id, &name)?;
+            (function.signature(), function.doc.markdown())
+        }
+        Definition::Param {
+            file_id,
+            function,
+            name,
+        } => {
+            let items = db.items(file_id);
+            let function = items.functions.iter().find(|it| it.range == function)?;
+            let param = function.param(&name)?;
+            let doc = function.doc.param(&name).map(str::to_owned);
+            (param.label(), doc.filter(|it| !it.is_empty()))
+        }
+        Definition::Component(file_id) => {
+            let items = db.items(file_id);
+            let component = items.component.as_ref()?;
+            let keyword = match component.is_interface {
+                true => "interface",
+                false => "component",
+            };
+            let name = db.component_name(file_id)?;
+            let doc = Some(component.doc.description.clone()).filter(|it| !it.is_empty());
+            (format!("{keyword} {name}"), doc)
+        }
+        Definition::Local { .. } | Definition::Variable { .. } => return None,
+    };
+    let mut markup = format!("```cfml\n{signature}\n```");
+    if let Some(doc) = doc {
+        markup.push_str("\n\n---\n\n");
+        markup.push_str(&doc);
+    }
+    Some(HoverResult { range, markup })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixture;
+
+    fn check(fixture: &str, expect: &str) {
+        let (analysis, position) = fixture::position(fixture);
+        let hover = analysis.hover(position).unwrap();
+        assert_eq!(hover.markup, expect);
+    }
+
+    #[test]
+    fn hovers_calls_with_docblocks() {
+        check(
+            r#"
+//- /Main.cfc
+component {
+    function index() {
+        new Users().fi$0nd(1);
+    }
+}
+//- /Users.cfc
+component {
+    /**
+     * Finds a user.
+     * @id The id
+     * @return The user
+     */
+    public User function find(required numeric id) {}
+}
+"#,
+            "```cfml
+User find(numeric id)
+```
+
+---
+
+Finds a user.
+
+**Parameters**
+
+- `id`: The id
+
+**Returns** The user",
+        );
+    }
+
+    #[test]
+    fn hovers_tag_hints() {
+        check(
+            r#"
+//- /Users.cfc
+<cfcomponent hint="The users">
+    <cffunction name="find" hint="Finds a user">
+        <cfargument name="id" type="numeric" hint="The id">
+        <cfreturn i$0d>
+    </cffunction>
+</cfcomponent>
+"#,
+            "```cfml
+numeric id
+```
+
+---
+
+The id",
+        );
+        check(
+            r#"
+//- /Main.cfc
+component {
+    variables.users = new Us$0ers();
+}
+//- /Users.cfc
+<cfcomponent hint="The users">
+</cfcomponent>
+"#,
+            "```cfml
+component Users
+```
+
+---
+
+The users",
+        );
+    }
+}
@@ -0,0 +1,374 @@
+//! Methods a component inherits or must provide: the methods of its
+//! interfaces it lacks, and the inherited methods it can override.
+
+use syntax::ast;
+use virtual_fs::FileId;
+
+use crate::{
+    assists::{add_members, at_member_level, indent_unit, Assist, AssistKind},
+    db::RootDatabase,
+    diagnostics::{fix, Diagnostic, DiagnosticCode},
+    items::FunctionInfo,
+    source_change::SourceChange,
+    FileRange,
+};
+
+/// Reports the interfaces of a component whose methods it does not
+/// declare or inherit, with a fix adding stubs for them.
+pub(crate) fn missing_interface_methods(
+    db: &RootDatabase,
+    file_id: FileId,
+    file: &ast::SourceFile,
+    res: &mut Vec<Diagnostic>,
+) {
+    let items = db.items(file_id);
+    let Some(component) = items.component.as_ref().filter(|it| !it.is_interface) else {
+        return;
+    };
+    let text = db.file_text(file_id);
+    for interface in &component.implements {
+        let Some(interface_id) = db.resolve_component(file_id, &interface.path) else {
+            continue;
+        };
+        let mut missing: Vec<(FunctionInfo, bool)> = Vec::new();
+        for (function, from_script) in inherited(db, interface_id) {
+            let is_listed = missing
+                .iter()
+                .any(|(it, _)| it.name.eq_ignore_ascii_case(&function.name));
+            if !is_listed && db.find_function(file_id, &function.name).is_none() {
+                missing.push((function, from_script));
+            }
+        }
+        if missing.is_empty() {
+            continue;
+        }
+
+        let names: Vec<_> = missing
+            .iter()
+            .map(|(it, _)| format!("`{}`", it.name))
+            .collect();
+        let message = format!(
+            "missing methods of `{}`: {}",
+            interface.path,
+            names.join(", ")
+        );
+        let mut diagnostic = Diagnostic::new(
+            DiagnosticCode::MISSING_INTERFACE_METHOD,
+            interface.range,
+            message,
+        );
+        let unit = indent_unit(&text);
+        let is_script = file.component().is_some();
+        let stubs: Vec<_> = missing
+            .iter()
+            .map(|(it, from_script)| {
+                let body = match is_script {
+                    true => "throw(message=\"Not implemented\");",
+                    false => "<cfthrow message=\"Not implemented\">",
+                };
+                declaration(it, *from_script, is_script, &unit, body)
+            })
+            .collect();
+        if let Some(edit) = add_members(file, &text, &stubs) {
+            let label = format!("Implement missing methods of `{}`", interface.path);
+            diagnostic = diagnostic.with_fixes(vec![fix(file_id, label, edit)]);
+        }
+        res.push(diagnostic);
+    }
+}
+
+/// Offers to override each method inherited from the components extended
+/// by the one at `frange`, outside of its functions.
+pub(crate) fn override_methods(db: &RootDatabase, frange: FileRange) -> Vec<Assist> {
+    let file = db.parse(frange.file_id).tree();
+    let text = db.file_text(frange.file_id);
+    let items = db.items(frange.file_id);
+    let Some(component) = items.component.as_ref().filter(|it| !it.is_interface) else {
+        return Vec::new();
+    };
+    if !at_member_level(&file, component, frange.range) {
+        return Vec::new();
+    }
+
+    let is_script = file.component().is_some();
+    let unit = indent_unit(&text);
+    let mut res: Vec<Assist> = Vec::new();
+    let Some(parent) = db.parent_component(frange.file_id) else {
+        return Vec::new();
+    };
+    for (function, from_script) in inherited(db, parent) {
+        let label = format!("Override `{}`", function.name);
+        if items.function(&function.name).is_some() || res.iter().any(|it| it.label == label) {
+            continue;
+        }
+        let call = format!("super.{}(argumentCollection = arguments)", function.name);
+        let returns = function
+            .return_type
+            .as_deref()
+            .is_none_or(|it| !it.eq_ignore_ascii_case("void"));
+        let body = match (is_script, returns) {
+            (true, true) => format!("return {call};"),
+            (true, false) => format!("{call};"),
+            (false, true) => format!("<cfreturn {call}>"),
+            (false, false) => format!("<cfset {call}>"),
+        };
+        let member = declaration(&function, from_script, is_script, &unit, &body);
+        let Some(edit) = add_members(&file, &text, &[member]) else {
+            return Vec::new();
+        };
+        res.push(Assist {
+            label,
+            kind: AssistKind::Generate,
+            source_change: SourceChange::from_text_edit(frange.file_id, edit),
+        });
+    }
+    res
+}
+
+/// The functions of `file_id` and the components it extends, nearest
+/// first, and whether each is declared in script.
+fn inherited(db: &RootDatabase, file_id: FileId) -> Vec<(FunctionInfo, bool)> {
+    db.component_chain(file_id)
+        .into_iter()
+        .flat_map(|it| {
+            let is_script = db.parse(it).tree().component().is_some();
+            let functions = db.items(it).functions.clone();
+            functions
+                .into_iter()
+                .map(move |function| (function, is_script))
+        })
+        .collect()
+}
+
+/// The declaration of a function with the signature of `function`, in
+/// script or in tags, whose body is the statement `body`. The default
+/// values of its parameters are converted from the syntax it is declared
+/// in.
+pub(crate) fn declaration(
+    function: &FunctionInfo,
+    from_script: bool,
+    is_script: bool,
+    unit: &str,
+    body: &str,
+) -> String {
+    let access = function.access.as_deref().unwrap_or("public");
+    if is_script {
+        let params: Vec<_> = function
+            .params
+            .iter()
+            .map(|it| {
+                let mut param = String::new();
+                if it.required {
+                    param.push_str("required ");
+                }
+                if let Some(ty) = &it.type_name {
+                    param.push_str(ty);
+                    param.push(' ');
+                }
+                param.push_str(&it.name);
+                match &it.default {
+                    Some(default) if from_script || default.starts_with(['"', '\'']) => {
+                        param.push_str(&format!("={default}"));
+                    }
+                    Some(default) => param.push_str(&format!("=\"{default}\"")),
+                    None => (),
+                }
+                param
+            })
+            .collect();
+        let return_type = function
+            .return_type
+            .as_ref()
+            .map_or_else(String::new, |it| format!("{it} "));
+        return format!(
+            "{access} {return_type}function {}({}) {{\n{unit}{body}\n}}",
+            function.name,
+            params.join(", ")
+        );
+    }
+
+    let mut lines = vec![format!(
+        "<cffunction name=\"{}\" access=\"{access}\"{}>",
+        function.name,
+        function
+            .return_type
+            .as_ref()
+            .map_or_else(String::new, |it| format!(" returntype=\"{it}\""))
+    )];
+    for param in &function.params {
+        let mut tag = format!("{unit}<cfargument name=\"{}\"", param.name);
+        if let Some(ty) = &param.type_name {
+            tag.push_str(&format!(" type=\"{ty}\""));
+        }
+        if param.required {
+            tag.push_str(" required=\"true\"");
+        }
+        match &param.default {
+            Some(default) if !from_script || default.starts_with(['"', '\'']) => {
+                tag.push_str(&format!(" default={default}"));
+            }
+            Some(default) if is_literal(default) => {
+                tag.push_str(&format!(" default=\"{default}\""));
+            }
+            Some(default) => tag.push_str(&format!(" default=\"#{default}#\"")),
+            None => (),
+        }
+        tag.push('>');
+        lines.push(tag);
+    }
+    lines.push(format!("{unit}{body}"));
+    lines.push("</cffunction>".to_owned());
+    lines.join("\n")
+}
+
+/// Whether a script default value can be written as is in a tag
+/// attribute: a number or a boolean.
+fn is_literal(value: &str) -> bool {
+    value.parse::<f64>().is_ok()
+        || ["true", "false", "yes", "no"]
+            .iter()
+            .any(|it| it.eq_ignore_ascii_case(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use virtual_fs::FileId;
+
+    use crate::{fixture, DiagnosticCode, DiagnosticsConfig, FileRange};
+
+    /// The text of the first file after the fix of its missing interface
+    /// methods diagnostic, which covers `range`.
+    fn check_fix(fixture: &str, message: &str, expect: &str) {
+        let analysis = fixture::parse(fixture).analysis;
+        let mut text = analysis.file_text(FileId(0)).to_string();
+        let diagnostics: Vec<_> = analysis
+            .diagnostics(&DiagnosticsConfig::default(), FileId(0))
+            .into_iter()
+            .filter(|it| it.code == DiagnosticCode::MISSING_INTERFACE_METHOD)
+            .collect();
+        let [diagnostic] = &diagnostics[..] else {
+            panic!("expected one diagnostic, got {diagnostics:?}");
+        };
+        assert_eq!(diagnostic.message, message);
+        diagnostic.fixes[0].source_change.source_file_edits[&FileId(0)].apply(&mut text);
+        assert_eq!(text, expect);
+    }
+
+    #[test]
+    fn implements_missing_methods() {
+        check_fix(
+            r#"
+//- /models/Admin.cfc
+component implements="IAdmin" accessors=true {
+    property name;
+
+    function grant() {}
+}
+//- /models/IAdmin.cfc
+interface extends="IUser" {
+    function grant();
+    public boolean function revoke(required string role, numeric level=1, scope="all");
+}
+//- /models/IUser.cfc
+interface {
+    string function getName();
+    function audit(struct options={});
+}
+"#,
+            "missing methods of `IAdmin`: `revoke`, `audit`",
+            r#"component implements="IAdmin" accessors=true {
+    property name;
+
+    function grant() {}
+
+    public boolean function revoke(required string role, numeric level=1, scope="all") {
+        throw(message="Not implemented");
+    }
+
+    public function audit(struct options={}) {
+        throw(message="Not implemented");
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn implements_missing_tag_methods() {
+        check_fix(
+            r##"
+//- /Admin.cfc
+<cfcomponent implements="IAdmin">
+</cfcomponent>
+//- /IAdmin.cfc
+<cfinterface>
+    <cffunction name="revoke" access="public" returntype="boolean">
+        <cfargument name="role" type="string" required="true">
+        <cfargument name="level" type="numeric" default="1">
+        <cfargument name="since" default="#now()#">
+    </cffunction>
+</cfinterface>
+"##,
+            "missing methods of `IAdmin`: `revoke`",
+            r##"<cfcomponent implements="IAdmin">
+    <cffunction name="revoke" access="public" returntype="boolean">
+        <cfargument name="role" type="string" required="true">
+        <cfargument name="level" type="numeric" default="1">
+        <cfargument name="since" default="#now()#">
+        <cfthrow message="Not implemented">
+    </cffunction>
+</cfcomponent>
+"##,
+        );
+    }
+
+    #[test]
+    fn overrides_inherited_methods() {
+        let (fixture, range) = fixture::selection(
+            r#"
+//- /User.cfc
+component$0 extends="Base" {
+    function save() {}
+}
+//- /Base.cfc
+component extends="Entity" {
+    function save() {}
+    private void function validate(required struct data) {}
+}
+//- /Entity.cfc
+component {
+    any function init() {}
+    function validate() {}
+}
+"#,
+        );
+        let analysis = fixture::parse(&fixture).analysis;
+        let assists = analysis.assists(FileRange {
+            file_id: FileId(0),
+            range,
+        });
+        let labels: Vec<_> = assists.iter().map(|it| it.label.as_str()).collect();
+        assert_eq!(labels, ["Override `validate`", "Override `init`"]);
+
+        let mut text = analysis.file_text(FileId(0)).to_string();
+        assists[0].source_change.source_file_edits[&FileId(0)].apply(&mut text);
+        assert_eq!(
+            text,
+            r#"component extends="Base" {
+    function save() {}
+
+    private void function validate(required struct data) {
+        super.validate(argumentCollection = arguments);
+    }
+}
+"#
+        );
+
+        let at = syntax::TextSize::from(text.find("{}").unwrap() as u32);
+        let assists = analysis.assists(FileRange {
+            file_id: FileId(0),
+            range: syntax::TextRange::empty(at),
+        });
+        assert!(assists.is_empty(), "{assists:?}");
+    }
+}
@@ -0,0 +1,589 @@
+//! The declarations of a file: the component it defines, its properties and
+//! its functions, whether written as tags or in cfscript.
+
+use syntax::{
+    ast::{self, AstNode},
+    SyntaxKind, SyntaxNode, TextRange, TextSize,
+};
+
+use crate::docs::{component_docs, function_docs, Documentation};
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct FileItems {
+    pub component: Option<ComponentInfo>,
+    /// Functions declared at the top level of the file or component, in
+    /// source order. Closures and nested functions are not included.
+    pub functions: Vec<FunctionInfo>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComponentInfo {
+    pub is_interface: bool,
+    pub extends: Option<PathRef>,
+    pub implements: Vec<PathRef>,
+    /// Whether `accessors=true` asks for implicit getters and setters.
+    pub accessors: bool,
+    pub properties: Vec<PropertyInfo>,
+    pub doc: Documentation,
+    /// The `component` keyword, or the name of the `<cfcomponent>` tag.
+    pub keyword_range: TextRange,
+    pub range: TextRange,
+}
+
+impl ComponentInfo {
+    /// The implicit getter or setter called `name` that `accessors=true`
+    /// generates for one of the properties.
+    pub fn accessor(&self, name: &str) -> Option<FunctionInfo> {
+        if !self.accessors {
+            return None;
+        }
+        let prefix = name.get(..3)?;
+        let property = self
+            .properties
+            .iter()
+            .find(|it| it.name.eq_ignore_ascii_case(&name[3..]))?;
+        match prefix.to_ascii_lowercase().as_str() {
+            "get" if property.has_getter => Some(property.getter()),
+            "set" if property.has_setter => Some(property.setter()),
+            _ => None,
+        }
+    }
+
+    /// The implicit accessors of all properties, getters first.
+    pub fn accessors(&self) -> Vec<FunctionInfo> {
+        if !self.accessors {
+            return Vec::new();
+        }
+        let properties = self.properties.iter();
+        let getters = properties.clone().filter(|it| it.has_getter);
+        let setters = properties.filter(|it| it.has_setter);
+        getters
+            .map(PropertyInfo::getter)
+            .chain(setters.map(PropertyInfo::setter))
+            .collect()
+    }
+}
+
+/// `name` with its first letter in upper case, as in accessor names.
+fn capitalize(name: &str) -> String {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+        None => String::new(),
+    }
+}
+
+/// A dotted component path written in the source: `models.User`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PathRef {
+    pub path: String,
+    /// The range of the path text, without quotes.
+    pub range: TextRange,
+}
+
+impl PathRef {
+    /// The range of the last segment of the path, the component name.
+    pub fn name_range(&self) -> TextRange {
+        let offset = self.path.rfind('.').map_or(0, |it| it + 1);
+        TextRange::new(
+            self.range.start() + TextSize::from(offset as u32),
+            self.range.end(),
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionInfo {
+    pub name: String,
+    pub name_range: TextRange,
+    pub range: TextRange,
+    /// The lowercase access modifier, if one is given.
+    pub access: Option<String>,
+    pub return_type: Option<String>,
+    pub params: Vec<ParamInfo>,
+    pub is_static: bool,
+    pub doc: Documentation,
+}
+
+impl FunctionInfo {
+    pub fn param(&self, name: &str) -> Option<&ParamInfo> {
+        self.params
+            .iter()
+            .find(|it| it.name.eq_ignore_ascii_case(name))
+    }
+
+    /// `string getName()`, with `any` for a missing return type.
+    pub fn signature(&self) -> String {
+        let params: Vec<_> = self.params.iter().map(ParamInfo::label).collect();
+        let return_type = self.return_type.as_deref().unwrap_or("any");
+        format!("{return_type} {}({})", self.name, params.join(", "))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParamInfo {
+    pub name: String,
+    pub name_range: TextRange,
+    pub type_name: Option<String>,
+    pub required: bool,
+    /// The source text of the default value.
+    pub default: Option<String>,
+}
+
+impl ParamInfo {
+    /// `string name`, as written in a signature.
+    pub fn label(&self) -> String {
+        match &self.type_name {
+            Some(ty) => format!("{ty} {}", self.name),
+            None => self.name.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PropertyInfo {
+    pub name: String,
+    pub name_range: TextRange,
+    pub type_name: Option<String>,
+    /// Whether `accessors=true` generates a getter, unless `getter=false`.
+    pub has_getter: bool,
+    /// Whether `accessors=true` generates a setter, unless `setter=false`.
+    pub has_setter: bool,
+    pub range: TextRange,
+}
+
+impl PropertyInfo {
+    /// `getName()`, returning the property. Accessors are declared by their
+    /// property.
+    pub fn getter(&self) -> FunctionInfo {
+        FunctionInfo {
+            name: format!("get{}", capitalize(&self.name)),
+            name_range: self.name_range,
+            range: self.range,
+            access: Some("public".to_owned()),
+            return_type: self.type_name.clone(),
+            params: Vec::new(),
+            is_static: false,
+            doc: Documentation::default(),
+        }
+    }
+
+    /// `setName(name)`, assigning the property.
+    pub fn setter(&self) -> FunctionInfo {
+        FunctionInfo {
+            name: format!("set{}", capitalize(&self.name)),
+            name_range: self.name_range,
+            range: self.range,
+            access: Some("public".to_owned()),
+            return_type: None,
+            params: vec![ParamInfo {
+                name: self.name.clone(),
+                name_range: self.name_range,
+                type_name: self.type_name.clone(),
+                required: true,
+                default: None,
+            }],
+            is_static: false,
+            doc: Documentation::default(),
+        }
+    }
+}
+
+impl FileItems {
+    pub(crate) fn new(file: &ast::SourceFile) -> FileItems {
+        let functions = file
+            .syntax()
+            .descendants()
+            .filter_map(FunctionNode::cast)
+            .filter(|it| {
+                it.syntax()
+                    .ancestors()
+                    .skip(1)
+                    .all(|node| !is_scope_owner(&node))
+            })
+            .filter_map(|it| it.info())
+            .collect();
+        FileItems {
+            component: component_info(file),
+            functions,
+        }
+    }
+
+    pub fn function(&self, name: &str) -> Option<&FunctionInfo> {
+        self.functions
+            .iter()
+            .find(|it| it.name.eq_ignore_ascii_case(name))
+    }
+}
+
+/// A function declared in script or with `<cffunction>`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) enum FunctionNode {
+    Script(ast::FunctionDecl),
+    Tag(ast::Tag),
+}
+
+impl FunctionNode {
+    pub(crate) fn cast(node: SyntaxNode) -> Option<FunctionNode> {
+        if let Some(decl) = ast::FunctionDecl::cast(node.clone()) {
+            return Some(FunctionNode::Script(decl));
+        }
+        ast::Tag::cast(node)
+            .filter(|it| it.name() == "cffunction")
+            .map(FunctionNode::Tag)
+    }
+
+    pub(crate) fn syntax(&self) -> &SyntaxNode {
+        match self {
+            FunctionNode::Script(it) => it.syntax(),
+            FunctionNode::Tag(it) => it.syntax(),
+        }
+    }
+
+    pub(crate) fn name(&self) -> Option<(String, TextRange)> {
+        match self {
+            FunctionNode::Script(it) => {
+                let token = it.name_token()?;
+                Some((token.text().to_owned(), token.text_range()))
+            }
+            FunctionNode::Tag(it) => {
+                let attribute = it.attribute("name")?;
+                Some((attribute.value_text()?, attribute.value_range()?))
+            }
+        }
+    }
+
+    /// The `<cfargument>` tags of a tag function.
+    pub(crate) fn argument_tags(&self) -> Vec<ast::Tag> {
+        match self {
+            FunctionNode::Script(_) => Vec::new(),
+            FunctionNode::Tag(it) => it
+                .child_tags()
+                .filter(|it| it.name() == "cfargument")
+                .collect(),
+        }
+    }
+
+    pub(crate) fn params(&self) -> Vec<ParamInfo> {
+        match self {
+            FunctionNode::Script(it) => it.params().filter_map(|it| script_param(&it)).collect(),
+            FunctionNode::Tag(_) => self.argument_tags().iter().filter_map(tag_param).collect(),
+        }
+    }
+
+    pub(crate) fn info(&self) -> Option<FunctionInfo> {
+        let (name, name_range) = self.name()?;
+        let (access, return_type, is_static) = match self {
+            FunctionNode::Script(it) => (
+                it.access().or_else(|| {
+                    it.attribute_value("access")
+                        .map(|it| it.to_ascii_lowercase())
+                }),
+                it.return_type()
+                    .map(|it| it.text())
+                    .or_else(|| it.attribute_value("returntype")),
+                it.is_static(),
+            ),
+            FunctionNode::Tag(it) => (
+                it.attribute_value("access")
+                    .map(|it| it.to_ascii_lowercase()),
+                it.attribute_value("returntype"),
+                false,
+            ),
+        };
+        Some(FunctionInfo {
+            name,
+            name_range,
+            range: self.syntax().text_range(),
+            access,
+            return_type,
+            params: self.params(),
+            is_static,
+            doc: function_docs(self),
+        })
+    }
+}
+
+/// Nodes that introduce their own `local` and `arguments` scopes.
+pub(crate) fn is_scope_owner(node: &SyntaxNode) -> bool {
+    match node.kind() {
+        SyntaxKind::FUNCTION_DECL | SyntaxKind::CLOSURE_EXPR | SyntaxKind::ARROW_EXPR => true,
+        SyntaxKind::TAG => FunctionNode::cast(node.clone()).is_some(),
+        _ => false,
+    }
+}
+
+fn script_param(param: &ast::Param) -> Option<ParamInfo> {
+    let token = param.name_token()?;
+    Some(ParamInfo {
+        name: token.text().to_owned(),
+        name_range: token.text_range(),
+        type_name: param.type_ref().map(|it| it.text()),
+        required: param.is_required(),
+        default: param
+            .default_value()
+            .map(|it| it.syntax().text().to_string()),
+    })
+}
+
+fn tag_param(tag: &ast::Tag) -> Option<ParamInfo> {
+    let name = tag.attribute("name")?;
+    Some(ParamInfo {
+        name: name.value_text()?,
+        name_range: name.value_range()?,
+        type_name: tag.attribute_value("type"),
+        required: tag
+            .attribute_value("required")
+            .is_some_and(|it| is_truthy(&it)),
+        default: tag
+            .attribute("default")
+            .and_then(|it| Some(it.value()?.text())),
+    })
+}
+
+/// Whether a CFML boolean attribute value is true.
+pub(crate) fn is_truthy(value: &str) -> bool {
+    matches!(
+        value.trim().to_ascii_lowercase().as_str(),
+        "true" | "yes" | "1"
+    )
+}
+
+fn component_info(file: &ast::SourceFile) -> Option<ComponentInfo> {
+    if let Some(component) = file.component() {
+        let attribute = |name: &str| {
+            component
+                .attributes()
+                .find(|it| it.name() == name)
+                .and_then(|it| Some((it.value_text()?, it.value_range()?)))
+        };
+        let properties = component
+            .body()
+            .into_iter()
+            .flat_map(|it| it.statements().collect::<Vec<_>>())
+            .filter_map(|it| ast::PropertyDecl::cast(it.syntax().clone()))
+            .filter_map(|it| script_property(&it))
+            .collect();
+        let doc = component_docs(file);
+        return Some(ComponentInfo {
+            is_interface: component.is_interface(),
+            extends: attribute("extends").and_then(|(path, range)| path_refs(&path, range).pop()),
+            implements: attribute("implements")
+                .map(|(path, range)| path_refs(&path, range))
+                .unwrap_or_default(),
+            accessors: attribute("accessors")
+                .map(|(it, _)| it)
+                .or_else(|| doc.metadata("accessors").map(str::to_owned))
+                .is_some_and(|it| is_truthy(&it)),
+            properties,
+            doc,
+            keyword_range: component.keyword()?.text_range(),
+            range: component.syntax().text_range(),
+        });
+    }
+
+    let tag = file
+        .tags()
+        .find(|it| matches!(it.name().as_str(), "cfcomponent" | "cfinterface"))?;
+    let attribute = |name: &str| {
+        let attribute = tag.attribute(name)?;
+        Some((attribute.value_text()?, attribute.value_range()?))
+    };
+    let properties = tag
+        .syntax()
+        .descendants()
+        .filter_map(ast::Tag::cast)
+        .filter(|it| it.name() == "cfproperty")
+        .filter_map(|it| tag_property(&it))
+        .collect();
+    Some(ComponentInfo {
+        is_interface: tag.name() == "cfinterface",
+        extends: attribute("extends").and_then(|(path, range)| path_refs(&path, range).pop()),
+        implements: attribute("implements")
+            .map(|(path, range)| path_refs(&path, range))
+            .unwrap_or_default(),
+        accessors: attribute("accessors").is_some_and(|(it, _)| is_truthy(&it)),
+        properties,
+        doc: component_docs(file),
+        keyword_range: tag.name_token()?.text_range(),
+        range: tag.syntax().text_range(),
+    })
+}
+
+fn script_property(property: &ast::PropertyDecl) -> Option<PropertyInfo> {
+    let (name, name_range) = match property.attribute("name") {
+        Some(attribute) => (attribute.value_text()?, attribute.value_range()?),
+        None => {
+            let token = property.name_token()?;
+            (token.text().to_owned(), token.text_range())
+        }
+    };
+    let type_name = property
+        .type_ref()
+        .map(|it| it.text())
+        .or_else(|| property.attribute("type")?.value_text());
+    let flag = |name: &str| {
+        property
+            .attribute(name)
+            .and_then(|it| it.value_text())
+            .is_none_or(|it| is_truthy(&it))
+    };
+    Some(PropertyInfo {
+        name,
+        name_range,
+        type_name,
+        has_getter: flag("getter"),
+        has_setter: flag("setter"),
+        range: property.syntax().text_range(),
+    })
+}
+
+fn tag_property(tag: &ast::Tag) -> Option<PropertyInfo> {
+    let name = tag.attribute("name")?;
+    Some(PropertyInfo {
+        name: name.value_text()?,
+        name_range: name.value_range()?,
+        type_name: tag.attribute_value("type"),
+        has_getter: tag
+            .attribute_value("getter")
+            .is_none_or(|it| is_truthy(&it)),
+        has_setter: tag
+            .attribute_value("setter")
+            .is_none_or(|it| is_truthy(&it)),
+        range: tag.syntax().text_range(),
+    })
+}
+
+/// Splits a comma-separated list of component paths, as in `implements`.
+///
+/// The ranges are only exact if the value contains no escapes; values
+/// whose text differs in length from their range yield no paths.
+pub(crate) fn path_refs(value: &str, range: TextRange) -> Vec<PathRef> {
+    if TextSize::of(value) != range.len() {
+        return Vec::new();
+    }
+    let mut offset = 0;
+    let mut refs = Vec::new();
+    for segment in value.split(',') {
+        let trimmed = segment.trim();
+        if !trimmed.is_empty() {
+            let start = offset + segment.find(trimmed).unwrap_or(0);
+            let start = range.start() + TextSize::from(start as u32);
+            refs.push(PathRef {
+                path: trimmed.to_owned(),
+                range: TextRange::at(start, TextSize::of(trimmed)),
+            });
+        }
+        offset += segment.len() + 1;
+    }
+    refs
+}
+
+#[cfg(test)]
+mod tests {
+    use syntax::ast::SourceFile;
+
+    use super::*;
+
+    fn items(text: &str) -> FileItems {
+        FileItems::new(&SourceFile::parse(text).tree())
+    }
+
+    #[test]
+    fn script_component() {
+        let items = items(
+            r#"component extends="base.Model" implements="IOne, a.ITwo" accessors="true" {
+    property name="id" type="numeric";
+    property models.Role role;
+    property name="age" getter=false;
+    private string function greet(required string name, age = 1) {
+        var inner = function(x) {};
+    }
+    static function make() {}
+}"#,
+        );
+        let component = items.component.clone().unwrap();
+        assert_eq!(component.extends.as_ref().unwrap().path, "base.Model");
+        let implements: Vec<_> = component
+            .implements
+            .iter()
+            .map(|it| it.path.as_str())
+            .collect();
+        assert_eq!(implements, ["IOne", "a.ITwo"]);
+        assert!(component.accessors);
+        let properties: Vec<_> = component
+            .properties
+            .iter()
+            .map(|it| (it.name.as_str(), it.type_name.as_deref()))
+            .collect();
+        assert_eq!(
+            properties,
+            [
+                ("id", Some("numeric")),
+                ("role", Some("models.Role")),
+                ("age", None)
+            ]
+        );
+        assert!(component.accessor("getAge").is_none());
+        assert!(component.accessor("setAge").is_some());
+        let accessors: Vec<_> = component
+            .accessors()
+            .into_iter()
+            .map(|it| it.name)
+            .collect();
+        assert_eq!(
+            accessors,
+            ["getId", "getRole", "setId", "setRole", "setAge"]
+        );
+
+        let names: Vec<_> = items.functions.iter().map(|it| it.name.as_str()).collect();
+        assert_eq!(names, ["greet", "make"]);
+        let greet = &items.functions[0];
+        assert_eq!(greet.access.as_deref(), Some("private"));
+        assert_eq!(greet.return_type.as_deref(), Some("string"));
+        assert!(greet.params[0].required);
+        assert_eq!(greet.params[1].default.as_deref(), Some("1"));
+        assert!(items.functions[1].is_static);
+    }
+
+    #[test]
+    fn tag_component() {
+        let items = items(
+            r##"<cfcomponent extends="Base" accessors="yes">
+<cfproperty name="id" type="numeric">
+<cfproperty name="age" setter="false">
+<cffunction name="go" access="public" returntype="string">
+    <cfargument name="a" type="models.User" required="true">
+    <cfargument name="b" default="#now()#">
+</cffunction>
+</cfcomponent>"##,
+        );
+        let component = items.component.clone().unwrap();
+        assert_eq!(component.extends.as_ref().unwrap().path, "Base");
+        assert!(component.accessors);
+        assert_eq!(component.properties[0].name, "id");
+        assert!(component.accessor("getAge").is_some());
+        assert!(component.accessor("SETAGE").is_none());
+        assert!(component.accessor("setId").is_some());
+
+        let go = items.function("GO").unwrap();
+        assert_eq!(go.access.as_deref(), Some("public"));
+        let params: Vec<_> = go
+            .params
+            .iter()
+            .map(|it| (it.name.as_str(), it.required, it.default.as_deref()))
+            .collect();
+        assert_eq!(
+            params,
+            [("a", true, None), ("b", false, Some(r##""#now()#""##))]
+        );
+    }
+
+    #[test]
+    fn path_ref_ranges() {
+        let refs = path_refs(" a.B ,C", TextRange::new(10.into(), 17.into()));
+        assert_eq!(refs[0].range, TextRange::new(11.into(), 14.into()));
+        assert_eq!(refs[0].name_range(), TextRange::new(13.into(), 14.into()));
+        assert_eq!(refs[1].range, TextRange::new(16.into(), 17.into()));
+    }
+}
@@ -0,0 +1,276 @@
+//! Language features for CFML, independent of the LSP protocol.
+//!
+//! [`AnalysisHost`] owns the current state of all files and is updated with
+//! [`Change`]s; [`Analysis`] is an immutable snapshot answering queries.
+//! Offsets and ranges are byte-based; conversion to LSP positions happens
+//! in the server.
+
+mod assists;
+mod builtin_tags;
+mod builtins;
+mod completion;
+mod db;
+mod diagnostics;
+mod docs;
+mod document_highlight;
+mod document_symbols;
+mod extract;
+mod folding_ranges;
+mod formatting;
+mod hover;
+mod inheritance;
+mod items;
+mod line_index;
+mod linked_editing;
+mod lints;
+mod locking;
+mod lowering;
+mod properties;
+mod references;
+mod rename;
+mod script_to_tags;
+mod security;
+mod selection_ranges;
+mod signature_help;
+mod source_change;
+mod sql_injection;
+mod suppressions;
+mod symbol_index;
+mod syntax_highlighting;
+mod tag_to_script;
+mod taint;
+mod text_edit;
+mod usages;
+mod xss;
+
+#[cfg(test)]
+mod fixture;
+
+use std::sync::Arc;
+
+use syntax::{Parse, TextRange, TextSize};
+use virtual_fs::{FileId, VirtualFsPath};
+
+pub use crate::{
+    assists::{Assist, AssistKind},
+    builtin_tags::{builtin_tag, BuiltinTag, TagAttribute},
+    builtins::{builtin_function, BuiltinFunction, BuiltinParam},
+    completion::CompletionItem,
+    diagnostics::{
+        find_rule, Diagnostic, DiagnosticCode, DiagnosticsConfig, Rule, RuleConfig, Severity, RULES,
+    },
+    docs::Documentation,
+    document_highlight::HighlightedRange,
+    document_symbols::{DocumentSymbol, SymbolKind},
+    folding_ranges::{Fold, FoldKind},
+    formatting::{BlockSpacing, Casing, FormatOptions},
+    hover::HoverResult,
+    line_index::{LineCol, LineColUtf16, LineIndex},
+    linked_editing::TAG_NAME_PATTERN,
+    rename::RenameError,
+    security::TaintRule,
+    signature_help::{ParameterHelp, SignatureHelp},
+    source_change::{FileSystemEdit, SourceChange},
+    symbol_index::{FileSymbol, Query},
+    syntax_highlighting::{Highlight, HlMod, HlMods, HlRange, HlTag},
+    text_edit::{Indel, TextEdit, TextEditBuilder},
+    usages::Access,
+};
+
+use crate::db::RootDatabase;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FilePosition {
+    pub file_id: FileId,
+    pub offset: TextSize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FileRange {
+    pub file_id: FileId,
+    pub range: TextRange,
+}
+
+/// A batch of updates to apply to an [`AnalysisHost`].
+#[derive(Debug, Default)]
+pub struct Change {
+    roots: Option<Vec<VirtualFsPath>>,
+    files_changed: Vec<(FileId, VirtualFsPath, Option<Arc<str>>)>,
+}
+
+impl Change {
+    pub fn new() -> Change {
+        Change::default()
+    }
+
+    /// Sets the directories component paths are resolved against.
+    pub fn set_roots(&mut self, roots: Vec<VirtualFsPath>) {
+        self.roots = Some(roots);
+    }
+
+    /// Records the new text of a file, or its deletion if `text` is `None`.
+    pub fn change_file(&mut self, file_id: FileId, path: VirtualFsPath, text: Option<Arc<str>>) {
+        self.files_changed.push((file_id, path, text));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.roots.is_none() && self.files_changed.is_empty()
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct AnalysisHost {
+    db: RootDatabase,
+}
+
+impl AnalysisHost {
+    pub fn new() -> AnalysisHost {
+        AnalysisHost::default()
+    }
+
+    /// A snapshot of the current state, unaffected by later changes.
+    pub fn analysis(&self) -> Analysis {
+        Analysis {
+            db: self.db.clone(),
+        }
+    }
+
+    pub fn apply_change(&mut self, change: Change) {
+        if let Some(roots) = change.roots {
+            self.db.set_roots(roots);
+        }
+        for (file_id, path, text) in change.files_changed {
+            self.db.set_file_text(file_id, path, text);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Analysis {
+    db: RootDatabase,
+}
+
+impl Analysis {
+    pub fn file_text(&self, file_id: FileId) -> Arc<str> {
+        self.db.file_text(file_id)
+    }
+
+    pub fn file_line_index(&self, file_id: FileId) -> Arc<LineIndex> {
+        self.db.line_index(file_id)
+    }
+
+    pub fn parse(&self, file_id: FileId) -> Parse {
+        self.db.parse(file_id)
+    }
+
+    /// The problems found in a file.
+    pub fn diagnostics(&self, config: &DiagnosticsConfig, file_id: FileId) -> Vec<Diagnostic> {
+        diagnostics::diagnostics(&self.db, config, file_id)
+    }
+
+    /// A number that changes whenever the diagnostics of a file may have:
+    /// when it, a component it depends on or the set of files changes.
+    /// `None` if there is no such file.
+    pub fn diagnostics_revision(&self, file_id: FileId) -> Option<u64> {
+        diagnostics::revision(&self.db, file_id)
+    }
+
+    /// The outline of a file, nested by containment.
+    pub fn document_symbols(&self, file_id: FileId) -> Vec<DocumentSymbol> {
+        let component_name = self.db.component_name(file_id);
+        document_symbols::document_symbols(&self.db.parse(file_id).tree(), component_name)
+    }
+
+    pub fn folding_ranges(&self, file_id: FileId) -> Vec<Fold> {
+        folding_ranges::folding_ranges(&self.db.parse(file_id).tree())
+    }
+
+    /// The ranges selection expands through from `position`, innermost
+    /// first.
+    pub fn selection_ranges(&self, position: FilePosition) -> Vec<TextRange> {
+        let file = self.db.parse(position.file_id).tree();
+        selection_ranges::selection_ranges(&file, position.offset)
+    }
+
+    /// The occurrences of the variable or tag name at `position` in its
+    /// file.
+    pub fn document_highlight(&self, position: FilePosition) -> Option<Vec<HighlightedRange>> {
+        document_highlight::document_highlight(&self.db, position)
+    }
+
+    /// The start and end tag names to edit together from `position`.
+    pub fn linked_editing_ranges(&self, position: FilePosition) -> Option<Vec<TextRange>> {
+        let file = self.db.parse(position.file_id).tree();
+        linked_editing::linked_editing_ranges(&file, position.offset)
+    }
+
+    /// Components, functions and properties of all files matching `query`,
+    /// best matches first.
+    pub fn symbol_search(&self, query: Query) -> Vec<FileSymbol> {
+        symbol_index::world_symbols(&self.db, &query)
+    }
+
+    /// Semantic highlighting of the whole file.
+    pub fn highlight(&self, file_id: FileId) -> Vec<HlRange> {
+        syntax_highlighting::highlight(&self.db, file_id, None)
+    }
+
+    /// Semantic highlighting of the ranges intersecting `range`.
+    pub fn highlight_range(&self, frange: FileRange) -> Vec<HlRange> {
+        syntax_highlighting::highlight(&self.db, frange.file_id, Some(frange.range))
+    }
+
+    /// Builds the symbol index entries of all files not indexed yet, so
+    /// that later searches only have to scan it.
+    pub fn prime_symbol_index(&self) {
+        symbol_index::prime(&self.db);
+    }
+
+    /// The range of the symbol to rename at `position`, with a placeholder
+    /// for the new name.
+    pub fn prepare_rename(
+        &self,
+        position: FilePosition,
+    ) -> Result<(TextRange, String), RenameError> {
+        rename::prepare_rename(&self.db, position)
+    }
+
+    /// The signature and documentation of the symbol at `position`.
+    pub fn hover(&self, position: FilePosition) -> Option<HoverResult> {
+        hover::hover(&self.db, position)
+    }
+
+    /// The signature of the function whose arguments surround `position`.
+    pub fn signature_help(&self, position: FilePosition) -> Option<SignatureHelp> {
+        signature_help::signature_help(&self.db, position)
+    }
+
+    /// The methods that can be typed after the dot before `position`.
+    pub fn completions(&self, position: FilePosition) -> Vec<CompletionItem> {
+        completion::completions(&self.db, position)
+    }
+
+    /// The edits formatting a file, or only the lines `range` touches.
+    /// `None` if the file has syntax errors.
+    pub fn format(
+        &self,
+        file_id: FileId,
+        range: Option<TextRange>,
+        options: &FormatOptions,
+    ) -> Option<TextEdit> {
+        formatting::format(&self.db, file_id, range, options)
+    }
+
+    /// The refactorings offered for the selection `frange`.
+    pub fn assists(&self, frange: FileRange) -> Vec<Assist> {
+        assists::assists(&self.db, frange)
+    }
+
+    pub fn rename(
+        &self,
+        position: FilePosition,
+        new_name: &str,
+    ) -> Result<SourceChange, RenameError> {
+        rename::rename(&self.db, position, new_name)
+    }
+}
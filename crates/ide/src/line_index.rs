@@ -0,0 +1,203 @@
+//! Conversion between byte offsets and line/column positions.
+//!
+//! Columns are stored as UTF-8 byte offsets into the line; the LSP layer
+//! converts them to UTF-16 code units with [`LineIndex::to_utf16`].
+
+use rustc_hash::FxHashMap;
+use syntax::{TextRange, TextSize};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineIndex {
+    /// Offset of the start of each line but the first.
+    newlines: Vec<TextSize>,
+    /// Non-ASCII characters of each line, for UTF-16 conversion.
+    wide_chars: FxHashMap<u32, Vec<WideChar>>,
+    len: TextSize,
+}
+
+/// A line and a UTF-8 column, both zero-based.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LineCol {
+    pub line: u32,
+    pub col: u32,
+}
+
+/// A line and a UTF-16 column, both zero-based.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LineColUtf16 {
+    pub line: u32,
+    pub col: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct WideChar {
+    /// UTF-8 column of the start of the character.
+    start: u32,
+    len_utf8: u32,
+    len_utf16: u32,
+}
+
+impl LineIndex {
+    pub fn new(text: &str) -> LineIndex {
+        let mut newlines = Vec::new();
+        let mut wide_chars = FxHashMap::default();
+        let mut line_wide_chars = Vec::new();
+        let mut line = 0;
+        let mut line_start = 0;
+
+        for (offset, c) in text.char_indices() {
+            if c == '\n' {
+                if !line_wide_chars.is_empty() {
+                    wide_chars.insert(line, std::mem::take(&mut line_wide_chars));
+                }
+                newlines.push(TextSize::from((offset + 1) as u32));
+                line += 1;
+                line_start = offset + 1;
+                continue;
+            }
+            if !c.is_ascii() {
+                line_wide_chars.push(WideChar {
+                    start: (offset - line_start) as u32,
+                    len_utf8: c.len_utf8() as u32,
+                    len_utf16: c.len_utf16() as u32,
+                });
+            }
+        }
+        if !line_wide_chars.is_empty() {
+            wide_chars.insert(line, line_wide_chars);
+        }
+
+        LineIndex {
+            newlines,
+            wide_chars,
+            len: TextSize::of(text),
+        }
+    }
+
+    pub fn len(&self) -> TextSize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == TextSize::from(0)
+    }
+
+    pub fn line_count(&self) -> u32 {
+        self.newlines.len() as u32 + 1
+    }
+
+    pub fn line_col(&self, offset: TextSize) -> LineCol {
+        let offset = offset.min(self.len);
+        let line = self.newlines.partition_point(|&it| it <= offset);
+        let line_start = self.line_start(line as u32);
+        LineCol {
+            line: line as u32,
+            col: (offset - line_start).into(),
+        }
+    }
+
+    /// The offset of `line_col`, or `None` if the line is out of bounds.
+    /// Columns past the end of the line are clamped to it.
+    pub fn offset(&self, line_col: LineCol) -> Option<TextSize> {
+        if line_col.line >= self.line_count() {
+            return None;
+        }
+        let start = self.line_start(line_col.line);
+        let end = self
+            .newlines
+            .get(line_col.line as usize)
+            .map_or(self.len, |&it| it - TextSize::from(1));
+        Some((start + TextSize::from(line_col.col)).min(end))
+    }
+
+    /// The range of `line`, without its line terminator.
+    pub fn line_range(&self, line: u32) -> TextRange {
+        let start = self.line_start(line);
+        let end = self
+            .newlines
+            .get(line as usize)
+            .map_or(self.len, |&it| it - TextSize::from(1));
+        TextRange::new(start, end.max(start))
+    }
+
+    pub fn to_utf16(&self, line_col: LineCol) -> LineColUtf16 {
+        let mut col = line_col.col;
+        for c in self.wide_chars(line_col.line) {
+            if c.start >= line_col.col {
+                break;
+            }
+            col = col - c.len_utf8 + c.len_utf16;
+        }
+        LineColUtf16 {
+            line: line_col.line,
+            col,
+        }
+    }
+
+    pub fn to_utf8(&self, line_col: LineColUtf16) -> LineCol {
+        // Difference between UTF-8 and UTF-16 columns so far.
+        let mut diff = 0;
+        for c in self.wide_chars(line_col.line) {
+            if c.start - diff >= line_col.col {
+                break;
+            }
+            diff += c.len_utf8 - c.len_utf16;
+        }
+        LineCol {
+            line: line_col.line,
+            col: line_col.col + diff,
+        }
+    }
+
+    fn line_start(&self, line: u32) -> TextSize {
+        match line {
+            0 => TextSize::from(0),
+            _ => self
+                .newlines
+                .get(line as usize - 1)
+                .copied()
+                .unwrap_or(self.len),
+        }
+    }
+
+    fn wide_chars(&self, line: u32) -> &[WideChar] {
+        self.wide_chars.get(&line).map_or(&[], Vec::as_slice)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_col_round_trip() {
+        let text = "ab\ncd\n\nef";
+        let index = LineIndex::new(text);
+        for offset in 0..=text.len() as u32 {
+            let offset = TextSize::from(offset);
+            assert_eq!(index.offset(index.line_col(offset)), Some(offset));
+        }
+        assert_eq!(
+            index.line_col(TextSize::from(4)),
+            LineCol { line: 1, col: 1 }
+        );
+        assert_eq!(index.line_count(), 4);
+        assert_eq!(index.offset(LineCol { line: 4, col: 0 }), None);
+        assert_eq!(
+            index.offset(LineCol { line: 0, col: 10 }),
+            Some(TextSize::from(2))
+        );
+    }
+
+    #[test]
+    fn utf16_columns() {
+        let text = "aé😀b\nx";
+        let index = LineIndex::new(text);
+        let b = index.line_col(TextSize::from(text.find('b').unwrap() as u32));
+        assert_eq!(b, LineCol { line: 0, col: 7 });
+        let wide = index.to_utf16(b);
+        assert_eq!(wide, LineColUtf16 { line: 0, col: 4 });
+        assert_eq!(index.to_utf8(wide), b);
+        assert_eq!(index.to_utf16(LineCol { line: 1, col: 1 }).col, 1);
+    }
+}
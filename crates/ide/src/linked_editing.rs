@@ -0,0 +1,65 @@
+//! Linked editing of the start and end tag names of a CFML tag pair, so
+//! renaming one renames the other.
+
+use syntax::{
+    ast::{self, AstNode},
+    SyntaxKind::*,
+    TextRange, TextSize,
+};
+
+/// What a tag name may be edited to, as an ECMAScript regular expression:
+/// `cfloop`, custom tags like `cf_panel` and `cfimport` prefixed names like
+/// `ui:button`.
+pub const TAG_NAME_PATTERN: &str = r"[A-Za-z_$][\w$.:-]*";
+
+/// The names of the start and end tag of the tag pair whose name covers
+/// `offset`. Both must be spelled the same, as clients edit them as one.
+pub(crate) fn linked_editing_ranges(
+    file: &ast::SourceFile,
+    offset: TextSize,
+) -> Option<Vec<TextRange>> {
+    let token = file
+        .syntax()
+        .token_at_offset(offset)
+        .find(|it| it.kind() == TAG_NAME)?;
+    let tag = token.parent()?.parent().and_then(ast::Tag::cast)?;
+    let start = tag.name_token()?;
+    let end = tag.end_tag()?.name_token()?;
+    (start.text() == end.text()).then(|| vec![start.text_range(), end.text_range()])
+}
+
+#[cfg(test)]
+mod tests {
+    use syntax::ast::SourceFile;
+
+    use super::*;
+
+    fn check(text: &str, expect: Option<&[&str]>) {
+        let offset = text.find("$0").unwrap();
+        let text = text.replace("$0", "");
+        let file = SourceFile::parse(&text).tree();
+        let ranges = linked_editing_ranges(&file, TextSize::from(offset as u32));
+        let actual: Option<Vec<_>> = ranges.map(|it| it.iter().map(|r| &text[*r]).collect());
+        assert_eq!(actual.as_deref(), expect);
+    }
+
+    #[test]
+    fn tag_pairs() {
+        check(
+            "<cfloop index=\"i\" from=\"1\" to=\"2\">#i#</cf$0loop>",
+            Some(&["cfloop", "cfloop"]),
+        );
+        check("<cf_$0panel>x</cf_panel>", Some(&["cf_panel", "cf_panel"]));
+        check(
+            "<cfimport prefix=\"ui\" taglib=\"tags\">\n<ui:button$0>Go</ui:button>",
+            Some(&["ui:button", "ui:button"]),
+        );
+    }
+
+    #[test]
+    fn unpaired_or_differently_cased() {
+        check("<cfset$0 x = 1>", None);
+        check("<cfif a>x<cfel$0se>y</cfif>", None);
+        check("<cfloop$0 list=\"a\" index=\"i\"></CFLOOP>", None);
+    }
+}
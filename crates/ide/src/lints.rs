@@ -0,0 +1,510 @@
+//! Lints ported from CFLint, under their CFLint names: code that works but
+//! is likely wrong, unsafe or left over from debugging.
+
+use rustc_hash::FxHashSet;
+use syntax::{
+    ast::{self, AstNode},
+    SyntaxKind::*,
+    SyntaxNode, TextRange,
+};
+use virtual_fs::FileId;
+
+use crate::{
+    db::RootDatabase,
+    diagnostics::{fix, Diagnostic, DiagnosticCode, DiagnosticsConfig},
+    sql_injection,
+    text_edit::TextEdit,
+    usages::{Access, Receiver, VarScope},
+};
+
+/// Tags and functions that should not be left in code, with the rule that
+/// reports them.
+const AVOIDED_TAGS: &[(&str, DiagnosticCode)] = &[
+    ("cfdump", DiagnosticCode::AVOID_USING_CFDUMP_TAG),
+    ("cfabort", DiagnosticCode::AVOID_USING_CFABORT_TAG),
+    ("cfexecute", DiagnosticCode::AVOID_USING_CFEXECUTE_TAG),
+];
+const AVOIDED_FUNCTIONS: &[(&str, DiagnosticCode)] =
+    &[("writeDump", DiagnosticCode::AVOID_USING_WRITEDUMP)];
+
+pub(crate) fn lints(
+    db: &RootDatabase,
+    config: &DiagnosticsConfig,
+    file_id: FileId,
+    file: &ast::SourceFile,
+    res: &mut Vec<Diagnostic>,
+) {
+    missing_var(db, file_id, file, res);
+    arg_var_conflict(db, file_id, res);
+    sql_injection::query_params(db, config, file_id, file, res);
+    avoided(db, file_id, file, res);
+    function_size(db, config, file_id, res);
+}
+
+/// Checks for names a function assigns without declaring them with `var`
+/// or writing them to `local`, which then live in the `variables` scope
+/// shared by all calls. This includes the variables tags like
+/// `<cfquery name="q">` or `cfquery(name = "q")` assign. Properties are
+/// assigned on purpose.
+fn missing_var(
+    db: &RootDatabase,
+    file_id: FileId,
+    file: &ast::SourceFile,
+    res: &mut Vec<Diagnostic>,
+) {
+    let mut properties = FxHashSet::default();
+    for file_id in db.component_chain(file_id) {
+        if let Some(component) = &db.items(file_id).component {
+            let names = component
+                .properties
+                .iter()
+                .map(|it| it.name.to_ascii_lowercase());
+            properties.extend(names);
+        }
+    }
+    let mut seen = FxHashSet::default();
+    for var in &db.usages(file_id).vars {
+        let Some(function) = var.function else {
+            continue;
+        };
+        if var.access != Access::Write
+            || var.scoped
+            || var.is_decl
+            || var.scope != VarScope::Variables
+        {
+            continue;
+        }
+        let key = var.name.to_ascii_lowercase();
+        if properties.contains(&key) || !seen.insert((function, key)) {
+            continue;
+        }
+        let mut fixes = Vec::new();
+        if let Some(offset) = var_keyword_offset(file, var.range) {
+            let edit = TextEdit::insert(offset, "var ".to_owned());
+            fixes.push(fix(
+                file_id,
+                format!("Declare `{}` with `var`", var.name),
+                edit,
+            ));
+        }
+        let edit = TextEdit::insert(var.range.start(), "local.".to_owned());
+        fixes.push(fix(
+            file_id,
+            format!("Prefix `{}` with `local.`", var.name),
+            edit,
+        ));
+        res.push(
+            Diagnostic::new(
+                DiagnosticCode::MISSING_VAR,
+                var.range,
+                format!(
+                    "`{}` is not declared with `var`, so it is shared through the `variables` scope",
+                    var.name
+                ),
+            )
+            .with_fixes(fixes),
+        );
+    }
+}
+
+/// Where `var` goes to declare the name at `range`: before `x = 1` as a
+/// statement or in `<cfset>`, before the initializer of a `for` loop and
+/// before the variable of a `for-in` loop.
+fn var_keyword_offset(file: &ast::SourceFile, range: TextRange) -> Option<syntax::TextSize> {
+    let name_ref = file
+        .syntax()
+        .covering_element(range)
+        .ancestors()
+        .find(|it| it.kind() == NAME_REF)?;
+    let parent = name_ref.parent()?;
+    if parent.kind() == FOR_IN_STMT {
+        return Some(name_ref.text_range().start());
+    }
+    let assign = ast::AssignExpr::cast(parent)?;
+    if assign.target()?.syntax() != &name_ref || assign.op_token()?.kind() != EQ {
+        return None;
+    }
+    let statement = assign.syntax().parent()?;
+    let declarable = match statement.kind() {
+        EXPR_STMT => true,
+        START_TAG => statement
+            .parent()
+            .and_then(ast::Tag::cast)
+            .is_some_and(|it| it.name() == "cfset"),
+        FOR_STMT => statement
+            .children()
+            .next()
+            .is_some_and(|it| &it == assign.syntax()),
+        _ => false,
+    };
+    declarable.then(|| assign.syntax().text_range().start())
+}
+
+/// Checks for local variables declared with the name of an argument of
+/// the same function, which hide the argument.
+fn arg_var_conflict(db: &RootDatabase, file_id: FileId, res: &mut Vec<Diagnostic>) {
+    let usages = db.usages(file_id);
+    let params: FxHashSet<_> = usages
+        .vars
+        .iter()
+        .filter_map(|it| match it.scope {
+            VarScope::Arguments { owner } if it.is_decl => {
+                Some((owner, it.name.to_ascii_lowercase()))
+            }
+            _ => None,
+        })
+        .collect();
+    for var in &usages.vars {
+        let VarScope::Local { owner } = var.scope else {
+            continue;
+        };
+        if var.is_decl && params.contains(&(owner, var.name.to_ascii_lowercase())) {
+            res.push(Diagnostic::new(
+                DiagnosticCode::ARG_VAR_CONFLICT,
+                var.range,
+                format!(
+                    "`{}` is declared both as an argument and with `var`",
+                    var.name
+                ),
+            ));
+        }
+    }
+}
+
+/// Checks for tags and functions used while debugging, or that run other
+/// programs.
+fn avoided(db: &RootDatabase, file_id: FileId, file: &ast::SourceFile, res: &mut Vec<Diagnostic>) {
+    for node in file.syntax().descendants() {
+        match node.kind() {
+            TAG => {
+                let Some(tag) = ast::Tag::cast(node) else {
+                    continue;
+                };
+                let name = tag.name();
+                let Some(&(_, code)) = AVOIDED_TAGS.iter().find(|(it, _)| *it == name) else {
+                    continue;
+                };
+                if let Some(token) = tag.name_token() {
+                    let message = format!("avoid leaving `<{name}>` in the code");
+                    res.push(Diagnostic::new(code, token.text_range(), message));
+                }
+            }
+            SCRIPT_TAG_STMT => {
+                let is_abort =
+                    ast::ScriptTagStmt::cast(node.clone()).is_some_and(|it| it.name() == "abort");
+                if let Some(token) = is_abort.then(|| first_ident(&node)).flatten() {
+                    let message = "avoid leaving `abort` in the code".to_owned();
+                    res.push(Diagnostic::new(
+                        DiagnosticCode::AVOID_USING_ABORT,
+                        token,
+                        message,
+                    ));
+                }
+            }
+            _ => (),
+        }
+    }
+    for call in &db.usages(file_id).calls {
+        let avoided = AVOIDED_FUNCTIONS
+            .iter()
+            .find(|(it, _)| it.eq_ignore_ascii_case(&call.name));
+        if let (Some(&(name, code)), Receiver::None) = (avoided, &call.receiver) {
+            let message = format!("avoid leaving `{name}` in the code");
+            res.push(Diagnostic::new(code, call.name_range, message));
+        }
+    }
+}
+
+fn first_ident(node: &SyntaxNode) -> Option<TextRange> {
+    node.children_with_tokens()
+        .filter_map(|it| it.into_token())
+        .find(|it| it.kind() == IDENT)
+        .map(|it| it.text_range())
+}
+
+/// Checks for functions longer than `length` lines or with more than
+/// `maximum` arguments.
+fn function_size(
+    db: &RootDatabase,
+    config: &DiagnosticsConfig,
+    file_id: FileId,
+    res: &mut Vec<Diagnostic>,
+) {
+    let text = db.file_text(file_id);
+    let max_length: usize = config
+        .option(DiagnosticCode::EXCESSIVE_FUNCTION_LENGTH, "length")
+        .unwrap_or(usize::MAX);
+    let max_args: usize = config
+        .option(DiagnosticCode::EXCESSIVE_ARGUMENTS, "maximum")
+        .unwrap_or(usize::MAX);
+    for function in &db.items(file_id).functions {
+        let lines = text[function.range].lines().count();
+        if lines > max_length {
+            res.push(Diagnostic::new(
+                DiagnosticCode::EXCESSIVE_FUNCTION_LENGTH,
+                function.name_range,
+                format!(
+                    "`{}` has {lines} lines, more than {max_length}",
+                    function.name
+                ),
+            ));
+        }
+        let args = function.params.len();
+        if args > max_args {
+            res.push(Diagnostic::new(
+                DiagnosticCode::EXCESSIVE_ARGUMENTS,
+                function.name_range,
+                format!(
+                    "`{}` has {args} arguments, more than {max_args}",
+                    function.name
+                ),
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use virtual_fs::FileId;
+
+    use crate::{find_rule, fixture, DiagnosticCode, DiagnosticsConfig, RuleConfig, Severity};
+
+    /// The codes of the lints of the first file and the text they cover.
+    fn check(config: &DiagnosticsConfig, fixture: &str, expect: &[(&str, &str)]) {
+        let analysis = fixture::parse(fixture).analysis;
+        let text = analysis.file_text(FileId(0));
+        let actual: Vec<_> = analysis
+            .diagnostics(config, FileId(0))
+            .into_iter()
+            .filter(|it| find_rule(it.code.as_str()).is_some_and(|rule| rule.checker.is_some()))
+            .map(|it| (it.code.as_str(), &text[it.range]))
+            .collect();
+        assert_eq!(actual, expect);
+    }
+
+    /// The unscoped variables of the first file, with the labels of their
+    /// fixes.
+    fn unscoped(fixture: &str) -> Vec<(String, Vec<String>)> {
+        let analysis = fixture::parse(fixture).analysis;
+        let text = analysis.file_text(FileId(0));
+        analysis
+            .diagnostics(&DiagnosticsConfig::default(), FileId(0))
+            .into_iter()
+            .filter(|it| it.code == DiagnosticCode::MISSING_VAR)
+            .map(|it| {
+                let fixes = it.fixes.into_iter().map(|it| it.label).collect();
+                (text[it.range].to_owned(), fixes)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn unscoped_variables() {
+        let actual = unscoped(
+            r#"
+//- /Service.cfc
+component {
+    property name="cache";
+    total = 0;
+    function load(ids) {
+        var result = 0;
+        cache = {};
+        total = 1;
+        total += 2;
+        for (id in arguments.ids) {}
+        for (i = 1; i <= 2; i++) {}
+        local.count = 1;
+        count = 2;
+        var each = function() { inner.x = 1; inner = 1; };
+    }
+}
+"#,
+        );
+        let fixes = |name: &str, var: bool| {
+            let mut fixes = vec![format!("Prefix `{name}` with `local.`")];
+            if var {
+                fixes.insert(0, format!("Declare `{name}` with `var`"));
+            }
+            (name.to_owned(), fixes)
+        };
+        assert_eq!(
+            actual,
+            [
+                fixes("total", true),
+                fixes("id", true),
+                fixes("i", true),
+                fixes("inner", true),
+            ]
+        );
+    }
+
+    #[test]
+    fn unscoped_tag_variables() {
+        let fixture = r#"
+//- /page.cfm
+<cffunction name="report">
+    <cfquery name="q">select 1</cfquery>
+    <cfloop index="row" from="1" to="2"></cfloop>
+    <cfhttp url="https://example.com" result="response">
+    <cfset rows = 1>
+</cffunction>
+"#;
+        let names: Vec<_> = unscoped(fixture)
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        assert_eq!(names, ["q", "row", "response", "rows"]);
+
+        let analysis = fixture::parse(fixture).analysis;
+        let mut fixes = analysis
+            .diagnostics(&DiagnosticsConfig::default(), FileId(0))
+            .into_iter()
+            .flat_map(|it| it.fixes);
+        let mut change = fixes.next().unwrap().source_change;
+        for fix in fixes.filter(|it| it.label.starts_with("Declare")) {
+            for (file_id, edit) in fix.source_change.source_file_edits {
+                change.insert_source_edit(file_id, edit);
+            }
+        }
+        assert_eq!(
+            fixture::render_change(&analysis, &change).trim(),
+            r#"
+//- /page.cfm
+<cffunction name="report">
+    <cfquery name="local.q">select 1</cfquery>
+    <cfloop index="row" from="1" to="2"></cfloop>
+    <cfhttp url="https://example.com" result="response">
+    <cfset var rows = 1>
+</cffunction>
+"#
+            .trim()
+        );
+    }
+
+    #[test]
+    fn unscoped_script_tag_variables() {
+        let actual = unscoped(
+            r#"
+//- /Service.cfc
+component {
+    function load() {
+        cfhttp(url = "https://example.com", result = "response");
+        cfquery(name = "q", datasource = "app") {
+            writeOutput("select 1");
+        }
+        cfquery(name = "local.rows") {}
+    }
+}
+"#,
+        );
+        let fixes = |name: &str| {
+            (
+                name.to_owned(),
+                vec![format!("Prefix `{name}` with `local.`")],
+            )
+        };
+        assert_eq!(actual, [fixes("response"), fixes("q")]);
+    }
+
+    #[test]
+    fn arg_var_conflict() {
+        check(
+            &DiagnosticsConfig::default(),
+            r#"
+//- /Service.cfc
+component {
+    function find(id, name) {
+        var id = 1;
+        var other = function(name) { var name = 2; };
+    }
+}
+"#,
+            &[("ARG_VAR_CONFLICT", "id"), ("ARG_VAR_CONFLICT", "name")],
+        );
+    }
+
+    #[test]
+    fn avoided_tags_and_functions() {
+        check(
+            &DiagnosticsConfig::default(),
+            r#"
+//- /page.cfm
+<cfdump var="x"><cfexecute name="ls"></cfexecute>
+<cfscript>
+    writeDump(x);
+    abort;
+</cfscript>
+<cfabort>
+"#,
+            &[
+                ("AVOID_USING_CFDUMP_TAG", "cfdump"),
+                ("AVOID_USING_CFEXECUTE_TAG", "cfexecute"),
+                ("AVOID_USING_WRITEDUMP", "writeDump"),
+                ("AVOID_USING_ABORT", "abort"),
+                ("AVOID_USING_CFABORT_TAG", "cfabort"),
+            ],
+        );
+    }
+
+    #[test]
+    fn configured_rules() {
+        let fixture = r#"
+//- /Service.cfc
+component {
+    function long(a, b, c) {
+        writeDump(a);
+        return a;
+    }
+}
+"#;
+        check(
+            &DiagnosticsConfig::default(),
+            fixture,
+            &[("AVOID_USING_WRITEDUMP", "writeDump")],
+        );
+
+        let mut config = DiagnosticsConfig::default();
+        let options = |name: &str, value: &str| {
+            let options = [(name.to_owned(), value.to_owned())];
+            RuleConfig {
+                options: options.into_iter().collect(),
+                ..RuleConfig::default()
+            }
+        };
+        config
+            .rules
+            .insert("EXCESSIVE_FUNCTION_LENGTH", options("length", "3"));
+        config
+            .rules
+            .insert("EXCESSIVE_ARGUMENTS", options("maximum", "not a number"));
+        let disabled = RuleConfig {
+            enabled: false,
+            ..RuleConfig::default()
+        };
+        config.rules.insert("AVOID_USING_WRITEDUMP", disabled);
+        check(&config, fixture, &[("EXCESSIVE_FUNCTION_LENGTH", "long")]);
+
+        config
+            .rules
+            .insert("EXCESSIVE_ARGUMENTS", options("maximum", "2"));
+        let error = RuleConfig {
+            severity: Some(Severity::Error),
+            ..RuleConfig::default()
+        };
+        config.rules.insert("EXCESSIVE_FUNCTION_LENGTH", error);
+        let analysis = fixture::parse(fixture).analysis;
+        let actual: Vec<_> = analysis
+            .diagnostics(&config, FileId(0))
+            .into_iter()
+            .map(|it| (it.code, it.severity, it.message))
+            .collect();
+        assert_eq!(
+            actual,
+            [(
+                DiagnosticCode::EXCESSIVE_ARGUMENTS,
+                Severity::Info,
+                "`long` has 3 arguments, more than 2".to_owned()
+            ),]
+        );
+    }
+}
@@ -0,0 +1,511 @@
+//! Shared scopes written without a lock: the `application`, `session` and
+//! `server` scopes are shared between requests, so writes to them belong
+//! in a `<cflock>` or `lock` block for the scope, or a named lock.
+//!
+//! Reading part of a compound value outside a lock is also reported when
+//! the value is written under one elsewhere in the file, as the read may
+//! see it half updated. `onApplicationStart` and `onSessionStart` run
+//! before their scope is shared and need no locks.
+
+use rustc_hash::FxHashSet;
+use syntax::{
+    ast::{self, AstNode},
+    NodeOrToken,
+    SyntaxKind::*,
+    SyntaxNode, TextRange,
+};
+use virtual_fs::FileId;
+
+use crate::{
+    assists::{indent_unit, line_ending, line_indent, Assist, AssistKind},
+    db::RootDatabase,
+    diagnostics::{fix, Diagnostic, DiagnosticCode},
+    source_change::SourceChange,
+    taint::called_function,
+    text_edit::TextEdit,
+    FileRange,
+};
+
+const SHARED_SCOPES: &[&str] = &["application", "session", "server"];
+
+/// Built-in functions changing the value passed as their first argument.
+const MUTATING_FUNCTIONS: &[&str] = &[
+    "structinsert",
+    "structupdate",
+    "structdelete",
+    "structappend",
+    "structclear",
+    "arrayappend",
+    "arrayprepend",
+    "arrayinsertat",
+    "arraydeleteat",
+    "arraydelete",
+    "arrayclear",
+    "arrayset",
+    "arraysort",
+    "queryaddrow",
+    "querysetcell",
+];
+
+/// Member functions changing the value they are called on.
+const MUTATING_METHODS: &[&str] = &[
+    "insert", "update", "delete", "append", "prepend", "insertat", "deleteat", "clear", "set",
+    "sort", "addrow", "setcell",
+];
+
+/// A path into a shared scope, like `application.cache.users`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SharedPath {
+    /// The lowercase scope name.
+    scope: String,
+    /// The lowercase name of the variable in the scope, empty if it is not
+    /// a literal.
+    name: String,
+    /// The number of member and index steps after the scope.
+    depth: usize,
+}
+
+impl SharedPath {
+    fn of(expr: &ast::Expr) -> Option<SharedPath> {
+        let mut expr = expr.clone();
+        let mut name = String::new();
+        let mut depth = 0;
+        loop {
+            expr = match expr {
+                ast::Expr::MemberExpr(it) => {
+                    name = it
+                        .name_token()
+                        .map_or_else(String::new, |it| it.text().to_ascii_lowercase());
+                    it.receiver()?
+                }
+                ast::Expr::IndexExpr(it) => {
+                    name = match it.index() {
+                        Some(ast::Expr::StringLit(index)) => {
+                            index.value().unwrap_or_default().to_ascii_lowercase()
+                        }
+                        _ => String::new(),
+                    };
+                    it.base()?
+                }
+                ast::Expr::NameRef(it) => {
+                    let scope = it.name().to_ascii_lowercase();
+                    return (depth > 0 && SHARED_SCOPES.contains(&scope.as_str()))
+                        .then_some(SharedPath { scope, name, depth });
+                }
+                _ => return None,
+            };
+            depth += 1;
+        }
+    }
+}
+
+/// An access to a shared scope.
+struct Access {
+    expr: ast::Expr,
+    path: SharedPath,
+    is_write: bool,
+}
+
+pub(crate) fn unlocked_scopes(
+    db: &RootDatabase,
+    file_id: FileId,
+    file: &ast::SourceFile,
+    res: &mut Vec<Diagnostic>,
+) {
+    let accesses = accesses(file.syntax());
+    if accesses.is_empty() {
+        return;
+    }
+    let text = db.file_text(file_id);
+    let mut locked_writes = FxHashSet::default();
+    for access in accesses.iter().filter(|it| it.is_write) {
+        let node = access.expr.syntax();
+        if is_locked(node, &access.path.scope) {
+            locked_writes.insert((access.path.scope.clone(), access.path.name.clone()));
+            continue;
+        }
+        let message = format!(
+            "`{}` is written outside a lock on the `{}` scope",
+            node, access.path.scope
+        );
+        let fixes = wrap_fix(&text, file_id, node, &access.path.scope);
+        res.push(
+            Diagnostic::new(
+                DiagnosticCode::UNLOCKED_SCOPE_WRITE,
+                node.text_range(),
+                message,
+            )
+            .with_fixes(fixes.into_iter().collect()),
+        );
+    }
+    for access in accesses
+        .iter()
+        .filter(|it| !it.is_write && it.path.depth > 1)
+    {
+        let key = (access.path.scope.clone(), access.path.name.clone());
+        let node = access.expr.syntax();
+        if !locked_writes.contains(&key) || is_locked(node, &access.path.scope) {
+            continue;
+        }
+        let message = format!(
+            "`{}` is read outside a lock, but `{}.{}` is written under one",
+            node, access.path.scope, access.path.name
+        );
+        let fixes = wrap_fix(&text, file_id, node, &access.path.scope);
+        res.push(
+            Diagnostic::new(
+                DiagnosticCode::UNLOCKED_SCOPE_READ,
+                node.text_range(),
+                message,
+            )
+            .with_fixes(fixes.into_iter().collect()),
+        );
+    }
+}
+
+/// Wraps the statements intersecting the selection in an exclusive lock on
+/// the shared scope they access, in tag or script syntax to match them.
+pub(crate) fn wrap_in_lock(db: &RootDatabase, frange: FileRange) -> Option<Assist> {
+    let file = db.parse(frange.file_id).tree();
+    let (range, is_script) = selected_statements(file.syntax(), frange.range)?;
+    let scope = accesses(file.syntax())
+        .into_iter()
+        .find(|it| range.contains_range(it.expr.syntax().text_range()))?
+        .path
+        .scope;
+    let text = db.file_text(frange.file_id);
+    Some(Assist {
+        label: format!("Wrap in an exclusive `{scope}` lock"),
+        kind: AssistKind::RefactorRewrite,
+        source_change: SourceChange::from_text_edit(
+            frange.file_id,
+            wrap(&text, range, is_script, &scope),
+        ),
+    })
+}
+
+/// The accesses to shared scopes under `root`, in source order.
+fn accesses(root: &SyntaxNode) -> Vec<Access> {
+    let mut writes: Vec<ast::Expr> = Vec::new();
+    for node in root.descendants() {
+        let target = match node.kind() {
+            ASSIGN_EXPR => ast::AssignExpr::cast(node).and_then(|it| it.target()),
+            PREFIX_EXPR => ast::PrefixExpr::cast(node)
+                .filter(|it| {
+                    it.op_token()
+                        .is_some_and(|op| matches!(op.text(), "++" | "--"))
+                })
+                .and_then(|it| it.operand()),
+            POSTFIX_EXPR => ast::PostfixExpr::cast(node).and_then(|it| it.operand()),
+            CALL_EXPR => ast::CallExpr::cast(node).and_then(|it| mutated_value(&it)),
+            _ => None,
+        };
+        writes.extend(target);
+    }
+    let mut accesses = Vec::new();
+    for node in root.descendants() {
+        let Some(expr) = ast::Expr::cast(node.clone()) else {
+            continue;
+        };
+        if !matches!(expr, ast::Expr::MemberExpr(_) | ast::Expr::IndexExpr(_)) {
+            continue;
+        }
+        let is_write = writes.contains(&expr);
+        let is_outermost = node.parent().is_none_or(|parent| {
+            let receiver = match ast::Expr::cast(parent) {
+                Some(ast::Expr::MemberExpr(it)) => it.receiver(),
+                Some(ast::Expr::IndexExpr(it)) => it.base(),
+                _ => None,
+            };
+            receiver.as_ref() != Some(&expr)
+        });
+        let is_method = node
+            .parent()
+            .and_then(ast::CallExpr::cast)
+            .and_then(|it| it.callee())
+            .is_some_and(|it| it == expr);
+        if !is_write && (!is_outermost || is_method) {
+            continue;
+        }
+        if let Some(path) = SharedPath::of(&expr) {
+            accesses.push(Access {
+                expr,
+                path,
+                is_write,
+            });
+        }
+    }
+    accesses
+}
+
+/// The value a call of a mutating function or method changes.
+fn mutated_value(call: &ast::CallExpr) -> Option<ast::Expr> {
+    let name = called_function(&ast::Expr::CallExpr(call.clone()));
+    if name.is_some_and(|it| MUTATING_FUNCTIONS.contains(&it.as_str())) {
+        return call.args().next().and_then(|arg| match arg {
+            ast::Arg::Positional(it) => Some(it),
+            ast::Arg::Named(it) => it.value(),
+        });
+    }
+    let ast::Expr::MemberExpr(callee) = call.callee()? else {
+        return None;
+    };
+    let method = callee.name_token()?.text().to_ascii_lowercase();
+    MUTATING_METHODS
+        .contains(&method.as_str())
+        .then(|| callee.receiver())
+        .flatten()
+}
+
+/// Whether `node` is in a lock on `scope`, a named lock, or a function
+/// running before `scope` is shared.
+fn is_locked(node: &SyntaxNode, scope: &str) -> bool {
+    let start_function = match scope {
+        "application" => "onapplicationstart",
+        "session" => "onsessionstart",
+        _ => "",
+    };
+    node.ancestors().any(|it| {
+        if let Some(tag) = ast::Tag::cast(it.clone()) {
+            return match tag.name().as_str() {
+                "cflock" => {
+                    tag.attribute("name").is_some()
+                        || tag
+                            .attribute_value("scope")
+                            .is_some_and(|it| it.eq_ignore_ascii_case(scope))
+                }
+                "cffunction" => tag
+                    .attribute_value("name")
+                    .is_some_and(|it| it.eq_ignore_ascii_case(start_function)),
+                _ => false,
+            };
+        }
+        if let Some(stmt) = ast::ScriptTagStmt::cast(it.clone()) {
+            return stmt.name() == "lock"
+                && (stmt.attributes().any(|it| it.name() == "name")
+                    || stmt
+                        .attribute_value("scope")
+                        .is_some_and(|it| it.eq_ignore_ascii_case(scope)));
+        }
+        ast::FunctionDecl::cast(it)
+            .and_then(|it| it.name())
+            .is_some_and(|it| it.eq_ignore_ascii_case(start_function))
+    })
+}
+
+/// Wraps the statement around `node` in a lock on `scope`.
+fn wrap_fix(text: &str, file_id: FileId, node: &SyntaxNode, scope: &str) -> Option<Assist> {
+    let (range, is_script) = selected_statements(node, node.text_range())?;
+    let label = format!("Wrap in an exclusive `{scope}` lock");
+    Some(fix(file_id, label, wrap(text, range, is_script, scope)))
+}
+
+/// The range from the first to the last statement or tag of one block
+/// intersecting `range`, found from `node` up, and whether they are
+/// cfscript statements.
+fn selected_statements(node: &SyntaxNode, range: TextRange) -> Option<(TextRange, bool)> {
+    let element = match node.covering_element(range) {
+        NodeOrToken::Node(it) => it,
+        NodeOrToken::Token(it) => it.parent()?,
+    };
+    for container in element.ancestors() {
+        let (statements, is_script): (Vec<_>, _) = match container.kind() {
+            BLOCK | SCRIPT => (
+                container
+                    .children()
+                    .filter(|it| ast::Stmt::can_cast(it.kind()))
+                    .collect(),
+                true,
+            ),
+            TAG | SOURCE_FILE => (
+                container.children().filter(|it| it.kind() == TAG).collect(),
+                false,
+            ),
+            _ => continue,
+        };
+        let selected: Vec<_> = statements
+            .iter()
+            .map(|it| it.text_range())
+            .filter(|it| match range.is_empty() {
+                true => it.contains_inclusive(range.start()),
+                false => it.intersect(range).is_some_and(|it| !it.is_empty()),
+            })
+            .collect();
+        if let (Some(first), Some(last)) = (selected.first(), selected.last()) {
+            return Some((first.cover(*last), is_script));
+        }
+    }
+    None
+}
+
+/// Wraps the text at `range` in an exclusive lock on `scope`, indented one
+/// level deeper, keeping the line endings of the file.
+fn wrap(text: &str, range: TextRange, is_script: bool, scope: &str) -> TextEdit {
+    let indent = line_indent(text, range.start());
+    let unit = &indent_unit(text);
+    let newline = line_ending(text);
+    let (open, close) = if is_script {
+        (
+            format!("lock scope=\"{scope}\" type=\"exclusive\" timeout=\"10\" {{"),
+            "}",
+        )
+    } else {
+        (
+            format!("<cflock scope=\"{scope}\" type=\"exclusive\" timeout=\"10\">"),
+            "</cflock>",
+        )
+    };
+    let body = text[range]
+        .split('\n')
+        .map(|it| match it.trim().is_empty() {
+            true => it.to_owned(),
+            false => format!("{unit}{it}"),
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    let body = body.strip_prefix(unit).unwrap_or(&body);
+    let wrapped = format!("{open}{newline}{indent}{unit}{body}{newline}{indent}{close}");
+    TextEdit::replace(range, wrapped)
+}
+
+#[cfg(test)]
+mod tests {
+    use virtual_fs::FileId;
+
+    use crate::{fixture, DiagnosticCode, DiagnosticsConfig, FileRange};
+
+    /// The locking diagnostics of the first file: their code and the text
+    /// they cover.
+    fn check(fixture: &str, expect: &[(&str, &str)]) {
+        let analysis = fixture::parse(fixture).analysis;
+        let text = analysis.file_text(FileId(0));
+        let actual: Vec<_> = analysis
+            .diagnostics(&DiagnosticsConfig::default(), FileId(0))
+            .into_iter()
+            .filter(|it| {
+                it.code == DiagnosticCode::UNLOCKED_SCOPE_WRITE
+                    || it.code == DiagnosticCode::UNLOCKED_SCOPE_READ
+            })
+            .map(|it| (it.code.0, &text[it.range]))
+            .collect();
+        assert_eq!(actual, expect);
+    }
+
+    /// The text of the first file after wrapping the statements between
+    /// the first two `$0` markers, or at the only one, in a lock.
+    fn check_assist(fixture: &str, expect: &str) {
+        let (fixture, range) = fixture::selection(fixture);
+        let analysis = fixture::parse(&fixture).analysis;
+        let assist = analysis
+            .assists(FileRange {
+                file_id: FileId(0),
+                range,
+            })
+            .into_iter()
+            .find(|it| it.label.starts_with("Wrap in an exclusive"))
+            .expect("no assist");
+        let mut text = analysis.file_text(FileId(0)).to_string();
+        assist.source_change.source_file_edits[&FileId(0)].apply(&mut text);
+        assert_eq!(text, expect);
+    }
+
+    #[test]
+    fn unlocked_writes() {
+        check(
+            r#"
+//- /Application.cfc
+component {
+    function onApplicationStart() {
+        application.cache = {};
+    }
+
+    function onRequestStart() {
+        session.visits++;
+        application["hits"] = 1;
+        arrayAppend(session.history, cgi.script_name);
+        lock scope="session" type="exclusive" timeout="10" {
+            session.cart.items = [];
+        }
+        lock name="cacheUpdate" type="exclusive" timeout="10" {
+            application.cache.users = [];
+        }
+        var users = application.cache.users;
+        var items = session.cart.items;
+        var total = session.cart;
+        var local = request.x.y;
+    }
+}
+"#,
+            &[
+                ("unlocked-scope-write", "session.visits"),
+                ("unlocked-scope-write", "application[\"hits\"]"),
+                ("unlocked-scope-write", "session.history"),
+                ("unlocked-scope-read", "application.cache.users"),
+                ("unlocked-scope-read", "session.cart.items"),
+            ],
+        );
+    }
+
+    #[test]
+    fn unlocked_tag_writes() {
+        check(
+            r#"
+//- /page.cfm
+<cfset server.started = now()>
+<cflock scope="Server" type="exclusive" timeout="10">
+    <cfset server.count = 1>
+</cflock>
+<cflock scope="application" type="exclusive" timeout="10">
+    <cfset server.other = 1>
+</cflock>
+"#,
+            &[
+                ("unlocked-scope-write", "server.started"),
+                ("unlocked-scope-write", "server.other"),
+            ],
+        );
+    }
+
+    #[test]
+    fn wraps_script_statements() {
+        check_assist(
+            r#"
+//- /a.cfc
+component {
+    function f() {
+        var x = 1;
+        $0session.count = 1;
+        if (x) {
+            session.flag = true;
+        }$0
+    }
+}
+"#,
+            r#"component {
+    function f() {
+        var x = 1;
+        lock scope="session" type="exclusive" timeout="10" {
+            session.count = 1;
+            if (x) {
+                session.flag = true;
+            }
+        }
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn wraps_tags() {
+        check_assist(
+            r#"
+//- /a.cfm
+<cfif x>
+	<cfset application.co$0unt = 1>
+</cfif>
+"#,
+            "<cfif x>\n\t<cflock scope=\"application\" type=\"exclusive\" timeout=\"10\">\n\t\t<cfset application.count = 1>\n\t</cflock>\n</cfif>\n",
+        );
+    }
+}
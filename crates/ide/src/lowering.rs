@@ -0,0 +1,135 @@
+//! Shared machinery of the conversions between tags and cfscript: a writer
+//! of indented lines and the copying of expressions into the other syntax.
+
+use syntax::{SyntaxKind::*, SyntaxNode};
+
+use crate::assists::{indent_unit, line_ending};
+
+/// Writes converted code line by line, indented like the file it goes in.
+pub(crate) struct Writer {
+    pub(crate) buf: String,
+    /// The indentation of the converted code.
+    pub(crate) indent: String,
+    pub(crate) unit: String,
+    pub(crate) newline: &'static str,
+    depth: usize,
+    /// Whether a blank line separates the next line from the previous one.
+    blank_line: bool,
+    /// Whether the next line is the first of a block.
+    at_block_start: bool,
+}
+
+impl Writer {
+    pub(crate) fn new(text: &str, indent: String) -> Writer {
+        Writer {
+            buf: String::new(),
+            indent,
+            unit: indent_unit(text),
+            newline: line_ending(text),
+            depth: 0,
+            blank_line: false,
+            at_block_start: true,
+        }
+    }
+
+    pub(crate) fn line(&mut self, line: &str) {
+        if self.blank_line && !self.at_block_start {
+            self.buf.push_str(self.newline);
+        }
+        self.blank_line = false;
+        self.at_block_start = false;
+        self.buf.push_str(&self.indent);
+        for _ in 0..self.depth {
+            self.buf.push_str(&self.unit);
+        }
+        self.buf.push_str(line);
+        self.buf.push_str(self.newline);
+    }
+
+    /// Indents the following lines one level deeper, starting a block.
+    pub(crate) fn indent(&mut self) {
+        self.depth += 1;
+        self.at_block_start = true;
+    }
+
+    /// Ends a block, dropping a blank line before its end.
+    pub(crate) fn dedent(&mut self) {
+        self.depth -= 1;
+        self.blank_line = false;
+    }
+
+    /// Keeps a blank line from the whitespace `text` between two converted
+    /// statements.
+    pub(crate) fn space(&mut self, text: &str) {
+        if text.matches('\n').count() > 1 {
+            self.blank_line = true;
+        }
+    }
+
+    pub(crate) fn blank_line(&mut self) {
+        self.blank_line = true;
+    }
+
+    /// The indentation of the lines nested in the current one.
+    pub(crate) fn nested_indent(&self) -> String {
+        format!("{}{}", self.indent, self.unit.repeat(self.depth + 1))
+    }
+
+    /// The written code, to replace code at the start of its first line.
+    pub(crate) fn finish(self) -> String {
+        self.buf[self.indent.len()..].trim_end().to_owned()
+    }
+}
+
+/// The syntax an expression is copied into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Syntax {
+    Script,
+    /// Tag expressions, where `>` would end the tag: comparisons are
+    /// written with `eq`, `gt` and the other operator words.
+    Tags,
+}
+
+/// The source of an expression in `syntax`, with the variables in
+/// `renames` renamed.
+pub(crate) fn copy_expr<S: AsRef<str>>(
+    node: &SyntaxNode,
+    renames: &[(S, S)],
+    syntax: Syntax,
+) -> String {
+    let mut buf = String::new();
+    for token in node
+        .descendants_with_tokens()
+        .filter_map(|it| it.into_token())
+    {
+        let is_variable =
+            token.kind() == IDENT && token.parent().is_some_and(|it| it.kind() == NAME_REF);
+        let rename = renames
+            .iter()
+            .find(|(from, _)| is_variable && token.text().eq_ignore_ascii_case(from.as_ref()));
+        let operator = match token.kind() {
+            EQ2 => "eq",
+            NEQ => "neq",
+            GT => "gt",
+            GTEQ => "gte",
+            LT => "lt",
+            LTEQ => "lte",
+            _ => "",
+        };
+        match rename {
+            Some((_, to)) => buf.push_str(to.as_ref()),
+            None if syntax == Syntax::Tags && !operator.is_empty() => {
+                // `a>b` needs spaces around its word: `a gt b`.
+                if !buf.ends_with(char::is_whitespace) {
+                    buf.push(' ');
+                }
+                buf.push_str(operator);
+                if token.next_token().is_none_or(|it| it.kind() != WHITESPACE) {
+                    buf.push(' ');
+                }
+            }
+            None => buf.push_str(token.text()),
+        }
+    }
+    buf
+}
@@ -0,0 +1,318 @@
+//! Code generated from the properties of a component: a constructor taking
+//! all of them, explicit getters and setters, and a `toStruct` memento.
+
+use crate::{
+    assists::{add_members, at_member_level, indent_unit, Assist, AssistKind},
+    db::RootDatabase,
+    docs::Documentation,
+    inheritance::declaration,
+    items::{FunctionInfo, ParamInfo, PropertyInfo},
+    source_change::SourceChange,
+    FileRange,
+};
+
+/// Offers to generate, in the syntax of the component at `frange`, the
+/// functions built from its properties that it does not declare yet.
+pub(crate) fn generate_from_properties(db: &RootDatabase, frange: FileRange) -> Vec<Assist> {
+    let file = db.parse(frange.file_id).tree();
+    let text = db.file_text(frange.file_id);
+    let items = db.items(frange.file_id);
+    let Some(component) = items
+        .component
+        .as_ref()
+        .filter(|it| !it.is_interface && !it.properties.is_empty())
+    else {
+        return Vec::new();
+    };
+    if !at_member_level(&file, component, frange.range) {
+        return Vec::new();
+    }
+
+    let is_script = file.component().is_some();
+    let unit = indent_unit(&text);
+    let properties = &component.properties;
+    let mut res = Vec::new();
+    let mut push = |label: &str, members: Vec<String>| {
+        if members.is_empty() {
+            return;
+        }
+        if let Some(edit) = add_members(&file, &text, &members) {
+            res.push(Assist {
+                label: label.to_owned(),
+                kind: AssistKind::Generate,
+                source_change: SourceChange::from_text_edit(frange.file_id, edit),
+            });
+        }
+    };
+
+    if items.function("init").is_none() {
+        let init = FunctionInfo {
+            name: "init".to_owned(),
+            name_range: component.keyword_range,
+            range: component.range,
+            access: Some("public".to_owned()),
+            return_type: db.component_name(frange.file_id),
+            params: properties
+                .iter()
+                .map(|it| ParamInfo {
+                    name: it.name.clone(),
+                    name_range: it.name_range,
+                    type_name: it.type_name.clone(),
+                    required: true,
+                    default: None,
+                })
+                .collect(),
+            is_static: false,
+            doc: Documentation::default(),
+        };
+        let mut body: Vec<_> = properties.iter().map(|it| assign(it, is_script)).collect();
+        body.push(return_this(is_script));
+        let body = body.join(&format!("\n{unit}"));
+        push(
+            "Generate `init`",
+            vec![declaration(&init, is_script, is_script, &unit, &body)],
+        );
+    }
+
+    let mut accessors = Vec::new();
+    for property in properties {
+        let getter = property.getter();
+        if property.has_getter && items.function(&getter.name).is_none() {
+            let body = match is_script {
+                true => format!("return variables.{};", property.name),
+                false => format!("<cfreturn variables.{}>", property.name),
+            };
+            accessors.push(declaration(&getter, is_script, is_script, &unit, &body));
+        }
+        let setter = property.setter();
+        if property.has_setter && items.function(&setter.name).is_none() {
+            let body = format!(
+                "{}\n{unit}{}",
+                assign(property, is_script),
+                return_this(is_script)
+            );
+            accessors.push(declaration(&setter, is_script, is_script, &unit, &body));
+        }
+    }
+    push("Generate getters and setters", accessors);
+
+    if items.function("toStruct").is_none() {
+        let to_struct = FunctionInfo {
+            name: "toStruct".to_owned(),
+            name_range: component.keyword_range,
+            range: component.range,
+            access: Some("public".to_owned()),
+            return_type: Some("struct".to_owned()),
+            params: Vec::new(),
+            is_static: false,
+            doc: Documentation::default(),
+        };
+        let body = match is_script {
+            true => {
+                let fields: Vec<_> = properties
+                    .iter()
+                    .map(|it| format!("{unit}{unit}\"{0}\": variables.{0}", it.name))
+                    .collect();
+                format!("return {{\n{}\n{unit}}};", fields.join(",\n"))
+            }
+            false => {
+                let mut lines = vec!["<cfset var memento = {}>".to_owned()];
+                lines.extend(
+                    properties
+                        .iter()
+                        .map(|it| format!("<cfset memento[\"{0}\"] = variables.{0}>", it.name)),
+                );
+                lines.push("<cfreturn memento>".to_owned());
+                lines.join(&format!("\n{unit}"))
+            }
+        };
+        push(
+            "Generate `toStruct`",
+            vec![declaration(&to_struct, is_script, is_script, &unit, &body)],
+        );
+    }
+    res
+}
+
+/// The statement copying the argument named after `property` into it.
+fn assign(property: &PropertyInfo, is_script: bool) -> String {
+    match is_script {
+        true => format!("variables.{0} = arguments.{0};", property.name),
+        false => format!("<cfset variables.{0} = arguments.{0}>", property.name),
+    }
+}
+
+fn return_this(is_script: bool) -> String {
+    match is_script {
+        true => "return this;".to_owned(),
+        false => "<cfreturn this>".to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use virtual_fs::FileId;
+
+    use crate::{fixture, FileRange};
+
+    /// The text of the first file after applying the assist `label` at the
+    /// `$0` marker.
+    fn check(label: &str, fixture: &str, expect: &str) {
+        let (fixture, range) = fixture::selection(fixture);
+        let analysis = fixture::parse(&fixture).analysis;
+        let assists = analysis.assists(FileRange {
+            file_id: FileId(0),
+            range,
+        });
+        let Some(assist) = assists.iter().find(|it| it.label == label) else {
+            panic!("no assist `{label}` in {assists:?}");
+        };
+        let mut text = analysis.file_text(FileId(0)).to_string();
+        assist.source_change.source_file_edits[&FileId(0)].apply(&mut text);
+        assert_eq!(text, expect);
+    }
+
+    #[test]
+    fn generates_init() {
+        check(
+            "Generate `init`",
+            r#"
+//- /models/User.cfc
+component$0 accessors="true" {
+    property string name;
+    property numeric age;
+}
+"#,
+            r#"component accessors="true" {
+    property string name;
+    property numeric age;
+
+    public User function init(required string name, required numeric age) {
+        variables.name = arguments.name;
+        variables.age = arguments.age;
+        return this;
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn generates_missing_accessors() {
+        check(
+            "Generate getters and setters",
+            r#"
+//- /User.cfc
+component$0 accessors="true" {
+    property string name;
+    property age;
+
+    function getAge() {
+        return variables.age;
+    }
+}
+"#,
+            r#"component accessors="true" {
+    property string name;
+    property age;
+
+    function getAge() {
+        return variables.age;
+    }
+
+    public string function getName() {
+        return variables.name;
+    }
+
+    public function setName(required string name) {
+        variables.name = arguments.name;
+        return this;
+    }
+
+    public function setAge(required age) {
+        variables.age = arguments.age;
+        return this;
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn skips_disabled_accessors() {
+        check(
+            "Generate getters and setters",
+            r#"
+//- /User.cfc
+<cfcomponent accessors="true">$0
+    <cfproperty name="name" getter="false">
+    <cfproperty name="age" setter="false">
+</cfcomponent>
+"#,
+            r#"<cfcomponent accessors="true">
+    <cfproperty name="name" getter="false">
+    <cfproperty name="age" setter="false">
+
+    <cffunction name="setName" access="public">
+        <cfargument name="name" required="true">
+        <cfset variables.name = arguments.name>
+        <cfreturn this>
+    </cffunction>
+
+    <cffunction name="getAge" access="public">
+        <cfreturn variables.age>
+    </cffunction>
+</cfcomponent>
+"#,
+        );
+    }
+
+    #[test]
+    fn generates_tag_memento() {
+        check(
+            "Generate `toStruct`",
+            r#"
+//- /User.cfc
+<cfcomponent$0 accessors="true">
+    <cfproperty name="name" type="string">
+    <cfproperty name="age">
+</cfcomponent>
+"#,
+            r#"<cfcomponent accessors="true">
+    <cfproperty name="name" type="string">
+    <cfproperty name="age">
+
+    <cffunction name="toStruct" access="public" returntype="struct">
+        <cfset var memento = {}>
+        <cfset memento["name"] = variables.name>
+        <cfset memento["age"] = variables.age>
+        <cfreturn memento>
+    </cffunction>
+</cfcomponent>
+"#,
+        );
+    }
+
+    #[test]
+    fn generates_script_memento() {
+        check(
+            "Generate `toStruct`",
+            r#"
+//- /User.cfc
+component$0 {
+    property name;
+}
+"#,
+            r#"component {
+    property name;
+
+    public struct function toStruct() {
+        return {
+            "name": variables.name
+        };
+    }
+}
+"#,
+        );
+    }
+}
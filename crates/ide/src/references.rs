@@ -0,0 +1,274 @@
+//! Classifies the name under the cursor and finds every reference to it,
+//! across files when the symbol is visible outside its own file.
+
+use syntax::TextRange;
+use virtual_fs::FileId;
+
+use crate::{
+    db::RootDatabase,
+    items::FunctionInfo,
+    usages::{CallSite, Receiver, VarScope},
+    FilePosition,
+};
+
+/// A symbol that references can be resolved to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Definition {
+    /// The component defined by a `.cfc` file.
+    Component(FileId),
+    /// A function of a page or component, by the file that declares it.
+    Function { file_id: FileId, name: String },
+    /// A parameter of the function or closure spanning `function`.
+    Param {
+        file_id: FileId,
+        function: TextRange,
+        name: String,
+    },
+    /// A `local` variable of the function or closure spanning `function`.
+    Local {
+        file_id: FileId,
+        function: TextRange,
+        name: String,
+    },
+    /// A `variables` scope variable of a page or component.
+    Variable { file_id: FileId, name: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Reference {
+    pub(crate) file_id: FileId,
+    pub(crate) range: TextRange,
+}
+
+/// The symbol whose name covers `position`, with the range of that name.
+pub(crate) fn classify(
+    db: &RootDatabase,
+    position: FilePosition,
+) -> Option<(Definition, TextRange)> {
+    let FilePosition { file_id, offset } = position;
+    let usages = db.usages(file_id);
+    let items = db.items(file_id);
+
+    if let Some(var) = usages.var_at(offset) {
+        let name = var.name.clone();
+        let definition = match var.scope {
+            VarScope::Local { owner } => Definition::Local {
+                file_id,
+                function: owner,
+                name,
+            },
+            VarScope::Arguments { owner } => Definition::Param {
+                file_id,
+                function: owner,
+                name,
+            },
+            VarScope::Variables => Definition::Variable { file_id, name },
+            VarScope::This | VarScope::Other(_) => return None,
+        };
+        return Some((definition, var.range));
+    }
+
+    if let Some(call) = usages.call_at(offset) {
+        let (decl_file, function) = resolve_call(db, file_id, call)?;
+        let definition = Definition::Function {
+            file_id: decl_file,
+            name: function.name,
+        };
+        return Some((definition, call.name_range));
+    }
+
+    for call in &usages.calls {
+        let Some((name, range)) = call
+            .args
+            .iter()
+            .filter_map(|it| it.name.as_ref())
+            .find(|(_, range)| range.contains_inclusive(offset))
+        else {
+            continue;
+        };
+        let (decl_file, function) = resolve_call(db, file_id, call)?;
+        let param = function.param(name)?;
+        let definition = Definition::Param {
+            file_id: decl_file,
+            function: function.range,
+            name: param.name.clone(),
+        };
+        return Some((definition, *range));
+    }
+
+    if let Some(function) = items
+        .functions
+        .iter()
+        .find(|it| it.name_range.contains_inclusive(offset))
+    {
+        let definition = Definition::Function {
+            file_id,
+            name: function.name.clone(),
+        };
+        return Some((definition, function.name_range));
+    }
+
+    if let Some(path) = usages.component_ref_at(offset) {
+        let name_range = path.name_range();
+        if offset < name_range.start() {
+            return None;
+        }
+        let target = db.resolve_component(file_id, &path.path)?;
+        return Some((Definition::Component(target), name_range));
+    }
+
+    let component = items.component.as_ref()?;
+    if component.keyword_range.contains_inclusive(offset) && db.component_name(file_id).is_some() {
+        return Some((Definition::Component(file_id), component.keyword_range));
+    }
+    None
+}
+
+/// The function a call resolves to, and the file that declares it.
+pub(crate) fn resolve_call(
+    db: &RootDatabase,
+    file_id: FileId,
+    call: &CallSite,
+) -> Option<(FileId, FunctionInfo)> {
+    match &call.receiver {
+        Receiver::None | Receiver::This => db.find_function(file_id, &call.name),
+        Receiver::Super => db.find_function(db.parent_component(file_id)?, &call.name),
+        Receiver::Component(path) => {
+            db.find_function(db.resolve_component(file_id, path)?, &call.name)
+        }
+        Receiver::Unknown => None,
+    }
+}
+
+/// All references to `definition`, declarations included.
+pub(crate) fn find_references(db: &RootDatabase, definition: &Definition) -> Vec<Reference> {
+    let mut refs = Vec::new();
+    match definition {
+        Definition::Local {
+            file_id,
+            function,
+            name,
+        }
+        | Definition::Param {
+            file_id,
+            function,
+            name,
+        } => {
+            let is_param = matches!(definition, Definition::Param { .. });
+            for var in db.usages(*file_id).vars.iter() {
+                let matches_scope = match var.scope {
+                    VarScope::Local { owner } => !is_param && owner == *function,
+                    VarScope::Arguments { owner } => is_param && owner == *function,
+                    _ => false,
+                };
+                if matches_scope && var.name.eq_ignore_ascii_case(name) {
+                    refs.push(Reference {
+                        file_id: *file_id,
+                        range: var.range,
+                    });
+                }
+            }
+            if is_param {
+                named_arg_refs(db, *file_id, *function, name, &mut refs);
+            }
+        }
+        Definition::Variable { file_id, name } => {
+            for var in db.usages(*file_id).vars.iter() {
+                if var.scope == VarScope::Variables && var.name.eq_ignore_ascii_case(name) {
+                    refs.push(Reference {
+                        file_id: *file_id,
+                        range: var.range,
+                    });
+                }
+            }
+        }
+        Definition::Function { file_id, name } => {
+            if let Some(function) = db.items(*file_id).function(name) {
+                refs.push(Reference {
+                    file_id: *file_id,
+                    range: function.name_range,
+                });
+            }
+            for_each_call_to(db, *file_id, name, |call_file, call| {
+                refs.push(Reference {
+                    file_id: call_file,
+                    range: call.name_range,
+                });
+            });
+        }
+        Definition::Component(target) => {
+            let Some(name) = db.component_name(*target) else {
+                return refs;
+            };
+            for file_id in db.files_containing(&name) {
+                for path in db.usages(file_id).component_refs.iter() {
+                    if db.resolve_component(file_id, &path.path) == Some(*target) {
+                        refs.push(Reference {
+                            file_id,
+                            range: path.name_range(),
+                        });
+                    }
+                }
+            }
+        }
+    }
+    refs
+}
+
+/// `f(name = value)` arguments naming a parameter of the function spanning
+/// `function` in `file_id`.
+fn named_arg_refs(
+    db: &RootDatabase,
+    file_id: FileId,
+    function: TextRange,
+    param: &str,
+    refs: &mut Vec<Reference>,
+) {
+    let items = db.items(file_id);
+    let Some(info) = items.functions.iter().find(|it| it.range == function) else {
+        return;
+    };
+    for_each_call_to(db, file_id, &info.name, |call_file, call| {
+        let names = call.args.iter().filter_map(|it| it.name.as_ref());
+        for (name, range) in names {
+            if name.eq_ignore_ascii_case(param) {
+                refs.push(Reference {
+                    file_id: call_file,
+                    range: *range,
+                });
+            }
+        }
+    });
+}
+
+/// Calls anywhere in the workspace resolving to the function `name` of
+/// `decl_file`.
+pub(crate) fn for_each_call_to(
+    db: &RootDatabase,
+    decl_file: FileId,
+    name: &str,
+    mut f: impl FnMut(FileId, &CallSite),
+) {
+    for file_id in db.files_containing(name) {
+        for call in db.usages(file_id).calls.iter() {
+            if !call.name.eq_ignore_ascii_case(name) {
+                continue;
+            }
+            let resolved = resolve_call(db, file_id, call);
+            if resolved.is_some_and(|(file, function)| {
+                file == decl_file && function.name.eq_ignore_ascii_case(name)
+            }) {
+                f(file_id, call);
+            }
+        }
+    }
+}
+
+/// The name of the function or closure spanning `range`, for messages.
+pub(crate) fn function_name(db: &RootDatabase, file_id: FileId, range: TextRange) -> String {
+    let items = db.items(file_id);
+    match items.functions.iter().find(|it| it.range == range) {
+        Some(function) => format!("`{}`", function.name),
+        None => "this closure".to_owned(),
+    }
+}
@@ -0,0 +1,482 @@
+//! Renaming of functions, parameters, variables and components.
+//!
+//! Renaming a component renames its `.cfc` file and the last segment of
+//! every dotted path that resolves to it.
+
+use std::fmt;
+
+use syntax::{SyntaxKind, TextRange};
+use virtual_fs::AnchoredPathBuf;
+
+use crate::{
+    db::RootDatabase,
+    references::{classify, find_references, function_name, Definition},
+    source_change::{FileSystemEdit, SourceChange},
+    text_edit::TextEdit,
+    usages::{is_identifier, is_scope_name, VarScope},
+    FilePosition,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenameError(pub String);
+
+impl fmt::Display for RenameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl std::error::Error for RenameError {}
+
+macro_rules! format_err {
+    ($($arg:tt)*) => {
+        RenameError(format!($($arg)*))
+    };
+}
+
+macro_rules! bail {
+    ($($arg:tt)*) => {
+        return Err(format_err!($($arg)*))
+    };
+}
+
+/// The range of the name to rename and its current text.
+pub(crate) fn prepare_rename(
+    db: &RootDatabase,
+    position: FilePosition,
+) -> Result<(TextRange, String), RenameError> {
+    let (definition, range) = classify(db, position).ok_or_else(no_symbol)?;
+    let placeholder = match &definition {
+        Definition::Component(file_id) => db.component_name(*file_id).unwrap_or_default(),
+        _ => db.file_text(position.file_id)[range].to_owned(),
+    };
+    Ok((range, placeholder))
+}
+
+pub(crate) fn rename(
+    db: &RootDatabase,
+    position: FilePosition,
+    new_name: &str,
+) -> Result<SourceChange, RenameError> {
+    let (definition, _) = classify(db, position).ok_or_else(no_symbol)?;
+    if let Definition::Function { file_id, name } = &definition {
+        if db.items(*file_id).function(name).is_none() {
+            bail!("`{name}` is an implicit accessor: rename its property instead");
+        }
+    }
+    check_identifier(new_name)?;
+    check_collision(db, &definition, new_name)?;
+
+    let mut edits: Vec<_> = find_references(db, &definition)
+        .into_iter()
+        .map(|it| (it.file_id, it.range))
+        .collect();
+    edits.sort_by_key(|&(file_id, range)| (file_id, range.start()));
+    edits.dedup();
+
+    let mut change = SourceChange::default();
+    for (file_id, ranges) in group_by_file(&edits) {
+        let mut builder = TextEdit::builder();
+        for range in ranges {
+            builder.replace(range, new_name.to_owned());
+        }
+        change.insert_source_edit(file_id, builder.finish());
+    }
+
+    if let Definition::Component(file_id) = definition {
+        change.push_file_system_edit(FileSystemEdit::MoveFile {
+            src: file_id,
+            dst: AnchoredPathBuf {
+                anchor: file_id,
+                path: format!("{new_name}.cfc"),
+            },
+        });
+    }
+    Ok(change)
+}
+
+fn no_symbol() -> RenameError {
+    format_err!("No references found at position")
+}
+
+fn check_identifier(new_name: &str) -> Result<(), RenameError> {
+    if !is_identifier(new_name) {
+        bail!("Invalid name `{new_name}`: not an identifier");
+    }
+    if is_scope_name(new_name) || SyntaxKind::from_keyword(new_name).is_some() {
+        bail!("Invalid name `{new_name}`: reserved word");
+    }
+    Ok(())
+}
+
+/// Refuses renames that would make two symbols of the same scope share a
+/// name. Changing only the case of a name is always allowed.
+fn check_collision(
+    db: &RootDatabase,
+    definition: &Definition,
+    new_name: &str,
+) -> Result<(), RenameError> {
+    match definition {
+        Definition::Function { file_id, name } => {
+            if name.eq_ignore_ascii_case(new_name) {
+                return Ok(());
+            }
+            if db.items(*file_id).function(new_name).is_some() {
+                let owner = db
+                    .component_name(*file_id)
+                    .unwrap_or_else(|| "this file".to_owned());
+                bail!("A function named `{new_name}` already exists in `{owner}`");
+            }
+        }
+        Definition::Param {
+            file_id,
+            function,
+            name,
+        }
+        | Definition::Local {
+            file_id,
+            function,
+            name,
+        } => {
+            if name.eq_ignore_ascii_case(new_name) {
+                return Ok(());
+            }
+            let collides = db.usages(*file_id).vars.iter().any(|var| {
+                let in_function = match var.scope {
+                    VarScope::Local { owner } | VarScope::Arguments { owner } => owner == *function,
+                    _ => false,
+                };
+                in_function && var.name.eq_ignore_ascii_case(new_name)
+            });
+            if collides {
+                let function = function_name(db, *file_id, *function);
+                bail!("`{new_name}` is already declared in {function}");
+            }
+        }
+        Definition::Variable { file_id, name } => {
+            if name.eq_ignore_ascii_case(new_name) {
+                return Ok(());
+            }
+            let usages = db.usages(*file_id);
+            let collides = usages.vars.iter().any(|var| {
+                var.scope == VarScope::Variables && var.name.eq_ignore_ascii_case(new_name)
+            });
+            if collides || db.items(*file_id).function(new_name).is_some() {
+                bail!("`{new_name}` already exists in the `variables` scope");
+            }
+        }
+        Definition::Component(file_id) => {
+            let Some(path) = db.file_path(*file_id) else {
+                return Ok(());
+            };
+            let same_name = db
+                .component_name(*file_id)
+                .is_some_and(|it| it.eq_ignore_ascii_case(new_name));
+            let target = path
+                .parent()
+                .and_then(|it| it.join(&format!("{new_name}.cfc")));
+            if !same_name && target.is_some_and(|it| db.file_exists(&it)) {
+                bail!("A component named `{new_name}` already exists in this directory");
+            }
+        }
+    }
+    Ok(())
+}
+
+fn group_by_file(
+    edits: &[(virtual_fs::FileId, TextRange)],
+) -> Vec<(virtual_fs::FileId, Vec<TextRange>)> {
+    let mut groups: Vec<(virtual_fs::FileId, Vec<TextRange>)> = Vec::new();
+    for &(file_id, range) in edits {
+        match groups.last_mut() {
+            Some((last, ranges)) if *last == file_id => ranges.push(range),
+            _ => groups.push((file_id, vec![range])),
+        }
+    }
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixture;
+
+    fn check(new_name: &str, before: &str, after: &str) {
+        let (analysis, position) = fixture::position(before);
+        let change = analysis.rename(position, new_name).unwrap();
+        assert_eq!(
+            fixture::render_change(&analysis, &change).trim(),
+            after.trim()
+        );
+    }
+
+    fn check_err(new_name: &str, before: &str, message: &str) {
+        let (analysis, position) = fixture::position(before);
+        let err = analysis.rename(position, new_name).unwrap_err();
+        assert_eq!(err.to_string(), message);
+    }
+
+    #[test]
+    fn renames_local_variable() {
+        check(
+            "total",
+            r#"
+//- /Cart.cfc
+component {
+    function sum(items) {
+        var $0t = 0;
+        for (var item in items) { local.t += item; }
+        return t;
+    }
+    function other() { return t; }
+}
+"#,
+            r#"
+//- /Cart.cfc
+component {
+    function sum(items) {
+        var total = 0;
+        for (var item in items) { local.total += item; }
+        return total;
+    }
+    function other() { return t; }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn renames_parameter_with_named_arguments() {
+        check(
+            "recipient",
+            r#"
+//- /services/Mailer.cfc
+component {
+    function send(required string $0to, string body = "") {
+        return arguments.to & to;
+    }
+    function resend() { send(to = "a", body = "b"); }
+}
+//- /app.cfm
+<cfscript>
+mailer = new services.Mailer();
+mailer.send(to: "x");
+other.send(to: "y");
+</cfscript>
+"#,
+            r#"
+//- /app.cfm
+<cfscript>
+mailer = new services.Mailer();
+mailer.send(recipient: "x");
+other.send(to: "y");
+</cfscript>
+//- /services/Mailer.cfc
+component {
+    function send(required string recipient, string body = "") {
+        return arguments.recipient & recipient;
+    }
+    function resend() { send(recipient = "a", body = "b"); }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn renames_tag_argument_from_call_site() {
+        check(
+            "userId",
+            r#"
+//- /Users.cfc
+<cfcomponent>
+<cffunction name="load">
+    <cfargument name="id" required="true">
+    <cfreturn arguments.id>
+</cffunction>
+<cffunction name="caller">
+    <cfreturn load($0id = 1)>
+</cffunction>
+</cfcomponent>
+"#,
+            r#"
+//- /Users.cfc
+<cfcomponent>
+<cffunction name="load">
+    <cfargument name="userId" required="true">
+    <cfreturn arguments.userId>
+</cffunction>
+<cffunction name="caller">
+    <cfreturn load(userId = 1)>
+</cffunction>
+</cfcomponent>
+"#,
+        );
+    }
+
+    #[test]
+    fn renames_function_across_files_and_inheritance() {
+        check(
+            "persist",
+            r#"
+//- /models/Base.cfc
+component {
+    function $0save() {}
+    function init() { save(); this.save(); return this; }
+}
+//- /models/User.cfc
+component extends="Base" {
+    function update() { super.save(); variables.save(); }
+}
+//- /index.cfm
+<cfset user = createObject("component", "models.User")>
+<cfset user.save()>
+<cfinvoke component="models.Base" method="save">
+"#,
+            r#"
+//- /index.cfm
+<cfset user = createObject("component", "models.User")>
+<cfset user.persist()>
+<cfinvoke component="models.Base" method="persist">
+//- /models/Base.cfc
+component {
+    function persist() {}
+    function init() { persist(); this.persist(); return this; }
+}
+//- /models/User.cfc
+component extends="Base" {
+    function update() { super.persist(); variables.persist(); }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn renames_component_and_paths() {
+        check(
+            "Customer",
+            r#"
+//- /models/User.cfc
+component {
+    function clone() { return new User(); }
+}
+//- /models/Admin.cfc
+component extends="models.User" implements="IAdmin" {}
+//- /index.cfm
+<cfscript>
+u = new models.$0User();
+function f(models.User u) {}
+o = createObject("component", "models.User");
+</cfscript>
+"#,
+            r#"
+//- /index.cfm
+<cfscript>
+u = new models.Customer();
+function f(models.Customer u) {}
+o = createObject("component", "models.Customer");
+</cfscript>
+//- /models/Admin.cfc
+component extends="models.Customer" implements="IAdmin" {}
+//- /models/User.cfc
+component {
+    function clone() { return new Customer(); }
+}
+move /models/User.cfc -> Customer.cfc
+"#,
+        );
+    }
+
+    #[test]
+    fn renames_component_from_keyword() {
+        check(
+            "Thing",
+            r#"
+//- /Widget.cfc
+$0component {}
+//- /a.cfm
+<cfset w = new Widget()>
+"#,
+            r#"
+//- /a.cfm
+<cfset w = new Thing()>
+move /Widget.cfc -> Thing.cfc
+"#,
+        );
+    }
+
+    #[test]
+    fn refuses_collisions() {
+        check_err(
+            "b",
+            r#"
+//- /A.cfc
+component {
+    function $0a() {}
+    function b() {}
+}
+"#,
+            "A function named `b` already exists in `A`",
+        );
+        check_err(
+            "y",
+            "//- /A.cfc\ncomponent { function f($0x, y) {} }",
+            "`y` is already declared in `f`",
+        );
+        check_err(
+            "y",
+            "//- /a.cfm\n<cfscript>f = function() { var $0x = 1; var y = 2; };</cfscript>",
+            "`y` is already declared in this closure",
+        );
+        check_err(
+            "g",
+            "//- /a.cfm\n<cfset $0x = 1><cffunction name=\"g\"></cffunction>",
+            "`g` already exists in the `variables` scope",
+        );
+        check_err(
+            "B",
+            "//- /A.cfc\n$0component {}\n//- /B.cfc\ncomponent {}",
+            "A component named `B` already exists in this directory",
+        );
+    }
+
+    #[test]
+    fn refuses_invalid_names_and_positions() {
+        check_err(
+            "1x",
+            "//- /a.cfm\n<cfset $0x = 1>",
+            "Invalid name `1x`: not an identifier",
+        );
+        check_err(
+            "local",
+            "//- /a.cfm\n<cfset $0x = 1>",
+            "Invalid name `local`: reserved word",
+        );
+        check_err(
+            "y",
+            "//- /a.cfm\n<cfset x = $01>",
+            "No references found at position",
+        );
+        check_err(
+            "y",
+            "//- /a.cfm\n<cfset x = writeOutput$0(1)>",
+            "No references found at position",
+        );
+        check_err(
+            "fetchName",
+            "//- /A.cfc\ncomponent accessors=true { property name; function f() { get$0Name(); } }",
+            "`getName` is an implicit accessor: rename its property instead",
+        );
+    }
+
+    #[test]
+    fn prepares_rename() {
+        let (analysis, position) = fixture::position("//- /Widget.cfc\ncomp$0onent {}");
+        let (range, placeholder) = analysis.prepare_rename(position).unwrap();
+        assert_eq!((u32::from(range.start()), u32::from(range.end())), (0, 9));
+        assert_eq!(placeholder, "Widget");
+
+        let (analysis, position) =
+            fixture::position("//- /a.cfm\n<cfset abc = 1><cfoutput>#a$0bc#</cfoutput>");
+        let (_, placeholder) = analysis.prepare_rename(position).unwrap();
+        assert_eq!(placeholder, "abc");
+    }
+}
@@ -0,0 +1,1344 @@
+//! Converting a `<cfscript>` block to tags, the reverse of
+//! [`tag_to_script`](crate::tag_to_script): `if` becomes `<cfif>`,
+//! `queryExecute()` becomes `<cfquery>`, docblocks become `hint`s, and
+//! whatever else has no tag of its own becomes `<cfset>`.
+//!
+//! Some statements have no tag equivalent, like closures or `do`/`while`
+//! loops. They are reported as hints and keep the block from being
+//! converted.
+
+use syntax::{
+    ast::{self, AstNode},
+    NodeOrToken,
+    SyntaxKind::*,
+    SyntaxNode, SyntaxToken, TextRange,
+};
+
+use crate::{
+    assists::{line_indent, Assist, AssistKind},
+    builtin_tags::builtin_tag,
+    db::RootDatabase,
+    diagnostics::{Diagnostic, DiagnosticCode},
+    docs::{docblock, parse_docblock},
+    lowering::{copy_expr, Syntax, Writer},
+    source_change::SourceChange,
+    taint::called_function,
+    text_edit::TextEdit,
+    FileRange,
+};
+
+/// Converts the `<cfscript>` block at `frange`.
+pub(crate) fn convert_to_tags(db: &RootDatabase, frange: FileRange) -> Option<Assist> {
+    let file = db.parse(frange.file_id).tree();
+    let text = db.file_text(frange.file_id);
+    let node = match file.syntax().covering_element(frange.range) {
+        NodeOrToken::Node(it) => it,
+        NodeOrToken::Token(it) => it.parent()?,
+    };
+    let tag = node
+        .ancestors()
+        .filter_map(ast::Tag::cast)
+        .find(|it| it.name() == "cfscript")?;
+    let script = tag.script()?;
+    tag.end_tag()?;
+
+    let range = tag.syntax().text_range();
+    let mut lowerer = Lowerer::new(&text, script.syntax());
+    lowerer.w.indent = line_indent(&text, range.start()).to_owned();
+    lowerer.block(script.syntax());
+    if !lowerer.problems.is_empty() {
+        return None;
+    }
+    Some(Assist {
+        label: "Convert cfscript to tags".to_owned(),
+        kind: AssistKind::RefactorRewrite,
+        source_change: SourceChange::from_text_edit(
+            frange.file_id,
+            TextEdit::replace(range, lowerer.w.finish()),
+        ),
+    })
+}
+
+/// Reports the statements of `<cfscript>` blocks without a tag equivalent.
+pub(crate) fn no_tag_equivalent(file: &ast::SourceFile, res: &mut Vec<Diagnostic>) {
+    let scripts = file
+        .syntax()
+        .descendants()
+        .filter_map(ast::Tag::cast)
+        .filter(|it| it.name() == "cfscript")
+        .filter_map(|it| it.script());
+    for script in scripts {
+        let mut lowerer = Lowerer::new("", script.syntax());
+        lowerer.block(script.syntax());
+        for (range, problem) in lowerer.problems {
+            if problem.is_empty() {
+                continue;
+            }
+            let message =
+                format!("{problem}, so this `<cfscript>` block cannot be converted to tags");
+            res.push(Diagnostic::new(
+                DiagnosticCode::NO_TAG_EQUIVALENT,
+                range,
+                message,
+            ));
+        }
+    }
+}
+
+/// What a `for (x in y)` loop iterates over.
+enum Collection {
+    Array,
+    Struct,
+    /// `listToArray(list, delimiters)`.
+    List(ast::CallExpr),
+}
+
+struct Lowerer {
+    w: Writer,
+    script: SyntaxNode,
+    /// Variables renamed in converted expressions, like the exception
+    /// variable of a `catch` block to `cfcatch`.
+    renames: Vec<(String, String)>,
+    /// The statements without a tag equivalent and why, or an empty
+    /// message for syntax errors.
+    problems: Vec<(TextRange, String)>,
+}
+
+impl Lowerer {
+    fn new(text: &str, script: &SyntaxNode) -> Lowerer {
+        Lowerer {
+            w: Writer::new(text, String::new()),
+            script: script.clone(),
+            renames: Vec::new(),
+            problems: Vec::new(),
+        }
+    }
+
+    fn problem(&mut self, node: &SyntaxNode, message: &str) {
+        self.problems.push((node.text_range(), message.to_owned()));
+    }
+
+    fn open(&mut self, tag: &str) {
+        self.w.line(tag);
+        self.w.indent();
+    }
+
+    fn close(&mut self, tag: &str) {
+        self.w.dedent();
+        self.w.line(tag);
+    }
+
+    /// An expression in tag syntax.
+    fn expr(&mut self, expr: &SyntaxNode) -> String {
+        let closures = expr
+            .descendants()
+            .filter(|it| matches!(it.kind(), CLOSURE_EXPR | ARROW_EXPR))
+            .filter(|it| {
+                it.ancestors()
+                    .skip(1)
+                    .all(|it| !matches!(it.kind(), CLOSURE_EXPR | ARROW_EXPR))
+            });
+        for closure in closures.collect::<Vec<_>>() {
+            self.problem(&closure, "closures have no tag equivalent");
+        }
+        copy_expr(expr, &self.renames, Syntax::Tags)
+            .trim()
+            .to_owned()
+    }
+
+    /// An expression as a quoted attribute value: `"text"` or `"#expr#"`.
+    fn attribute(&mut self, expr: &ast::Expr) -> String {
+        match expr {
+            ast::Expr::StringLit(it) => self.expr(it.syntax()),
+            ast::Expr::Literal(it) => format!("\"{}\"", it.syntax().text()),
+            _ => format!("\"#{}#\"", self.expr(expr.syntax())),
+        }
+    }
+
+    fn comment(&mut self, token: &SyntaxToken) {
+        let text = token.text();
+        let text = match token.kind() {
+            LINE_COMMENT => text.trim_start_matches('/'),
+            _ => text.trim_start_matches("/*").trim_end_matches("*/"),
+        };
+        let text = text.trim().replace("--->", "-- ->");
+        if !text.contains('\n') {
+            self.w.line(format!("<!--- {text} --->").trim_end());
+            return;
+        }
+        self.w.line("<!---");
+        self.w.indent();
+        for line in text.lines() {
+            let line = line.trim();
+            self.w.line(line.strip_prefix('*').unwrap_or(line).trim());
+        }
+        self.w.dedent();
+        self.w.line("--->");
+    }
+
+    /// Converts the statements and comments of a script or block.
+    fn block(&mut self, node: &SyntaxNode) {
+        for element in node.children_with_tokens() {
+            match element {
+                NodeOrToken::Token(it) => match it.kind() {
+                    WHITESPACE => self.w.space(it.text()),
+                    BLOCK_COMMENT if it.text().starts_with("/**") && precedes_function(&it) => (),
+                    LINE_COMMENT | BLOCK_COMMENT => self.comment(&it),
+                    L_CURLY | R_CURLY => (),
+                    _ => self.problems.push((it.text_range(), String::new())),
+                },
+                NodeOrToken::Node(it) if it.kind() == FUNCTION_DECL => self.function(&it),
+                NodeOrToken::Node(it) => self.stmt(&it),
+            }
+        }
+    }
+
+    fn stmt(&mut self, node: &SyntaxNode) {
+        match node.kind() {
+            BLOCK => self.block(node),
+            EMPTY_STMT => (),
+            VAR_DECL => {
+                let initializer = ast::VarDecl::cast(node.clone()).and_then(|it| it.initializer());
+                if let Some(ast::Expr::CallExpr(call)) = &initializer {
+                    if let Some(name) = node.children_with_tokens().find(|it| it.kind() == IDENT) {
+                        let name = name.to_string();
+                        if self.query(&name, call) {
+                            return;
+                        }
+                    }
+                }
+                let decl = self.expr(node);
+                self.w.line(&format!(
+                    "<cfset {}>",
+                    decl.trim_end_matches(';').trim_end()
+                ));
+            }
+            EXPR_STMT => match node.children().find_map(ast::Expr::cast) {
+                Some(expr) => self.expr_stmt(&expr),
+                None => self.problems.push((node.text_range(), String::new())),
+            },
+            RETURN_STMT => match node.children().find_map(ast::Expr::cast) {
+                Some(expr) => {
+                    let expr = self.expr(expr.syntax());
+                    self.w.line(&format!("<cfreturn {expr}>"));
+                }
+                None => self.w.line("<cfreturn>"),
+            },
+            BREAK_STMT => self.w.line("<cfbreak>"),
+            CONTINUE_STMT => self.w.line("<cfcontinue>"),
+            IF_STMT => {
+                self.if_stmt(node, "<cfif");
+                self.w.line("</cfif>");
+            }
+            FOR_STMT => self.for_stmt(node),
+            FOR_IN_STMT => self.for_in_stmt(node),
+            WHILE_STMT => {
+                let condition = node.children().find_map(ast::Expr::cast);
+                let condition = condition.map_or_else(String::new, |it| self.expr(it.syntax()));
+                self.open(&format!("<cfloop condition={}>", quote(&condition)));
+                self.body(node);
+                self.close("</cfloop>");
+            }
+            DO_WHILE_STMT => self.problem(node, "`do`/`while` loops have no tag equivalent"),
+            SWITCH_STMT => self.switch_stmt(node),
+            TRY_STMT => self.try_stmt(node),
+            SCRIPT_TAG_STMT => self.script_tag(node),
+            FUNCTION_DECL => self.function(node),
+            PROPERTY_DECL | COMPONENT | IMPORT_STMT => {
+                self.problem(node, "declarations have no tag equivalent in `<cfscript>`");
+            }
+            _ => self.problems.push((node.text_range(), String::new())),
+        }
+    }
+
+    /// Converts the statement nested in `node`, its body.
+    fn body(&mut self, node: &SyntaxNode) {
+        if let Some(body) = node
+            .children()
+            .filter(|it| ast::Stmt::can_cast(it.kind()))
+            .last()
+        {
+            self.stmt(&body);
+        }
+    }
+
+    fn expr_stmt(&mut self, expr: &ast::Expr) {
+        let handled = match expr {
+            ast::Expr::CallExpr(call) => self.call(call),
+            ast::Expr::AssignExpr(assign) => match (assign.target(), assign.value()) {
+                (Some(target), Some(ast::Expr::CallExpr(call)))
+                    if assign.op_token().is_some_and(|it| it.kind() == EQ) =>
+                {
+                    let target = self.expr(target.syntax());
+                    self.query(&target, &call)
+                }
+                _ => false,
+            },
+            _ => false,
+        };
+        if !handled {
+            let expr = self.expr(expr.syntax());
+            self.w.line(&format!("<cfset {expr}>"));
+        }
+    }
+
+    /// Converts a call of a function with a tag equivalent, returning
+    /// whether it has one.
+    fn call(&mut self, call: &ast::CallExpr) -> bool {
+        let Some(function) = called_function(&ast::Expr::CallExpr(call.clone())) else {
+            return false;
+        };
+        let args: Vec<_> = call.args().collect();
+        if function == "queryexecute" {
+            return self.query("", call);
+        }
+        if function == "writeoutput" {
+            let [ast::Arg::Positional(value)] = &args[..] else {
+                return false;
+            };
+            let output = match value {
+                ast::Expr::StringLit(it) => {
+                    let text = self.expr(it.syntax());
+                    let quote = &text[..1];
+                    text[1..text.len() - 1].replace(&quote.repeat(2), quote)
+                }
+                _ => format!("#{}#", self.expr(value.syntax())),
+            };
+            self.w.line(&format!("<cfoutput>{output}</cfoutput>"));
+            return true;
+        }
+        let (tag, positional) = match function.as_str() {
+            "throw" => ("cfthrow", "message"),
+            "writedump" => ("cfdump", "var"),
+            "writelog" => ("cflog", "text"),
+            "location" => ("cflocation", "url"),
+            _ if function.starts_with("cf") && builtin_tag(&function).is_some() => {
+                (function.as_str(), "")
+            }
+            _ => return false,
+        };
+        let mut attributes = Vec::new();
+        for (i, arg) in args.iter().enumerate() {
+            let (name, value) = match arg {
+                ast::Arg::Named(it) => match (it.name_token(), it.value()) {
+                    (Some(name), Some(value)) => (name.text().to_owned(), value),
+                    _ => return false,
+                },
+                ast::Arg::Positional(it) if i == 0 && args.len() == 1 && !positional.is_empty() => {
+                    (positional.to_owned(), it.clone())
+                }
+                ast::Arg::Positional(_) => return false,
+            };
+            attributes.push(format!("{name}={}", self.attribute(&value)));
+        }
+        self.w.line(&start_tag(tag, &attributes));
+        true
+    }
+
+    /// Converts `target = queryExecute(sql, params, options)` to
+    /// `<cfquery>`, if its SQL is a literal and its parameters and options
+    /// are literal structs or arrays. Returns whether it was converted.
+    fn query(&mut self, target: &str, call: &ast::CallExpr) -> bool {
+        if called_function(&ast::Expr::CallExpr(call.clone())).as_deref() != Some("queryexecute") {
+            return false;
+        }
+        let args: Vec<_> = call.args().collect();
+        let args: Option<Vec<_>> = args
+            .iter()
+            .map(|it| match it {
+                ast::Arg::Positional(it) => Some(it.clone()),
+                ast::Arg::Named(_) => None,
+            })
+            .collect();
+        let (sql, params, options) = match args.as_deref() {
+            Some([ast::Expr::StringLit(sql)]) => (sql, None, None),
+            Some([ast::Expr::StringLit(sql), params]) => (sql, Some(params), None),
+            Some([ast::Expr::StringLit(sql), params, ast::Expr::StructLit(options)]) => {
+                (sql, Some(params), Some(options))
+            }
+            _ => return false,
+        };
+        let params: Vec<(String, ast::Expr)> = match params {
+            None => Vec::new(),
+            Some(ast::Expr::StructLit(it)) => {
+                match it
+                    .entries()
+                    .map(|it| Some((entry_key(&it)?, it.value()?)))
+                    .collect()
+                {
+                    Some(it) => it,
+                    None => return false,
+                }
+            }
+            Some(ast::Expr::ArrayLit(it)) => it.elements().map(|it| (String::new(), it)).collect(),
+            Some(_) => return false,
+        };
+        let mut query_params = Vec::new();
+        for (name, value) in params {
+            let attributes = match &value {
+                ast::Expr::StructLit(it) => {
+                    let mut attributes = Vec::new();
+                    for entry in it.entries() {
+                        let (Some(key), Some(value)) = (entry_key(&entry), entry.value()) else {
+                            return false;
+                        };
+                        attributes.push(format!("{key}={}", self.attribute(&value)));
+                    }
+                    attributes
+                }
+                _ => vec![format!("value={}", self.attribute(&value))],
+            };
+            query_params.push((name, start_tag("cfqueryparam", &attributes)));
+        }
+
+        let mut attributes = Vec::new();
+        if !target.is_empty() {
+            attributes.push(format!("name={}", quote(target)));
+        }
+        for entry in options.into_iter().flat_map(|it| it.entries()) {
+            let (Some(key), Some(value)) = (entry_key(&entry), entry.value()) else {
+                return false;
+            };
+            attributes.push(format!("{key}={}", self.attribute(&value)));
+        }
+        let Some(sql) = with_params(&self.expr(sql.syntax()), &query_params) else {
+            return false;
+        };
+        let sql = sql.trim();
+        let start = start_tag("cfquery", &attributes);
+        if !sql.contains('\n') {
+            self.w.line(&format!("{start}{sql}</cfquery>"));
+            return true;
+        }
+        self.open(&start);
+        let lines: Vec<_> = sql.lines().collect();
+        let common = lines[1..]
+            .iter()
+            .filter(|it| !it.trim().is_empty())
+            .map(|it| it.len() - it.trim_start().len())
+            .min()
+            .unwrap_or(0);
+        self.w.line(lines[0]);
+        for line in &lines[1..] {
+            match line.trim().is_empty() {
+                true => self.w.blank_line(),
+                false => self.w.line(line[common..].trim_end()),
+            }
+        }
+        self.close("</cfquery>");
+        true
+    }
+
+    /// Converts an `if` statement, with its `else if` branches as
+    /// `<cfelseif>`, but without the end tag.
+    fn if_stmt(&mut self, node: &SyntaxNode, tag: &str) {
+        let condition = node.children().find_map(ast::Expr::cast);
+        let condition = condition.map_or_else(String::new, |it| self.expr(it.syntax()));
+        if tag != "<cfif" {
+            self.w.dedent();
+        }
+        self.open(&format!("{tag} {condition}>"));
+        self.body(node);
+        let Some(branch) = node.children().find(|it| it.kind() == ELSE_BRANCH) else {
+            self.w.dedent();
+            return;
+        };
+        match branch.children().find(|it| ast::Stmt::can_cast(it.kind())) {
+            Some(it) if it.kind() == IF_STMT => self.if_stmt(&it, "<cfelseif"),
+            body => {
+                self.w.dedent();
+                self.open("<cfelse>");
+                if let Some(body) = body {
+                    self.stmt(&body);
+                }
+                self.w.dedent();
+            }
+        }
+    }
+
+    fn for_stmt(&mut self, node: &SyntaxNode) {
+        let mut parts: [Option<SyntaxNode>; 3] = Default::default();
+        let mut part = 0;
+        for element in node.children_with_tokens() {
+            match element {
+                NodeOrToken::Token(it) if it.kind() == SEMICOLON => part += 1,
+                NodeOrToken::Token(it) if it.kind() == R_PAREN => break,
+                NodeOrToken::Node(it) if part < 3 => parts[part] = Some(it),
+                _ => (),
+            }
+        }
+        let [init, condition, step] = parts;
+        if let Some(head) = self.counting_loop(&init, &condition, &step) {
+            self.open(&head);
+            self.body(node);
+            self.close("</cfloop>");
+            return;
+        }
+
+        let continues = node.descendants().filter(|it| it.kind() == CONTINUE_STMT);
+        let continues = continues.filter(|it| {
+            it.ancestors()
+                .find(|it| {
+                    matches!(
+                        it.kind(),
+                        FOR_STMT | FOR_IN_STMT | WHILE_STMT | DO_WHILE_STMT
+                    )
+                })
+                .as_ref()
+                == Some(node)
+        });
+        if let Some(it) = continues.last() {
+            self.problem(&it, "`continue` in this `for` loop has no tag equivalent");
+        }
+        if let Some(init) = init {
+            let init = self.expr(&init);
+            self.w.line(&format!("<cfset {init}>"));
+        }
+        let condition = condition.map_or_else(|| "true".to_owned(), |it| self.expr(&it));
+        self.open(&format!("<cfloop condition={}>", quote(&condition)));
+        self.body(node);
+        if let Some(step) = step {
+            let step = self.expr(&step);
+            self.w.line(&format!("<cfset {step}>"));
+        }
+        self.close("</cfloop>");
+    }
+
+    /// The start tag of `<cfloop index from to>` for a loop counting a
+    /// variable up or down.
+    fn counting_loop(
+        &mut self,
+        init: &Option<SyntaxNode>,
+        condition: &Option<SyntaxNode>,
+        step: &Option<SyntaxNode>,
+    ) -> Option<String> {
+        let (index, from, is_var) = match init.clone()? {
+            it if it.kind() == VAR_DECL => {
+                let decl = ast::VarDecl::cast(it)?;
+                (
+                    decl.name_token()?.text().to_owned(),
+                    decl.initializer()?,
+                    true,
+                )
+            }
+            it => match ast::Expr::cast(it)? {
+                ast::Expr::AssignExpr(it) if it.op_token()?.kind() == EQ => {
+                    let Some(ast::Expr::NameRef(target)) = it.target() else {
+                        return None;
+                    };
+                    (target.name(), it.value()?, false)
+                }
+                _ => return None,
+            },
+        };
+        let is_index = |expr: Option<ast::Expr>| matches!(expr, Some(ast::Expr::NameRef(it)) if it.name().eq_ignore_ascii_case(&index));
+
+        let ast::Expr::BinExpr(condition) = ast::Expr::cast(condition.clone()?)? else {
+            return None;
+        };
+        if !is_index(condition.lhs()) {
+            return None;
+        }
+        let (is_up, offset) = match condition.op().as_str() {
+            "<=" | "lte" | "le" => (true, 0),
+            "<" | "lt" => (true, -1),
+            ">=" | "gte" | "ge" => (false, 0),
+            ">" | "gt" => (false, 1),
+            _ => return None,
+        };
+
+        let step = ast::Expr::cast(step.clone()?)?;
+        let step = match &step {
+            ast::Expr::PostfixExpr(it) if is_index(it.operand()) => increment(it.syntax())?,
+            ast::Expr::PrefixExpr(it) if is_index(it.operand()) => increment(it.syntax())?,
+            ast::Expr::AssignExpr(it) if is_index(it.target()) => {
+                let value = it.value()?;
+                match (it.op_token()?.kind(), &value) {
+                    (PLUSEQ, ast::Expr::Literal(it)) if is_up => it.syntax().text().to_string(),
+                    (MINUSEQ, ast::Expr::Literal(it)) if !is_up => {
+                        format!("-{}", it.syntax().text())
+                    }
+                    (PLUSEQ, _) if is_up => format!("#{}#", self.expr(value.syntax())),
+                    _ => return None,
+                }
+            }
+            _ => return None,
+        };
+        if is_up == step.starts_with('-') {
+            return None;
+        }
+
+        let to = condition.rhs()?;
+        let to = match (&to, offset) {
+            (_, 0) => self.attribute(&to),
+            (ast::Expr::Literal(it), _) => match it.syntax().text().to_string().parse::<i64>() {
+                Ok(to) => format!("\"{}\"", to + offset),
+                Err(_) => return None,
+            },
+            (_, -1) => format!("\"#{} - 1#\"", self.expr(to.syntax())),
+            _ => format!("\"#{} + 1#\"", self.expr(to.syntax())),
+        };
+        let from = self.attribute(&from);
+        if is_var {
+            self.w.line(&format!(
+                "<cfset var {index} = {}>",
+                &from[1..from.len() - 1].trim_matches('#')
+            ));
+        }
+        let mut attributes = vec![
+            format!("index=\"{index}\""),
+            format!("from={from}"),
+            format!("to={to}"),
+        ];
+        if step != "1" {
+            attributes.push(format!("step=\"{step}\""));
+        }
+        Some(start_tag("cfloop", &attributes))
+    }
+
+    fn for_in_stmt(&mut self, node: &SyntaxNode) {
+        let Some(iterable) = node
+            .children_with_tokens()
+            .skip_while(|it| it.kind() != IN_KW)
+            .find_map(|it| it.into_node().and_then(ast::Expr::cast))
+        else {
+            return self.problems.push((node.text_range(), String::new()));
+        };
+        let Some(variable) = node.children().next() else {
+            return;
+        };
+        let (item, is_var) = match ast::VarDecl::cast(variable.clone()) {
+            Some(it) => (
+                it.name_token()
+                    .map_or_else(String::new, |it| it.text().to_owned()),
+                true,
+            ),
+            None => (self.expr(&variable), false),
+        };
+
+        let head = match self.collection(&iterable, node) {
+            Some(Collection::Array) => {
+                let array = self.attribute(&iterable);
+                start_tag(
+                    "cfloop",
+                    &[format!("array={array}"), format!("index=\"{item}\"")],
+                )
+            }
+            Some(Collection::Struct) => {
+                let collection = self.attribute(&iterable);
+                let attributes = [
+                    format!("collection={collection}"),
+                    format!("item=\"{item}\""),
+                ];
+                start_tag("cfloop", &attributes)
+            }
+            Some(Collection::List(call)) => {
+                let mut args = call.args().map(|it| match it {
+                    ast::Arg::Positional(it) => Some(it),
+                    ast::Arg::Named(it) => it.value(),
+                });
+                let list = args.next().flatten();
+                let delimiters = args.next().flatten();
+                let Some(list) = list else {
+                    return self
+                        .problems
+                        .push((call.syntax().text_range(), String::new()));
+                };
+                let mut attributes = vec![
+                    format!("list={}", self.attribute(&list)),
+                    format!("index=\"{item}\""),
+                ];
+                if let Some(delimiters) = delimiters {
+                    attributes.push(format!("delimiters={}", self.attribute(&delimiters)));
+                }
+                start_tag("cfloop", &attributes)
+            }
+            None => {
+                let message = format!(
+                    "the loop's collection `{}` is not known to be an array or a struct",
+                    iterable.syntax().text()
+                );
+                return self.problem(node, &message);
+            }
+        };
+        if is_var {
+            self.w.line(&format!("<cfset var {item} = \"\">"));
+        }
+        self.open(&head);
+        self.body(node);
+        self.close("</cfloop>");
+    }
+
+    /// Whether `expr` is an array, a struct or a list made an array, from
+    /// its own form or, for a variable, from the type of the argument it
+    /// is or its last assignment before `at`.
+    fn collection(&self, expr: &ast::Expr, at: &SyntaxNode) -> Option<Collection> {
+        let kind = |expr: &ast::Expr| match expr {
+            ast::Expr::ArrayLit(_) => Some(Collection::Array),
+            ast::Expr::StructLit(_) => Some(Collection::Struct),
+            ast::Expr::CallExpr(call) => {
+                let function = called_function(expr)?;
+                match function.as_str() {
+                    "listtoarray" => Some(Collection::List(call.clone())),
+                    "structnew" | "structcopy" => Some(Collection::Struct),
+                    "structkeyarray" | "arraynew" => Some(Collection::Array),
+                    _ if function.starts_with("array") => Some(Collection::Array),
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+        if let Some(kind) = kind(expr) {
+            return Some(kind);
+        }
+        let path = ast::expr_path_text(expr)?;
+        let argument = path
+            .strip_prefix("arguments.")
+            .unwrap_or(&path)
+            .to_ascii_lowercase();
+        let param = at
+            .ancestors()
+            .find_map(ast::FunctionDecl::cast)
+            .into_iter()
+            .flat_map(|it| it.params())
+            .find(|it| {
+                it.name()
+                    .is_some_and(|it| it.eq_ignore_ascii_case(&argument))
+            });
+        match param.and_then(|it| it.type_ref()) {
+            Some(ty) if ty.text().eq_ignore_ascii_case("array") => return Some(Collection::Array),
+            Some(ty) if ty.text().eq_ignore_ascii_case("struct") => {
+                return Some(Collection::Struct)
+            }
+            _ => (),
+        }
+        let value = self
+            .script
+            .descendants()
+            .take_while(|it| it.text_range().start() < at.text_range().start())
+            .filter_map(|it| match it.kind() {
+                ASSIGN_EXPR => {
+                    let assign = ast::AssignExpr::cast(it)?;
+                    let target = ast::expr_path_text(&assign.target()?)?;
+                    target.eq_ignore_ascii_case(&path).then(|| assign.value())?
+                }
+                VAR_DECL => {
+                    let decl = ast::VarDecl::cast(it)?;
+                    let name = decl.name_token()?;
+                    name.text()
+                        .eq_ignore_ascii_case(&path)
+                        .then(|| decl.initializer())?
+                }
+                _ => None,
+            })
+            .last()?;
+        kind(&value)
+    }
+
+    fn switch_stmt(&mut self, node: &SyntaxNode) {
+        let expression = match node.children().find_map(ast::Expr::cast) {
+            Some(it) => self.attribute(&it),
+            None => return self.problems.push((node.text_range(), String::new())),
+        };
+        self.open(&start_tag(
+            "cfswitch",
+            &[format!("expression={expression}")],
+        ));
+        let clauses: Vec<_> = node
+            .children()
+            .filter(|it| it.kind() == CASE_CLAUSE)
+            .collect();
+        let mut values = Vec::new();
+        for (i, clause) in clauses.iter().enumerate() {
+            let is_default = clause
+                .children_with_tokens()
+                .any(|it| it.kind() == DEFAULT_KW);
+            if !is_default {
+                let value = clause.children().find_map(ast::Expr::cast);
+                let value = match &value {
+                    Some(ast::Expr::Literal(it)) => Some(it.syntax().text().to_string()),
+                    Some(ast::Expr::StringLit(it)) => it.value(),
+                    _ => None,
+                };
+                match value {
+                    Some(value) => values.push(value),
+                    None => self.problem(
+                        clause,
+                        "`case` values must be literals to have a tag equivalent",
+                    ),
+                }
+            }
+            let mut body: Vec<_> = clause
+                .children()
+                .filter(|it| ast::Stmt::can_cast(it.kind()))
+                .collect();
+            if body.is_empty() && !is_default && i + 1 < clauses.len() {
+                continue;
+            }
+            let ends = body.last().is_some_and(|it| it.kind() == BREAK_STMT);
+            if ends {
+                body.pop();
+            } else if i + 1 < clauses.len() && body.last().is_none_or(|it| it.kind() != RETURN_STMT)
+            {
+                self.problem(
+                    clause,
+                    "falling through to the next `case` has no tag equivalent",
+                );
+            }
+            let tag = match is_default {
+                true => {
+                    self.open("<cfdefaultcase>");
+                    "</cfdefaultcase>"
+                }
+                false => {
+                    let values = std::mem::take(&mut values);
+                    let delimiter = [",", "|", ";"]
+                        .into_iter()
+                        .find(|it| values.iter().all(|value| !value.contains(it)))
+                        .unwrap_or(",");
+                    let mut attributes = vec![format!("value={}", quote(&values.join(delimiter)))];
+                    if delimiter != "," {
+                        attributes.push(format!("delimiters=\"{delimiter}\""));
+                    }
+                    self.open(&start_tag("cfcase", &attributes));
+                    "</cfcase>"
+                }
+            };
+            for stmt in body {
+                self.stmt(&stmt);
+            }
+            self.close(tag);
+        }
+        self.close("</cfswitch>");
+    }
+
+    fn try_stmt(&mut self, node: &SyntaxNode) {
+        self.open("<cftry>");
+        for child in node.children() {
+            match child.kind() {
+                BLOCK => self.block(&child),
+                CATCH_CLAUSE => {
+                    let ty = child.children().find_map(|it| match it.kind() {
+                        TYPE_REF => Some(it.text().to_string()),
+                        STRING => ast::StringLit::cast(it)?.value(),
+                        _ => None,
+                    });
+                    let variable = child
+                        .children_with_tokens()
+                        .filter(|it| it.kind() == IDENT)
+                        .last()
+                        .map(|it| it.to_string());
+                    let attributes: Vec<_> = ty
+                        .filter(|it| !it.eq_ignore_ascii_case("any"))
+                        .map(|it| format!("type={}", quote(&it)))
+                        .into_iter()
+                        .collect();
+                    self.open(&start_tag("cfcatch", &attributes));
+                    if let Some(variable) = variable {
+                        self.renames.push((variable, "cfcatch".to_owned()));
+                    }
+                    self.body(&child);
+                    self.renames.pop();
+                    self.close("</cfcatch>");
+                }
+                FINALLY_CLAUSE => {
+                    self.open("<cffinally>");
+                    self.body(&child);
+                    self.close("</cffinally>");
+                }
+                _ => (),
+            }
+        }
+        self.close("</cftry>");
+    }
+
+    /// Converts `lock name="x" { }` and other tags written as statements.
+    fn script_tag(&mut self, node: &SyntaxNode) {
+        let Some(stmt) = ast::ScriptTagStmt::cast(node.clone()) else {
+            return;
+        };
+        let name = stmt.name();
+        let mut attributes = Vec::new();
+        if let Some(value) = node.children().find_map(ast::Expr::cast) {
+            let attribute = match name.as_str() {
+                "include" => "template",
+                "throw" => "message",
+                "abort" => "showerror",
+                "exit" => "method",
+                "location" => "url",
+                _ => {
+                    let message = format!("`{name} {}` has no tag equivalent", value.syntax());
+                    return self.problem(node, &message);
+                }
+            };
+            attributes.push(format!("{attribute}={}", self.attribute(&value)));
+        }
+        for attribute in stmt.attributes() {
+            let Some(key) = attribute.name_token() else {
+                continue;
+            };
+            match attribute.value() {
+                Some(value) => {
+                    attributes.push(format!("{}={}", key.text(), self.attribute(&value)));
+                }
+                None => attributes.push(key.text().to_owned()),
+            }
+        }
+        let tag = format!("cf{name}");
+        let start = start_tag(&tag, &attributes);
+        match stmt.body() {
+            Some(body) => {
+                self.open(&start);
+                self.block(body.syntax());
+                self.close(&format!("</{tag}>"));
+            }
+            None => self.w.line(&start),
+        }
+    }
+
+    fn function(&mut self, node: &SyntaxNode) {
+        let Some(function) = ast::FunctionDecl::cast(node.clone()) else {
+            return;
+        };
+        if function.is_static() {
+            return self.problem(node, "static functions have no tag equivalent");
+        }
+        let (Some(name), Some(body)) = (function.name(), function.body()) else {
+            return self.problems.push((node.text_range(), String::new()));
+        };
+        let doc = docblock(node)
+            .map(|it| parse_docblock(it.text()))
+            .unwrap_or_default();
+        let is_param = |name: &str| {
+            function.params().any(|it| {
+                it.name_token()
+                    .is_some_and(|it| it.text().eq_ignore_ascii_case(name))
+            })
+        };
+
+        let mut attributes = vec![format!("name=\"{name}\"")];
+        if let Some(access) = function.access() {
+            attributes.push(format!("access=\"{access}\""));
+        }
+        if let Some(ty) = function.return_type() {
+            attributes.push(format!("returntype=\"{}\"", ty.text()));
+        }
+        for attribute in function.attributes() {
+            let Some(key) = attribute.name_token() else {
+                continue;
+            };
+            match attribute.value() {
+                Some(value) => {
+                    attributes.push(format!("{}={}", key.text(), self.attribute(&value)))
+                }
+                None => attributes.push(key.text().to_owned()),
+            }
+        }
+        for (key, value) in &doc.metadata {
+            if !is_param(key) && !has_attribute(function.attributes(), key) {
+                attributes.push(format!("{key}={}", quote(&escape(value))));
+            }
+        }
+        if !doc.description.is_empty() && !has_attribute(function.attributes(), "hint") {
+            let hint: Vec<_> = doc.description.split_whitespace().collect();
+            attributes.push(format!("hint={}", quote(&escape(&hint.join(" ")))));
+        }
+        self.open(&start_tag("cffunction", &attributes));
+
+        for param in function.params() {
+            let Some(name) = param.name() else {
+                continue;
+            };
+            let mut attributes = vec![format!("name=\"{name}\"")];
+            if let Some(ty) = param.type_ref() {
+                attributes.push(format!("type=\"{}\"", ty.text()));
+            }
+            if param.is_required() {
+                attributes.push("required=\"true\"".to_owned());
+            }
+            if let Some(default) = param.default_value() {
+                attributes.push(format!("default={}", self.attribute(&default)));
+            }
+            let hint = doc.param(&name).or_else(|| doc.metadata(&name));
+            if let Some(hint) = hint.filter(|_| !has_attribute(param.attributes(), "hint")) {
+                attributes.push(format!("hint={}", quote(&escape(hint))));
+            }
+            for attribute in param.attributes() {
+                let (Some(key), Some(value)) = (attribute.name_token(), attribute.value()) else {
+                    continue;
+                };
+                attributes.push(format!("{}={}", key.text(), self.attribute(&value)));
+            }
+            self.w.line(&start_tag("cfargument", &attributes));
+        }
+        self.block(body.syntax());
+        self.close("</cffunction>");
+    }
+}
+
+/// Whether one of `attributes` is called `name`.
+fn has_attribute(mut attributes: impl Iterator<Item = ast::ScriptAttribute>, name: &str) -> bool {
+    attributes.any(|it| {
+        it.name_token()
+            .is_some_and(|it| it.text().eq_ignore_ascii_case(name))
+    })
+}
+
+fn start_tag(name: &str, attributes: &[String]) -> String {
+    let mut tag = format!("<{name}");
+    for attribute in attributes {
+        tag.push(' ');
+        tag.push_str(attribute);
+    }
+    tag.push('>');
+    tag
+}
+
+/// `text` as an attribute value, in single quotes if it has double quotes.
+fn quote(text: &str) -> String {
+    let text = text.replace('#', "##");
+    match text.contains('"') && !text.contains('\'') {
+        true => format!("'{text}'"),
+        false => format!("\"{}\"", text.replace('"', "\"\"")),
+    }
+}
+
+/// Escapes `#` in text going in an attribute value.
+fn escape(text: &str) -> String {
+    text.replace('#', "##")
+}
+
+/// `1` for `i++` and `-1` for `i--`.
+fn increment(node: &SyntaxNode) -> Option<String> {
+    let op = node.children_with_tokens().find_map(|it| match it.kind() {
+        PLUS2 => Some("1"),
+        MINUS2 => Some("-1"),
+        _ => None,
+    })?;
+    Some(op.to_owned())
+}
+
+fn entry_key(entry: &ast::StructEntry) -> Option<String> {
+    match entry.key_token() {
+        Some(it) => Some(it.text().to_owned()),
+        None => ast::StringLit::cast(entry.syntax().first_child()?)?.value(),
+    }
+}
+
+/// The SQL of a `queryExecute` string literal, with its `:name` or `?`
+/// placeholders replaced by the `<cfqueryparam>`s of `params`.
+fn with_params(literal: &str, params: &[(String, String)]) -> Option<String> {
+    let quote = literal.chars().next()?;
+    let sql =
+        literal[1..literal.len() - 1].replace(&quote.to_string().repeat(2), &quote.to_string());
+    let mut res = String::new();
+    let mut positional = params.iter().filter(|(name, _)| name.is_empty());
+    let mut in_string = false;
+    let mut chars = sql.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\'' => in_string = !in_string,
+            '?' if !in_string => {
+                let (_, param) = positional.next()?;
+                res.push_str(param);
+                continue;
+            }
+            ':' if !in_string && !sql[..i].ends_with(':') => {
+                let rest = &sql[i + 1..];
+                let len = rest
+                    .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                    .unwrap_or(rest.len());
+                let param = params
+                    .iter()
+                    .find(|(name, _)| !name.is_empty() && name.eq_ignore_ascii_case(&rest[..len]));
+                if let Some((_, param)) = param {
+                    res.push_str(param);
+                    for _ in 0..len {
+                        chars.next();
+                    }
+                    continue;
+                }
+            }
+            _ => (),
+        }
+        res.push(c);
+    }
+    Some(res)
+}
+
+/// Whether the docblock `token` documents the function right after it.
+fn precedes_function(token: &SyntaxToken) -> bool {
+    let mut next = token.next_sibling_or_token();
+    while let Some(element) = next {
+        match element.kind() {
+            WHITESPACE => next = element.next_sibling_or_token(),
+            kind => return kind == FUNCTION_DECL,
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use virtual_fs::FileId;
+
+    use crate::{fixture, DiagnosticCode, DiagnosticsConfig, FileRange};
+
+    /// The first file after converting the `<cfscript>` block at the `$0`
+    /// cursor.
+    fn check(fixture: &str, expect: &str) {
+        let (fixture, range) = fixture::selection(fixture);
+        let analysis = fixture::parse(&fixture).analysis;
+        let mut text = analysis.file_text(FileId(0)).to_string();
+        convert(&analysis, range, &mut text);
+        assert_eq!(text, expect);
+    }
+
+    fn convert(analysis: &crate::Analysis, range: syntax::TextRange, text: &mut String) {
+        let assist = analysis
+            .assists(FileRange {
+                file_id: FileId(0),
+                range,
+            })
+            .into_iter()
+            .find(|it| it.label.starts_with("Convert"))
+            .expect("no conversion");
+        assist.source_change.source_file_edits[&FileId(0)].apply(text);
+    }
+
+    #[test]
+    fn converts_statements() {
+        check(
+            r##"
+//- /page.cfm
+<div>
+    <cfscript>$0
+        // Totals
+        var total = 0;
+        items = [1, 2];
+
+        for (i = 1; i < arrayLen(items); i++) {
+            if (items[i] > 1 && !done) {
+                total += items[i];
+            } else if (items[i] == 0 || items[i]!=total) {
+                continue;
+            } else {
+                writeOutput("<b>#i#</b> is ""small""");
+            }
+        }
+        for (item in items) {
+            writeOutput(item);
+        }
+        for (var letter in listToArray("a;b", ";")) {}
+        while (total gt 0) {
+            total = total - 1;
+        }
+        switch (total) {
+            case 1:
+            case "a,b":
+                x = 1;
+                break;
+            default:
+                x = 2;
+        }
+        try {
+            include "helpers.cfm";
+        } catch (database e) {
+            writeLog(text=e.message, type="error");
+            rethrow;
+        }
+        lock name="counter" type="exclusive" timeout=10 {
+            application.count++;
+        }
+        throw(type="Done", message="All done");
+    </cfscript>
+</div>
+"##,
+            r##"<div>
+    <!--- Totals --->
+    <cfset var total = 0>
+    <cfset items = [1, 2]>
+
+    <cfloop index="i" from="1" to="#arrayLen(items) - 1#">
+        <cfif items[i] gt 1 && !done>
+            <cfset total += items[i]>
+        <cfelseif items[i] eq 0 || items[i] neq total>
+            <cfcontinue>
+        <cfelse>
+            <cfoutput><b>#i#</b> is "small"</cfoutput>
+        </cfif>
+    </cfloop>
+    <cfloop array="#items#" index="item">
+        <cfoutput>#item#</cfoutput>
+    </cfloop>
+    <cfset var letter = "">
+    <cfloop list="a;b" index="letter" delimiters=";">
+    </cfloop>
+    <cfloop condition="total gt 0">
+        <cfset total = total - 1>
+    </cfloop>
+    <cfswitch expression="#total#">
+        <cfcase value="1|a,b" delimiters="|">
+            <cfset x = 1>
+        </cfcase>
+        <cfdefaultcase>
+            <cfset x = 2>
+        </cfdefaultcase>
+    </cfswitch>
+    <cftry>
+        <cfinclude template="helpers.cfm">
+        <cfcatch type="database">
+            <cflog text="#cfcatch.message#" type="error">
+            <cfrethrow>
+        </cfcatch>
+    </cftry>
+    <cflock name="counter" type="exclusive" timeout="10">
+        <cfset application.count++>
+    </cflock>
+    <cfthrow type="Done" message="All done">
+</div>
+"##,
+        );
+    }
+
+    #[test]
+    fn converts_functions_and_queries() {
+        check(
+            r##"
+//- /page.cfm
+<cfscript>
+    /**
+     * Finds a user
+     *
+     * @id The id
+     */
+    public query function find(required numeric id, string role="admin") output=false {
+        var q = queryExecute("
+            SELECT * FROM users
+            WHERE id = :id AND role = :role AND name <> 'a:b'
+        ", {id: {value: arguments.id, cfsqltype: "cf_sql_integer"}, role: arguments.role}, {datasource: variables.dsn});
+        local.n = queryExecute("SELECT COUNT(*) FROM t WHERE x = ?", [x]);
+        return q;
+    }$0
+</cfscript>
+"##,
+            r##"<cffunction name="find" access="public" returntype="query" output="false" hint="Finds a user">
+    <cfargument name="id" type="numeric" required="true" hint="The id">
+    <cfargument name="role" type="string" default="admin">
+    <cfquery name="q" datasource="#variables.dsn#">
+        SELECT * FROM users
+        WHERE id = <cfqueryparam value="#arguments.id#" cfsqltype="cf_sql_integer"> AND role = <cfqueryparam value="#arguments.role#"> AND name <> 'a:b'
+    </cfquery>
+    <cfquery name="local.n">SELECT COUNT(*) FROM t WHERE x = <cfqueryparam value="#x#"></cfquery>
+    <cfreturn q>
+</cffunction>
+"##,
+        );
+    }
+
+    #[test]
+    fn round_trips() {
+        let original = r##"<cfcomponent>
+    <cffunction name="total" access="public" returntype="numeric" output="false" hint="Sums the items">
+        <cfargument name="items" type="array" required="true" hint="The items">
+        <cfset var sum = 0>
+        <!--- Skip empty items --->
+        <cfloop array="#items#" index="item">
+            <cfif len(item) gt 0>
+                <cfset sum += item>
+            </cfif>
+        </cfloop>
+        <cftry>
+            <cfset log(sum)>
+            <cfcatch type="any">
+                <cfset sum = 0>
+            </cfcatch>
+        </cftry>
+        <cfreturn sum>
+    </cffunction>
+</cfcomponent>
+"##;
+        let analysis = fixture::parse(&format!("//- /a.cfc\n{original}")).analysis;
+        let mut text = original.to_owned();
+        let at = syntax::TextSize::from(original.find("total").unwrap() as u32);
+        convert(&analysis, syntax::TextRange::empty(at), &mut text);
+        assert!(text.contains("<cfscript>"), "{text}");
+
+        let analysis = fixture::parse(&format!("//- /a.cfc\n{text}")).analysis;
+        let at = syntax::TextSize::from(text.find("function total").unwrap() as u32);
+        convert(&analysis, syntax::TextRange::empty(at), &mut text);
+        assert_eq!(text, original.replace(" type=\"any\"", ""));
+    }
+
+    #[test]
+    fn for_in_over_unknown_collection_not_converted() {
+        // Tags loop over arrays and structs with different attributes, so
+        // the collection has to be known to be one of them.
+        let fixture = r#"
+//- /page.cfm
+<cfscript>$0
+    for (k in arr) {}
+</cfscript>
+"#;
+        let (fixture, range) = fixture::selection(fixture);
+        let analysis = fixture::parse(&fixture).analysis;
+        let assists = analysis.assists(FileRange {
+            file_id: FileId(0),
+            range,
+        });
+        assert!(assists.is_empty(), "{assists:?}");
+
+        check(
+            r#"
+//- /page.cfm
+<cfscript>$0
+    arr = [1];
+    for (k in arr) {}
+</cfscript>
+"#,
+            r##"<cfset arr = [1]>
+<cfloop array="#arr#" index="k">
+</cfloop>
+"##,
+        );
+    }
+
+    #[test]
+    fn reports_statements_without_tags() {
+        let fixture = r#"
+//- /page.cfm
+<cfscript>
+    names = arrayMap(users, (it) => it.name);
+    do { i++; } while (i < 3);
+    for (key in settings) {}
+    switch (x) { case 1: y = 1; case 2: y = 2; }
+    cb = function() { return 1; };
+</cfscript>
+"#;
+        let analysis = fixture::parse(fixture).analysis;
+        let text = analysis.file_text(FileId(0));
+        let diagnostics: Vec<_> = analysis
+            .diagnostics(&DiagnosticsConfig::default(), FileId(0))
+            .into_iter()
+            .filter(|it| it.code == DiagnosticCode::NO_TAG_EQUIVALENT)
+            .map(|it| format!("{}: {}", &text[it.range], it.message))
+            .collect();
+        let suffix = ", so this `<cfscript>` block cannot be converted to tags";
+        assert_eq!(
+            diagnostics,
+            [
+                format!("(it) => it.name: closures have no tag equivalent{suffix}"),
+                format!("do {{ i++; }} while (i < 3);: `do`/`while` loops have no tag equivalent{suffix}"),
+                format!("for (key in settings) {{}}: the loop's collection `settings` is not known to be an array or a struct{suffix}"),
+                format!("case 1: y = 1;: falling through to the next `case` has no tag equivalent{suffix}"),
+                format!("function() {{ return 1; }}: closures have no tag equivalent{suffix}"),
+            ]
+        );
+        let at = syntax::TextSize::from(text.find("names").unwrap() as u32);
+        let assists = analysis.assists(FileRange {
+            file_id: FileId(0),
+            range: syntax::TextRange::empty(at),
+        });
+        assert!(assists.is_empty(), "{assists:?}");
+    }
+}
@@ -0,0 +1,503 @@
+//! Security rules written as sources, sinks and sanitizers: a value traced
+//! to a source must not reach a sink unless a sanitizer is called on it.
+//!
+//! Sinks are written as `function()` for every argument of a function,
+//! `function(2)` or `function(name)` for one of them, `tag.attribute` for
+//! an attribute of a tag in tag or script syntax, and
+//! `tag[attribute=value].attribute` for one only when another attribute has
+//! a value, as in `cffile[action=write].file`.
+//!
+//! The built-in rules take their lists from their options, and projects add
+//! rules of their own, reported as `tainted-data`. SQL and output sinks
+//! have rules of their own, with fixes: see `sql_injection` and `xss`.
+
+use syntax::{
+    ast::{self, AstNode},
+    SyntaxKind::*,
+    TextRange,
+};
+use virtual_fs::FileId;
+
+use crate::{
+    builtins::builtin_function,
+    db::RootDatabase,
+    diagnostics::{Diagnostic, DiagnosticCode, DiagnosticsConfig},
+    taint::{called_function, function_list, TaintSpec, Tracer, SOURCES},
+};
+
+pub(crate) const CODE_SINKS: &str = "evaluate(),iif(),precisionEvaluate()";
+pub(crate) const FILE_PATH_SINKS: &str =
+    "cffile[action=write].file,cffile[action=append].file,cffile[action=upload].destination,\
+    fileWrite(1)";
+pub(crate) const COMMAND_SINKS: &str = "cfexecute.name,cfexecute.arguments";
+pub(crate) const REDIRECT_SINKS: &str = "cflocation.url,location(1)";
+
+/// The built-in rules, configured by their `sources`, `sinks` and
+/// `sanitizers` options.
+const BUILTIN_RULES: &[DiagnosticCode] = &[
+    DiagnosticCode::TAINTED_CODE,
+    DiagnosticCode::TAINTED_FILE_PATH,
+    DiagnosticCode::TAINTED_COMMAND,
+    DiagnosticCode::TAINTED_REDIRECT,
+];
+
+/// A security rule of a project: values from `sources` must not reach
+/// `sinks` without passing through one of the `sanitizers`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TaintRule {
+    /// The name of the rule, for messages.
+    pub name: String,
+    /// Scopes, and functions written with `()`. The sources the built-in
+    /// rules default to if empty.
+    pub sources: Vec<String>,
+    pub sinks: Vec<String>,
+    /// The names of the functions whose results are safe.
+    pub sanitizers: Vec<String>,
+}
+
+impl TaintRule {
+    /// The sinks not written in one of the forms sinks take.
+    pub fn invalid_sinks(&self) -> impl Iterator<Item = &str> {
+        self.sinks
+            .iter()
+            .map(String::as_str)
+            .filter(|it| Sink::parse(it).is_none())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Sink {
+    /// The arguments of calls of a function.
+    Call {
+        function: String,
+        arg: Option<SinkArg>,
+    },
+    /// An attribute of a tag, when the attribute `condition` has the given
+    /// value.
+    Attribute {
+        tag: String,
+        condition: Option<(String, String)>,
+        attribute: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum SinkArg {
+    /// A zero-based position.
+    Index(usize),
+    Name(String),
+}
+
+impl Sink {
+    fn parse(spec: &str) -> Option<Sink> {
+        let spec = spec.trim();
+        if let Some((function, arg)) = spec.split_once('(') {
+            let arg = arg.strip_suffix(')')?.trim();
+            let arg = match arg.parse::<usize>() {
+                _ if arg.is_empty() => None,
+                Ok(0) => return None,
+                Ok(index) => Some(SinkArg::Index(index - 1)),
+                Err(_) => Some(SinkArg::Name(arg.to_owned())),
+            };
+            return Some(Sink::Call {
+                function: function.trim().to_owned(),
+                arg,
+            });
+        }
+        let (tag, attribute) = spec.rsplit_once('.')?;
+        let (tag, condition) = match tag.split_once('[') {
+            Some((tag, condition)) => {
+                let (name, value) = condition.strip_suffix(']')?.split_once('=')?;
+                let value = value.trim().trim_matches(|c| c == '"' || c == '\'');
+                (tag, Some((name.trim().to_owned(), value.to_owned())))
+            }
+            None => (tag, None),
+        };
+        if tag.is_empty() || attribute.is_empty() {
+            return None;
+        }
+        Some(Sink::Attribute {
+            tag: tag.trim().to_owned(),
+            condition,
+            attribute: attribute.trim().to_owned(),
+        })
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Sink::Call { function, .. } => format!("`{function}()`"),
+            Sink::Attribute { tag, attribute, .. } => {
+                format!("the `{attribute}` attribute of `<{tag}>`")
+            }
+        }
+    }
+
+    /// The values `node` passes to the sink.
+    fn values(&self, node: &syntax::SyntaxNode) -> Vec<ast::Expr> {
+        if let Some(call) = ast::CallExpr::cast(node.clone()) {
+            let Some(name) = called_function(&ast::Expr::CallExpr(call.clone())) else {
+                return Vec::new();
+            };
+            return match self {
+                Sink::Call { function, arg } if function.eq_ignore_ascii_case(&name) => {
+                    call_args(&call, arg.as_ref())
+                }
+                Sink::Attribute { tag, attribute, .. } if tag.eq_ignore_ascii_case(&name) => {
+                    // Tags called as functions in script, like `cfexecute(name = "ls")`.
+                    let arg = SinkArg::Name(attribute.clone());
+                    call_args(&call, Some(&arg))
+                }
+                _ => Vec::new(),
+            };
+        }
+        let Sink::Attribute {
+            tag,
+            condition,
+            attribute,
+        } = self
+        else {
+            return Vec::new();
+        };
+        if let Some(it) = ast::Tag::cast(node.clone()) {
+            let applies = tag.eq_ignore_ascii_case(&it.name())
+                && condition.as_ref().is_none_or(|(name, value)| {
+                    it.attribute_value(name)
+                        .is_some_and(|it| it.eq_ignore_ascii_case(value))
+                });
+            let value = it.attribute(attribute).and_then(|it| it.value());
+            return match value {
+                Some(ast::AttributeValue::String(it)) if applies => {
+                    it.hash_exprs().filter_map(|it| it.expr()).collect()
+                }
+                Some(ast::AttributeValue::Hash(it)) if applies => it.expr().into_iter().collect(),
+                _ => Vec::new(),
+            };
+        }
+        if let Some(it) = ast::ScriptTagStmt::cast(node.clone()) {
+            let applies = tag.eq_ignore_ascii_case(&format!("cf{}", it.name()))
+                && condition.as_ref().is_none_or(|(name, value)| {
+                    it.attribute_value(name)
+                        .is_some_and(|it| it.eq_ignore_ascii_case(value))
+                });
+            if !applies {
+                return Vec::new();
+            }
+            return it
+                .attributes()
+                .filter(|it| it.name().eq_ignore_ascii_case(attribute))
+                .filter_map(|it| it.value())
+                .collect();
+        }
+        Vec::new()
+    }
+}
+
+/// The arguments of `call` for `arg`, or all of them. Positions and names
+/// of built-in functions are matched with each other.
+fn call_args(call: &ast::CallExpr, arg: Option<&SinkArg>) -> Vec<ast::Expr> {
+    let builtin =
+        called_function(&ast::Expr::CallExpr(call.clone())).and_then(|it| builtin_function(&it));
+    let params = builtin.map_or(&[][..], |it| &it.params[..]);
+    let (index, name) = match arg {
+        None => (None, None),
+        Some(SinkArg::Index(index)) => (Some(*index), params.get(*index).map(|it| it.name)),
+        Some(SinkArg::Name(name)) => (
+            params
+                .iter()
+                .position(|it| it.name.eq_ignore_ascii_case(name)),
+            Some(name.as_str()),
+        ),
+    };
+    let mut values = Vec::new();
+    let mut position = 0;
+    for it in call.args() {
+        match it {
+            ast::Arg::Positional(value) => {
+                if arg.is_none() || index == Some(position) {
+                    values.push(value);
+                }
+                position += 1;
+            }
+            ast::Arg::Named(named) => {
+                let matches = arg.is_none()
+                    || named
+                        .name_token()
+                        .zip(name)
+                        .is_some_and(|(token, name)| token.text().eq_ignore_ascii_case(name));
+                if matches {
+                    values.extend(named.value());
+                }
+            }
+        }
+    }
+    values
+}
+
+/// A rule to check: its code, the name of a project's rule, and what it
+/// traces.
+struct Check {
+    code: DiagnosticCode,
+    name: Option<String>,
+    spec: TaintSpec,
+    sinks: Vec<Sink>,
+}
+
+pub(crate) fn tainted_sinks(
+    db: &RootDatabase,
+    config: &DiagnosticsConfig,
+    file_id: FileId,
+    file: &ast::SourceFile,
+    res: &mut Vec<Diagnostic>,
+) {
+    let mut checks = Vec::new();
+    for &code in BUILTIN_RULES {
+        if !config.is_enabled(code) {
+            continue;
+        }
+        let option = |name| config.option::<String>(code, name).unwrap_or_default();
+        checks.push(Check {
+            code,
+            name: None,
+            spec: TaintSpec::new(&option("sources"), function_list(&option("sanitizers"))),
+            sinks: option("sinks").split(',').filter_map(Sink::parse).collect(),
+        });
+    }
+    if config.is_enabled(DiagnosticCode::TAINTED_DATA) {
+        for rule in &config.taint_rules {
+            let sources = match rule.sources.is_empty() {
+                true => SOURCES.to_owned(),
+                false => rule.sources.join(","),
+            };
+            checks.push(Check {
+                code: DiagnosticCode::TAINTED_DATA,
+                name: Some(rule.name.clone()),
+                spec: TaintSpec::new(&sources, function_list(&rule.sanitizers.join(","))),
+                sinks: rule.sinks.iter().filter_map(|it| Sink::parse(it)).collect(),
+            });
+        }
+    }
+    checks.retain(|it| !it.sinks.is_empty());
+    if checks.is_empty() {
+        return;
+    }
+
+    let tracers: Vec<_> = checks
+        .iter()
+        .map(|it| Tracer::new(db, file_id, &it.spec))
+        .collect();
+    for node in file.syntax().descendants() {
+        if !matches!(node.kind(), CALL_EXPR | TAG | SCRIPT_TAG_STMT) {
+            continue;
+        }
+        for (check, tracer) in checks.iter().zip(&tracers) {
+            let mut reported: Vec<TextRange> = Vec::new();
+            for sink in &check.sinks {
+                for value in sink.values(&node) {
+                    let range = value.syntax().text_range();
+                    if reported.contains(&range) {
+                        continue;
+                    }
+                    let Some(origin) = tracer.origin(&value).filter(|it| it.is_source()) else {
+                        continue;
+                    };
+                    reported.push(range);
+                    let mut message = format!(
+                        "`{}` comes from {} and reaches {}",
+                        value.syntax(),
+                        origin.describe(),
+                        sink.describe()
+                    );
+                    if let Some(name) = &check.name {
+                        message.push_str(&format!(", which the `{name}` rule forbids"));
+                    }
+                    res.push(Diagnostic::new(check.code, range, message));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use virtual_fs::FileId;
+
+    use crate::{fixture, DiagnosticsConfig, RuleConfig, TaintRule};
+
+    /// The security diagnostics of the first file: their code, the text
+    /// they cover and their message.
+    fn check(config: &DiagnosticsConfig, fixture: &str, expect: &[(&str, &str, &str)]) {
+        let analysis = fixture::parse(fixture).analysis;
+        let text = analysis.file_text(FileId(0));
+        let actual: Vec<_> = analysis
+            .diagnostics(config, FileId(0))
+            .into_iter()
+            .filter(|it| it.code.as_str().starts_with("tainted-"))
+            .map(|it| (it.code.as_str(), text[it.range].to_owned(), it.message))
+            .collect();
+        let actual: Vec<_> = actual
+            .iter()
+            .map(|(code, text, message)| (*code, text.as_str(), message.as_str()))
+            .collect();
+        assert_eq!(actual, expect);
+    }
+
+    #[test]
+    fn builtin_sinks() {
+        check(
+            &DiagnosticsConfig::default(),
+            r##"
+//- /page.cfm
+<cfset path = "/tmp/" & form.name>
+<cffile action="write" file="#path#" output="#form.body#">
+<cffile action="read" file="#path#" variable="x">
+<cfexecute name="#url.cmd#" arguments="-l #val(url.n)#">
+<cflocation url="#cgi.http_referer#">
+<cfscript>
+    evaluate("x = " & getHTTPRequestData().content);
+    fileWrite(file = url.file, data = "x");
+    location(url.next, false);
+    cfexecute(name = cookie.cmd);
+</cfscript>
+"##,
+            &[
+                (
+                    "tainted-file-path",
+                    "path",
+                    "`path` comes from the `form` scope and reaches the `file` attribute of `<cffile>`",
+                ),
+                (
+                    "tainted-command",
+                    "url.cmd",
+                    "`url.cmd` comes from the `url` scope and reaches the `name` attribute of `<cfexecute>`",
+                ),
+                (
+                    "tainted-redirect",
+                    "cgi.http_referer",
+                    "`cgi.http_referer` comes from the `cgi` scope and reaches the `url` attribute of `<cflocation>`",
+                ),
+                (
+                    "tainted-code",
+                    r#""x = " & getHTTPRequestData().content"#,
+                    r#"`"x = " & getHTTPRequestData().content` comes from `getHTTPRequestData()` and reaches `evaluate()`"#,
+                ),
+                (
+                    "tainted-file-path",
+                    "url.file",
+                    "`url.file` comes from the `url` scope and reaches `fileWrite()`",
+                ),
+                (
+                    "tainted-redirect",
+                    "url.next",
+                    "`url.next` comes from the `url` scope and reaches `location()`",
+                ),
+                (
+                    "tainted-command",
+                    "cookie.cmd",
+                    "`cookie.cmd` comes from the `cookie` scope and reaches the `name` attribute of `<cfexecute>`",
+                ),
+            ],
+        );
+    }
+
+    #[test]
+    fn traces_across_functions() {
+        check(
+            &DiagnosticsConfig::default(),
+            r#"
+//- /Runner.cfc
+component {
+    function run(command) {
+        cfexecute(name = arguments.command);
+    }
+
+    function start() {
+        run(readCommand());
+        run("ls");
+    }
+
+    private function readCommand() {
+        var command = url.command;
+        return trim(command);
+    }
+
+    function safe(value) {
+        return evaluate(value);
+    }
+}
+"#,
+            &[(
+                "tainted-command",
+                "arguments.command",
+                "`arguments.command` comes from the `url` scope and reaches the `name` attribute of `<cfexecute>`",
+            )],
+        );
+    }
+
+    #[test]
+    fn configured_rules() {
+        let mut config = DiagnosticsConfig::default();
+        config.rules.insert(
+            "tainted-code",
+            RuleConfig {
+                options: [("sanitizers".to_owned(), "cleanExpression".to_owned())]
+                    .into_iter()
+                    .collect(),
+                ..RuleConfig::default()
+            },
+        );
+        config.taint_rules.push(TaintRule {
+            name: "no-session-mail".to_owned(),
+            sources: vec!["session".to_owned(), "readInbox()".to_owned()],
+            sinks: vec!["cfmail.to".to_owned(), "sendMail(to)".to_owned()],
+            sanitizers: vec!["checkAddress".to_owned()],
+        });
+        check(
+            &config,
+            r##"
+//- /page.cfm
+<cfmail to="#session.email#" from="a@b.c" subject="hi"></cfmail>
+<cfmail to="#checkAddress(session.email)#" from="a@b.c" subject="hi"></cfmail>
+<cfscript>
+    sendMail(subject = "hi", to = readInbox().sender);
+    evaluate(cleanExpression(url.expression));
+    evaluate(url.expression);
+</cfscript>
+"##,
+            &[
+                (
+                    "tainted-data",
+                    "session.email",
+                    "`session.email` comes from the `session` scope and reaches the `to` attribute of `<cfmail>`, which the `no-session-mail` rule forbids",
+                ),
+                (
+                    "tainted-data",
+                    "readInbox().sender",
+                    "`readInbox().sender` comes from `readInbox()` and reaches `sendMail()`, which the `no-session-mail` rule forbids",
+                ),
+                (
+                    "tainted-code",
+                    "url.expression",
+                    "`url.expression` comes from the `url` scope and reaches `evaluate()`",
+                ),
+            ],
+        );
+    }
+
+    #[test]
+    fn invalid_sinks() {
+        let rule = TaintRule {
+            sinks: vec![
+                "evaluate()".to_owned(),
+                "fileWrite(0)".to_owned(),
+                "cflocation".to_owned(),
+                "cffile[action].file".to_owned(),
+            ],
+            ..TaintRule::default()
+        };
+        let invalid: Vec<_> = rule.invalid_sinks().collect();
+        assert_eq!(
+            invalid,
+            ["fileWrite(0)", "cflocation", "cffile[action].file"]
+        );
+    }
+}
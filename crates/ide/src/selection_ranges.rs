@@ -0,0 +1,212 @@
+//! Expand selection: the ranges around an offset, from the word under the
+//! cursor out to the whole file.
+//!
+//! Besides every enclosing node, the steps include the contents of strings,
+//! blocks and argument lists without their delimiters, and the body of a
+//! tag (or of a `cfif` branch) without its start and end tags. HTML text is
+//! not parsed, so inside it selection grows from the word to the text run
+//! and then to the enclosing CFML tag.
+
+use syntax::{
+    ast::{self, AstNode},
+    tags, SyntaxKind,
+    SyntaxKind::*,
+    SyntaxNode, SyntaxToken, TextRange, TextSize, TokenAtOffset,
+};
+
+/// The ranges containing `offset`, innermost first, each strictly
+/// containing the previous one.
+pub(crate) fn selection_ranges(file: &ast::SourceFile, offset: TextSize) -> Vec<TextRange> {
+    let root = file.syntax();
+    let mut ranges = Ranges {
+        offset,
+        acc: Vec::new(),
+    };
+    let token = match root.token_at_offset(offset) {
+        TokenAtOffset::None => {
+            ranges.push(root.text_range());
+            return ranges.acc;
+        }
+        TokenAtOffset::Single(it) => it,
+        TokenAtOffset::Between(left, right) => {
+            match priority(right.kind()) >= priority(left.kind()) {
+                true => right,
+                false => left,
+            }
+        }
+    };
+
+    if matches!(token.kind(), TEXT | STRING_TEXT) || token.kind().is_comment() {
+        if let Some(word) = word_range(&token, offset) {
+            ranges.push(word);
+        }
+        ranges.push(trim(root, token.text_range()));
+    }
+    ranges.push(token.text_range());
+
+    for node in token.parent_ancestors() {
+        match node.kind() {
+            TAG => {
+                if let Some(body) = tag_body(&node) {
+                    ranges.push(trim(root, body));
+                    ranges.push(body);
+                }
+            }
+            STRING => {
+                if let Some(string) = ast::StringLit::cast(node.clone()) {
+                    ranges.push(string.contents_range());
+                }
+            }
+            BLOCK | ARG_LIST | PARAM_LIST | PAREN_EXPR | STRUCT_LIT | ARRAY_LIT => {
+                if let Some(inner) = delimited_inner(&node) {
+                    ranges.push(trim(root, inner));
+                    ranges.push(inner);
+                }
+            }
+            _ => (),
+        }
+        ranges.push(node.text_range());
+    }
+    ranges.acc
+}
+
+struct Ranges {
+    offset: TextSize,
+    acc: Vec<TextRange>,
+}
+
+impl Ranges {
+    /// Adds `range` if it contains the offset and grows the selection.
+    fn push(&mut self, range: TextRange) {
+        if !range.contains_inclusive(self.offset) {
+            return;
+        }
+        match self.acc.last() {
+            Some(last) if !range.contains_range(*last) || range == *last => (),
+            _ => self.acc.push(range),
+        }
+    }
+}
+
+/// Which of two adjacent tokens the cursor is on: names over punctuation
+/// over whitespace.
+fn priority(kind: SyntaxKind) -> u8 {
+    match kind {
+        IDENT | TAG_NAME | ATTR_NAME | NUMBER | STRING_TEXT => 3,
+        kind if kind.is_keyword() => 3,
+        WHITESPACE => 0,
+        kind if kind.is_trivia() => 1,
+        _ => 2,
+    }
+}
+
+/// The run of word characters around `offset` in a text, string or comment.
+fn word_range(token: &SyntaxToken, offset: TextSize) -> Option<TextRange> {
+    let text = token.text();
+    let start = token.text_range().start();
+    let rel = usize::from(offset - start);
+    let is_word = |c: char| c.is_alphanumeric() || c == '_' || c == '-';
+    let before = text[..rel]
+        .char_indices()
+        .rev()
+        .take_while(|(_, c)| is_word(*c))
+        .last()
+        .map_or(rel, |(idx, _)| idx);
+    let after = text[rel..]
+        .char_indices()
+        .find(|(_, c)| !is_word(*c))
+        .map_or(text.len(), |(idx, _)| rel + idx);
+    (before < after).then(|| {
+        TextRange::new(
+            start + TextSize::from(before as u32),
+            start + TextSize::from(after as u32),
+        )
+    })
+}
+
+/// Between the end of the start tag and the end tag, or the first
+/// `cfelseif`/`cfelse` branch. A branch's body runs to the end of it.
+fn tag_body(node: &SyntaxNode) -> Option<TextRange> {
+    let tag = ast::Tag::cast(node.clone())?;
+    let start = tag.start_tag()?.syntax().text_range().end();
+    let end = node
+        .children()
+        .find(|it| {
+            it.kind() == END_TAG
+                || ast::Tag::cast(it.clone()).is_some_and(|it| tags::is_if_branch(&it.name()))
+        })
+        .map(|it| it.text_range().start());
+    let end = match end {
+        Some(end) => end,
+        None if tags::is_if_branch(&tag.name()) => node.text_range().end(),
+        None => return None,
+    };
+    Some(TextRange::new(start, end))
+}
+
+/// The range between the first and last token of a bracketed node.
+fn delimited_inner(node: &SyntaxNode) -> Option<TextRange> {
+    let open = node.first_token()?;
+    let close = node.last_token()?;
+    let is_open = matches!(open.kind(), L_CURLY | L_PAREN | L_BRACK);
+    let is_close = matches!(close.kind(), R_CURLY | R_PAREN | R_BRACK);
+    (is_open && is_close && open != close)
+        .then(|| TextRange::new(open.text_range().end(), close.text_range().start()))
+}
+
+/// `range` without leading and trailing whitespace.
+fn trim(root: &SyntaxNode, range: TextRange) -> TextRange {
+    let text = root.text().slice(range).to_string();
+    let leading = text.len() - text.trim_start().len();
+    let start = range.start() + TextSize::from(leading as u32);
+    TextRange::at(start, TextSize::of(text.trim()))
+}
+
+#[cfg(test)]
+mod tests {
+    use syntax::ast::SourceFile;
+
+    use super::*;
+
+    fn check(text: &str, expect: &[&str]) {
+        let offset = text.find("$0").unwrap();
+        let text = text.replace("$0", "");
+        let file = SourceFile::parse(&text).tree();
+        let ranges = selection_ranges(&file, TextSize::from(offset as u32));
+        let actual: Vec<_> = ranges.iter().map(|it| &text[*it]).collect();
+        assert_eq!(actual, expect);
+    }
+
+    #[test]
+    fn script_expressions() {
+        check(
+            "component {\n  function f(a) {\n    x = user.na$0me(a);\n  }\n}",
+            &[
+                "name",
+                "user.name",
+                "user.name(a)",
+                "x = user.name(a)",
+                "x = user.name(a);",
+                "\n    x = user.name(a);\n  ",
+                "{\n    x = user.name(a);\n  }",
+                "function f(a) {\n    x = user.name(a);\n  }",
+                "\n  function f(a) {\n    x = user.name(a);\n  }\n",
+                "{\n  function f(a) {\n    x = user.name(a);\n  }\n}",
+                "component {\n  function f(a) {\n    x = user.name(a);\n  }\n}",
+            ],
+        );
+    }
+
+    #[test]
+    fn template_text_in_cfif_branch() {
+        check(
+            "<cfif a>\n  <p>Hello wo$0rld</p>\n<cfelse>\n  no\n</cfif>",
+            &[
+                "world",
+                "<p>Hello world</p>",
+                "\n  <p>Hello world</p>\n",
+                "<cfif a>\n  <p>Hello world</p>\n<cfelse>\n  no\n</cfif>",
+            ],
+        );
+    }
+}
@@ -0,0 +1,137 @@
+//! Signature help: the signature of the function whose arguments are being
+//! typed, with the documentation of its parameters.
+
+use crate::{db::RootDatabase, references::resolve_call, FilePosition};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignatureHelp {
+    /// The signature: `string find(numeric id)`.
+    pub label: String,
+    /// The documentation of the function, in Markdown.
+    pub documentation: Option<String>,
+    pub parameters: Vec<ParameterHelp>,
+    /// The parameter of the argument at the cursor, if there is one.
+    pub active_parameter: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParameterHelp {
+    /// The text of the parameter in the signature: `numeric id`.
+    pub label: String,
+    pub documentation: Option<String>,
+}
+
+/// The help for the innermost call of a user-defined function whose
+/// argument list contains `position`.
+pub(crate) fn signature_help(db: &RootDatabase, position: FilePosition) -> Option<SignatureHelp> {
+    let FilePosition { file_id, offset } = position;
+    let text = db.file_text(file_id);
+    let usages = db.usages(file_id);
+    let call = usages
+        .calls
+        .iter()
+        .filter(|it| {
+            let is_closed = text[it.range].ends_with(')');
+            it.name_range.end() < offset
+                && (offset < it.range.end() || !is_closed && offset == it.range.end())
+        })
+        .min_by_key(|it| it.range.len())?;
+    let (_, function) = resolve_call(db, file_id, call)?;
+
+    let index = call
+        .args
+        .iter()
+        .take_while(|it| it.range.end() < offset)
+        .count();
+    let active_parameter = match call.args.get(index).and_then(|it| it.name.as_ref()) {
+        Some((name, _)) => function
+            .params
+            .iter()
+            .position(|it| it.name.eq_ignore_ascii_case(name)),
+        None => (index < function.params.len()).then_some(index),
+    };
+    let parameters = function
+        .params
+        .iter()
+        .map(|it| ParameterHelp {
+            label: it.label(),
+            documentation: function
+                .doc
+                .param(&it.name)
+                .filter(|it| !it.is_empty())
+                .map(str::to_owned),
+        })
+        .collect();
+    Some(SignatureHelp {
+        label: function.signature(),
+        documentation: Some(function.doc.description.clone()).filter(|it| !it.is_empty()),
+        parameters,
+        active_parameter,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixture;
+
+    fn check(fixture: &str, expect: Option<(&str, Option<usize>)>) {
+        let (analysis, position) = fixture::position(fixture);
+        let help = analysis.signature_help(position);
+        let help = help
+            .as_ref()
+            .map(|it| (it.label.as_str(), it.active_parameter));
+        assert_eq!(help, expect);
+    }
+
+    const USERS: &str = r#"
+//- /Users.cfc
+component {
+    /**
+     * Finds users.
+     * @param role The role
+     */
+    function find(numeric id, string role) {}
+}
+"#;
+
+    #[test]
+    fn tracks_the_active_parameter() {
+        let page = |call: &str| {
+            format!("//- /page.cfm\n<cfscript>users = new Users(); {call}</cfscript>{USERS}")
+        };
+        let find = Some("any find(numeric id, string role)");
+        check(&page("users.find($0);"), find.map(|it| (it, Some(0))));
+        check(&page("users.find(1, $0);"), find.map(|it| (it, Some(1))));
+        check(
+            &page("users.find(1, \"a\"$0);"),
+            find.map(|it| (it, Some(1))),
+        );
+        check(
+            &page("users.find(role = $0\"a\");"),
+            find.map(|it| (it, Some(1))),
+        );
+        check(
+            &page("users.find(1, \"a\", $0);"),
+            find.map(|it| (it, None)),
+        );
+        check(&page("users.find(1)$0;"), None);
+    }
+
+    #[test]
+    fn documents_parameters() {
+        let (analysis, position) = fixture::position(&format!(
+            "//- /page.cfm\n<cfset new Users().find(1, $0)>{USERS}"
+        ));
+        let help = analysis.signature_help(position).unwrap();
+        assert_eq!(help.documentation.as_deref(), Some("Finds users."));
+        let docs: Vec<_> = help
+            .parameters
+            .iter()
+            .map(|it| (it.label.as_str(), it.documentation.as_deref()))
+            .collect();
+        assert_eq!(
+            docs,
+            [("numeric id", None), ("string role", Some("The role"))]
+        );
+    }
+}
@@ -0,0 +1,50 @@
+//! Changes spanning several files, possibly creating or moving some of them.
+
+use rustc_hash::FxHashMap;
+use virtual_fs::{AnchoredPathBuf, FileId};
+
+use crate::text_edit::TextEdit;
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SourceChange {
+    pub source_file_edits: FxHashMap<FileId, TextEdit>,
+    pub file_system_edits: Vec<FileSystemEdit>,
+}
+
+impl SourceChange {
+    pub fn from_text_edit(file_id: FileId, edit: TextEdit) -> SourceChange {
+        let mut change = SourceChange::default();
+        change.insert_source_edit(file_id, edit);
+        change
+    }
+
+    /// Adds `edit` to the edits of `file_id`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `edit` overlaps an edit already recorded for the file.
+    pub fn insert_source_edit(&mut self, file_id: FileId, edit: TextEdit) {
+        match self.source_file_edits.get_mut(&file_id) {
+            Some(existing) => existing
+                .union(edit)
+                .expect("overlapping edits for the same file"),
+            None => {
+                self.source_file_edits.insert(file_id, edit);
+            }
+        }
+    }
+
+    pub fn push_file_system_edit(&mut self, edit: FileSystemEdit) {
+        self.file_system_edits.push(edit);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.source_file_edits.is_empty() && self.file_system_edits.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileSystemEdit {
+    /// Moves `src` to `dst`, a path relative to the directory of `dst.anchor`.
+    MoveFile { src: FileId, dst: AnchoredPathBuf },
+}
@@ -0,0 +1,585 @@
+//! Values interpolated into SQL: `#expr#` in the body of a `<cfquery>` and
+//! in a string literal passed to `queryExecute`, which should be passed as
+//! query parameters instead.
+//!
+//! Calls of functions that only return numbers or dates, like `val`, are
+//! safe to interpolate. Values traced to the sources of the rule, by
+//! default the `url`, `form`, `cgi` and `cookie` scopes and
+//! `getHTTPRequestData()`, come straight from the request and are reported
+//! as errors.
+
+use syntax::{
+    ast::{self, AstNode},
+    SyntaxKind::*,
+    TextRange, TextSize,
+};
+use virtual_fs::FileId;
+
+use crate::{
+    assists::Assist,
+    db::RootDatabase,
+    diagnostics::{fix, Diagnostic, DiagnosticCode, DiagnosticsConfig, Severity},
+    taint::{function_list, Origin, TaintSpec, Tracer, SAFE_FUNCTIONS, SOURCES},
+    text_edit::TextEdit,
+    usages::is_identifier,
+};
+
+pub(crate) fn query_params(
+    db: &RootDatabase,
+    config: &DiagnosticsConfig,
+    file_id: FileId,
+    file: &ast::SourceFile,
+    res: &mut Vec<Diagnostic>,
+) {
+    let text = db.file_text(file_id);
+    let cfquery_spec = taint_spec(config, DiagnosticCode::CFQUERYPARAM_REQ);
+    let query_execute_spec = taint_spec(config, DiagnosticCode::QUERYPARAM_REQ);
+    let cx = Context {
+        file_id,
+        text: &text,
+    };
+    let cfquery = Tracer::new(db, file_id, &cfquery_spec);
+    let query_execute = Tracer::new(db, file_id, &query_execute_spec);
+    for node in file.syntax().descendants() {
+        if let Some(tag) = ast::Tag::cast(node.clone()) {
+            if tag.name() == "cfquery" {
+                cx.cfquery(&tag, &cfquery, res);
+            }
+        } else if let Some(call) = ast::CallExpr::cast(node) {
+            let is_query_execute = matches!(
+                call.callee(),
+                Some(ast::Expr::NameRef(it)) if it.name().eq_ignore_ascii_case("queryExecute")
+            );
+            if is_query_execute {
+                cx.query_execute(&call, &query_execute, res);
+            }
+        }
+    }
+}
+
+/// The sources of a rule and the functions whose results are safe to
+/// interpolate into SQL, unless the `sources` and `functions` options of
+/// the rule list others.
+fn taint_spec(config: &DiagnosticsConfig, code: DiagnosticCode) -> TaintSpec {
+    let sources: String = config
+        .option(code, "sources")
+        .unwrap_or_else(|| SOURCES.to_owned());
+    let functions: String = config
+        .option(code, "functions")
+        .unwrap_or_else(|| SAFE_FUNCTIONS.to_owned());
+    TaintSpec::new(&sources, function_list(&functions))
+}
+
+struct Context<'a> {
+    file_id: FileId,
+    text: &'a str,
+}
+
+impl Context<'_> {
+    /// Checks the `#expr#` of a `<cfquery>` outside the attributes of tags
+    /// like `<cfqueryparam>`.
+    fn cfquery(&self, tag: &ast::Tag, tracer: &Tracer, res: &mut Vec<Diagnostic>) {
+        let Some(start_tag) = tag.start_tag() else {
+            return;
+        };
+        let body_start = start_tag.syntax().text_range().end();
+        let body_end = tag.end_tag().map_or(tag.syntax().text_range().end(), |it| {
+            it.syntax().text_range().start()
+        });
+        let body = TextRange::new(body_start, body_end.max(body_start));
+        let hashes: Vec<_> = tag
+            .syntax()
+            .descendants()
+            .filter_map(ast::HashExpr::cast)
+            .filter(|hash| {
+                !hash
+                    .syntax()
+                    .ancestors()
+                    .any(|it| it.kind() == ATTRIBUTE || it == *start_tag.syntax())
+            })
+            .collect();
+        let ranges: Vec<_> = hashes.iter().map(|it| it.syntax().text_range()).collect();
+        for hash in &hashes {
+            let Some(expr) = hash.expr() else {
+                continue;
+            };
+            if tracer.is_sanitized(&expr) {
+                continue;
+            }
+            let quoted = sql_string(self.text, body, &ranges, hash.syntax().text_range(), "'");
+            let fix = self.cfqueryparam_fix(hash, quoted);
+            let diagnostic =
+                self.interpolated(DiagnosticCode::CFQUERYPARAM_REQ, hash, tracer.origin(&expr));
+            res.push(diagnostic.with_fixes(fix.into_iter().collect()));
+        }
+    }
+
+    /// Checks the `#expr#` of a string literal passed to `queryExecute` as
+    /// its SQL.
+    fn query_execute(&self, call: &ast::CallExpr, tracer: &Tracer, res: &mut Vec<Diagnostic>) {
+        let mut sql = None;
+        let mut params = None;
+        let mut positional = 0;
+        for arg in call.args() {
+            match arg {
+                ast::Arg::Positional(it) => {
+                    match positional {
+                        0 => sql = Some((it, None)),
+                        1 => params = Some(it),
+                        _ => (),
+                    }
+                    positional += 1;
+                }
+                ast::Arg::Named(it) => {
+                    let Some(name) = it.name_token() else {
+                        continue;
+                    };
+                    if name.text().eq_ignore_ascii_case("sql") {
+                        sql = it.value().map(|value| (value, Some(it.clone())));
+                    } else if name.text().eq_ignore_ascii_case("params") {
+                        params = it.value();
+                    }
+                }
+            }
+        }
+        let Some((ast::Expr::StringLit(sql), named)) = sql else {
+            return;
+        };
+        let hashes: Vec<_> = sql.hash_exprs().collect();
+        let ranges: Vec<_> = hashes.iter().map(|it| it.syntax().text_range()).collect();
+        let outer_quote =
+            sql.syntax()
+                .first_token()
+                .map_or("\"", |it| if it.text() == "'" { "'" } else { "\"" });
+        let sql_quote = if outer_quote == "'" { "''" } else { "'" };
+        for hash in &hashes {
+            let Some(expr) = hash.expr() else {
+                continue;
+            };
+            if tracer.is_sanitized(&expr) {
+                continue;
+            }
+            let range = hash.syntax().text_range();
+            let quoted = sql_string(self.text, sql.contents_range(), &ranges, range, sql_quote);
+            let fix = self.named_param_fix(
+                &sql,
+                hash,
+                quoted,
+                outer_quote,
+                params.as_ref(),
+                named.as_ref(),
+            );
+            let diagnostic =
+                self.interpolated(DiagnosticCode::QUERYPARAM_REQ, hash, tracer.origin(&expr));
+            res.push(diagnostic.with_fixes(fix.into_iter().collect()));
+        }
+    }
+
+    fn interpolated(
+        &self,
+        code: DiagnosticCode,
+        hash: &ast::HashExpr,
+        origin: Option<Origin>,
+    ) -> Diagnostic {
+        let value = self.value_text(hash);
+        let instead = if code == DiagnosticCode::CFQUERYPARAM_REQ {
+            "<cfqueryparam>"
+        } else {
+            "a query parameter"
+        };
+        let range = hash.syntax().text_range();
+        let Some(origin) = origin else {
+            let message = format!("`{value}` is interpolated into SQL, use {instead} instead");
+            return Diagnostic::new(code, range, message);
+        };
+        let message = format!(
+            "`{value}` comes from {} and is interpolated into SQL, use {instead} instead",
+            origin.describe()
+        );
+        let mut diagnostic = Diagnostic::new(code, range, message);
+        if origin.is_source() {
+            diagnostic.severity = Severity::Error;
+        }
+        diagnostic
+    }
+
+    /// Replaces `#expr#`, or the SQL string literal around it, with a
+    /// `<cfqueryparam>`.
+    fn cfqueryparam_fix(&self, hash: &ast::HashExpr, quoted: Option<TextRange>) -> Option<Assist> {
+        let (range, value, sql_type) = match quoted {
+            Some(range) => {
+                let contents = TextRange::new(
+                    range.start() + TextSize::from(1),
+                    range.end() - TextSize::from(1),
+                );
+                let value = self.text[contents].replace("''", "'");
+                (range, value, "cf_sql_varchar")
+            }
+            None => {
+                let range = hash.syntax().text_range();
+                (range, self.text[range].to_owned(), "cf_sql_integer")
+            }
+        };
+        let quote = match (value.contains('"'), value.contains('\'')) {
+            (false, _) => '"',
+            (true, false) => '\'',
+            (true, true) => return None,
+        };
+        let param = format!("<cfqueryparam value={quote}{value}{quote} cfsqltype=\"{sql_type}\">");
+        let label = format!("Use `<cfqueryparam>` for `{}`", self.value_text(hash));
+        Some(fix(self.file_id, label, TextEdit::replace(range, param)))
+    }
+
+    /// Replaces `#expr#`, or the SQL string literal around it, with a
+    /// named parameter added to the `params` struct of the call.
+    fn named_param_fix(
+        &self,
+        sql: &ast::StringLit,
+        hash: &ast::HashExpr,
+        quoted: Option<TextRange>,
+        outer_quote: &str,
+        params: Option<&ast::Expr>,
+        named: Option<&ast::NamedArg>,
+    ) -> Option<Assist> {
+        let expr = hash.expr()?;
+        let (range, value, sql_type) = match quoted {
+            Some(range) => {
+                let quote_len = TextSize::of(if outer_quote == "'" { "''" } else { "'" });
+                let contents = TextRange::new(range.start() + quote_len, range.end() - quote_len);
+                let value = if contents == hash.syntax().text_range() {
+                    expr.syntax().text().to_string()
+                } else {
+                    format!("{outer_quote}{}{outer_quote}", &self.text[contents])
+                };
+                (range, value, "cf_sql_varchar")
+            }
+            None => (
+                hash.syntax().text_range(),
+                expr.syntax().text().to_string(),
+                "cf_sql_integer",
+            ),
+        };
+
+        let mut taken: Vec<String> = Vec::new();
+        let sql_text = &self.text[sql.contents_range()];
+        for (i, _) in sql_text.match_indices(':') {
+            let name: String = sql_text[i + 1..]
+                .chars()
+                .take_while(|c| c.is_ascii_alphanumeric() || *c == '_')
+                .collect();
+            taken.push(name.to_ascii_lowercase());
+        }
+        if let Some(ast::Expr::StructLit(params)) = params {
+            let keys = params.entries().filter_map(|it| it.key_token());
+            taken.extend(keys.map(|it| it.text().to_ascii_lowercase()));
+        }
+        let base = param_name(&expr);
+        let name = (1..)
+            .map(|i| {
+                if i == 1 {
+                    base.clone()
+                } else {
+                    format!("{base}{i}")
+                }
+            })
+            .find(|it| !taken.contains(&it.to_ascii_lowercase()))?;
+        let entry = format!("{name}: {{value: {value}, cfsqltype: \"{sql_type}\"}}");
+
+        let mut builder = TextEdit::builder();
+        builder.replace(range, format!(":{name}"));
+        match params {
+            Some(ast::Expr::StructLit(params)) => match params.entries().last() {
+                Some(last) => {
+                    builder.insert(last.syntax().text_range().end(), format!(", {entry}"))
+                }
+                None => {
+                    let open = params.syntax().text_range().start() + TextSize::from(1);
+                    builder.insert(open, entry);
+                }
+            },
+            Some(_) => return None,
+            None => {
+                let (end, text) = match named {
+                    Some(named) => (
+                        named.syntax().text_range().end(),
+                        format!(", params = {{{entry}}}"),
+                    ),
+                    None => (sql.syntax().text_range().end(), format!(", {{{entry}}}")),
+                };
+                builder.insert(end, text);
+            }
+        }
+        let label = format!(
+            "Pass `{}` as the parameter `:{name}`",
+            self.value_text(hash)
+        );
+        Some(fix(self.file_id, label, builder.finish()))
+    }
+
+    fn value_text(&self, hash: &ast::HashExpr) -> String {
+        hash.expr().map_or_else(
+            || hash.syntax().text().to_string(),
+            |it| it.syntax().text().to_string(),
+        )
+    }
+}
+
+/// The name of the parameter for `expr`: the last name in it, as `id` for
+/// `url.id`.
+fn param_name(expr: &ast::Expr) -> String {
+    let name = match expr {
+        ast::Expr::NameRef(it) => Some(it.name()),
+        ast::Expr::MemberExpr(it) => it.name_token().map(|it| it.text().to_owned()),
+        ast::Expr::IndexExpr(it) => match it.index() {
+            Some(ast::Expr::StringLit(index)) => index.value(),
+            _ => None,
+        },
+        _ => None,
+    };
+    name.filter(|it| is_identifier(it))
+        .unwrap_or_else(|| "param".to_owned())
+}
+
+/// The SQL string literal, quoted with `quote`, around `target` in the SQL
+/// at `range`. Interpolated `hashes` are skipped over, and a doubled quote
+/// is an escaped one.
+fn sql_string(
+    text: &str,
+    range: TextRange,
+    hashes: &[TextRange],
+    target: TextRange,
+    quote: &str,
+) -> Option<TextRange> {
+    let escaped = quote.repeat(2);
+    let mut offset = usize::from(range.start());
+    let end = usize::from(range.end());
+    let mut start = None;
+    while offset < end {
+        if let Some(hash) = hashes.iter().find(|it| usize::from(it.start()) == offset) {
+            if *hash == target && start.is_none() {
+                return None;
+            }
+            offset = usize::from(hash.end());
+            continue;
+        }
+        let rest = &text[offset..end];
+        if start.is_some() && rest.starts_with(&escaped) {
+            offset += escaped.len();
+        } else if rest.starts_with(quote) {
+            offset += quote.len();
+            match start {
+                None => start = Some(offset - quote.len()),
+                Some(it) => {
+                    let string =
+                        TextRange::new(TextSize::from(it as u32), TextSize::from(offset as u32));
+                    if string.contains_range(target) {
+                        return Some(string);
+                    }
+                    start = None;
+                }
+            }
+        } else {
+            offset += rest.chars().next().map_or(1, char::len_utf8);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use virtual_fs::FileId;
+
+    use crate::{fixture, DiagnosticCode, DiagnosticsConfig, RuleConfig, Severity};
+
+    /// The SQL diagnostics of the first file: the text they cover and
+    /// their severity.
+    fn check(config: &DiagnosticsConfig, fixture: &str, expect: &[(&str, &str, Severity)]) {
+        let analysis = fixture::parse(fixture).analysis;
+        let text = analysis.file_text(FileId(0));
+        let actual: Vec<_> = analysis
+            .diagnostics(config, FileId(0))
+            .into_iter()
+            .filter(|it| {
+                it.code == DiagnosticCode::CFQUERYPARAM_REQ
+                    || it.code == DiagnosticCode::QUERYPARAM_REQ
+            })
+            .map(|it| (it.code.as_str(), &text[it.range], it.severity))
+            .collect();
+        assert_eq!(actual, expect);
+    }
+
+    /// The text of the first file after applying the first fix of the
+    /// diagnostic covering `$0`.
+    fn check_fix(fixture: &str, expect: &str) {
+        let fixture = fixture::parse(fixture);
+        let position = fixture.position.unwrap();
+        let analysis = fixture.analysis;
+        let diagnostic = analysis
+            .diagnostics(&DiagnosticsConfig::default(), position.file_id)
+            .into_iter()
+            .find(|it| it.range.contains_inclusive(position.offset) && !it.fixes.is_empty())
+            .expect("no fix");
+        let mut text = analysis.file_text(position.file_id).to_string();
+        let fix = diagnostic.fixes.into_iter().next().unwrap();
+        fix.source_change.source_file_edits[&position.file_id].apply(&mut text);
+        assert_eq!(text, expect);
+    }
+
+    #[test]
+    fn interpolations() {
+        check(
+            &DiagnosticsConfig::default(),
+            r##"
+//- /page.cfm
+<cfquery name="q" datasource="#dsn#">
+    select * from users
+    where id = #url.id#
+    and name = <cfqueryparam value="#form.name#">
+    and age > #val(form.age)#
+    and kind = '#kind#'
+</cfquery>
+<cfscript>
+    queryExecute("select * from users where id = #arguments.id#");
+    queryExecute(sql = "select 1 where a = #a#", params = {});
+    queryExecute("select * from users where id = :id", {id: url.id});
+</cfscript>
+"##,
+            &[
+                ("CFQUERYPARAM_REQ", "#url.id#", Severity::Error),
+                ("CFQUERYPARAM_REQ", "#kind#", Severity::Warning),
+                ("QUERYPARAM_REQ", "#arguments.id#", Severity::Warning),
+                ("QUERYPARAM_REQ", "#a#", Severity::Warning),
+            ],
+        );
+    }
+
+    #[test]
+    fn traces_origins() {
+        check(
+            &DiagnosticsConfig::default(),
+            r##"
+//- /Users.cfc
+component {
+    function find(name) {
+        var id = url.id;
+        var other = trim(id);
+        local.key = form["key"];
+        queryExecute("select * from t where id = #other# and key = #local.key#");
+        queryExecute("select * from t where name = '#name#' and x = #variables.x#");
+    }
+}
+"##,
+            &[
+                ("QUERYPARAM_REQ", "#other#", Severity::Error),
+                ("QUERYPARAM_REQ", "#local.key#", Severity::Error),
+                ("QUERYPARAM_REQ", "#name#", Severity::Warning),
+                ("QUERYPARAM_REQ", "#variables.x#", Severity::Warning),
+            ],
+        );
+        let analysis = fixture::parse(
+            r##"
+//- /Users.cfc
+component {
+    function find(name) {
+        queryExecute("select * from t where name = '#name#'");
+    }
+}
+"##,
+        )
+        .analysis;
+        let diagnostics = analysis.diagnostics(&DiagnosticsConfig::default(), FileId(0));
+        assert_eq!(
+            diagnostics[0].message,
+            "`name` comes from an argument and is interpolated into SQL, use a query parameter instead"
+        );
+    }
+
+    #[test]
+    fn configured_safe_functions() {
+        let mut config = DiagnosticsConfig::default();
+        let mut rule = RuleConfig::default();
+        rule.options
+            .insert("functions".to_owned(), "sanitize".to_owned());
+        config
+            .rules
+            .insert(DiagnosticCode::CFQUERYPARAM_REQ.as_str(), rule);
+        check(
+            &config,
+            r##"
+//- /page.cfm
+<cfquery name="q">select * from t where a = #sanitize(a)# and b = #val(b)#</cfquery>
+"##,
+            &[("CFQUERYPARAM_REQ", "#val(b)#", Severity::Warning)],
+        );
+    }
+
+    #[test]
+    fn cfqueryparam_fixes() {
+        check_fix(
+            r##"
+//- /page.cfm
+<cfquery name="q">
+    select * from users where id = #url.i$0d#
+</cfquery>
+"##,
+            r##"<cfquery name="q">
+    select * from users where id = <cfqueryparam value="#url.id#" cfsqltype="cf_sql_integer">
+</cfquery>
+"##,
+        );
+        check_fix(
+            r##"
+//- /page.cfm
+<cfquery name="q">
+    select * from users where name like '%#form.na$0me#%' and kind = 'it''s'
+</cfquery>
+"##,
+            r##"<cfquery name="q">
+    select * from users where name like <cfqueryparam value="%#form.name#%" cfsqltype="cf_sql_varchar"> and kind = 'it''s'
+</cfquery>
+"##,
+        );
+    }
+
+    #[test]
+    fn named_param_fixes() {
+        check_fix(
+            r##"
+//- /page.cfm
+<cfscript>
+    queryExecute("select * from users where id = #url.i$0d#");
+</cfscript>
+"##,
+            r##"<cfscript>
+    queryExecute("select * from users where id = :id", {id: {value: url.id, cfsqltype: "cf_sql_integer"}});
+</cfscript>
+"##,
+        );
+        check_fix(
+            r##"
+//- /page.cfm
+<cfscript>
+    queryExecute("select * from t where id = :id and name = '#form.i$0d#'", {id: 1});
+</cfscript>
+"##,
+            r##"<cfscript>
+    queryExecute("select * from t where id = :id and name = :id2", {id: 1, id2: {value: form.id, cfsqltype: "cf_sql_varchar"}});
+</cfscript>
+"##,
+        );
+        check_fix(
+            r##"
+//- /page.cfm
+<cfscript>
+    queryExecute(sql = 'select * from t where name like ''%#na$0me#%''', params = {});
+    queryExecute(sql = "select 1 where a = #a#");
+</cfscript>
+"##,
+            r##"<cfscript>
+    queryExecute(sql = 'select * from t where name like :name', params = {name: {value: '%#name#%', cfsqltype: "cf_sql_varchar"}});
+    queryExecute(sql = "select 1 where a = #a#");
+</cfscript>
+"##,
+        );
+    }
+}
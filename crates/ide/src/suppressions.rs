@@ -0,0 +1,183 @@
+//! Comments turning diagnostics off:
+//!
+//! - `cfls-disable RULE, ...` anywhere in a file, for the whole file;
+//! - `cfls-disable-line RULE, ...` for the line the comment starts on;
+//! - `cfls-disable-next-line RULE, ...` for the line after the comment.
+//!
+//! Without rules they turn every rule off. Text after `--` is a reason and
+//! ignored. Syntax errors cannot be turned off.
+
+use rustc_hash::FxHashMap;
+use syntax::{
+    ast::{self, AstNode},
+    SyntaxToken,
+};
+
+use crate::{line_index::LineIndex, Diagnostic, DiagnosticCode};
+
+/// The rules a comment turns off.
+#[derive(Debug, Default)]
+struct Rules {
+    all: bool,
+    codes: Vec<String>,
+}
+
+impl Rules {
+    fn add(&mut self, codes: Vec<&str>) {
+        if codes.is_empty() {
+            self.all = true;
+        }
+        self.codes.extend(codes.into_iter().map(str::to_owned));
+    }
+
+    fn contains(&self, code: DiagnosticCode) -> bool {
+        self.all
+            || self
+                .codes
+                .iter()
+                .any(|it| it.eq_ignore_ascii_case(code.as_str()))
+    }
+}
+
+/// Drops the diagnostics turned off by comments of `file`.
+pub(crate) fn retain_unsuppressed(
+    file: &ast::SourceFile,
+    line_index: &LineIndex,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let mut whole_file = Rules::default();
+    let mut lines: FxHashMap<u32, Rules> = FxHashMap::default();
+    let comments = file
+        .syntax()
+        .descendants_with_tokens()
+        .filter_map(|it| it.into_token())
+        .filter(|it| it.kind().is_comment());
+    for comment in comments {
+        let Some((directive, codes)) = directive(&comment) else {
+            continue;
+        };
+        let range = comment.text_range();
+        match directive {
+            "cfls-disable" => whole_file.add(codes),
+            "cfls-disable-line" => {
+                let line = line_index.line_col(range.start()).line;
+                lines.entry(line).or_default().add(codes);
+            }
+            "cfls-disable-next-line" => {
+                let line = line_index.line_col(range.end()).line + 1;
+                lines.entry(line).or_default().add(codes);
+            }
+            _ => {}
+        }
+    }
+    diagnostics.retain(|it| {
+        if it.code == DiagnosticCode::SYNTAX_ERROR {
+            return true;
+        }
+        let line = line_index.line_col(it.range.start()).line;
+        !whole_file.contains(it.code)
+            && !lines
+                .get(&line)
+                .is_some_and(|rules| rules.contains(it.code))
+    });
+}
+
+/// The directive a comment starts with and the rules it lists.
+fn directive(comment: &SyntaxToken) -> Option<(&str, Vec<&str>)> {
+    let text = comment.text();
+    let text = ["<!---", "//", "/*"]
+        .iter()
+        .find_map(|it| text.strip_prefix(it))?;
+    let text = ["--->", "*/"]
+        .iter()
+        .find_map(|it| text.strip_suffix(it))
+        .unwrap_or(text);
+    let text = text.split("--").next().unwrap_or_default().trim();
+    let (directive, rest) = text.split_once(char::is_whitespace).unwrap_or((text, ""));
+    if !directive.starts_with("cfls-disable") {
+        return None;
+    }
+    let codes = rest
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|it| !it.is_empty())
+        .collect();
+    Some((directive, codes))
+}
+
+#[cfg(test)]
+mod tests {
+    use virtual_fs::FileId;
+
+    use crate::{fixture, DiagnosticsConfig};
+
+    /// The codes of the diagnostics of the first file and the text they
+    /// cover.
+    fn check(fixture: &str, expect: &[(&str, &str)]) {
+        let analysis = fixture::parse(fixture).analysis;
+        let text = analysis.file_text(FileId(0));
+        let actual: Vec<_> = analysis
+            .diagnostics(&DiagnosticsConfig::default(), FileId(0))
+            .into_iter()
+            .map(|it| (it.code.as_str(), &text[it.range]))
+            .collect();
+        assert_eq!(actual, expect);
+    }
+
+    #[test]
+    fn line_comments() {
+        check(
+            r#"
+//- /a.cfc
+component {
+    function f() {
+        // cfls-disable-next-line MISSING_VAR -- set by the framework
+        a = 1;
+        b = 1; // cfls-disable-line missing_var, ARG_VAR_CONFLICT
+        c = 1; /* cfls-disable-line AVOID_USING_ABORT */
+        writeDump(c); // cfls-disable-line
+    }
+}
+"#,
+            &[("MISSING_VAR", "c")],
+        );
+    }
+
+    #[test]
+    fn tag_comments() {
+        check(
+            r##"
+//- /a.cfm
+<!--- cfls-disable-next-line AVOID_USING_CFDUMP_TAG --->
+<cfdump var="#x#">
+<cfdump var="#y#">
+<cfabort>
+"##,
+            &[
+                ("AVOID_USING_CFDUMP_TAG", "cfdump"),
+                ("AVOID_USING_CFABORT_TAG", "cfabort"),
+            ],
+        );
+    }
+
+    #[test]
+    fn whole_file() {
+        check(
+            r##"
+//- /a.cfm
+<cfdump var="#y#">
+<cfabort>
+<!--- cfls-disable AVOID_USING_CFDUMP_TAG, AVOID_USING_CFABORT_TAG --->
+"##,
+            &[],
+        );
+        check(
+            r##"
+//- /a.cfm
+<!--- cfls-disable --->
+<cfdump var="#y#">
+<cfif a>
+"##,
+            &[("syntax-error", "cfif")],
+        );
+    }
+}
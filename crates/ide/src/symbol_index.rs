@@ -0,0 +1,272 @@
+//! The workspace symbol index: the components, functions and properties of
+//! every file, searched with case-insensitive fuzzy matching.
+//!
+//! Each file's entries are computed from its declarations once and cached
+//! with the file, so a query only re-indexes files changed since the last
+//! one.
+
+use std::sync::Arc;
+
+use syntax::TextRange;
+use virtual_fs::FileId;
+
+use crate::{db::RootDatabase, SymbolKind};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileSymbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    /// The component declaring a function or property.
+    pub container_name: Option<String>,
+    pub file_id: FileId,
+    pub range: TextRange,
+    pub name_range: TextRange,
+}
+
+/// A symbol with its name lowercased once, at indexing time.
+#[derive(Debug)]
+pub(crate) struct IndexedSymbol {
+    lowercase: String,
+    symbol: FileSymbol,
+}
+
+/// The kinds of symbol a query is restricted to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SymbolFilter {
+    Component,
+    Function,
+    Property,
+}
+
+impl SymbolFilter {
+    fn matches(self, kind: SymbolKind) -> bool {
+        match self {
+            SymbolFilter::Component => {
+                matches!(kind, SymbolKind::Component | SymbolKind::Interface)
+            }
+            SymbolFilter::Function => matches!(kind, SymbolKind::Function | SymbolKind::Method),
+            SymbolFilter::Property => kind == SymbolKind::Property,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Query {
+    lowercase: String,
+    filter: Option<SymbolFilter>,
+    limit: usize,
+}
+
+impl Query {
+    /// Parses a query as typed by the user. A leading `component:`,
+    /// `function:` or `property:` (or `c:`, `f:`, `p:`) restricts the kind
+    /// of symbol; the rest is matched against names.
+    pub fn new(text: &str) -> Query {
+        let text = text.trim();
+        let (filter, text) = match text.split_once(':') {
+            Some((prefix, rest)) => match prefix.to_ascii_lowercase().as_str() {
+                "c" | "component" => (Some(SymbolFilter::Component), rest),
+                "f" | "function" => (Some(SymbolFilter::Function), rest),
+                "p" | "property" => (Some(SymbolFilter::Property), rest),
+                _ => (None, text),
+            },
+            None => (None, text),
+        };
+        Query {
+            lowercase: text.trim().to_lowercase(),
+            filter,
+            limit: usize::MAX,
+        }
+    }
+
+    pub fn limit(&mut self, limit: usize) {
+        self.limit = limit;
+    }
+
+    /// How well `name` matches, lower is better: exact, prefix, substring,
+    /// then subsequence matches.
+    fn score(&self, lowercase: &str) -> Option<u8> {
+        let query = self.lowercase.as_str();
+        if lowercase == query {
+            Some(0)
+        } else if lowercase.starts_with(query) {
+            Some(1)
+        } else if lowercase.contains(query) {
+            Some(2)
+        } else if is_subsequence(query, lowercase) {
+            Some(3)
+        } else {
+            None
+        }
+    }
+}
+
+fn is_subsequence(needle: &str, haystack: &str) -> bool {
+    if needle.is_ascii() {
+        let mut haystack = haystack.bytes();
+        return needle.bytes().all(|c| haystack.any(|it| it == c));
+    }
+    let mut haystack = haystack.chars();
+    needle.chars().all(|c| haystack.any(|it| it == c))
+}
+
+/// The index entries of one file.
+pub(crate) fn file_symbols(db: &RootDatabase, file_id: FileId) -> Arc<[IndexedSymbol]> {
+    let items = db.items(file_id);
+    let component_name = db.component_name(file_id);
+    let mut symbols = Vec::new();
+    let mut push = |name: &str, kind, container_name: Option<&String>, range, name_range| {
+        symbols.push(IndexedSymbol {
+            lowercase: name.to_lowercase(),
+            symbol: FileSymbol {
+                name: name.to_owned(),
+                kind,
+                container_name: container_name.cloned(),
+                file_id,
+                range,
+                name_range,
+            },
+        });
+    };
+
+    let container = items.component.as_ref().and(component_name.as_ref());
+    if let (Some(component), Some(name)) = (&items.component, &component_name) {
+        let kind = match component.is_interface {
+            true => SymbolKind::Interface,
+            false => SymbolKind::Component,
+        };
+        push(name, kind, None, component.range, component.keyword_range);
+        for property in &component.properties {
+            let kind = SymbolKind::Property;
+            push(
+                &property.name,
+                kind,
+                container,
+                property.range,
+                property.name_range,
+            );
+        }
+    }
+    for function in &items.functions {
+        let kind = match container {
+            Some(_) => SymbolKind::Method,
+            None => SymbolKind::Function,
+        };
+        push(
+            &function.name,
+            kind,
+            container,
+            function.range,
+            function.name_range,
+        );
+    }
+    symbols.into()
+}
+
+pub(crate) fn world_symbols(db: &RootDatabase, query: &Query) -> Vec<FileSymbol> {
+    let indexes: Vec<_> = db
+        .files()
+        .into_iter()
+        .map(|file_id| db.file_symbols(file_id))
+        .collect();
+    let mut matches = Vec::new();
+    for index in &indexes {
+        for entry in index.iter() {
+            if query
+                .filter
+                .is_some_and(|it| !it.matches(entry.symbol.kind))
+            {
+                continue;
+            }
+            if let Some(score) = query.score(&entry.lowercase) {
+                matches.push((score, entry));
+            }
+        }
+    }
+    let cmp = |(score_a, a): &(u8, &IndexedSymbol), (score_b, b): &(u8, &IndexedSymbol)| {
+        (score_a, a.lowercase.len(), &a.lowercase).cmp(&(score_b, b.lowercase.len(), &b.lowercase))
+    };
+    if matches.len() > query.limit {
+        matches.select_nth_unstable_by(query.limit, cmp);
+        matches.truncate(query.limit);
+    }
+    matches.sort_by(cmp);
+    matches
+        .into_iter()
+        .take(query.limit)
+        .map(|(_, entry)| entry.symbol.clone())
+        .collect()
+}
+
+/// Indexes every file not indexed yet.
+pub(crate) fn prime(db: &RootDatabase) {
+    for file_id in db.files() {
+        db.file_symbols(file_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixture;
+
+    use super::*;
+
+    fn check(fixture: &str, query: &str, expect: &[&str]) {
+        let analysis = fixture::parse(fixture).analysis;
+        let symbols = analysis.symbol_search(Query::new(query));
+        let actual: Vec<_> = symbols
+            .iter()
+            .map(|it| match &it.container_name {
+                Some(container) => format!("{:?} {container}.{}", it.kind, it.name),
+                None => format!("{:?} {}", it.kind, it.name),
+            })
+            .collect();
+        assert_eq!(actual, expect);
+    }
+
+    const FIXTURE: &str = r#"
+//- /models/User.cfc
+component {
+    property name="userName";
+    function save() {}
+    function getUserName() {}
+}
+//- /models/IUserStore.cfc
+interface {
+    function find(id);
+}
+//- /pages/index.cfm
+<cffunction name="renderUser"></cffunction>
+"#;
+
+    #[test]
+    fn fuzzy_and_case_insensitive() {
+        check(
+            FIXTURE,
+            "user",
+            &[
+                "Component User",
+                "Property User.userName",
+                "Interface IUserStore",
+                "Function renderUser",
+                "Method User.getUserName",
+            ],
+        );
+        check(FIXTURE, "gun", &["Method User.getUserName"]);
+    }
+
+    #[test]
+    fn kind_filter() {
+        check(
+            FIXTURE,
+            "c:user",
+            &["Component User", "Interface IUserStore"],
+        );
+        check(
+            FIXTURE,
+            "function:user",
+            &["Function renderUser", "Method User.getUserName"],
+        );
+        check(FIXTURE, "p:", &["Property User.userName"]);
+    }
+}
@@ -0,0 +1,526 @@
+//! Semantic highlighting: a classification of the ranges of a file that a
+//! TextMate grammar cannot get right on its own.
+//!
+//! Tokens are first classified by their syntax, then refined with what the
+//! usages of the file know: which names are parameters, which calls go to
+//! built-in functions, which strings name components. `#expr#` islands keep
+//! the highlighting of their expression with an extra modifier, and the SQL
+//! text of `<cfquery>` is split into keywords, strings and numbers.
+
+use std::ops::BitOr;
+
+use syntax::{
+    ast::{self, AstNode},
+    NodeOrToken,
+    SyntaxKind::*,
+    SyntaxNode, SyntaxToken, TextRange, TextSize, WalkEvent,
+};
+use virtual_fs::FileId;
+
+use crate::{
+    builtins::builtin_function,
+    db::RootDatabase,
+    usages::{is_builtin_type, is_scope_name, Receiver, VarScope},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HlRange {
+    pub range: TextRange,
+    pub highlight: Highlight,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Highlight {
+    pub tag: HlTag,
+    pub mods: HlMods,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HlTag {
+    /// The name of a CFML tag.
+    Tag,
+    /// A tag attribute or a script declaration attribute.
+    Attribute,
+    /// A scope keyword: `variables`, `session`, `arguments`, ...
+    Scope,
+    Keyword,
+    Operator,
+    Comment,
+    String,
+    Number,
+    Function,
+    Method,
+    /// A component name or path.
+    Component,
+    /// A built-in type in a signature.
+    Type,
+    Parameter,
+    Variable,
+    Property,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum HlMod {
+    Declaration = 0,
+    /// A built-in function.
+    DefaultLibrary,
+    Deprecated,
+    /// Inside a `#expr#` island.
+    Interpolated,
+    /// Part of the SQL of a `<cfquery>`.
+    Sql,
+}
+
+impl HlMod {
+    pub const ALL: &'static [HlMod] = &[
+        HlMod::Declaration,
+        HlMod::DefaultLibrary,
+        HlMod::Deprecated,
+        HlMod::Interpolated,
+        HlMod::Sql,
+    ];
+
+    fn mask(self) -> u32 {
+        1 << (self as u8)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct HlMods(u32);
+
+impl HlMods {
+    pub fn contains(self, m: HlMod) -> bool {
+        self.0 & m.mask() != 0
+    }
+
+    pub fn iter(self) -> impl Iterator<Item = HlMod> {
+        HlMod::ALL
+            .iter()
+            .copied()
+            .filter(move |it| self.contains(*it))
+    }
+}
+
+impl BitOr<HlMod> for HlTag {
+    type Output = Highlight;
+
+    fn bitor(self, rhs: HlMod) -> Highlight {
+        Highlight::from(self) | rhs
+    }
+}
+
+impl BitOr<HlMod> for Highlight {
+    type Output = Highlight;
+
+    fn bitor(mut self, rhs: HlMod) -> Highlight {
+        self.mods.0 |= rhs.mask();
+        self
+    }
+}
+
+impl From<HlTag> for Highlight {
+    fn from(tag: HlTag) -> Highlight {
+        Highlight {
+            tag,
+            mods: HlMods::default(),
+        }
+    }
+}
+
+/// Highlights the whole file, or only the ranges intersecting `range`.
+/// The result is sorted and non-overlapping.
+pub(crate) fn highlight(
+    db: &RootDatabase,
+    file_id: FileId,
+    range: Option<TextRange>,
+) -> Vec<HlRange> {
+    let root = db.parse(file_id).syntax_node();
+    let mut hl = Highlights::default();
+    let mut islands = Vec::new();
+
+    // Whether the SQL text so far ends inside a string.
+    let mut in_sql_string = false;
+    for event in root.preorder_with_tokens() {
+        let WalkEvent::Enter(element) = event else {
+            continue;
+        };
+        match element {
+            NodeOrToken::Token(token) if token.kind() == TEXT && is_in_cfquery(&token) => {
+                highlight_sql(&token, &mut in_sql_string, &mut hl);
+            }
+            NodeOrToken::Token(token) => {
+                if let Some(highlight) = token_highlight(&token) {
+                    hl.push(token.text_range(), highlight);
+                }
+            }
+            NodeOrToken::Node(node) => match node.kind() {
+                HASH_EXPR => islands.push(node.text_range()),
+                TAG => in_sql_string = false,
+                _ => (),
+            },
+        }
+    }
+
+    let items = db.items(file_id);
+    let usages = db.usages(file_id);
+    for var in &usages.vars {
+        let tag = match var.scope {
+            VarScope::Arguments { .. } => HlTag::Parameter,
+            VarScope::This => HlTag::Property,
+            _ => HlTag::Variable,
+        };
+        let mut highlight = Highlight::from(tag);
+        if var.is_decl {
+            highlight = highlight | HlMod::Declaration;
+        }
+        hl.overlay(var.range, highlight);
+    }
+    for function in &items.functions {
+        let tag = match items.component {
+            Some(_) => HlTag::Method,
+            None => HlTag::Function,
+        };
+        hl.overlay(function.name_range, tag | HlMod::Declaration);
+    }
+    for call in &usages.calls {
+        let highlight = match call.receiver {
+            Receiver::None if db.find_function(file_id, &call.name).is_none() => {
+                match builtin_function(&call.name) {
+                    Some(builtin) if builtin.deprecated.is_some() => {
+                        HlTag::Function | HlMod::DefaultLibrary | HlMod::Deprecated
+                    }
+                    Some(_) => HlTag::Function | HlMod::DefaultLibrary,
+                    None => HlTag::Function.into(),
+                }
+            }
+            Receiver::None => HlTag::Function.into(),
+            _ => HlTag::Method.into(),
+        };
+        hl.overlay(call.name_range, highlight);
+    }
+    for path in &usages.component_refs {
+        hl.overlay(path.range, HlTag::Component.into());
+    }
+
+    let mut ranges = hl.ranges;
+    for island in islands {
+        let start = ranges.partition_point(|it| it.range.end() <= island.start());
+        for it in ranges[start..]
+            .iter_mut()
+            .take_while(|it| it.range.start() < island.end())
+        {
+            it.highlight = it.highlight | HlMod::Interpolated;
+        }
+    }
+    if let Some(range) = range {
+        ranges.retain(|it| it.range.intersect(range).is_some_and(|it| !it.is_empty()));
+    }
+    ranges
+}
+
+/// The highlighting of a token from its kind and parent alone.
+fn token_highlight(token: &SyntaxToken) -> Option<Highlight> {
+    let parent = token.parent()?;
+    let tag = match token.kind() {
+        LINE_COMMENT | BLOCK_COMMENT | TAG_COMMENT => HlTag::Comment,
+        TAG_NAME => HlTag::Tag,
+        ATTR_NAME => HlTag::Attribute,
+        ATTR_VALUE | QUOTE | STRING_TEXT => HlTag::String,
+        NUMBER => HlTag::Number,
+        HASH => HlTag::Operator,
+        WORD_OP => HlTag::Operator,
+        kind if kind.is_keyword() => HlTag::Keyword,
+        IDENT => return ident_highlight(token, &parent),
+        _ => return None,
+    };
+    Some(tag.into())
+}
+
+fn ident_highlight(token: &SyntaxToken, parent: &SyntaxNode) -> Option<Highlight> {
+    let is_first = || {
+        parent
+            .children_with_tokens()
+            .find(|it| !it.kind().is_trivia())
+            .is_some_and(|it| it.as_token() == Some(token))
+    };
+    let tag = match parent.kind() {
+        NAME_REF if is_scope_name(token.text()) || token.text().eq_ignore_ascii_case("super") => {
+            HlTag::Scope
+        }
+        SCRIPT_ATTRIBUTE if is_first() => HlTag::Attribute,
+        SCRIPT_TAG_STMT if is_first() => HlTag::Keyword,
+        TYPE_REF if is_builtin_type(&parent.text().to_string()) => HlTag::Type,
+        TYPE_REF | COMPONENT_PATH => HlTag::Component,
+        NAMED_ARG => HlTag::Parameter,
+        STRUCT_ENTRY | MEMBER_EXPR => HlTag::Property,
+        _ => return None,
+    };
+    Some(tag.into())
+}
+
+fn is_in_cfquery(token: &SyntaxToken) -> bool {
+    token
+        .parent()
+        .and_then(ast::Tag::cast)
+        .is_some_and(|it| it.name() == "cfquery")
+}
+
+/// Splits a piece of `<cfquery>` text into SQL keywords, strings, numbers
+/// and comments. `in_string` carries a string interrupted by a `#expr#`
+/// over to the next piece.
+fn highlight_sql(token: &SyntaxToken, in_string: &mut bool, hl: &mut Highlights) {
+    let start = token.text_range().start();
+    let mut push = |from: usize, to: usize, tag: HlTag| {
+        let range = TextRange::new(
+            start + TextSize::from(from as u32),
+            start + TextSize::from(to as u32),
+        );
+        hl.push(range, tag | HlMod::Sql);
+    };
+    let text = token.text();
+    let bytes = text.as_bytes();
+    let mut idx = 0;
+    if *in_string {
+        let end = sql_string_end(text, 0);
+        push(0, end.unwrap_or(text.len()), HlTag::String);
+        *in_string = end.is_none();
+        idx = end.unwrap_or(text.len());
+    }
+    while idx < text.len() {
+        let rest = &text[idx..];
+        let c = bytes[idx];
+        if rest.starts_with("--") {
+            let end = rest.find('\n').map_or(text.len(), |it| idx + it);
+            push(idx, end, HlTag::Comment);
+            idx = end;
+        } else if rest.starts_with("/*") {
+            let end = rest.find("*/").map_or(text.len(), |it| idx + it + 2);
+            push(idx, end, HlTag::Comment);
+            idx = end;
+        } else if c == b'\'' {
+            let end = sql_string_end(text, idx + 1);
+            *in_string = end.is_none();
+            let end = end.unwrap_or(text.len());
+            push(idx, end, HlTag::String);
+            idx = end;
+        } else if c.is_ascii_digit() {
+            let len = rest
+                .bytes()
+                .take_while(|it| it.is_ascii_digit() || *it == b'.')
+                .count();
+            push(idx, idx + len, HlTag::Number);
+            idx += len;
+        } else if c.is_ascii_alphabetic() || c == b'_' {
+            let len = rest
+                .bytes()
+                .take_while(|it| it.is_ascii_alphanumeric() || *it == b'_')
+                .count();
+            if is_sql_keyword(&rest[..len]) {
+                push(idx, idx + len, HlTag::Keyword);
+            }
+            idx += len;
+        } else {
+            idx += rest.chars().next().map_or(1, char::len_utf8);
+        }
+    }
+}
+
+/// The end of a single-quoted SQL string whose contents start at `from`,
+/// after its closing quote. `''` escapes a quote.
+fn sql_string_end(text: &str, from: usize) -> Option<usize> {
+    let bytes = text.as_bytes();
+    let mut idx = from;
+    while idx < bytes.len() {
+        if bytes[idx] == b'\'' {
+            if bytes.get(idx + 1) == Some(&b'\'') {
+                idx += 2;
+                continue;
+            }
+            return Some(idx + 1);
+        }
+        idx += 1;
+    }
+    None
+}
+
+const SQL_KEYWORDS: &[&str] = &[
+    "all", "and", "as", "asc", "between", "by", "case", "create", "cross", "delete", "desc",
+    "distinct", "drop", "else", "end", "exists", "from", "full", "group", "having", "in", "inner",
+    "insert", "into", "is", "join", "left", "like", "limit", "not", "null", "offset", "on", "or",
+    "order", "outer", "right", "select", "set", "table", "then", "top", "union", "update",
+    "values", "when", "where", "with",
+];
+
+fn is_sql_keyword(word: &str) -> bool {
+    SQL_KEYWORDS.iter().any(|it| it.eq_ignore_ascii_case(word))
+}
+
+/// Sorted, non-overlapping highlighted ranges.
+#[derive(Default)]
+struct Highlights {
+    ranges: Vec<HlRange>,
+}
+
+impl Highlights {
+    /// Appends a range after all existing ones.
+    fn push(&mut self, range: TextRange, highlight: Highlight) {
+        if range.is_empty() {
+            return;
+        }
+        debug_assert!(self
+            .ranges
+            .last()
+            .is_none_or(|it| it.range.end() <= range.start()));
+        self.ranges.push(HlRange { range, highlight });
+    }
+
+    /// Highlights `range`, replacing whatever it overlaps; ranges it only
+    /// partially covers keep their highlighting outside of it.
+    fn overlay(&mut self, range: TextRange, highlight: Highlight) {
+        if range.is_empty() {
+            return;
+        }
+        let start = self
+            .ranges
+            .partition_point(|it| it.range.end() <= range.start());
+        let end = self
+            .ranges
+            .partition_point(|it| it.range.start() < range.end());
+        let mut replacement = Vec::with_capacity(3);
+        if let Some(first) = self.ranges[start..end].first() {
+            if first.range.start() < range.start() {
+                replacement.push(HlRange {
+                    range: TextRange::new(first.range.start(), range.start()),
+                    highlight: first.highlight,
+                });
+            }
+        }
+        replacement.push(HlRange { range, highlight });
+        if let Some(last) = self.ranges[start..end].last() {
+            if last.range.end() > range.end() {
+                replacement.push(HlRange {
+                    range: TextRange::new(range.end(), last.range.end()),
+                    highlight: last.highlight,
+                });
+            }
+        }
+        self.ranges.splice(start..end, replacement);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixture;
+
+    use super::*;
+
+    fn check(fixture: &str, expect: &str) {
+        let fixture = fixture::parse(fixture);
+        let file_id = FileId(0);
+        let text = fixture.analysis.file_text(file_id);
+        let ranges = fixture.analysis.highlight(file_id);
+        let mut buf = String::new();
+        for it in ranges {
+            let mods: Vec<_> = it
+                .highlight
+                .mods
+                .iter()
+                .map(|it| format!("{it:?}"))
+                .collect();
+            buf.push_str(&format!(
+                "{:?} {:?} {}\n",
+                &text[it.range],
+                it.highlight.tag,
+                mods.join(",")
+            ));
+        }
+        let actual: Vec<_> = buf.lines().map(str::trim_end).collect();
+        let expect: Vec<_> = expect.trim().lines().map(str::trim).collect();
+        assert_eq!(actual, expect);
+    }
+
+    #[test]
+    fn script_component() {
+        check(
+            r#"
+//- /models/User.cfc
+component extends="Base" {
+    function save(required string name) {
+        variables.name = uCase(name);
+        var q = new models.Query();
+        return q.execute(parameterExists(x));
+    }
+}
+"#,
+            r#"
+"component" Keyword
+"extends" Attribute
+"\"" String
+"Base" Component
+"\"" String
+"function" Keyword
+"save" Method Declaration
+"required" Keyword
+"string" Type
+"name" Parameter Declaration
+"variables" Scope
+"name" Variable
+"uCase" Function DefaultLibrary
+"name" Parameter
+"var" Keyword
+"q" Variable Declaration
+"new" Keyword
+"models.Query" Component
+"return" Keyword
+"q" Variable
+"execute" Method
+"parameterExists" Function DefaultLibrary,Deprecated
+"x" Variable
+"#,
+        );
+    }
+
+    #[test]
+    fn template_islands_and_sql() {
+        check(
+            r##"
+//- /index.cfm
+<cfquery name="users">SELECT id FROM t WHERE n = '#url.n#' -- x
+</cfquery>
+<cfoutput>#helper(1)#</cfoutput>
+<cffunction name="helper"></cffunction>
+"##,
+            r##"
+"cfquery" Tag
+"name" Attribute
+"\"" String
+"users" Variable
+"\"" String
+"SELECT" Keyword Sql
+"FROM" Keyword Sql
+"WHERE" Keyword Sql
+"'" String Sql
+"#" Operator Interpolated
+"url" Scope Interpolated
+"n" Variable Interpolated
+"#" Operator Interpolated
+"'" String Sql
+"-- x" Comment Sql
+"cfquery" Tag
+"cfoutput" Tag
+"#" Operator Interpolated
+"helper" Function Interpolated
+"1" Number Interpolated
+"#" Operator Interpolated
+"cfoutput" Tag
+"cffunction" Tag
+"name" Attribute
+"\"" String
+"helper" Function Declaration
+"\"" String
+"cffunction" Tag
+"##,
+        );
+    }
+}
@@ -0,0 +1,1135 @@
+//! Converting tag-based code to cfscript: a whole tag component, or one
+//! `<cffunction>`, which is then wrapped in `<cfscript>`.
+//!
+//! Tags with a cfscript form are converted to it: `<cfquery>` becomes
+//! `queryExecute()` with its `<cfqueryparam>`s as parameters, `hint`s
+//! become docblocks and `<!--- --->` comments become `//` or `/* */`.
+//! Other built-in tags use the generic `cftag(attribute=value)` form; the
+//! conversion is not offered for code using custom tags.
+
+use syntax::{
+    ast::{self, AstNode},
+    tags, NodeOrToken, SyntaxElement,
+    SyntaxKind::*,
+    SyntaxNode, SyntaxToken, TextRange,
+};
+
+use crate::{
+    assists::{line_indent, Assist, AssistKind},
+    builtin_tags::builtin_tag,
+    db::RootDatabase,
+    lowering::{copy_expr, Syntax, Writer},
+    source_change::SourceChange,
+    text_edit::TextEdit,
+    FileRange,
+};
+
+/// Converts the component whose start tag is at `frange`, or the function
+/// whose start tag is at or which is selected by it.
+pub(crate) fn convert_to_script(db: &RootDatabase, frange: FileRange) -> Option<Assist> {
+    let file = db.parse(frange.file_id).tree();
+    let text = db.file_text(frange.file_id);
+    let node = match file.syntax().covering_element(frange.range) {
+        NodeOrToken::Node(it) => it,
+        NodeOrToken::Token(it) => it.parent()?,
+    };
+    let tag = node
+        .ancestors()
+        .filter_map(ast::Tag::cast)
+        .find(|it| {
+            matches!(
+                it.name().as_str(),
+                "cfcomponent" | "cfinterface" | "cffunction"
+            ) && it
+                .start_tag()
+                .is_some_and(|it| it.syntax().text_range().contains_range(frange.range))
+        })
+        .or_else(|| {
+            let mut functions = node.descendants().filter_map(ast::Tag::cast).filter(|it| {
+                it.name() == "cffunction" && frange.range.contains_range(it.syntax().text_range())
+            });
+            let function = functions.next()?;
+            functions.next().is_none().then_some(function)
+        })?;
+
+    let mut converter = Converter::new(&text);
+    if tag.name() != "cffunction" {
+        for element in file.syntax().children_with_tokens() {
+            match element {
+                NodeOrToken::Token(it) if it.kind() == TAG_COMMENT => converter.comment(&it),
+                NodeOrToken::Token(it) if it.kind() == TEXT && it.text().trim().is_empty() => {
+                    converter.w.space(it.text());
+                }
+                NodeOrToken::Node(it) if it == *tag.syntax() => converter.component(&tag)?,
+                _ => return None,
+            }
+        }
+        let range = TextRange::up_to(file.syntax().text_range().end());
+        return Some(assist(
+            frange,
+            "Convert component to cfscript",
+            range,
+            converter.w.buf,
+        ));
+    }
+
+    let in_interface = tag
+        .syntax()
+        .parent()
+        .and_then(ast::Tag::cast)
+        .is_some_and(|it| it.name() == "cfinterface");
+    if in_interface {
+        return None;
+    }
+    let range = tag.syntax().text_range();
+    converter.w.indent = line_indent(&text, range.start()).to_owned();
+    converter.line("<cfscript>");
+    converter.w.indent();
+    converter.function(&tag)?;
+    converter.w.dedent();
+    converter.line("</cfscript>");
+    let script = converter.w.finish();
+    Some(assist(
+        frange,
+        "Convert function to cfscript",
+        range,
+        script,
+    ))
+}
+
+fn assist(frange: FileRange, label: &str, range: TextRange, text: String) -> Assist {
+    Assist {
+        label: label.to_owned(),
+        kind: AssistKind::RefactorRewrite,
+        source_change: SourceChange::from_text_edit(frange.file_id, TextEdit::replace(range, text)),
+    }
+}
+
+/// How text in a tag body is converted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    /// Outside `<cfoutput>`: `#` is literal text.
+    Code,
+    /// Inside `<cfoutput>`: `#expr#` is evaluated.
+    Output,
+    /// Inside a `<cfquery>` with tags other than `<cfqueryparam>`, which
+    /// builds its SQL in a variable.
+    Sql,
+}
+
+struct Converter {
+    w: Writer,
+    in_function: bool,
+    in_interface: bool,
+    /// Variables renamed in converted expressions, like `cfcatch` in a
+    /// `catch` block.
+    renames: Vec<(&'static str, &'static str)>,
+}
+
+impl Converter {
+    fn new(text: &str) -> Converter {
+        Converter {
+            w: Writer::new(text, String::new()),
+            in_function: false,
+            in_interface: false,
+            renames: Vec::new(),
+        }
+    }
+
+    fn line(&mut self, line: &str) {
+        self.w.line(line);
+    }
+
+    fn open(&mut self, head: &str) {
+        self.w.line(&format!("{head} {{"));
+        self.w.indent();
+    }
+
+    /// Closes the current block and opens the next with `head`, as in
+    /// `} else {`.
+    fn reopen(&mut self, head: &str) {
+        self.w.dedent();
+        self.open(head);
+    }
+
+    fn close(&mut self) {
+        self.w.dedent();
+        self.w.line("}");
+    }
+
+    fn comment(&mut self, token: &SyntaxToken) {
+        let text = token.text();
+        let text = text.strip_prefix("<!---").unwrap_or(text);
+        let text = text.strip_suffix("--->").unwrap_or(text).trim();
+        if !text.contains('\n') {
+            self.line(format!("// {text}").trim_end());
+            return;
+        }
+        self.line("/*");
+        for line in text.lines() {
+            let line = line.trim().replace("*/", "* /");
+            self.line(format!(" * {line}").trim_end());
+        }
+        self.line(" */");
+    }
+
+    fn docblock(&mut self, lines: &[String]) {
+        if lines.is_empty() {
+            return;
+        }
+        self.line("/**");
+        for line in lines {
+            let line = line.replace("*/", "* /");
+            self.line(format!(" * {line}").trim_end());
+        }
+        self.line(" */");
+    }
+
+    fn expr(&self, node: &SyntaxNode) -> String {
+        copy_expr(node, &self.renames, Syntax::Script)
+    }
+
+    /// The value of an attribute as an expression: `"text"`, or `expr` for
+    /// `#expr#`.
+    fn attribute(&self, attribute: &ast::Attribute) -> Option<String> {
+        Some(match attribute.value()? {
+            ast::AttributeValue::Hash(it) => self.expr(it.expr()?.syntax()),
+            ast::AttributeValue::String(it) => {
+                let mut hashes = it.hash_exprs();
+                match (hashes.next(), hashes.next()) {
+                    (Some(hash), None) if hash.syntax().text_range() == it.contents_range() => {
+                        self.expr(hash.expr()?.syntax())
+                    }
+                    _ => self.expr(it.syntax()),
+                }
+            }
+            ast::AttributeValue::Unquoted(it) if is_number(it.text()) => it.text().to_owned(),
+            ast::AttributeValue::Unquoted(it) => format!("\"{}\"", it.text()),
+        })
+    }
+
+    /// The value of a metadata attribute, with numbers and booleans
+    /// unquoted: `output=false`.
+    fn metadata(&self, attribute: &ast::Attribute) -> Option<String> {
+        match attribute.value_text() {
+            Some(it) if is_number(&it) => Some(it),
+            Some(it) if it.eq_ignore_ascii_case("true") || it.eq_ignore_ascii_case("false") => {
+                Some(it.to_ascii_lowercase())
+            }
+            _ => self.attribute(attribute),
+        }
+    }
+
+    /// The `name=value` pairs of the attributes of `tag` not in `skip`.
+    fn attributes(&self, tag: &ast::Tag, skip: &[&str], metadata: bool) -> Option<Vec<String>> {
+        tag.attributes()
+            .filter(|it| !skip.contains(&it.name().as_str()))
+            .map(|it| {
+                let value = match metadata {
+                    true => self.metadata(&it)?,
+                    false => self.attribute(&it)?,
+                };
+                Some(format!("{}={value}", it.name_token()?.text()))
+            })
+            .collect()
+    }
+
+    fn component(&mut self, tag: &ast::Tag) -> Option<()> {
+        self.docblock(&hint(tag));
+        let keyword = match tag.name().as_str() {
+            "cfinterface" => "interface",
+            _ => "component",
+        };
+        let attributes = self.attributes(tag, &["hint"], true)?;
+        let head = [keyword.to_owned()].into_iter().chain(attributes);
+        self.open(&head.collect::<Vec<_>>().join(" "));
+        self.in_interface = keyword == "interface";
+        self.body(tag.syntax(), Mode::Code)?;
+        self.close();
+        Some(())
+    }
+
+    fn property(&mut self, tag: &ast::Tag) -> Option<()> {
+        self.docblock(&hint(tag));
+        let attributes = self.attributes(tag, &["hint"], true)?;
+        self.line(&format!("property {};", attributes.join(" ")));
+        Some(())
+    }
+
+    fn function(&mut self, tag: &ast::Tag) -> Option<()> {
+        let name = tag.attribute_value("name")?;
+        let arguments: Vec<_> = tag
+            .child_tags()
+            .filter(|it| it.name() == "cfargument")
+            .collect();
+
+        let mut doc = hint(tag);
+        let argument_hints: Vec<_> = arguments
+            .iter()
+            .filter_map(|it| {
+                let hint = it.attribute_value("hint")?;
+                Some(format!("@{} {}", it.attribute_value("name")?, hint.trim()))
+            })
+            .collect();
+        if !doc.is_empty() && !argument_hints.is_empty() {
+            doc.push(String::new());
+        }
+        doc.extend(argument_hints);
+        self.docblock(&doc);
+
+        let mut params = Vec::new();
+        for argument in &arguments {
+            params.push(self.param(argument)?);
+        }
+        let mut head = String::new();
+        for modifier in ["access", "returntype"] {
+            if let Some(it) = tag.attribute_value(modifier) {
+                head.push_str(&it);
+                head.push(' ');
+            }
+        }
+        head.push_str(&format!("function {name}({})", params.join(", ")));
+        for attribute in self.attributes(tag, &["name", "access", "returntype", "hint"], true)? {
+            head.push(' ');
+            head.push_str(&attribute);
+        }
+        if self.in_interface {
+            self.line(&format!("{head};"));
+            return Some(());
+        }
+        self.open(&head);
+        self.in_function = true;
+        let res = self.body(tag.syntax(), Mode::Code);
+        self.in_function = false;
+        res?;
+        self.close();
+        Some(())
+    }
+
+    /// A function parameter for `<cfargument>`.
+    fn param(&self, argument: &ast::Tag) -> Option<String> {
+        let mut param = String::new();
+        let required = argument
+            .attribute_value("required")
+            .is_some_and(|it| is_true(&it));
+        if required {
+            param.push_str("required ");
+        }
+        let ty = argument.attribute_value("type");
+        if let Some(ty) = &ty {
+            param.push_str(ty);
+            param.push(' ');
+        }
+        param.push_str(&argument.attribute_value("name")?);
+        if let Some(default) = argument.attribute("default") {
+            let is_string = ty.as_deref().is_none_or(|it| {
+                it.eq_ignore_ascii_case("string") || it.eq_ignore_ascii_case("any")
+            });
+            let default = match is_string {
+                true => self.attribute(&default)?,
+                false => self.metadata(&default)?,
+            };
+            param.push_str(&format!("={default}"));
+        }
+        let skip = ["name", "type", "required", "default", "hint"];
+        for attribute in self.attributes(argument, &skip, true)? {
+            param.push(' ');
+            param.push_str(&attribute);
+        }
+        Some(param)
+    }
+
+    /// Converts the content of `node`, without its start and end tags.
+    fn body(&mut self, node: &SyntaxNode, mode: Mode) -> Option<()> {
+        let mut text = Vec::new();
+        for element in node.children_with_tokens() {
+            match element.kind() {
+                START_TAG | END_TAG => continue,
+                TEXT | HASH_EXPR => {
+                    text.push(element);
+                    continue;
+                }
+                TAG if mode == Mode::Sql
+                    && tag_name(&element).as_deref() == Some("cfqueryparam") =>
+                {
+                    text.push(element);
+                    continue;
+                }
+                _ => (),
+            }
+            self.text(&std::mem::take(&mut text), mode)?;
+            match element {
+                NodeOrToken::Token(it) if it.kind() == TAG_COMMENT => self.comment(&it),
+                NodeOrToken::Node(it) if it.kind() == TAG => {
+                    self.tag(&ast::Tag::cast(it)?, mode)?
+                }
+                _ => return None,
+            }
+        }
+        self.text(&text, mode)
+    }
+
+    /// Converts a run of text, interpolations and, in SQL, query params.
+    fn text(&mut self, elements: &[SyntaxElement], mode: Mode) -> Option<()> {
+        let mut text = String::new();
+        let mut params = Vec::new();
+        for element in elements {
+            match element {
+                NodeOrToken::Token(it) => {
+                    let escaped = it.text().replace('"', "\"\"");
+                    match mode {
+                        Mode::Code => text.push_str(&escaped.replace('#', "##")),
+                        Mode::Output | Mode::Sql => text.push_str(&escaped),
+                    }
+                }
+                NodeOrToken::Node(it) if it.kind() == HASH_EXPR => text.push_str(&self.expr(it)),
+                NodeOrToken::Node(it) => {
+                    params.push(self.query_param(&ast::Tag::cast(it.clone())?)?);
+                    text.push('?');
+                }
+            }
+        }
+        if text.trim().is_empty() {
+            self.w.space(&text);
+            return Some(());
+        }
+        match mode {
+            Mode::Code | Mode::Output => {
+                self.line(&format!("writeOutput(\"{}\");", text.trim()));
+            }
+            Mode::Sql => {
+                let sql = text.split_whitespace().collect::<Vec<_>>().join(" ");
+                self.line(&format!("sql &= \" {sql}\";"));
+                for param in params {
+                    self.line(&format!("arrayAppend(params, {param});"));
+                }
+            }
+        }
+        Some(())
+    }
+
+    fn tag(&mut self, tag: &ast::Tag, mode: Mode) -> Option<()> {
+        let name = tag.name();
+        match name.as_str() {
+            "cfcomponent" | "cfinterface" => self.component(tag)?,
+            "cfproperty" => self.property(tag)?,
+            "cffunction" => self.function(tag)?,
+            // Converted with the tag they belong to.
+            "cfargument" | "cfelseif" | "cfelse" | "cfcatch" | "cffinally" => (),
+            "cfset" => {
+                let expr = match tag.var_decl() {
+                    Some(it) => self.expr(it.syntax()),
+                    None => self.expr(tag.expression()?.syntax()),
+                };
+                self.line(&format!("{};", expr.trim()));
+            }
+            "cfreturn" => match tag.expression() {
+                Some(it) => self.line(&format!("return {};", self.expr(it.syntax()).trim())),
+                None => self.line("return;"),
+            },
+            "cfbreak" => self.line("break;"),
+            "cfcontinue" => self.line("continue;"),
+            "cfif" => self.if_tag(tag, mode)?,
+            "cfloop" => self.loop_tag(tag, mode)?,
+            "cftry" => self.try_tag(tag, mode)?,
+            "cfswitch" => self.switch_tag(tag, mode)?,
+            "cfquery" => self.query(tag)?,
+            "cfscript" => self.script(tag)?,
+            "cfoutput" if tag.attribute("query").is_some() => {
+                let attributes = self.attributes(tag, &[], false)?;
+                self.open(&format!("loop {}", attributes.join(" ")));
+                self.body(tag.syntax(), Mode::Output)?;
+                self.close();
+            }
+            "cfoutput" => self.body(tag.syntax(), Mode::Output)?,
+            "cfinclude" => {
+                let template = tag.attribute("template")?;
+                let template = match template.value()? {
+                    ast::AttributeValue::String(it) => self.expr(it.syntax()),
+                    _ => format!("\"#{}#\"", self.attribute(&template)?),
+                };
+                self.line(&format!("include {template};"));
+            }
+            "cfthrow" | "cfdump" | "cflog" | "cflocation" => {
+                let function = match name.as_str() {
+                    "cfthrow" => "throw",
+                    "cfdump" => "writeDump",
+                    "cflog" => "writeLog",
+                    _ => "location",
+                };
+                let args = self.attributes(tag, &[], false)?;
+                self.line(&format!("{function}({});", args.join(", ")));
+            }
+            _ if name
+                .strip_prefix("cf")
+                .is_some_and(tags::is_script_statement) =>
+            {
+                let attributes = self.attributes(tag, &[], false)?;
+                let head = [name[2..].to_owned()].into_iter().chain(attributes);
+                let head = head.collect::<Vec<_>>().join(" ");
+                self.tag_body(tag, &head, ";", mode)?;
+            }
+            _ if builtin_tag(&name).is_some() => {
+                let attributes = self.attributes(tag, &[], false)?;
+                let head = format!("{name}({})", attributes.join(", "));
+                self.tag_body(tag, &head, ";", mode)?;
+            }
+            _ => return None,
+        }
+        Some(())
+    }
+
+    /// `head {` and the converted body of `tag`, or `head` and `end` if it
+    /// has none.
+    fn tag_body(&mut self, tag: &ast::Tag, head: &str, end: &str, mode: Mode) -> Option<()> {
+        if tag.end_tag().is_none() {
+            self.line(&format!("{head}{end}"));
+            return Some(());
+        }
+        self.open(head);
+        self.body(tag.syntax(), mode)?;
+        self.close();
+        Some(())
+    }
+
+    fn if_tag(&mut self, tag: &ast::Tag, mode: Mode) -> Option<()> {
+        let condition = self.expr(tag.expression()?.syntax());
+        self.open(&format!("if ({})", condition.trim()));
+        self.body(tag.syntax(), mode)?;
+        for branch in tag.child_tags() {
+            match branch.name().as_str() {
+                "cfelseif" => {
+                    let condition = self.expr(branch.expression()?.syntax());
+                    self.reopen(&format!("}} else if ({})", condition.trim()));
+                }
+                "cfelse" => self.reopen("} else"),
+                _ => continue,
+            }
+            self.body(branch.syntax(), mode)?;
+        }
+        self.close();
+        Some(())
+    }
+
+    fn loop_tag(&mut self, tag: &ast::Tag, mode: Mode) -> Option<()> {
+        let value = |name: &str| tag.attribute(name).and_then(|it| self.metadata(&it));
+        let index = tag.attribute_value("index");
+        let item = tag.attribute_value("item");
+        let head = if let (Some(index), Some(from), Some(to)) = (&index, value("from"), value("to"))
+        {
+            let (compare, step) = match value("step").as_deref() {
+                None | Some("1") => ("<=", format!("{index}++")),
+                Some("-1") => (">=", format!("{index}--")),
+                Some(step) => match step.strip_prefix('-') {
+                    Some(step) => (">=", format!("{index} -= {step}")),
+                    None => ("<=", format!("{index} += {step}")),
+                },
+            };
+            format!("for ({index} = {from}; {index} {compare} {to}; {step})")
+        } else if let Some(condition) = tag.attribute("condition") {
+            let condition = match condition.value()? {
+                ast::AttributeValue::String(it) => {
+                    let quote = it.syntax().first_token()?.text().to_owned();
+                    let text = it.syntax().text().to_string();
+                    text[1..text.len() - 1]
+                        .replace(&quote.repeat(2), &quote)
+                        .replace('#', "")
+                }
+                _ => self.attribute(&condition)?,
+            };
+            format!("while ({condition})")
+        } else if let (Some(array), Some(item)) =
+            (value("array"), item.as_ref().xor(index.as_ref()))
+        {
+            format!("for ({item} in {array})")
+        } else if let (Some(collection), Some(item)) = (value("collection"), &item) {
+            format!("for ({item} in {collection})")
+        } else if let (Some(list), Some(item)) = (value("list"), item.as_ref().or(index.as_ref())) {
+            let args = [Some(list), value("delimiters")];
+            let args = args.into_iter().flatten().collect::<Vec<_>>().join(", ");
+            format!("for ({item} in listToArray({args}))")
+        } else {
+            let attributes = self.attributes(tag, &[], false)?;
+            format!("loop {}", attributes.join(" "))
+        };
+        self.open(&head);
+        self.body(tag.syntax(), mode)?;
+        self.close();
+        Some(())
+    }
+
+    fn try_tag(&mut self, tag: &ast::Tag, mode: Mode) -> Option<()> {
+        self.open("try");
+        self.body(tag.syntax(), mode)?;
+        for clause in tag.child_tags() {
+            match clause.name().as_str() {
+                "cfcatch" => {
+                    let ty = clause.attribute_value("type");
+                    let ty = ty.as_deref().unwrap_or("any");
+                    let is_path = ty
+                        .split('.')
+                        .all(|it| it.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_'));
+                    let ty = match is_path {
+                        true => ty.to_owned(),
+                        false => format!("\"{ty}\""),
+                    };
+                    self.reopen(&format!("}} catch ({ty} e)"));
+                    self.renames.push(("cfcatch", "e"));
+                    let res = self.body(clause.syntax(), mode);
+                    self.renames.pop();
+                    res?;
+                }
+                "cffinally" => {
+                    self.reopen("} finally");
+                    self.body(clause.syntax(), mode)?;
+                }
+                _ => (),
+            }
+        }
+        self.close();
+        Some(())
+    }
+
+    fn switch_tag(&mut self, tag: &ast::Tag, mode: Mode) -> Option<()> {
+        let expression = self.attribute(&tag.attribute("expression")?)?;
+        self.open(&format!("switch ({expression})"));
+        for element in tag.syntax().children_with_tokens() {
+            let case = match element {
+                NodeOrToken::Token(it) if it.kind() == TAG_COMMENT => {
+                    self.comment(&it);
+                    continue;
+                }
+                NodeOrToken::Token(it) if it.kind() == TEXT && it.text().trim().is_empty() => {
+                    continue;
+                }
+                NodeOrToken::Node(it) if matches!(it.kind(), START_TAG | END_TAG) => continue,
+                NodeOrToken::Node(it) => ast::Tag::cast(it)?,
+                NodeOrToken::Token(_) => return None,
+            };
+            match case.name().as_str() {
+                "cfcase" => {
+                    let value = case.attribute("value")?;
+                    match value.value_text() {
+                        Some(values) => {
+                            let delimiters = case.attribute_value("delimiters");
+                            let delimiters = delimiters.as_deref().unwrap_or(",");
+                            for value in values.split(|c| delimiters.contains(c)) {
+                                self.line(&format!("case {}:", literal(value)));
+                            }
+                        }
+                        None => self.line(&format!("case {}:", self.attribute(&value)?)),
+                    }
+                    self.w.indent();
+                    self.body(case.syntax(), mode)?;
+                    self.line("break;");
+                    self.w.dedent();
+                }
+                "cfdefaultcase" => {
+                    self.line("default:");
+                    self.w.indent();
+                    self.body(case.syntax(), mode)?;
+                    self.w.dedent();
+                }
+                _ => return None,
+            }
+        }
+        self.close();
+        Some(())
+    }
+
+    fn query(&mut self, tag: &ast::Tag) -> Option<()> {
+        let assign = tag
+            .attribute_value("name")
+            .map_or_else(String::new, |it| format!("{it} = "));
+        let options = tag
+            .attributes()
+            .filter(|it| it.name() != "name")
+            .map(|it| {
+                Some(format!(
+                    "{}: {}",
+                    it.name_token()?.text(),
+                    self.attribute(&it)?
+                ))
+            })
+            .collect::<Option<Vec<_>>>()?;
+        let options = (!options.is_empty()).then(|| format!("{{{}}}", options.join(", ")));
+
+        let is_dynamic = tag
+            .syntax()
+            .descendants()
+            .skip(1)
+            .filter_map(ast::Tag::cast)
+            .any(|it| it.name() != "cfqueryparam");
+        if is_dynamic {
+            let var = if self.in_function { "var " } else { "" };
+            self.line(&format!("{var}sql = \"\";"));
+            self.line(&format!("{var}params = [];"));
+            self.body(tag.syntax(), Mode::Sql)?;
+            let options = options.map_or_else(String::new, |it| format!(", {it}"));
+            self.line(&format!("{assign}queryExecute(sql, params{options});"));
+            return Some(());
+        }
+
+        let mut sql = String::new();
+        let mut params: Vec<(String, String)> = Vec::new();
+        for element in tag.syntax().children_with_tokens() {
+            match element {
+                NodeOrToken::Token(it) if it.kind() == TAG_COMMENT => self.comment(&it),
+                NodeOrToken::Token(it) => sql.push_str(&it.text().replace('"', "\"\"")),
+                NodeOrToken::Node(it) if it.kind() == HASH_EXPR => sql.push_str(&self.expr(&it)),
+                NodeOrToken::Node(it) if it.kind() == TAG => {
+                    let param = ast::Tag::cast(it)?;
+                    let name = param_name(&param, &params);
+                    sql.push_str(&format!(":{name}"));
+                    params.push((name, self.query_param(&param)?));
+                }
+                NodeOrToken::Node(_) => (),
+            }
+        }
+        let mut args = vec![self.sql_string(&sql)];
+        if !params.is_empty() || options.is_some() {
+            let params: Vec<_> = params
+                .iter()
+                .map(|(name, value)| format!("{name}: {value}"))
+                .collect();
+            args.push(format!("{{{}}}", params.join(", ")));
+        }
+        args.extend(options);
+        self.line(&format!("{assign}queryExecute({});", args.join(", ")));
+        Some(())
+    }
+
+    /// The struct passed to `queryExecute` for a `<cfqueryparam>`.
+    fn query_param(&self, tag: &ast::Tag) -> Option<String> {
+        let entries = tag
+            .attributes()
+            .map(|it| {
+                Some(format!(
+                    "{}: {}",
+                    it.name_token()?.text(),
+                    self.attribute(&it)?
+                ))
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Some(format!("{{{}}}", entries.join(", ")))
+    }
+
+    /// A string literal of SQL, on its own lines if it spans several.
+    fn sql_string(&self, sql: &str) -> String {
+        let sql = sql.trim();
+        if !sql.contains('\n') {
+            return format!("\"{sql}\"");
+        }
+        let indent = self.w.nested_indent();
+        let mut buf = format!("\"{}", self.w.newline);
+        for line in dedent(sql) {
+            if !line.is_empty() {
+                buf.push_str(&indent);
+                buf.push_str(line);
+            }
+            buf.push_str(self.w.newline);
+        }
+        buf.push_str(&indent[..indent.len() - self.w.unit.len()]);
+        buf.push('"');
+        buf
+    }
+
+    /// Inlines the statements of `<cfscript>`.
+    fn script(&mut self, tag: &ast::Tag) -> Option<()> {
+        let script = tag.script()?.syntax().text().to_string();
+        for line in dedent(script.trim()) {
+            match line.is_empty() {
+                true => self.w.blank_line(),
+                false => self.line(line),
+            }
+        }
+        Some(())
+    }
+}
+
+/// The lines of `text`, without the indentation common to all but the
+/// first, which is assumed to be trimmed.
+fn dedent(text: &str) -> Vec<&str> {
+    let lines: Vec<_> = text.lines().map(str::trim_end).collect();
+    let common = lines
+        .iter()
+        .skip(1)
+        .filter(|it| !it.is_empty())
+        .map(|it| it.len() - it.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    let mut res = vec![lines[0]];
+    res.extend(lines[1..].iter().map(|it| &it[common.min(it.len())..]));
+    res
+}
+
+/// The lines of the `hint` of `tag`.
+fn hint(tag: &ast::Tag) -> Vec<String> {
+    let Some(hint) = tag.attribute_value("hint") else {
+        return Vec::new();
+    };
+    hint.trim().lines().map(|it| it.trim().to_owned()).collect()
+}
+
+/// A name for the parameter of a `<cfqueryparam>`: the variable its value
+/// comes from, made unique among `params`.
+fn param_name(tag: &ast::Tag, params: &[(String, String)]) -> String {
+    let expr = tag.attribute("value").and_then(|it| match it.value()? {
+        ast::AttributeValue::Hash(it) => it.expr(),
+        ast::AttributeValue::String(it) => it.hash_exprs().next()?.expr(),
+        ast::AttributeValue::Unquoted(_) => None,
+    });
+    let name = match expr {
+        Some(ast::Expr::NameRef(it)) => it.name(),
+        Some(ast::Expr::MemberExpr(it)) => it
+            .name_token()
+            .map_or_else(String::new, |it| it.text().to_owned()),
+        _ => String::new(),
+    };
+    let name = if name.is_empty() {
+        "param".to_owned()
+    } else {
+        name
+    };
+    let is_taken = |it: &str| params.iter().any(|(name, _)| name.eq_ignore_ascii_case(it));
+    if !is_taken(&name) && name != "param" {
+        return name;
+    }
+    (1..)
+        .map(|n| format!("{name}{n}"))
+        .find(|it| !is_taken(it))
+        .unwrap()
+}
+
+fn tag_name(element: &SyntaxElement) -> Option<String> {
+    Some(ast::Tag::cast(element.as_node()?.clone())?.name())
+}
+
+fn is_number(text: &str) -> bool {
+    !text.is_empty()
+        && text.parse::<f64>().is_ok()
+        && text
+            .chars()
+            .all(|c| c.is_ascii_digit() || c == '.' || c == '-')
+}
+
+fn is_true(text: &str) -> bool {
+    text.eq_ignore_ascii_case("true") || text.eq_ignore_ascii_case("yes")
+}
+
+/// A literal for a `<cfcase>` value.
+fn literal(value: &str) -> String {
+    match is_number(value) {
+        true => value.to_owned(),
+        false => format!("\"{}\"", value.replace('"', "\"\"").replace('#', "##")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use virtual_fs::FileId;
+
+    use crate::{fixture, FileRange};
+
+    /// The first file after the conversion offered at the `$0` cursor, or
+    /// for the selection between two `$0` markers.
+    fn check(label: &str, fixture: &str, expect: &str) {
+        let (fixture, range) = fixture::selection(fixture);
+        let analysis = fixture::parse(&fixture).analysis;
+        let assist = analysis
+            .assists(FileRange {
+                file_id: FileId(0),
+                range,
+            })
+            .into_iter()
+            .find(|it| it.label == label)
+            .expect("no conversion");
+        let mut text = analysis.file_text(FileId(0)).to_string();
+        assist.source_change.source_file_edits[&FileId(0)].apply(&mut text);
+        assert_eq!(text, expect);
+    }
+
+    fn check_not_offered(fixture: &str) {
+        let (fixture, range) = fixture::selection(fixture);
+        let analysis = fixture::parse(&fixture).analysis;
+        let assists = analysis.assists(FileRange {
+            file_id: FileId(0),
+            range,
+        });
+        assert!(
+            assists.iter().all(|it| !it.label.contains("cfscript")),
+            "{assists:?}"
+        );
+    }
+
+    #[test]
+    fn converts_component() {
+        check(
+            "Convert component to cfscript",
+            r##"
+//- /User.cfc
+<!--- Users of the site --->
+<cfcomponent hint="A user" extends="Base" out$0put="false">
+    <cfproperty name="name" type="string" hint="The full name">
+
+    <!---
+        Finds a user.
+        Returns an empty query if there is none.
+    --->
+    <cffunction name="find" access="public" returntype="query" hint="Finds a user" output="false">
+        <cfargument name="id" type="numeric" required="true" hint="The id">
+        <cfargument name="active" type="boolean" default="true">
+        <cfargument name="role" default="admin">
+        <cfset var q = "">
+        <cfquery name="q" datasource="#variables.dsn#">
+            SELECT *
+            FROM users
+            WHERE id = <cfqueryparam value="#arguments.id#" cfsqltype="cf_sql_integer">
+                AND role = <cfqueryparam value="#arguments.role#" cfsqltype="cf_sql_varchar">
+        </cfquery>
+        <cfreturn q>
+    </cffunction>
+
+    <cffunction name="count" returntype="numeric">
+        <cfquery name="local.total">SELECT COUNT(*) AS n FROM users</cfquery>
+        <cfreturn local.total.n>
+    </cffunction>
+</cfcomponent>
+"##,
+            r##"// Users of the site
+/**
+ * A user
+ */
+component extends="Base" output=false {
+    /**
+     * The full name
+     */
+    property name="name" type="string";
+
+    /*
+     * Finds a user.
+     * Returns an empty query if there is none.
+     */
+    /**
+     * Finds a user
+     *
+     * @id The id
+     */
+    public query function find(required numeric id, boolean active=true, role="admin") output=false {
+        var q = "";
+        q = queryExecute("
+            SELECT *
+            FROM users
+            WHERE id = :id
+                AND role = :role
+        ", {id: {value: arguments.id, cfsqltype: "cf_sql_integer"}, role: {value: arguments.role, cfsqltype: "cf_sql_varchar"}}, {datasource: variables.dsn});
+        return q;
+    }
+
+    numeric function count() {
+        local.total = queryExecute("SELECT COUNT(*) AS n FROM users");
+        return local.total.n;
+    }
+}
+"##,
+        );
+    }
+
+    #[test]
+    fn converts_control_flow() {
+        check(
+            "Convert component to cfscript",
+            r##"
+//- /Flow.cfc
+<cfcomp$0onent>
+	<cffunction name="run">
+		<cfif arguments.x gt 1>
+			<cfset y = 1>
+		<cfelseif arguments.x eq 0>
+			<cfset y = 0>
+		<cfelse>
+			<cfoutput>#y# is "negative"</cfoutput>
+		</cfif>
+		<cfloop index="i" from="10" to="1" step="-1">
+			<cfcontinue>
+		</cfloop>
+		<cfloop array="#items#" index="item"><cfbreak></cfloop>
+		<cfloop list="a;b" index="letter" delimiters=";"></cfloop>
+		<cfloop condition="i lt 10"><cfset i++></cfloop>
+		<cftry>
+			<cfinclude template="helpers.cfm">
+		<cfcatch type="database">
+			<cflog text="#cfcatch.message#" type="error">
+			<cfrethrow>
+		</cfcatch>
+		<cffinally>
+			<cflock name="done" timeout="5" type="exclusive"><cfset done = true></cflock>
+		</cffinally>
+		</cftry>
+		<cfswitch expression="#y#">
+			<cfcase value="1,2"><cfset z = "low"></cfcase>
+			<cfdefaultcase><cfthrow type="Range" message="Too high"></cfdefaultcase>
+		</cfswitch>
+		<cfscript>
+			z = y * 2;
+			if (z) {
+				z--;
+			}
+		</cfscript>
+		<cfhttp url="http://example.com" result="res">
+	</cffunction>
+</cfcomponent>
+"##,
+            r##"component {
+	function run() {
+		if (arguments.x gt 1) {
+			y = 1;
+		} else if (arguments.x eq 0) {
+			y = 0;
+		} else {
+			writeOutput("#y# is ""negative""");
+		}
+		for (i = 10; i >= 1; i--) {
+			continue;
+		}
+		for (item in items) {
+			break;
+		}
+		for (letter in listToArray("a;b", ";")) {
+		}
+		while (i lt 10) {
+			i++;
+		}
+		try {
+			include "helpers.cfm";
+		} catch (database e) {
+			writeLog(text=e.message, type="error");
+			rethrow;
+		} finally {
+			lock name="done" timeout="5" type="exclusive" {
+				done = true;
+			}
+		}
+		switch (y) {
+			case 1:
+			case 2:
+				z = "low";
+				break;
+			default:
+				throw(type="Range", message="Too high");
+		}
+		z = y * 2;
+		if (z) {
+			z--;
+		}
+		http url="http://example.com" result="res";
+	}
+}
+"##,
+        );
+    }
+
+    #[test]
+    fn converts_dynamic_query() {
+        check(
+            "Convert function to cfscript",
+            r##"
+//- /Search.cfc
+<cfcomponent>
+    <cffunction name="search">
+        <cfquery name="local.q">
+            SELECT * FROM users WHERE 1 = 1
+            <cfif len(arguments.name)>
+                AND name = <cfqueryparam value="#arguments.name#">
+            </cfif>
+        </cfquery>
+    </cffunction>
+    $0<cffunction name="other">
+        <!--- # is literal here --->
+        <p>#x#</p>
+    </cffunction>$0
+</cfcomponent>
+"##,
+            r##"<cfcomponent>
+    <cffunction name="search">
+        <cfquery name="local.q">
+            SELECT * FROM users WHERE 1 = 1
+            <cfif len(arguments.name)>
+                AND name = <cfqueryparam value="#arguments.name#">
+            </cfif>
+        </cfquery>
+    </cffunction>
+    <cfscript>
+        function other() {
+            // # is literal here
+            writeOutput("<p>##x##</p>");
+        }
+    </cfscript>
+</cfcomponent>
+"##,
+        );
+        check(
+            "Convert function to cfscript",
+            r##"
+//- /Search.cfc
+<cfcomponent>
+    <cffunction name="se$0arch">
+        <cfquery name="local.q">
+            SELECT * FROM users WHERE 1 = 1
+            <cfif len(arguments.name)>
+                AND name = <cfqueryparam value="#arguments.name#">
+            </cfif>
+        </cfquery>
+    </cffunction>
+</cfcomponent>
+"##,
+            r##"<cfcomponent>
+    <cfscript>
+        function search() {
+            var sql = "";
+            var params = [];
+            sql &= " SELECT * FROM users WHERE 1 = 1";
+            if (len(arguments.name)) {
+                sql &= " AND name = ?";
+                arrayAppend(params, {value: arguments.name});
+            }
+            local.q = queryExecute(sql, params);
+        }
+    </cfscript>
+</cfcomponent>
+"##,
+        );
+    }
+
+    #[test]
+    fn not_offered() {
+        check_not_offered(
+            r#"
+//- /a.cfc
+<cfcomponent>
+    <cffunction na$0me="f">
+        <cf_widget name="x">
+    </cffunction>
+</cfcomponent>
+"#,
+        );
+        check_not_offered(
+            r#"
+//- /a.cfc
+<cfcomponent>
+    <cffunction name="f">
+        <cfset x = $01>
+    </cffunction>
+</cfcomponent>
+"#,
+        );
+        check_not_offered(
+            r#"
+//- /a.cfm
+<p>Hi</p>
+<cfcomponent$0>
+</cfcomponent>
+"#,
+        );
+    }
+}
@@ -0,0 +1,475 @@
+//! Where values come from: an expression is traced back through the
+//! variables assigned earlier in the same function to the sources of a
+//! security rule, like the `url` scope, and to query columns.
+//!
+//! The trace crosses functions: a value read from an argument is traced
+//! into what the callers of the function pass, and a call of a declared
+//! function into what it returns. Calls of sanitizing functions end it.
+
+use std::sync::Arc;
+
+use rustc_hash::FxHashSet;
+use syntax::{
+    ast::{self, AstNode},
+    SyntaxKind::*,
+    SyntaxNode, TextRange,
+};
+use virtual_fs::FileId;
+
+use crate::{
+    db::RootDatabase,
+    references::{for_each_call_to, resolve_call},
+    usages::{scoped_name, Access, FileUsages, VarScope},
+};
+
+/// Where values the request controls come from: scopes, and functions
+/// written with `()`.
+pub(crate) const SOURCES: &str = "url,form,cgi,cookie,getHTTPRequestData()";
+
+/// Functions that only return numbers, dates or booleans, and so are safe
+/// wherever their result ends up.
+pub(crate) const SAFE_FUNCTIONS: &str = "val,int,fix,round,ceiling,floor,abs,numberFormat,\
+    decimalFormat,createODBCDate,createODBCDateTime,createODBCTime,isNumeric,isValid,\
+    yesNoFormat,trueFalseFormat,len";
+
+/// How many assignments, calls and returns back a value is traced.
+const TRACE_DEPTH: usize = 4;
+
+/// Query columns that hold numbers or column names rather than data.
+const QUERY_METADATA: &[&str] = &["currentrow", "recordcount", "columnlist"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Origin {
+    /// A source scope, by its lowercase name.
+    Scope(String),
+    /// A call of a source function, by its name as written.
+    Function(String),
+    /// An argument no caller passes a tainted value for.
+    Argument,
+    /// A column of the query with this name.
+    Query(String),
+}
+
+impl Origin {
+    pub(crate) fn describe(&self) -> String {
+        match self {
+            Origin::Scope(scope) => format!("the `{scope}` scope"),
+            Origin::Function(name) => format!("`{name}()`"),
+            Origin::Argument => "an argument".to_owned(),
+            Origin::Query(name) => format!("the query `{name}`"),
+        }
+    }
+
+    /// Whether the value comes from one of the sources.
+    pub(crate) fn is_source(&self) -> bool {
+        matches!(self, Origin::Scope(_) | Origin::Function(_))
+    }
+}
+
+/// The sources and sanitizers of a security rule.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct TaintSpec {
+    /// Lowercase scope names.
+    scopes: Vec<String>,
+    /// Lowercase function names.
+    functions: Vec<String>,
+    /// Lowercase function names.
+    sanitizers: Vec<String>,
+}
+
+impl TaintSpec {
+    /// A spec from a comma-separated list of sources, functions written
+    /// with `()`, and the names of the sanitizers.
+    pub(crate) fn new(sources: &str, sanitizers: Vec<String>) -> Self {
+        let mut spec = TaintSpec {
+            sanitizers,
+            ..TaintSpec::default()
+        };
+        for source in function_list(sources) {
+            match source.strip_suffix("()") {
+                Some(function) => spec.functions.push(function.to_owned()),
+                None => spec.scopes.push(source),
+            }
+        }
+        spec
+    }
+}
+
+pub(crate) struct Tracer<'a> {
+    db: &'a RootDatabase,
+    spec: &'a TaintSpec,
+    file_id: FileId,
+    file: ast::SourceFile,
+    usages: Arc<FileUsages>,
+    /// The lowercase names of the variables holding queries.
+    queries: FxHashSet<String>,
+}
+
+impl<'a> Tracer<'a> {
+    pub(crate) fn new(db: &'a RootDatabase, file_id: FileId, spec: &'a TaintSpec) -> Self {
+        let file = db.parse(file_id).tree();
+        Tracer {
+            db,
+            spec,
+            file_id,
+            queries: query_names(&file),
+            file,
+            usages: db.usages(file_id),
+        }
+    }
+
+    /// Where the value of `expr` comes from, if anywhere of interest.
+    pub(crate) fn origin(&self, expr: &ast::Expr) -> Option<Origin> {
+        self.trace(expr, TRACE_DEPTH)
+    }
+
+    /// Whether `expr` is a call of one of the sanitizers.
+    pub(crate) fn is_sanitized(&self, expr: &ast::Expr) -> bool {
+        called_function(expr).is_some_and(|it| self.spec.sanitizers.contains(&it))
+    }
+
+    fn trace(&self, expr: &ast::Expr, depth: usize) -> Option<Origin> {
+        match expr {
+            ast::Expr::ParenExpr(it) => self.trace(&it.expr()?, depth),
+            ast::Expr::HashExpr(it) => self.trace(&it.expr()?, depth),
+            ast::Expr::BinExpr(it) => it
+                .lhs()
+                .and_then(|lhs| self.trace(&lhs, depth))
+                .or_else(|| it.rhs().and_then(|rhs| self.trace(&rhs, depth))),
+            ast::Expr::TernaryExpr(it) => it
+                .then_expr()
+                .and_then(|then| self.trace(&then, depth))
+                .or_else(|| it.else_expr().and_then(|it| self.trace(&it, depth))),
+            ast::Expr::StringLit(it) => it
+                .hash_exprs()
+                .find_map(|hash| self.trace(&hash.expr()?, depth)),
+            ast::Expr::CallExpr(call) => {
+                if self.is_sanitized(expr) {
+                    return None;
+                }
+                if let Some(name) = called_function(expr) {
+                    if self.spec.functions.contains(&name) {
+                        let callee = call.callee()?.syntax().text().to_string();
+                        return Some(Origin::Function(callee));
+                    }
+                }
+                match self.return_origin(call, depth) {
+                    Some(Origin::Argument) | None => self.args_origin(call, depth),
+                    origin => origin,
+                }
+            }
+            ast::Expr::MemberExpr(member) => {
+                if let Some((scope, name)) = scoped_name(expr) {
+                    return match self.scope_origin(&scope) {
+                        Some(origin) => Some(origin),
+                        None => self.variable_origin(name.text_range(), depth),
+                    };
+                }
+                let receiver = member.receiver()?;
+                self.query_origin(
+                    &receiver,
+                    member.name_token().map(|it| it.text().to_owned()),
+                )
+                .or_else(|| self.trace(&receiver, depth))
+            }
+            ast::Expr::IndexExpr(it) => {
+                let base = it.base()?;
+                if let ast::Expr::NameRef(name) = &base {
+                    let scope = name.name().to_ascii_lowercase();
+                    if scope == "arguments" {
+                        return Some(Origin::Argument);
+                    }
+                    if let Some(origin) = self.scope_origin(&scope) {
+                        return Some(origin);
+                    }
+                }
+                self.query_origin(&base, None)
+                    .or_else(|| self.trace(&base, depth))
+            }
+            ast::Expr::NameRef(it) => {
+                let range = it.ident_token()?.text_range();
+                self.variable_origin(range, depth)
+                    .or_else(|| self.column_origin(it))
+            }
+            _ => None,
+        }
+    }
+
+    fn scope_origin(&self, scope: &str) -> Option<Origin> {
+        self.spec
+            .scopes
+            .iter()
+            .any(|it| it == scope)
+            .then(|| Origin::Scope(scope.to_owned()))
+    }
+
+    /// The first origin among the arguments of `call`.
+    fn args_origin(&self, call: &ast::CallExpr, depth: usize) -> Option<Origin> {
+        call.args().find_map(|arg| {
+            let value = match arg {
+                ast::Arg::Positional(it) => it,
+                ast::Arg::Named(it) => it.value()?,
+            };
+            self.trace(&value, depth)
+        })
+    }
+
+    /// The origin of what the function `call` resolves to returns, if it is
+    /// declared somewhere.
+    fn return_origin(&self, call: &ast::CallExpr, depth: usize) -> Option<Origin> {
+        if depth == 0 {
+            return None;
+        }
+        let range = call.syntax().text_range();
+        let site = self.usages.calls.iter().find(|it| it.range == range)?;
+        let (decl_file, function) = resolve_call(self.db, self.file_id, site)?;
+        let tracer = self.in_file(decl_file);
+        let node = node_at(&tracer.file, function.range)?;
+        let mut origin = None;
+        for value in returned_values(&node) {
+            match tracer.trace(&value, depth - 1) {
+                Some(Origin::Argument) => origin = Some(Origin::Argument),
+                Some(it) => return Some(it),
+                None => {}
+            }
+        }
+        origin
+    }
+
+    /// The query `receiver` names, when a column of it is read.
+    fn query_origin(&self, receiver: &ast::Expr, column: Option<String>) -> Option<Origin> {
+        let ast::Expr::NameRef(name) = receiver else {
+            return None;
+        };
+        let query = name.name();
+        let is_metadata = column.is_some_and(|it| {
+            QUERY_METADATA
+                .iter()
+                .any(|meta| meta.eq_ignore_ascii_case(&it))
+        });
+        let is_query = self.queries.contains(&query.to_ascii_lowercase());
+        (is_query && !is_metadata).then_some(Origin::Query(query))
+    }
+
+    /// The query whose column an unscoped name inside a `<cfoutput>` or
+    /// `<cfloop>` over that query reads, unless the name is a variable
+    /// assigned somewhere.
+    fn column_origin(&self, name_ref: &ast::NameRef) -> Option<Origin> {
+        let name = name_ref.name();
+        if QUERY_METADATA
+            .iter()
+            .any(|it| it.eq_ignore_ascii_case(&name))
+        {
+            return None;
+        }
+        let assigned = self.usages.vars.iter().any(|it| {
+            (it.access == Access::Write || it.is_decl) && it.name.eq_ignore_ascii_case(&name)
+        });
+        if assigned {
+            return None;
+        }
+        name_ref
+            .syntax()
+            .ancestors()
+            .filter_map(ast::Tag::cast)
+            .filter(|it| matches!(it.name().as_str(), "cfoutput" | "cfloop"))
+            .find_map(|it| it.attribute_value("query"))
+            .map(Origin::Query)
+    }
+
+    /// The origin of the variable read at `range`: what callers pass for an
+    /// argument, or the origin of a value assigned to it earlier in the same
+    /// function.
+    fn variable_origin(&self, range: TextRange, depth: usize) -> Option<Origin> {
+        let read = self.usages.var_at(range.start())?;
+        if let VarScope::Arguments { owner } = read.scope {
+            return self
+                .caller_origin(owner, &read.name, depth)
+                .or(Some(Origin::Argument));
+        }
+        if depth == 0 {
+            return None;
+        }
+        self.usages
+            .vars
+            .iter()
+            .filter(|it| {
+                it.access == Access::Write
+                    && it.scope == read.scope
+                    && it.function == read.function
+                    && it.range.end() <= read.range.start()
+                    && it.name.eq_ignore_ascii_case(&read.name)
+            })
+            .filter_map(|it| assigned_value(&self.file, it.range))
+            .find_map(|value| self.trace(&value, depth - 1))
+    }
+
+    /// The first origin other than an argument of the values callers pass
+    /// for the parameter `param` of the function spanning `function`.
+    fn caller_origin(&self, function: TextRange, param: &str, depth: usize) -> Option<Origin> {
+        if depth == 0 {
+            return None;
+        }
+        let items = self.db.items(self.file_id);
+        let info = items.functions.iter().find(|it| it.range == function)?;
+        let index = info
+            .params
+            .iter()
+            .position(|it| it.name.eq_ignore_ascii_case(param))?;
+        let mut origin = None;
+        for_each_call_to(self.db, self.file_id, &info.name, |call_file, call| {
+            if origin.is_some() {
+                return;
+            }
+            let arg = call
+                .args
+                .iter()
+                .find(|it| {
+                    it.name
+                        .as_ref()
+                        .is_some_and(|(name, _)| name.eq_ignore_ascii_case(param))
+                })
+                .or_else(|| call.args.iter().filter(|it| it.name.is_none()).nth(index));
+            let Some(arg) = arg else {
+                return;
+            };
+            let tracer = self.in_file(call_file);
+            let value =
+                node_at(&tracer.file, arg.range).and_then(|node| {
+                    match ast::NamedArg::cast(node.clone()) {
+                        Some(named) => named.value(),
+                        None => node.children().find_map(ast::Expr::cast),
+                    }
+                });
+            origin = value
+                .and_then(|it| tracer.trace(&it, depth - 1))
+                .filter(|it| *it != Origin::Argument);
+        });
+        origin
+    }
+
+    fn in_file(&self, file_id: FileId) -> Tracer<'a> {
+        Tracer::new(self.db, file_id, self.spec)
+    }
+}
+
+/// The lowercase name of the function `expr` calls, through parentheses.
+pub(crate) fn called_function(expr: &ast::Expr) -> Option<String> {
+    match expr {
+        ast::Expr::ParenExpr(it) => called_function(&it.expr()?),
+        ast::Expr::CallExpr(call) => match call.callee()? {
+            ast::Expr::NameRef(name) => Some(name.name().to_ascii_lowercase()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Splits a comma-separated list of names into lowercase names.
+pub(crate) fn function_list(functions: &str) -> Vec<String> {
+    functions
+        .split(',')
+        .map(|it| it.trim().to_ascii_lowercase())
+        .filter(|it| !it.is_empty())
+        .collect()
+}
+
+/// The outermost node spanning exactly `range`.
+fn node_at(file: &ast::SourceFile, range: TextRange) -> Option<SyntaxNode> {
+    let element = file.syntax().covering_element(range);
+    element
+        .ancestors()
+        .take_while(|it| it.text_range() == range)
+        .last()
+}
+
+/// The values the function `function` returns, by `return` statements and
+/// `<cfreturn>` tags outside nested functions.
+fn returned_values(function: &SyntaxNode) -> Vec<ast::Expr> {
+    function
+        .descendants()
+        .filter(|node| {
+            node.ancestors()
+                .skip(1)
+                .find(is_function)
+                .is_some_and(|it| it == *function)
+        })
+        .filter_map(|node| {
+            if node.kind() == RETURN_STMT {
+                return node.children().find_map(ast::Expr::cast);
+            }
+            let tag = ast::Tag::cast(node)?;
+            (tag.name() == "cfreturn")
+                .then(|| tag.expression())
+                .flatten()
+        })
+        .collect()
+}
+
+fn is_function(node: &SyntaxNode) -> bool {
+    match node.kind() {
+        FUNCTION_DECL | CLOSURE_EXPR | ARROW_EXPR => true,
+        _ => ast::Tag::cast(node.clone()).is_some_and(|it| it.name() == "cffunction"),
+    }
+}
+
+/// The value assigned to the variable named at `range`, by `x = value` or
+/// `var x = value`.
+fn assigned_value(file: &ast::SourceFile, range: TextRange) -> Option<ast::Expr> {
+    let element = file.syntax().covering_element(range);
+    for node in element.ancestors() {
+        if let Some(decl) = ast::VarDecl::cast(node.clone()) {
+            return decl.initializer();
+        }
+        if let Some(assign) = ast::AssignExpr::cast(node) {
+            let target = assign.target()?.syntax().text_range();
+            return (target.end() == range.end())
+                .then(|| assign.value())
+                .flatten();
+        }
+    }
+    None
+}
+
+/// The names of the queries of a file: those `<cfquery>` creates, that
+/// `<cfoutput>` and `<cfloop>` loop over and that `queryExecute` results
+/// are assigned to.
+fn query_names(file: &ast::SourceFile) -> FxHashSet<String> {
+    let mut names = FxHashSet::default();
+    for node in file.syntax().descendants() {
+        if let Some(tag) = ast::Tag::cast(node.clone()) {
+            let name = match tag.name().as_str() {
+                "cfquery" => tag.attribute_value("name"),
+                "cfoutput" | "cfloop" => tag.attribute_value("query"),
+                _ => None,
+            };
+            names.extend(name.map(|it| it.to_ascii_lowercase()));
+            continue;
+        }
+        let (target, value) = if let Some(decl) = ast::VarDecl::cast(node.clone()) {
+            (
+                decl.name_token().map(|it| it.text().to_owned()),
+                decl.initializer(),
+            )
+        } else if let Some(assign) = ast::AssignExpr::cast(node) {
+            let target = match assign.target() {
+                Some(ast::Expr::NameRef(it)) => Some(it.name()),
+                Some(target @ ast::Expr::MemberExpr(_)) => {
+                    scoped_name(&target).map(|(_, name)| name.text().to_owned())
+                }
+                _ => None,
+            };
+            (target, assign.value())
+        } else {
+            continue;
+        };
+        let is_query = value
+            .as_ref()
+            .and_then(called_function)
+            .is_some_and(|it| it == "queryexecute");
+        if let (Some(target), true) = (target, is_query) {
+            names.insert(target.to_ascii_lowercase());
+        }
+    }
+    names
+}
@@ -0,0 +1,189 @@
+//! Edits to the text of a single file.
+
+use syntax::{TextRange, TextSize};
+
+/// Replaces `delete` with `insert`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Indel {
+    pub insert: String,
+    pub delete: TextRange,
+}
+
+impl Indel {
+    pub fn insert(offset: TextSize, text: String) -> Indel {
+        Indel::replace(TextRange::empty(offset), text)
+    }
+
+    pub fn delete(range: TextRange) -> Indel {
+        Indel::replace(range, String::new())
+    }
+
+    pub fn replace(range: TextRange, replace_with: String) -> Indel {
+        Indel {
+            insert: replace_with,
+            delete: range,
+        }
+    }
+}
+
+/// A set of non-overlapping [`Indel`]s, sorted by offset.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+    indels: Vec<Indel>,
+}
+
+impl TextEdit {
+    pub fn builder() -> TextEditBuilder {
+        TextEditBuilder::default()
+    }
+
+    pub fn insert(offset: TextSize, text: String) -> TextEdit {
+        let mut builder = TextEdit::builder();
+        builder.insert(offset, text);
+        builder.finish()
+    }
+
+    pub fn delete(range: TextRange) -> TextEdit {
+        let mut builder = TextEdit::builder();
+        builder.delete(range);
+        builder.finish()
+    }
+
+    pub fn replace(range: TextRange, replace_with: String) -> TextEdit {
+        let mut builder = TextEdit::builder();
+        builder.replace(range, replace_with);
+        builder.finish()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.indels.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.indels.len()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Indel> {
+        self.indels.iter()
+    }
+
+    pub fn apply(&self, text: &mut String) {
+        for indel in self.indels.iter().rev() {
+            text.replace_range(std::ops::Range::<usize>::from(indel.delete), &indel.insert);
+        }
+    }
+
+    /// Merges `other` into `self`, failing if any of the edits overlap.
+    pub fn union(&mut self, other: TextEdit) -> Result<(), TextEdit> {
+        let mut indels = self.indels.clone();
+        indels.extend(other.indels.iter().cloned());
+        indels.sort_by_key(|it| (it.delete.start(), it.delete.end()));
+        if !check_disjoint(&indels) {
+            return Err(other);
+        }
+        self.indels = indels;
+        Ok(())
+    }
+}
+
+impl IntoIterator for TextEdit {
+    type Item = Indel;
+    type IntoIter = std::vec::IntoIter<Indel>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.indels.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a TextEdit {
+    type Item = &'a Indel;
+    type IntoIter = std::slice::Iter<'a, Indel>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.indels.iter()
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct TextEditBuilder {
+    indels: Vec<Indel>,
+}
+
+impl TextEditBuilder {
+    pub fn is_empty(&self) -> bool {
+        self.indels.is_empty()
+    }
+
+    pub fn replace(&mut self, range: TextRange, replace_with: String) {
+        self.indel(Indel::replace(range, replace_with));
+    }
+
+    pub fn delete(&mut self, range: TextRange) {
+        self.indel(Indel::delete(range));
+    }
+
+    pub fn insert(&mut self, offset: TextSize, text: String) {
+        self.indel(Indel::insert(offset, text));
+    }
+
+    pub fn indel(&mut self, indel: Indel) {
+        self.indels.push(indel);
+    }
+
+    /// Sorts the edits and drops exact duplicates, which arise naturally
+    /// when several references resolve to the same range.
+    ///
+    /// # Panics
+    ///
+    /// Panics if two different edits overlap.
+    pub fn finish(self) -> TextEdit {
+        let mut indels = self.indels;
+        indels.sort_by_key(|it| (it.delete.start(), it.delete.end()));
+        indels.dedup();
+        assert!(check_disjoint(&indels), "overlapping edits: {indels:?}");
+        TextEdit { indels }
+    }
+}
+
+fn check_disjoint(indels: &[Indel]) -> bool {
+    indels.windows(2).all(|it| {
+        let (lhs, rhs) = (&it[0], &it[1]);
+        lhs.delete.end() <= rhs.delete.start()
+            && !(lhs.delete.is_empty() && rhs.delete.is_empty() && lhs.delete == rhs.delete)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(start: u32, end: u32) -> TextRange {
+        TextRange::new(start.into(), end.into())
+    }
+
+    #[test]
+    fn applies_edits_back_to_front() {
+        let mut builder = TextEdit::builder();
+        builder.replace(range(0, 1), "xyz".to_owned());
+        builder.insert(3.into(), "!".to_owned());
+        builder.delete(range(4, 5));
+        let edit = builder.finish();
+
+        let mut text = "abcde".to_owned();
+        edit.apply(&mut text);
+        assert_eq!(text, "xyzbc!d");
+    }
+
+    #[test]
+    fn deduplicates_and_rejects_overlaps() {
+        let mut builder = TextEdit::builder();
+        builder.replace(range(0, 1), "x".to_owned());
+        builder.replace(range(0, 1), "x".to_owned());
+        let mut edit = builder.finish();
+        assert_eq!(edit.len(), 1);
+
+        assert!(edit.union(TextEdit::delete(range(0, 2))).is_err());
+        assert!(edit.union(TextEdit::delete(range(1, 2))).is_ok());
+        assert_eq!(edit.len(), 2);
+    }
+}
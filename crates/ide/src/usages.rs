@@ -0,0 +1,1235 @@
+//! The uses of names in a file: variables resolved to the scope they live
+//! in, function calls, and dotted component paths.
+//!
+//! Resolution follows the CFML lookup rules for unscoped names: inside a
+//! function a name is looked up in `local`, then `arguments`, then the
+//! scopes of enclosing closures, and finally `variables`. Assigning an
+//! undeclared unscoped name inside a function writes to `variables`.
+
+use rustc_hash::FxHashMap;
+use syntax::{
+    ast::{self, AstNode},
+    SyntaxKind::{self, *},
+    SyntaxNode, SyntaxToken, TextRange, TextSize, WalkEvent,
+};
+
+use crate::items::{is_scope_owner, path_refs, FunctionNode, PathRef};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Access {
+    Read,
+    Write,
+}
+
+/// The scope a variable resolves to.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum VarScope {
+    /// The `local` scope of the function or closure spanning `owner`.
+    Local { owner: TextRange },
+    /// The `arguments` scope of the function or closure spanning `owner`.
+    Arguments { owner: TextRange },
+    /// The `variables` scope of the page or component.
+    Variables,
+    /// The `this` scope of a component.
+    This,
+    /// Any other scope, by its lowercase name: `session`, `form`, ...
+    Other(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VarOccurrence {
+    pub name: String,
+    pub range: TextRange,
+    pub scope: VarScope,
+    pub access: Access,
+    /// Whether the name is written with a scope prefix, as in `local.x`.
+    pub scoped: bool,
+    /// Whether this is a declaration: `var x`, a parameter, a property or a
+    /// `catch` variable.
+    pub is_decl: bool,
+    /// The function or closure this occurrence appears in, if any.
+    pub function: Option<TextRange>,
+}
+
+/// What a method is called on.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Receiver {
+    /// `f()`: a function of the page or component, or a built-in.
+    None,
+    /// `this.f()` or `variables.f()`.
+    This,
+    /// `super.f()`.
+    Super,
+    /// A value known to be an instance of the component at this path, as
+    /// written in the file.
+    Component(String),
+    /// Anything else.
+    Unknown,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallSite {
+    pub name: String,
+    pub name_range: TextRange,
+    pub receiver: Receiver,
+    pub range: TextRange,
+    pub args: Vec<CallArg>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallArg {
+    /// The name and its range, for `name=value` arguments.
+    pub name: Option<(String, TextRange)>,
+    pub range: TextRange,
+}
+
+/// A member of a value whose component is known: `user.name`, or `user.`
+/// while the name is being typed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemberAccess {
+    /// The range of the name, empty after the dot if there is none yet.
+    pub name_range: TextRange,
+    pub receiver: Receiver,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct FileUsages {
+    pub vars: Vec<VarOccurrence>,
+    pub calls: Vec<CallSite>,
+    pub members: Vec<MemberAccess>,
+    pub component_refs: Vec<PathRef>,
+}
+
+impl FileUsages {
+    pub(crate) fn new(file: &ast::SourceFile) -> FileUsages {
+        let mut collector = Collector::default();
+        collector.collect_variable_types(file.syntax());
+        collector.visit(file.syntax(), Access::Read);
+        collector.usages
+    }
+
+    /// The variable occurrence whose name covers `offset`.
+    pub fn var_at(&self, offset: TextSize) -> Option<&VarOccurrence> {
+        self.vars
+            .iter()
+            .find(|it| it.range.contains_inclusive(offset))
+    }
+
+    pub fn call_at(&self, offset: TextSize) -> Option<&CallSite> {
+        self.calls
+            .iter()
+            .find(|it| it.name_range.contains_inclusive(offset))
+    }
+
+    pub fn member_at(&self, offset: TextSize) -> Option<&MemberAccess> {
+        self.members
+            .iter()
+            .find(|it| it.name_range.contains_inclusive(offset))
+    }
+
+    pub fn component_ref_at(&self, offset: TextSize) -> Option<&PathRef> {
+        self.component_refs
+            .iter()
+            .find(|it| it.range.contains_inclusive(offset))
+    }
+}
+
+/// Scope names that can prefix a variable.
+const SCOPES: &[&str] = &[
+    "application",
+    "arguments",
+    "attributes",
+    "caller",
+    "cgi",
+    "client",
+    "cookie",
+    "form",
+    "local",
+    "request",
+    "server",
+    "session",
+    "this",
+    "thread",
+    "url",
+    "variables",
+];
+
+pub(crate) fn is_scope_name(name: &str) -> bool {
+    SCOPES.iter().any(|it| it.eq_ignore_ascii_case(name))
+}
+
+/// Types that never name a component.
+const BUILTIN_TYPES: &[&str] = &[
+    "any",
+    "array",
+    "binary",
+    "boolean",
+    "closure",
+    "component",
+    "creditcard",
+    "date",
+    "email",
+    "eurodate",
+    "float",
+    "function",
+    "guid",
+    "integer",
+    "lambda",
+    "numeric",
+    "object",
+    "query",
+    "range",
+    "regex",
+    "ssn",
+    "string",
+    "struct",
+    "telephone",
+    "time",
+    "url",
+    "usdate",
+    "uuid",
+    "variablename",
+    "void",
+    "xml",
+    "zipcode",
+];
+
+pub(crate) fn is_builtin_type(name: &str) -> bool {
+    let name = name.trim().trim_end_matches("[]");
+    name.is_empty() || BUILTIN_TYPES.iter().any(|it| it.eq_ignore_ascii_case(name))
+}
+
+/// Tag attributes whose value names a variable the tag assigns.
+const VARIABLE_ATTRIBUTES: &[(&str, &str)] = &[
+    ("cfdirectory", "name"),
+    ("cfexecute", "variable"),
+    ("cffeed", "name"),
+    ("cffile", "variable"),
+    ("cfhttp", "result"),
+    ("cfimage", "name"),
+    ("cfinvoke", "returnvariable"),
+    ("cfloop", "index"),
+    ("cfloop", "item"),
+    ("cfparam", "name"),
+    ("cfquery", "name"),
+    ("cfquery", "result"),
+    ("cfsavecontent", "variable"),
+    ("cfstoredproc", "result"),
+    ("cfwddx", "output"),
+    ("cfzip", "variable"),
+];
+
+/// Tag attributes whose value names a variable the tag reads.
+const READ_ATTRIBUTES: &[(&str, &str)] = &[("cfloop", "query"), ("cfoutput", "query")];
+
+fn is_attribute_in(table: &[(&str, &str)], tag: &str, attribute: &str) -> bool {
+    table.iter().any(|&(t, a)| t == tag && a == attribute)
+}
+
+#[derive(Debug)]
+struct Frame {
+    owner: TextRange,
+    /// Lowercase names to their inferred component type.
+    params: FxHashMap<String, Option<String>>,
+    locals: FxHashMap<String, Option<String>>,
+}
+
+#[derive(Debug, Default)]
+struct Collector {
+    frames: Vec<Frame>,
+    /// Inferred component types of `variables` scope names.
+    variable_types: FxHashMap<String, String>,
+    usages: FileUsages,
+}
+
+impl Collector {
+    /// Records the types of properties and of `variables` assigned from
+    /// `new` anywhere in the file, so calls in functions declared before the
+    /// assignment still resolve.
+    fn collect_variable_types(&mut self, root: &SyntaxNode) {
+        for node in root.descendants() {
+            if let Some(property) = ast::PropertyDecl::cast(node.clone()) {
+                let name = match property.attribute("name") {
+                    Some(attribute) => attribute.value_text(),
+                    None => property.name_token().map(|it| it.text().to_owned()),
+                };
+                let ty = property
+                    .type_ref()
+                    .map(|it| it.text())
+                    .or_else(|| property.attribute("type")?.value_text());
+                if let (Some(name), Some(ty)) = (name, ty) {
+                    self.record_variable_type(&name, &ty);
+                }
+            } else if let Some(tag) = ast::Tag::cast(node.clone()) {
+                if tag.name() == "cfproperty" {
+                    if let (Some(name), Some(ty)) =
+                        (tag.attribute_value("name"), tag.attribute_value("type"))
+                    {
+                        self.record_variable_type(&name, &ty);
+                    }
+                }
+            } else if let Some(assign) = ast::AssignExpr::cast(node) {
+                let Some(target) = assign.target() else {
+                    continue;
+                };
+                let in_function = assign.syntax().ancestors().any(|it| is_scope_owner(&it));
+                let name = match scoped_name(&target) {
+                    Some((scope, name)) if scope == "variables" => name.text().to_owned(),
+                    None if !in_function => match &target {
+                        ast::Expr::NameRef(it) => it.name(),
+                        _ => continue,
+                    },
+                    _ => continue,
+                };
+                if let Some(ty) = assign.value().and_then(|it| constructed_type(&it)) {
+                    self.variable_types.insert(name.to_ascii_lowercase(), ty);
+                }
+            }
+        }
+    }
+
+    fn record_variable_type(&mut self, name: &str, ty: &str) {
+        if !is_builtin_type(ty) {
+            self.variable_types
+                .insert(name.to_ascii_lowercase(), ty.to_owned());
+        }
+    }
+
+    fn visit(&mut self, node: &SyntaxNode, access: Access) {
+        if is_scope_owner(node) {
+            self.enter_function(node);
+            self.visit_children(node);
+            self.frames.pop();
+            return;
+        }
+
+        match node.kind() {
+            PARAM => self.param(node),
+            VAR_DECL => self.var_decl(node),
+            CATCH_CLAUSE => {
+                if let Some(token) = direct_token(node, IDENT) {
+                    self.declare_local(&token);
+                }
+                self.visit_children(node);
+            }
+            NAME_REF => self.name_ref(node, access),
+            MEMBER_EXPR => self.member_expr(node, access),
+            CALL_EXPR => self.call_expr(node),
+            NEW_EXPR => self.new_expr(node),
+            ASSIGN_EXPR => {
+                let mut exprs = node.children().filter(|it| ast::Expr::can_cast(it.kind()));
+                if let Some(target) = exprs.next() {
+                    self.visit(&target, Access::Write);
+                }
+                for value in exprs {
+                    self.visit(&value, Access::Read);
+                }
+            }
+            PREFIX_EXPR | POSTFIX_EXPR => {
+                let is_update = node
+                    .children_with_tokens()
+                    .any(|it| matches!(it.kind(), PLUS2 | MINUS2));
+                let access = if is_update {
+                    Access::Write
+                } else {
+                    Access::Read
+                };
+                for child in node.children() {
+                    self.visit(&child, access);
+                }
+            }
+            FOR_IN_STMT => {
+                for child in node.children() {
+                    let is_target = child.kind() == NAME_REF
+                        && child
+                            .siblings_with_tokens(syntax::Direction::Next)
+                            .any(|it| it.kind() == IN_KW);
+                    let access = if is_target {
+                        Access::Write
+                    } else {
+                        Access::Read
+                    };
+                    self.visit(&child, access);
+                }
+            }
+            TYPE_REF => {
+                let in_catch = node.parent().is_some_and(|it| it.kind() == CATCH_CLAUSE);
+                if !in_catch {
+                    self.type_ref(node);
+                }
+            }
+            COMPONENT => {
+                for attribute in node.children().filter_map(ast::ScriptAttribute::cast) {
+                    if matches!(attribute.name().as_str(), "extends" | "implements") {
+                        if let (Some(value), Some(range)) =
+                            (attribute.value_text(), attribute.value_range())
+                        {
+                            self.usages.component_refs.extend(path_refs(&value, range));
+                        }
+                    }
+                }
+                self.visit_children(node);
+            }
+            PROPERTY_DECL => self.property_decl(node),
+            TAG => self.tag(node),
+            SCRIPT_TAG_STMT => self.script_tag(node),
+            _ => self.visit_children(node),
+        }
+    }
+
+    fn visit_children(&mut self, node: &SyntaxNode) {
+        for child in node.children() {
+            self.visit(&child, Access::Read);
+        }
+    }
+
+    fn enter_function(&mut self, node: &SyntaxNode) {
+        let mut frame = Frame {
+            owner: node.text_range(),
+            params: FxHashMap::default(),
+            locals: FxHashMap::default(),
+        };
+        let params = match FunctionNode::cast(node.clone()) {
+            Some(function) => function.params(),
+            None => node
+                .children()
+                .find_map(ast::ParamList::cast)
+                .into_iter()
+                .flat_map(|it| it.params().collect::<Vec<_>>())
+                .filter_map(|it| {
+                    let token = it.name_token()?;
+                    Some(crate::items::ParamInfo {
+                        name: token.text().to_owned(),
+                        name_range: token.text_range(),
+                        type_name: it.type_ref().map(|it| it.text()),
+                        required: it.is_required(),
+                        default: None,
+                    })
+                })
+                .collect(),
+        };
+        for param in params {
+            let ty = param.type_name.filter(|it| !is_builtin_type(it));
+            frame.params.insert(param.name.to_ascii_lowercase(), ty);
+        }
+        collect_locals(node, &mut frame.locals);
+        self.frames.push(frame);
+    }
+
+    fn function(&self) -> Option<TextRange> {
+        self.frames.last().map(|it| it.owner)
+    }
+
+    fn push_var(
+        &mut self,
+        name: &str,
+        range: TextRange,
+        scope: VarScope,
+        access: Access,
+        scoped: bool,
+        is_decl: bool,
+    ) {
+        self.usages.vars.push(VarOccurrence {
+            name: name.to_owned(),
+            range,
+            scope,
+            access,
+            scoped,
+            is_decl,
+            function: self.function(),
+        });
+    }
+
+    fn param(&mut self, node: &SyntaxNode) {
+        if let Some(token) = direct_token(node, IDENT) {
+            if let Some(owner) = self.function() {
+                let scope = VarScope::Arguments { owner };
+                self.push_var(
+                    token.text(),
+                    token.text_range(),
+                    scope,
+                    Access::Write,
+                    false,
+                    true,
+                );
+            }
+        }
+        self.visit_children(node);
+    }
+
+    fn var_decl(&mut self, node: &SyntaxNode) {
+        if let Some(token) = direct_token(node, IDENT) {
+            self.declare_local(&token);
+        }
+        self.visit_children(node);
+    }
+
+    fn declare_local(&mut self, token: &SyntaxToken) {
+        let scope = match self.function() {
+            Some(owner) => VarScope::Local { owner },
+            None => VarScope::Variables,
+        };
+        self.push_var(
+            token.text(),
+            token.text_range(),
+            scope,
+            Access::Write,
+            false,
+            true,
+        );
+    }
+
+    /// Resolves an unscoped name by the CFML lookup order.
+    fn resolve_unscoped(&self, name: &str) -> VarScope {
+        let key = name.to_ascii_lowercase();
+        for frame in self.frames.iter().rev() {
+            if frame.locals.contains_key(&key) {
+                return VarScope::Local { owner: frame.owner };
+            }
+            if frame.params.contains_key(&key) {
+                return VarScope::Arguments { owner: frame.owner };
+            }
+        }
+        VarScope::Variables
+    }
+
+    fn resolve_scoped(&self, scope: &str, name: &str) -> VarScope {
+        let key = name.to_ascii_lowercase();
+        match scope {
+            "local" => match self
+                .frames
+                .iter()
+                .rev()
+                .find(|it| it.locals.contains_key(&key))
+            {
+                Some(frame) => VarScope::Local { owner: frame.owner },
+                None => match self.function() {
+                    Some(owner) => VarScope::Local { owner },
+                    None => VarScope::Other("local".to_owned()),
+                },
+            },
+            "arguments" => match self.function() {
+                Some(owner) => VarScope::Arguments { owner },
+                None => VarScope::Other("arguments".to_owned()),
+            },
+            "variables" => VarScope::Variables,
+            "this" => VarScope::This,
+            other => VarScope::Other(other.to_owned()),
+        }
+    }
+
+    fn name_ref(&mut self, node: &SyntaxNode, access: Access) {
+        let Some(token) = node.first_token() else {
+            return;
+        };
+        let name = token.text();
+        if is_scope_name(name) || name.eq_ignore_ascii_case("super") {
+            return;
+        }
+        let scope = self.resolve_unscoped(name);
+        self.push_var(name, token.text_range(), scope, access, false, false);
+    }
+
+    fn member_expr(&mut self, node: &SyntaxNode, access: Access) {
+        let Some(member) = ast::MemberExpr::cast(node.clone()) else {
+            return;
+        };
+        self.push_member(&member);
+        match scoped_name(&ast::Expr::MemberExpr(member.clone())) {
+            Some((scope, token)) => {
+                let resolved = self.resolve_scoped(&scope, token.text());
+                self.push_var(
+                    token.text(),
+                    token.text_range(),
+                    resolved,
+                    access,
+                    true,
+                    false,
+                );
+            }
+            None => {
+                if let Some(receiver) = member.receiver() {
+                    self.visit(receiver.syntax(), Access::Read);
+                }
+            }
+        }
+    }
+
+    fn call_expr(&mut self, node: &SyntaxNode) {
+        let Some(call) = ast::CallExpr::cast(node.clone()) else {
+            return;
+        };
+        let callee = call.callee();
+        match &callee {
+            Some(ast::Expr::NameRef(name_ref)) => {
+                if let Some(token) = name_ref.ident_token() {
+                    self.push_call(&call, &token, Receiver::None);
+                    if token.text().eq_ignore_ascii_case("createObject") {
+                        self.create_object(&call);
+                    }
+                    if token
+                        .text()
+                        .get(..2)
+                        .is_some_and(|it| it.eq_ignore_ascii_case("cf"))
+                    {
+                        self.tag_call(&call, token.text());
+                    }
+                }
+            }
+            Some(ast::Expr::MemberExpr(member)) => {
+                self.push_member(member);
+                let receiver = member.receiver();
+                if let Some(token) = member.name_token() {
+                    let kind = self.receiver(receiver.as_ref());
+                    self.push_call(&call, &token, kind);
+                }
+                if let Some(receiver) = receiver {
+                    self.visit(receiver.syntax(), Access::Read);
+                }
+            }
+            Some(callee) => self.visit(callee.syntax(), Access::Read),
+            None => (),
+        }
+        if let Some(arg_list) = call.arg_list() {
+            self.visit_children(arg_list.syntax());
+        }
+    }
+
+    fn push_call(&mut self, call: &ast::CallExpr, name: &SyntaxToken, receiver: Receiver) {
+        let args = call
+            .arg_list()
+            .into_iter()
+            .flat_map(|it| it.syntax().children().collect::<Vec<_>>())
+            .filter(|it| matches!(it.kind(), ARG | NAMED_ARG))
+            .map(|it| CallArg {
+                name: ast::NamedArg::cast(it.clone())
+                    .and_then(|it| it.name_token())
+                    .map(|it| (it.text().to_owned(), it.text_range())),
+                range: it.text_range(),
+            })
+            .collect();
+        self.usages.calls.push(CallSite {
+            name: name.text().to_owned(),
+            name_range: name.text_range(),
+            receiver,
+            range: call.syntax().text_range(),
+            args,
+        });
+    }
+
+    fn push_member(&mut self, member: &ast::MemberExpr) {
+        let receiver = self.receiver(member.receiver().as_ref());
+        let name_range = match member.name_token() {
+            Some(token) => token.text_range(),
+            None => match direct_token(member.syntax(), DOT) {
+                Some(dot) => TextRange::empty(dot.text_range().end()),
+                None => return,
+            },
+        };
+        if receiver != Receiver::Unknown {
+            self.usages.members.push(MemberAccess {
+                name_range,
+                receiver,
+            });
+        }
+    }
+
+    /// What the members of `receiver` belong to.
+    fn receiver(&self, receiver: Option<&ast::Expr>) -> Receiver {
+        match receiver {
+            Some(ast::Expr::NameRef(it)) if is_scope_in(it, &["this", "variables"]) => {
+                Receiver::This
+            }
+            Some(ast::Expr::NameRef(it)) if is_scope_in(it, &["super"]) => Receiver::Super,
+            Some(receiver) => match self.infer_type(receiver) {
+                Some(path) => Receiver::Component(path),
+                None => Receiver::Unknown,
+            },
+            None => Receiver::Unknown,
+        }
+    }
+
+    /// `createObject("component", "models.User")` and `createObject("models.User")`.
+    fn create_object(&mut self, call: &ast::CallExpr) {
+        let args: Vec<_> = call.args().collect();
+        let path = match args.as_slice() {
+            [ast::Arg::Positional(ast::Expr::StringLit(path))] => path,
+            [ast::Arg::Positional(ast::Expr::StringLit(kind)), ast::Arg::Positional(ast::Expr::StringLit(path)), ..]
+                if kind
+                    .value()
+                    .is_some_and(|it| it.eq_ignore_ascii_case("component")) =>
+            {
+                path
+            }
+            _ => return,
+        };
+        if let Some(value) = path.value() {
+            self.usages
+                .component_refs
+                .extend(path_refs(&value, path.contents_range()));
+        }
+    }
+
+    /// The variables named by a tag called as a function, as the `result`
+    /// of `cfhttp(url = "...", result = "r")`.
+    fn tag_call(&mut self, call: &ast::CallExpr, name: &str) {
+        let tag = name.to_ascii_lowercase();
+        for arg in call.args() {
+            let ast::Arg::Named(arg) = arg else {
+                continue;
+            };
+            let (Some(attribute), Some(ast::Expr::StringLit(value))) =
+                (arg.name_token(), arg.value())
+            else {
+                continue;
+            };
+            if let Some(text) = value.value() {
+                let attribute = attribute.text().to_ascii_lowercase();
+                self.named_variable(&tag, &attribute, &text, value.contents_range());
+            }
+        }
+    }
+
+    fn new_expr(&mut self, node: &SyntaxNode) {
+        let Some(new_expr) = ast::NewExpr::cast(node.clone()) else {
+            return;
+        };
+        if let Some(path) = new_expr.path() {
+            let segments: Vec<_> = path.segments().collect();
+            if let (Some(first), Some(last)) = (segments.first(), segments.last()) {
+                self.usages.component_refs.push(PathRef {
+                    path: path.text(),
+                    range: TextRange::new(first.text_range().start(), last.text_range().end()),
+                });
+            }
+        } else if let Some(string) = new_expr.path_string() {
+            if let Some(value) = string.value() {
+                self.usages
+                    .component_refs
+                    .extend(path_refs(&value, string.contents_range()));
+            }
+        }
+        if let Some(arg_list) = new_expr.arg_list() {
+            self.visit_children(arg_list.syntax());
+        }
+    }
+
+    fn type_ref(&mut self, node: &SyntaxNode) {
+        let Some(type_ref) = ast::TypeRef::cast(node.clone()) else {
+            return;
+        };
+        let text = type_ref.text();
+        if is_builtin_type(&text) {
+            return;
+        }
+        let path = text.trim_end_matches("[]");
+        let range = TextRange::at(node.text_range().start(), TextSize::of(path));
+        self.usages.component_refs.extend(path_refs(path, range));
+    }
+
+    fn property_decl(&mut self, node: &SyntaxNode) {
+        let Some(property) = ast::PropertyDecl::cast(node.clone()) else {
+            return;
+        };
+        let name = match property.attribute("name") {
+            Some(attribute) => attribute.value_text().zip(attribute.value_range()),
+            None => property
+                .name_token()
+                .map(|it| (it.text().to_owned(), it.text_range())),
+        };
+        if let Some((name, range)) = name {
+            self.push_var(
+                &name,
+                range,
+                VarScope::Variables,
+                Access::Write,
+                false,
+                true,
+            );
+        }
+        if let Some(attribute) = property.attribute("type") {
+            self.attribute_type_ref(attribute.value_text(), attribute.value_range());
+        }
+        self.visit_children(node);
+    }
+
+    fn attribute_type_ref(&mut self, value: Option<String>, range: Option<TextRange>) {
+        if let (Some(value), Some(range)) = (value, range) {
+            if !is_builtin_type(&value) {
+                let path = value.trim_end_matches("[]");
+                let range = TextRange::at(range.start(), TextSize::of(path));
+                self.usages.component_refs.extend(path_refs(path, range));
+            }
+        }
+    }
+
+    fn tag(&mut self, node: &SyntaxNode) {
+        let Some(tag) = ast::Tag::cast(node.clone()) else {
+            return;
+        };
+        let name = tag.name();
+        match name.as_str() {
+            "cfcomponent" | "cfinterface" => {
+                for attribute in ["extends", "implements"] {
+                    if let Some(attribute) = tag.attribute(attribute) {
+                        if let (Some(value), Some(range)) =
+                            (attribute.value_text(), attribute.value_range())
+                        {
+                            self.usages.component_refs.extend(path_refs(&value, range));
+                        }
+                    }
+                }
+            }
+            "cfargument" => {
+                if let (Some(owner), Some(attribute)) = (self.function(), tag.attribute("name")) {
+                    if let (Some(value), Some(range)) =
+                        (attribute.value_text(), attribute.value_range())
+                    {
+                        let scope = VarScope::Arguments { owner };
+                        self.push_var(&value, range, scope, Access::Write, false, true);
+                    }
+                }
+                let ty = tag.attribute("type");
+                self.attribute_type_ref(
+                    ty.as_ref().and_then(|it| it.value_text()),
+                    ty.and_then(|it| it.value_range()),
+                );
+            }
+            "cffunction" => {
+                let ty = tag.attribute("returntype");
+                self.attribute_type_ref(
+                    ty.as_ref().and_then(|it| it.value_text()),
+                    ty.and_then(|it| it.value_range()),
+                );
+            }
+            "cfproperty" => {
+                if let Some(attribute) = tag.attribute("name") {
+                    if let (Some(value), Some(range)) =
+                        (attribute.value_text(), attribute.value_range())
+                    {
+                        self.push_var(
+                            &value,
+                            range,
+                            VarScope::Variables,
+                            Access::Write,
+                            false,
+                            true,
+                        );
+                    }
+                }
+                let ty = tag.attribute("type");
+                self.attribute_type_ref(
+                    ty.as_ref().and_then(|it| it.value_text()),
+                    ty.and_then(|it| it.value_range()),
+                );
+            }
+            "cfinvoke" | "cfobject" => {
+                let component = tag.attribute("component");
+                let path = component.as_ref().and_then(|it| it.value_text());
+                if let (Some(value), Some(range)) =
+                    (&path, component.and_then(|it| it.value_range()))
+                {
+                    self.usages.component_refs.extend(path_refs(value, range));
+                }
+                if name == "cfinvoke" {
+                    self.invoke(&tag, path);
+                }
+            }
+            _ => (),
+        }
+
+        let attributes = tag
+            .attributes()
+            .filter_map(|it| Some((it.name(), it.value_text()?, it.value_range()?)));
+        for (attribute, value, range) in attributes.collect::<Vec<_>>() {
+            self.named_variable(&name, &attribute, &value, range);
+        }
+        self.visit_children(node);
+    }
+
+    /// `<cfinvoke component="models.User" method="save">`.
+    fn invoke(&mut self, tag: &ast::Tag, component: Option<String>) {
+        let Some(method) = tag.attribute("method") else {
+            return;
+        };
+        let (Some(name), Some(name_range)) = (method.value_text(), method.value_range()) else {
+            return;
+        };
+        let receiver = match component {
+            Some(path) => Receiver::Component(path),
+            None if tag.attribute("component").is_some() => Receiver::Unknown,
+            None => Receiver::None,
+        };
+        self.usages.calls.push(CallSite {
+            name,
+            name_range,
+            receiver,
+            range: tag.syntax().text_range(),
+            args: Vec::new(),
+        });
+    }
+
+    fn script_tag(&mut self, node: &SyntaxNode) {
+        let Some(stmt) = ast::ScriptTagStmt::cast(node.clone()) else {
+            return;
+        };
+        let name = format!("cf{}", stmt.name());
+        let attributes = stmt
+            .attributes()
+            .filter_map(|it| Some((it.name(), it.value_text()?, it.value_range()?)));
+        for (attribute, value, range) in attributes.collect::<Vec<_>>() {
+            self.named_variable(&name, &attribute, &value, range);
+        }
+        self.visit_children(node);
+    }
+
+    /// A variable named by a tag attribute value: `<cfquery name="q">`.
+    fn named_variable(&mut self, tag: &str, attribute: &str, value: &str, range: TextRange) {
+        let access = if is_attribute_in(VARIABLE_ATTRIBUTES, tag, attribute) {
+            Access::Write
+        } else if is_attribute_in(READ_ATTRIBUTES, tag, attribute) {
+            Access::Read
+        } else {
+            return;
+        };
+        if TextSize::of(value) != range.len() || value.is_empty() {
+            return;
+        }
+        match value.split_once('.') {
+            Some((scope, name)) if is_scope_name(scope) && is_identifier(name) => {
+                let scope = scope.to_ascii_lowercase();
+                let resolved = self.resolve_scoped(&scope, name);
+                let start = range.start() + TextSize::of(scope.as_str()) + TextSize::from(1);
+                let range = TextRange::at(start, TextSize::of(name));
+                self.push_var(name, range, resolved, access, true, false);
+            }
+            None if is_identifier(value) => {
+                let scope = self.resolve_unscoped(value);
+                self.push_var(value, range, scope, access, false, false);
+            }
+            _ => (),
+        }
+    }
+
+    /// The component an expression is known to be an instance of.
+    fn infer_type(&self, expr: &ast::Expr) -> Option<String> {
+        if let Some(ty) = constructed_type(expr) {
+            return Some(ty);
+        }
+        match expr {
+            ast::Expr::ParenExpr(it) => self.infer_type(&it.expr()?),
+            ast::Expr::NameRef(it) => {
+                let key = it.name().to_ascii_lowercase();
+                for frame in self.frames.iter().rev() {
+                    if let Some(ty) = frame.locals.get(&key).or_else(|| frame.params.get(&key)) {
+                        return ty.clone();
+                    }
+                }
+                self.variable_types.get(&key).cloned()
+            }
+            ast::Expr::MemberExpr(_) => {
+                let (scope, token) = scoped_name(expr)?;
+                let key = token.text().to_ascii_lowercase();
+                match scope.as_str() {
+                    "local" => self
+                        .frames
+                        .iter()
+                        .rev()
+                        .find_map(|it| it.locals.get(&key)?.clone()),
+                    "arguments" => self.frames.last()?.params.get(&key)?.clone(),
+                    "variables" => self.variable_types.get(&key).cloned(),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Collects the locals of a function or closure, without descending into
+/// nested closures: `var` declarations, `local.x` assignments, `catch`
+/// variables and `local.`-prefixed tag variables.
+fn collect_locals(owner: &SyntaxNode, locals: &mut FxHashMap<String, Option<String>>) {
+    let mut preorder = owner.preorder();
+    while let Some(event) = preorder.next() {
+        let WalkEvent::Enter(node) = event else {
+            continue;
+        };
+        if node != *owner && is_scope_owner(&node) {
+            preorder.skip_subtree();
+            continue;
+        }
+        match node.kind() {
+            VAR_DECL => {
+                let Some(decl) = ast::VarDecl::cast(node) else {
+                    continue;
+                };
+                if let Some(token) = decl.name_token() {
+                    let ty = decl.initializer().and_then(|it| constructed_type(&it));
+                    locals.insert(token.text().to_ascii_lowercase(), ty);
+                }
+            }
+            CATCH_CLAUSE => {
+                if let Some(token) = direct_token(&node, IDENT) {
+                    locals.insert(token.text().to_ascii_lowercase(), None);
+                }
+            }
+            ASSIGN_EXPR => {
+                let Some(assign) = ast::AssignExpr::cast(node) else {
+                    continue;
+                };
+                let Some(target) = assign.target() else {
+                    continue;
+                };
+                if let Some((scope, token)) = scoped_name(&target) {
+                    if scope == "local" {
+                        let ty = assign.value().and_then(|it| constructed_type(&it));
+                        let key = token.text().to_ascii_lowercase();
+                        let entry = locals.entry(key).or_default();
+                        if ty.is_some() {
+                            *entry = ty;
+                        }
+                    }
+                }
+            }
+            ATTRIBUTE | SCRIPT_ATTRIBUTE => {
+                let value = ast::Attribute::cast(node.clone())
+                    .and_then(|it| it.value_text())
+                    .or_else(|| ast::ScriptAttribute::cast(node).and_then(|it| it.value_text()));
+                if let Some((scope, name)) = value.as_deref().and_then(|it| it.split_once('.')) {
+                    if scope.eq_ignore_ascii_case("local") && is_identifier(name) {
+                        locals.entry(name.to_ascii_lowercase()).or_default();
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+/// The type of `new path()` and `createObject("component", "path")`.
+fn constructed_type(expr: &ast::Expr) -> Option<String> {
+    match expr {
+        ast::Expr::NewExpr(it) => match it.path() {
+            Some(path) => Some(path.text()),
+            None => it.path_string()?.value(),
+        },
+        ast::Expr::CallExpr(call) => {
+            let name = call.name_token()?;
+            if !name.text().eq_ignore_ascii_case("createObject") {
+                return None;
+            }
+            let strings: Vec<_> = call
+                .args()
+                .map(|it| match it {
+                    ast::Arg::Positional(ast::Expr::StringLit(it)) => it.value(),
+                    _ => None,
+                })
+                .collect();
+            match strings.as_slice() {
+                [Some(path)] => Some(path.clone()),
+                [Some(kind), Some(path), ..] if kind.eq_ignore_ascii_case("component") => {
+                    Some(path.clone())
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Splits `scope.name` into the lowercase scope and the name token.
+pub(crate) fn scoped_name(expr: &ast::Expr) -> Option<(String, SyntaxToken)> {
+    let ast::Expr::MemberExpr(member) = expr else {
+        return None;
+    };
+    let ast::Expr::NameRef(receiver) = member.receiver()? else {
+        return None;
+    };
+    let scope = receiver.name();
+    if !is_scope_name(&scope) {
+        return None;
+    }
+    Some((scope.to_ascii_lowercase(), member.name_token()?))
+}
+
+fn is_scope_in(name_ref: &ast::NameRef, scopes: &[&str]) -> bool {
+    let name = name_ref.name();
+    scopes.iter().any(|it| it.eq_ignore_ascii_case(&name))
+}
+
+fn direct_token(node: &SyntaxNode, kind: SyntaxKind) -> Option<SyntaxToken> {
+    node.children_with_tokens()
+        .filter_map(|it| it.into_token())
+        .find(|it| it.kind() == kind)
+}
+
+pub(crate) fn is_identifier(text: &str) -> bool {
+    let mut chars = text.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+#[cfg(test)]
+mod tests {
+    use syntax::ast::SourceFile;
+
+    use super::*;
+
+    fn usages(text: &str) -> FileUsages {
+        FileUsages::new(&SourceFile::parse(text).tree())
+    }
+
+    fn vars(usages: &FileUsages) -> Vec<String> {
+        usages
+            .vars
+            .iter()
+            .map(|it| {
+                let scope = match &it.scope {
+                    VarScope::Local { .. } => "local",
+                    VarScope::Arguments { .. } => "arguments",
+                    VarScope::Variables => "variables",
+                    VarScope::This => "this",
+                    VarScope::Other(it) => it,
+                };
+                let access = match it.access {
+                    Access::Read => "r",
+                    Access::Write => "w",
+                };
+                format!(
+                    "{} {scope} {access}{}",
+                    it.name,
+                    if it.is_decl { " decl" } else { "" }
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn resolves_function_scopes() {
+        let usages = usages(
+            r#"component {
+    function f(a) {
+        var b = a;
+        local.c = arguments.a;
+        d = b + c;
+        for (e in a) {}
+        try {} catch (any err) { writeOutput(err); }
+    }
+}"#,
+        );
+        assert_eq!(
+            vars(&usages),
+            [
+                "a arguments w decl",
+                "b local w decl",
+                "a arguments r",
+                "c local w",
+                "a arguments r",
+                "d variables w",
+                "b local r",
+                "c local r",
+                "e variables w",
+                "a arguments r",
+                "err local w decl",
+                "err local r",
+            ]
+        );
+    }
+
+    #[test]
+    fn closures_capture_outer_scopes() {
+        let usages = usages(
+            "<cfscript>function f(a) { var b = 1; var g = function(c) { var d = a + b + c; }; }</cfscript>",
+        );
+        let scopes: Vec<_> = usages
+            .vars
+            .iter()
+            .map(|it| (it.name.as_str(), &it.scope))
+            .collect();
+        let outer = TextRange::new(10.into(), 82.into());
+        let inner = TextRange::new(45.into(), 79.into());
+        assert!(scopes.contains(&("a", &VarScope::Arguments { owner: outer })));
+        assert!(scopes.contains(&("c", &VarScope::Arguments { owner: inner })));
+        assert!(scopes.contains(&("d", &VarScope::Local { owner: inner })));
+        assert!(scopes.contains(&("b", &VarScope::Local { owner: outer })));
+    }
+
+    #[test]
+    fn tag_attributes_name_variables() {
+        let usages = usages(
+            r#"<cffunction name="f"><cfargument name="x"><cfset var q = 0><cfquery name="q">select 1</cfquery><cfloop index="local.i" from="1" to="2"></cfloop><cfhttp url="u" result="r"></cffunction>"#,
+        );
+        assert_eq!(
+            vars(&usages),
+            [
+                "x arguments w decl",
+                "q local w decl",
+                "q local w",
+                "i local w",
+                "r variables w"
+            ]
+        );
+    }
+
+    #[test]
+    fn records_calls_and_component_refs() {
+        let usages = usages(
+            r#"component extends="base.Model" {
+    property name="repo" type="models.Repo";
+    function f(models.User user) {
+        var svc = new services.Mailer();
+        svc.send(to = user.email, body: "x");
+        this.g(1);
+        super.h();
+        repo.find(1);
+        user.save();
+        var obj = createObject("component", "models.Other");
+        other();
+    }
+}"#,
+        );
+        let calls: Vec<_> = usages
+            .calls
+            .iter()
+            .map(|it| (it.name.as_str(), it.receiver.clone(), it.args.len()))
+            .collect();
+        assert_eq!(
+            calls,
+            [
+                ("send", Receiver::Component("services.Mailer".to_owned()), 2),
+                ("g", Receiver::This, 1),
+                ("h", Receiver::Super, 0),
+                ("find", Receiver::Component("models.Repo".to_owned()), 1),
+                ("save", Receiver::Component("models.User".to_owned()), 0),
+                ("createObject", Receiver::None, 2),
+                ("other", Receiver::None, 0),
+            ]
+        );
+        let named: Vec<_> = usages.calls[0]
+            .args
+            .iter()
+            .filter_map(|it| it.name.as_ref())
+            .map(|it| it.0.as_str())
+            .collect();
+        assert_eq!(named, ["to", "body"]);
+
+        let paths: Vec<_> = usages
+            .component_refs
+            .iter()
+            .map(|it| it.path.as_str())
+            .collect();
+        assert_eq!(
+            paths,
+            [
+                "base.Model",
+                "models.Repo",
+                "models.User",
+                "services.Mailer",
+                "models.Other"
+            ]
+        );
+    }
+}
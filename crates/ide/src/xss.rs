@@ -0,0 +1,673 @@
+//! Output of values from the request or a database without encoding:
+//! `#expr#` in a `<cfoutput>` and the arguments of `writeOutput`, which
+//! should be passed through the encoder for the HTML context they end up
+//! in, like `encodeForHTMLAttribute` inside an attribute value.
+//!
+//! The context of an output is found by scanning the HTML text before it,
+//! skipping CFML tags and expressions. Values traced to the sources of the
+//! rule, by default those the request controls, are reported as errors,
+//! query columns as warnings.
+
+use syntax::{
+    ast::{self, AstNode},
+    NodeOrToken,
+    SyntaxKind::*,
+    WalkEvent,
+};
+use virtual_fs::FileId;
+
+use crate::{
+    db::RootDatabase,
+    diagnostics::{fix, Diagnostic, DiagnosticCode, DiagnosticsConfig, Severity},
+    taint::{called_function, function_list, Origin, TaintSpec, Tracer, SAFE_FUNCTIONS, SOURCES},
+    text_edit::TextEdit,
+};
+
+/// Tags whose body a `<cfoutput>` around them outputs.
+const OUTPUT_TAGS: &[&str] = &[
+    "cfoutput",
+    "cfif",
+    "cfelseif",
+    "cfelse",
+    "cfloop",
+    "cfswitch",
+    "cfcase",
+    "cfdefaultcase",
+    "cftry",
+    "cfcatch",
+    "cffinally",
+    "cfsavecontent",
+    "cflock",
+];
+
+/// The built-in encoding functions.
+const ENCODERS: &[&str] = &[
+    "encodeForHTML",
+    "encodeForHTMLAttribute",
+    "encodeForJavaScript",
+    "encodeForCSS",
+    "encodeForURL",
+    "encodeForXML",
+    "encodeForXMLAttribute",
+    "encodeForXPath",
+    "esapiEncode",
+    "htmlEditFormat",
+    "htmlCodeFormat",
+    "xmlFormat",
+    "urlEncodedFormat",
+    "jsStringFormat",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum HtmlContext {
+    Text,
+    Attribute,
+    JavaScript,
+    Css,
+}
+
+impl HtmlContext {
+    /// The encoders safe in the context, the one fixes use first.
+    fn encoders(self) -> &'static [&'static str] {
+        match self {
+            HtmlContext::Text => &[
+                "encodeForHTML",
+                "encodeForHTMLAttribute",
+                "encodeForXML",
+                "encodeForXMLAttribute",
+                "htmlEditFormat",
+                "htmlCodeFormat",
+                "xmlFormat",
+            ],
+            HtmlContext::Attribute => &[
+                "encodeForHTMLAttribute",
+                "encodeForURL",
+                "encodeForXMLAttribute",
+                "urlEncodedFormat",
+            ],
+            HtmlContext::JavaScript => &["encodeForJavaScript", "jsStringFormat"],
+            HtmlContext::Css => &["encodeForCSS"],
+        }
+    }
+
+    fn describe(self) -> &'static str {
+        match self {
+            HtmlContext::Text => "HTML",
+            HtmlContext::Attribute => "an HTML attribute",
+            HtmlContext::JavaScript => "JavaScript",
+            HtmlContext::Css => "CSS",
+        }
+    }
+}
+
+pub(crate) fn unencoded_output(
+    db: &RootDatabase,
+    config: &DiagnosticsConfig,
+    file_id: FileId,
+    file: &ast::SourceFile,
+    res: &mut Vec<Diagnostic>,
+) {
+    let functions: String = config
+        .option(DiagnosticCode::UNENCODED_OUTPUT, "functions")
+        .unwrap_or_else(|| SAFE_FUNCTIONS.to_owned());
+    let sources: String = config
+        .option(DiagnosticCode::UNENCODED_OUTPUT, "sources")
+        .unwrap_or_else(|| SOURCES.to_owned());
+    let mut sanitizers = function_list(&functions);
+    sanitizers.extend(ENCODERS.iter().map(|it| it.to_ascii_lowercase()));
+    let spec = TaintSpec::new(&sources, sanitizers);
+    let cx = Context {
+        file_id,
+        tracer: Tracer::new(db, file_id, &spec),
+    };
+
+    let mut scanner = HtmlScanner::default();
+    for event in file.syntax().preorder_with_tokens() {
+        let WalkEvent::Enter(element) = event else {
+            continue;
+        };
+        match element {
+            NodeOrToken::Token(token) if token.kind() == TEXT => scanner.feed(token.text()),
+            NodeOrToken::Node(node) => {
+                if let Some(hash) = ast::HashExpr::cast(node.clone()) {
+                    if is_output(&hash) {
+                        if let Some(expr) = hash.expr() {
+                            cx.check(&expr, scanner.context(), res);
+                        }
+                        scanner.skip_value();
+                    }
+                } else if let Some(call) = ast::CallExpr::cast(node) {
+                    let is_output = called_function(&ast::Expr::CallExpr(call.clone()))
+                        .is_some_and(|it| it == "writeoutput" || it == "echo");
+                    if let (true, Some(output)) = (is_output, output_arg(&call)) {
+                        cx.output(&output, &mut HtmlScanner::default(), res);
+                    }
+                }
+            }
+            NodeOrToken::Token(_) => {}
+        }
+    }
+}
+
+/// Whether `hash` is output by a `<cfoutput>`, rather than part of an
+/// attribute or an expression.
+fn is_output(hash: &ast::HashExpr) -> bool {
+    let Some(parent) = hash.syntax().parent().and_then(ast::Tag::cast) else {
+        return false;
+    };
+    OUTPUT_TAGS.contains(&parent.name().as_str())
+        && parent
+            .syntax()
+            .ancestors()
+            .filter_map(ast::Tag::cast)
+            .any(|it| it.name() == "cfoutput")
+}
+
+/// The value `writeOutput` is called with.
+fn output_arg(call: &ast::CallExpr) -> Option<ast::Expr> {
+    call.args().find_map(|arg| match arg {
+        ast::Arg::Positional(it) => Some(it),
+        ast::Arg::Named(it) => it
+            .name_token()
+            .filter(|name| name.text().eq_ignore_ascii_case("output"))
+            .and_then(|_| it.value()),
+    })
+}
+
+struct Context<'a> {
+    file_id: FileId,
+    tracer: Tracer<'a>,
+}
+
+impl Context<'_> {
+    /// Checks the parts of the value passed to `writeOutput`, scanning the
+    /// string literals among them for the HTML context.
+    fn output(&self, expr: &ast::Expr, scanner: &mut HtmlScanner, res: &mut Vec<Diagnostic>) {
+        match expr {
+            ast::Expr::BinExpr(it) if it.op() == "&" => {
+                for operand in [it.lhs(), it.rhs()].into_iter().flatten() {
+                    self.output(&operand, scanner, res);
+                }
+            }
+            ast::Expr::StringLit(it) => {
+                let quote = it
+                    .syntax()
+                    .first_token()
+                    .map_or_else(String::new, |it| it.text().to_owned());
+                for element in it.syntax().children_with_tokens() {
+                    match element {
+                        NodeOrToken::Token(token) if token.kind() == STRING_TEXT => {
+                            scanner.feed(&token.text().replace(&quote.repeat(2), &quote));
+                        }
+                        NodeOrToken::Node(node) => {
+                            let expr = ast::HashExpr::cast(node).and_then(|it| it.expr());
+                            if let Some(expr) = expr {
+                                self.check(&expr, scanner.context(), res);
+                            }
+                        }
+                        NodeOrToken::Token(_) => {}
+                    }
+                }
+            }
+            _ => self.check(expr, scanner.context(), res),
+        }
+    }
+
+    /// Checks a value output in `context`: encoded for another context, or
+    /// traced to the request or a query without encoding.
+    fn check(&self, expr: &ast::Expr, context: HtmlContext, res: &mut Vec<Diagnostic>) {
+        let encoders = context.encoders();
+        if let Some(function) = called_function(expr) {
+            if encoders.iter().any(|it| it.eq_ignore_ascii_case(&function)) {
+                return;
+            }
+            if let Some(encoder) = ENCODERS
+                .iter()
+                .find(|it| it.eq_ignore_ascii_case(&function))
+            {
+                if *encoder != "esapiEncode" {
+                    self.wrong_encoder(expr, encoder, context, res);
+                }
+                return;
+            }
+        }
+        let Some(origin) = self.tracer.origin(expr) else {
+            return;
+        };
+        if origin == Origin::Argument {
+            return;
+        }
+        let value = expr.syntax().text().to_string();
+        let range = expr.syntax().text_range();
+        let message = format!(
+            "`{value}` comes from {} and is output into {} without encoding, wrap it in `{}`",
+            origin.describe(),
+            context.describe(),
+            encoders[0]
+        );
+        let label = format!("Wrap `{value}` in `{}`", encoders[0]);
+        let edit = TextEdit::replace(range, format!("{}({value})", encoders[0]));
+        let mut diagnostic = Diagnostic::new(DiagnosticCode::UNENCODED_OUTPUT, range, message)
+            .with_fixes(vec![fix(self.file_id, label, edit)]);
+        if origin.is_source() {
+            diagnostic.severity = Severity::Error;
+        }
+        res.push(diagnostic);
+    }
+
+    /// Reports a call of `encoder` that does not encode for `context`, when
+    /// what it encodes comes from the request or a query.
+    fn wrong_encoder(
+        &self,
+        expr: &ast::Expr,
+        encoder: &str,
+        context: HtmlContext,
+        res: &mut Vec<Diagnostic>,
+    ) {
+        let mut expr = expr.clone();
+        while let ast::Expr::ParenExpr(it) = &expr {
+            let Some(inner) = it.expr() else {
+                return;
+            };
+            expr = inner;
+        }
+        let ast::Expr::CallExpr(call) = &expr else {
+            return;
+        };
+        let Some(ast::Expr::NameRef(callee)) = call.callee() else {
+            return;
+        };
+        let Some(value) = output_arg(call) else {
+            return;
+        };
+        let origin = self.tracer.origin(&value);
+        if origin.is_none() || origin == Some(Origin::Argument) {
+            return;
+        }
+        let instead = context.encoders()[0];
+        let message = format!(
+            "`{encoder}` does not encode `{}` for {}, use `{instead}`",
+            value.syntax(),
+            context.describe()
+        );
+        let edit = TextEdit::replace(callee.syntax().text_range(), instead.to_owned());
+        let diagnostic = Diagnostic::new(
+            DiagnosticCode::UNENCODED_OUTPUT,
+            expr.syntax().text_range(),
+            message,
+        );
+        let label = format!("Use `{instead}`");
+        res.push(diagnostic.with_fixes(vec![fix(self.file_id, label, edit)]));
+    }
+}
+
+/// Where in an HTML document text ends up, as far as encoding goes.
+#[derive(Debug, Clone, Default)]
+enum ScanState {
+    #[default]
+    Text,
+    Comment,
+    /// The contents of a `<script>` or `<style>` element, by its name.
+    Raw(&'static str),
+    /// Inside a tag, after the name of the attribute seen last.
+    Tag {
+        name: String,
+        attr: String,
+        equals: bool,
+        space: bool,
+    },
+    Value {
+        name: String,
+        attr: String,
+        quote: Option<char>,
+    },
+}
+
+/// Follows the state of HTML text fed to it piece by piece.
+#[derive(Debug, Default)]
+struct HtmlScanner {
+    state: ScanState,
+}
+
+impl HtmlScanner {
+    fn context(&self) -> HtmlContext {
+        match &self.state {
+            ScanState::Text | ScanState::Comment => HtmlContext::Text,
+            ScanState::Raw("style") => HtmlContext::Css,
+            ScanState::Raw(_) => HtmlContext::JavaScript,
+            ScanState::Tag { .. } => HtmlContext::Attribute,
+            ScanState::Value { attr, .. } if attr.starts_with("on") => HtmlContext::JavaScript,
+            ScanState::Value { attr, .. } if attr == "style" => HtmlContext::Css,
+            ScanState::Value { .. } => HtmlContext::Attribute,
+        }
+    }
+
+    /// Skips over a value output where the scanner is, which may be the
+    /// value of an unquoted attribute.
+    fn skip_value(&mut self) {
+        if let ScanState::Tag {
+            name,
+            attr,
+            equals: true,
+            ..
+        } = &mut self.state
+        {
+            self.state = ScanState::Value {
+                name: std::mem::take(name),
+                attr: std::mem::take(attr),
+                quote: None,
+            };
+        }
+    }
+
+    fn feed(&mut self, text: &str) {
+        let mut rest = text;
+        while let Some(c) = rest.chars().next() {
+            let mut len = c.len_utf8();
+            let next = match &mut self.state {
+                ScanState::Text if rest.starts_with("<!--") => {
+                    len = 4;
+                    Some(ScanState::Comment)
+                }
+                ScanState::Text if c == '<' => {
+                    let after = rest[1..].strip_prefix('/').unwrap_or(&rest[1..]);
+                    let name: String = after
+                        .chars()
+                        .take_while(|c| c.is_ascii_alphanumeric() || *c == '-')
+                        .collect();
+                    if name.is_empty() || !name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+                        None
+                    } else {
+                        len = rest.len() - after.len() + name.len();
+                        Some(ScanState::Tag {
+                            name: name.to_ascii_lowercase(),
+                            attr: String::new(),
+                            equals: false,
+                            space: false,
+                        })
+                    }
+                }
+                ScanState::Text => None,
+                ScanState::Comment => rest.starts_with("-->").then(|| {
+                    len = 3;
+                    ScanState::Text
+                }),
+                ScanState::Raw(name) => {
+                    let end = rest
+                        .strip_prefix("</")
+                        .and_then(|it| it.get(..name.len()))
+                        .is_some_and(|it| it.eq_ignore_ascii_case(name));
+                    end.then(|| {
+                        len = 2 + name.len();
+                        ScanState::Tag {
+                            name: String::new(),
+                            attr: String::new(),
+                            equals: false,
+                            space: false,
+                        }
+                    })
+                }
+                ScanState::Tag {
+                    name,
+                    attr,
+                    equals,
+                    space,
+                } => match c {
+                    '>' => Some(match name.as_str() {
+                        "script" => ScanState::Raw("script"),
+                        "style" => ScanState::Raw("style"),
+                        _ => ScanState::Text,
+                    }),
+                    '=' => {
+                        *equals = true;
+                        None
+                    }
+                    '"' | '\'' => Some(ScanState::Value {
+                        name: std::mem::take(name),
+                        attr: if *equals {
+                            std::mem::take(attr)
+                        } else {
+                            String::new()
+                        },
+                        quote: Some(c),
+                    }),
+                    c if c.is_whitespace() => {
+                        *space = true;
+                        None
+                    }
+                    _ if *equals => Some(ScanState::Value {
+                        name: std::mem::take(name),
+                        attr: std::mem::take(attr),
+                        quote: None,
+                    }),
+                    c => {
+                        if *space {
+                            attr.clear();
+                            *space = false;
+                        }
+                        attr.push(c.to_ascii_lowercase());
+                        None
+                    }
+                },
+                ScanState::Value { name, quote, .. } => {
+                    let end = match quote {
+                        Some(quote) => c == *quote,
+                        None => c.is_whitespace() || c == '>',
+                    };
+                    if end && c == '>' {
+                        // Let the tag handle the `>` of an unquoted value.
+                        len = 0;
+                    }
+                    end.then(|| ScanState::Tag {
+                        name: std::mem::take(name),
+                        attr: String::new(),
+                        equals: false,
+                        space: true,
+                    })
+                }
+            };
+            if let Some(next) = next {
+                self.state = next;
+            }
+            rest = &rest[len..];
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use virtual_fs::FileId;
+
+    use crate::{fixture, DiagnosticCode, DiagnosticsConfig, Severity};
+
+    /// The unencoded output diagnostics of the first file: the text they
+    /// cover, their message and severity.
+    fn check(fixture: &str, expect: &[(&str, &str, Severity)]) {
+        let analysis = fixture::parse(fixture).analysis;
+        let text = analysis.file_text(FileId(0));
+        let actual: Vec<_> = analysis
+            .diagnostics(&DiagnosticsConfig::default(), FileId(0))
+            .into_iter()
+            .filter(|it| it.code == DiagnosticCode::UNENCODED_OUTPUT)
+            .map(|it| (&text[it.range], it.message, it.severity))
+            .collect();
+        let actual: Vec<_> = actual
+            .iter()
+            .map(|(range, message, severity)| (*range, message.as_str(), *severity))
+            .collect();
+        assert_eq!(actual, expect);
+    }
+
+    /// The text of the first file after applying the fix of the diagnostic
+    /// covering `$0`.
+    fn check_fix(fixture: &str, expect: &str) {
+        let fixture = fixture::parse(fixture);
+        let position = fixture.position.unwrap();
+        let analysis = fixture.analysis;
+        let diagnostic = analysis
+            .diagnostics(&DiagnosticsConfig::default(), position.file_id)
+            .into_iter()
+            .find(|it| {
+                it.code == DiagnosticCode::UNENCODED_OUTPUT
+                    && it.range.contains_inclusive(position.offset)
+            })
+            .expect("no diagnostic");
+        let mut text = analysis.file_text(position.file_id).to_string();
+        let fix = diagnostic.fixes.into_iter().next().unwrap();
+        fix.source_change.source_file_edits[&position.file_id].apply(&mut text);
+        assert_eq!(text, expect);
+    }
+
+    #[test]
+    fn html_contexts() {
+        check(
+            r##"
+//- /page.cfm
+<cfoutput>
+<!-- #url.a# -->
+<a href="/search?q=#url.q#" title=#url.t# onclick='go("#url.b#")'>#url.c#</a>
+<div style="color: #url.color#" #url.attrs#>#encodeForHTML(url.d)#</div>
+<cfif x EQ "#url.e#"><p>#url.f#</p></cfif>
+<script>var a = "#url.g#";</script>
+<span>#val(url.n)# #arguments.x#</span>
+</cfoutput>
+<p>#url.nope#</p>
+"##,
+            &[
+                (
+                    "url.a",
+                    "`url.a` comes from the `url` scope and is output into HTML without encoding, wrap it in `encodeForHTML`",
+                    Severity::Error,
+                ),
+                (
+                    "url.q",
+                    "`url.q` comes from the `url` scope and is output into an HTML attribute without encoding, wrap it in `encodeForHTMLAttribute`",
+                    Severity::Error,
+                ),
+                (
+                    "url.t",
+                    "`url.t` comes from the `url` scope and is output into an HTML attribute without encoding, wrap it in `encodeForHTMLAttribute`",
+                    Severity::Error,
+                ),
+                (
+                    "url.b",
+                    "`url.b` comes from the `url` scope and is output into JavaScript without encoding, wrap it in `encodeForJavaScript`",
+                    Severity::Error,
+                ),
+                (
+                    "url.c",
+                    "`url.c` comes from the `url` scope and is output into HTML without encoding, wrap it in `encodeForHTML`",
+                    Severity::Error,
+                ),
+                (
+                    "url.color",
+                    "`url.color` comes from the `url` scope and is output into CSS without encoding, wrap it in `encodeForCSS`",
+                    Severity::Error,
+                ),
+                (
+                    "url.attrs",
+                    "`url.attrs` comes from the `url` scope and is output into an HTML attribute without encoding, wrap it in `encodeForHTMLAttribute`",
+                    Severity::Error,
+                ),
+                (
+                    "url.f",
+                    "`url.f` comes from the `url` scope and is output into HTML without encoding, wrap it in `encodeForHTML`",
+                    Severity::Error,
+                ),
+                (
+                    "url.g",
+                    "`url.g` comes from the `url` scope and is output into JavaScript without encoding, wrap it in `encodeForJavaScript`",
+                    Severity::Error,
+                ),
+            ],
+        );
+    }
+
+    #[test]
+    fn query_columns_and_encoders() {
+        check(
+            r##"
+//- /page.cfm
+<cfquery name="users">select name, bio from users</cfquery>
+<cfset title = form.title>
+<cfoutput query="users">
+<li data-bio="#encodeForHTML(bio)#">#name# #users.currentRow# #users.bio#</li>
+<h1>#title#</h1>
+</cfoutput>
+"##,
+            &[
+                (
+                    "encodeForHTML(bio)",
+                    "`encodeForHTML` does not encode `bio` for an HTML attribute, use `encodeForHTMLAttribute`",
+                    Severity::Warning,
+                ),
+                (
+                    "name",
+                    "`name` comes from the query `users` and is output into HTML without encoding, wrap it in `encodeForHTML`",
+                    Severity::Warning,
+                ),
+                (
+                    "users.bio",
+                    "`users.bio` comes from the query `users` and is output into HTML without encoding, wrap it in `encodeForHTML`",
+                    Severity::Warning,
+                ),
+                (
+                    "title",
+                    "`title` comes from the `form` scope and is output into HTML without encoding, wrap it in `encodeForHTML`",
+                    Severity::Error,
+                ),
+            ],
+        );
+    }
+
+    #[test]
+    fn write_output() {
+        check(
+            r##"
+//- /page.cfm
+<cfscript>
+    writeOutput(url.a);
+    writeOutput("<a href='#url.b#' title='" & encodeForHTML(url.c) & "'>");
+    writeOutput("<p>" & encodeForHTML(url.d) & "</p>");
+</cfscript>
+"##,
+            &[
+                (
+                    "url.a",
+                    "`url.a` comes from the `url` scope and is output into HTML without encoding, wrap it in `encodeForHTML`",
+                    Severity::Error,
+                ),
+                (
+                    "url.b",
+                    "`url.b` comes from the `url` scope and is output into an HTML attribute without encoding, wrap it in `encodeForHTMLAttribute`",
+                    Severity::Error,
+                ),
+                (
+                    "encodeForHTML(url.c)",
+                    "`encodeForHTML` does not encode `url.c` for an HTML attribute, use `encodeForHTMLAttribute`",
+                    Severity::Warning,
+                ),
+            ],
+        );
+    }
+
+    #[test]
+    fn fixes() {
+        check_fix(
+            r##"
+//- /page.cfm
+<cfoutput><a title="#url.$0t#">x</a></cfoutput>
+"##,
+            r##"<cfoutput><a title="#encodeForHTMLAttribute(url.t)#">x</a></cfoutput>
+"##,
+        );
+        check_fix(
+            r##"
+//- /page.cfm
+<cfoutput><script>go(#encodeFor$0HTML(url.t)#)</script></cfoutput>
+"##,
+            r##"<cfoutput><script>go(#encodeForJavaScript(url.t)#)</script></cfoutput>
+"##,
+        );
+    }
+}
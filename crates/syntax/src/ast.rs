@@ -0,0 +1,1022 @@
+//! Typed views over the untyped syntax tree.
+//!
+//! Each wrapper is a zero-cost newtype around a [`SyntaxNode`] of a given
+//! kind, with accessors for the parts of the construct. Accessors return
+//! `Option` since the tree may be incomplete while the user is typing.
+
+use rowan::TextRange;
+
+use crate::{
+    SyntaxKind::{self, *},
+    SyntaxNode, SyntaxToken,
+};
+
+pub trait AstNode {
+    fn can_cast(kind: SyntaxKind) -> bool
+    where
+        Self: Sized;
+
+    fn cast(syntax: SyntaxNode) -> Option<Self>
+    where
+        Self: Sized;
+
+    fn syntax(&self) -> &SyntaxNode;
+}
+
+macro_rules! ast_node {
+    ($(#[$attr:meta])* $name:ident, $kind:ident) => {
+        $(#[$attr])*
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        pub struct $name(SyntaxNode);
+
+        impl AstNode for $name {
+            fn can_cast(kind: SyntaxKind) -> bool {
+                kind == $kind
+            }
+
+            fn cast(syntax: SyntaxNode) -> Option<Self> {
+                Self::can_cast(syntax.kind()).then(|| $name(syntax))
+            }
+
+            fn syntax(&self) -> &SyntaxNode {
+                &self.0
+            }
+        }
+    };
+}
+
+fn child<N: AstNode>(parent: &SyntaxNode) -> Option<N> {
+    parent.children().find_map(N::cast)
+}
+
+fn children<N: AstNode>(parent: &SyntaxNode) -> impl Iterator<Item = N> {
+    parent.children().filter_map(N::cast)
+}
+
+fn token(parent: &SyntaxNode, kind: SyntaxKind) -> Option<SyntaxToken> {
+    parent
+        .children_with_tokens()
+        .filter_map(|it| it.into_token())
+        .find(|it| it.kind() == kind)
+}
+
+ast_node!(SourceFile, SOURCE_FILE);
+
+impl SourceFile {
+    /// The script component or interface declared by this file, if any.
+    pub fn component(&self) -> Option<Component> {
+        child(&self.0)
+    }
+
+    /// The top-level tags of a template or tag-based component.
+    pub fn tags(&self) -> impl Iterator<Item = Tag> {
+        children(&self.0)
+    }
+}
+
+// region: tags
+
+ast_node!(
+    /// A CFML tag element: `<cfif ...>...</cfif>` or `<cfset ...>`.
+    Tag,
+    TAG
+);
+
+impl Tag {
+    pub fn start_tag(&self) -> Option<StartTag> {
+        child(&self.0)
+    }
+
+    pub fn end_tag(&self) -> Option<EndTag> {
+        child(&self.0)
+    }
+
+    /// The lowercase tag name.
+    pub fn name(&self) -> String {
+        self.name_token()
+            .map(|it| it.text().to_ascii_lowercase())
+            .unwrap_or_default()
+    }
+
+    pub fn name_token(&self) -> Option<SyntaxToken> {
+        self.start_tag()?.name_token()
+    }
+
+    pub fn attributes(&self) -> impl Iterator<Item = Attribute> {
+        self.start_tag()
+            .into_iter()
+            .flat_map(|it| children::<Attribute>(it.syntax()).collect::<Vec<_>>())
+    }
+
+    /// Looks an attribute up by name, case-insensitively.
+    pub fn attribute(&self, name: &str) -> Option<Attribute> {
+        self.attributes().find(|it| {
+            it.name_token()
+                .is_some_and(|t| t.text().eq_ignore_ascii_case(name))
+        })
+    }
+
+    /// The literal value of the attribute `name`, if it has no interpolation.
+    pub fn attribute_value(&self, name: &str) -> Option<String> {
+        self.attribute(name)?.value_text()
+    }
+
+    /// The expression of `<cfset>`, `<cfif>`, `<cfelseif>` and `<cfreturn>`.
+    pub fn expression(&self) -> Option<Expr> {
+        child(self.start_tag()?.syntax())
+    }
+
+    /// The declaration of `<cfset var x = 1>`.
+    pub fn var_decl(&self) -> Option<VarDecl> {
+        child(self.start_tag()?.syntax())
+    }
+
+    /// The cfscript body of `<cfscript>`.
+    pub fn script(&self) -> Option<Script> {
+        child(&self.0)
+    }
+
+    /// Tags nested directly in the body of this tag.
+    pub fn child_tags(&self) -> impl Iterator<Item = Tag> {
+        children(&self.0)
+    }
+}
+
+ast_node!(StartTag, START_TAG);
+
+impl StartTag {
+    pub fn name_token(&self) -> Option<SyntaxToken> {
+        token(&self.0, TAG_NAME)
+    }
+
+    pub fn is_self_closed(&self) -> bool {
+        token(&self.0, SLASH_R_ANGLE).is_some()
+    }
+}
+
+ast_node!(EndTag, END_TAG);
+
+impl EndTag {
+    pub fn name_token(&self) -> Option<SyntaxToken> {
+        token(&self.0, TAG_NAME)
+    }
+}
+
+ast_node!(Attribute, ATTRIBUTE);
+
+/// The value of a tag attribute.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum AttributeValue {
+    /// `name="value"`, possibly with `#expr#` interpolation.
+    String(StringLit),
+    /// `name=#expr#`.
+    Hash(HashExpr),
+    /// `name=value`.
+    Unquoted(SyntaxToken),
+}
+
+impl AttributeValue {
+    /// The source text of the value, including quotes and hashes.
+    pub fn text(&self) -> String {
+        match self {
+            AttributeValue::String(it) => it.syntax().text().to_string(),
+            AttributeValue::Hash(it) => it.syntax().text().to_string(),
+            AttributeValue::Unquoted(it) => it.text().to_owned(),
+        }
+    }
+}
+
+impl Attribute {
+    pub fn name_token(&self) -> Option<SyntaxToken> {
+        token(&self.0, ATTR_NAME)
+    }
+
+    /// The lowercase attribute name.
+    pub fn name(&self) -> String {
+        self.name_token()
+            .map(|it| it.text().to_ascii_lowercase())
+            .unwrap_or_default()
+    }
+
+    pub fn value(&self) -> Option<AttributeValue> {
+        if let Some(string) = child::<StringLit>(&self.0) {
+            return Some(AttributeValue::String(string));
+        }
+        if let Some(hash) = child::<HashExpr>(&self.0) {
+            return Some(AttributeValue::Hash(hash));
+        }
+        token(&self.0, ATTR_VALUE).map(AttributeValue::Unquoted)
+    }
+
+    /// The literal value, if it has no interpolation.
+    pub fn value_text(&self) -> Option<String> {
+        match self.value()? {
+            AttributeValue::String(it) => it.value(),
+            AttributeValue::Hash(_) => None,
+            AttributeValue::Unquoted(it) => Some(it.text().to_owned()),
+        }
+    }
+
+    /// The range of the value without its quotes.
+    pub fn value_range(&self) -> Option<TextRange> {
+        match self.value()? {
+            AttributeValue::String(it) => Some(it.contents_range()),
+            AttributeValue::Hash(it) => Some(it.syntax().text_range()),
+            AttributeValue::Unquoted(it) => Some(it.text_range()),
+        }
+    }
+}
+
+ast_node!(
+    /// The cfscript body of a `<cfscript>` tag.
+    Script,
+    SCRIPT
+);
+
+impl Script {
+    pub fn statements(&self) -> impl Iterator<Item = Stmt> {
+        children(&self.0)
+    }
+}
+
+ast_node!(
+    /// `#expr#`.
+    HashExpr,
+    HASH_EXPR
+);
+
+impl HashExpr {
+    pub fn expr(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+}
+
+// endregion: tags
+
+// region: declarations
+
+ast_node!(
+    /// A script `component { }` or `interface { }` declaration.
+    Component,
+    COMPONENT
+);
+
+impl Component {
+    pub fn is_interface(&self) -> bool {
+        token(&self.0, INTERFACE_KW).is_some()
+    }
+
+    pub fn keyword(&self) -> Option<SyntaxToken> {
+        token(&self.0, COMPONENT_KW).or_else(|| token(&self.0, INTERFACE_KW))
+    }
+
+    pub fn attributes(&self) -> impl Iterator<Item = ScriptAttribute> {
+        children(&self.0)
+    }
+
+    /// The literal value of the attribute `name`.
+    pub fn attribute_value(&self, name: &str) -> Option<String> {
+        find_script_attribute(self.attributes(), name)?.value_text()
+    }
+
+    pub fn body(&self) -> Option<Block> {
+        child(&self.0)
+    }
+}
+
+fn find_script_attribute(
+    mut attributes: impl Iterator<Item = ScriptAttribute>,
+    name: &str,
+) -> Option<ScriptAttribute> {
+    attributes.find(|it| {
+        it.name_token()
+            .is_some_and(|t| t.text().eq_ignore_ascii_case(name))
+    })
+}
+
+ast_node!(
+    /// `name=value` metadata on a script declaration.
+    ScriptAttribute,
+    SCRIPT_ATTRIBUTE
+);
+
+impl ScriptAttribute {
+    pub fn name_token(&self) -> Option<SyntaxToken> {
+        token(&self.0, IDENT)
+    }
+
+    /// The lowercase attribute name.
+    pub fn name(&self) -> String {
+        self.name_token()
+            .map(|it| it.text().to_ascii_lowercase())
+            .unwrap_or_default()
+    }
+
+    pub fn value(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+
+    /// The literal value: string contents, or the text of a name, path,
+    /// number or boolean.
+    pub fn value_text(&self) -> Option<String> {
+        match self.value()? {
+            Expr::StringLit(it) => it.value(),
+            Expr::Literal(it) => Some(it.syntax().text().to_string()),
+            it @ (Expr::NameRef(_) | Expr::MemberExpr(_)) => Some(expr_path_text(&it)?),
+            _ => None,
+        }
+    }
+
+    /// The range of the value, without quotes for strings.
+    pub fn value_range(&self) -> Option<TextRange> {
+        match self.value()? {
+            Expr::StringLit(it) => Some(it.contents_range()),
+            it => Some(it.syntax().text_range()),
+        }
+    }
+}
+
+/// The dotted text of a name or member chain: `a.b.c`.
+pub fn expr_path_text(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::NameRef(it) => Some(it.name()),
+        Expr::MemberExpr(it) => {
+            let receiver = expr_path_text(&it.receiver()?)?;
+            Some(format!("{receiver}.{}", it.name_token()?.text()))
+        }
+        _ => None,
+    }
+}
+
+ast_node!(FunctionDecl, FUNCTION_DECL);
+
+impl FunctionDecl {
+    pub fn name_token(&self) -> Option<SyntaxToken> {
+        token(&self.0, IDENT)
+    }
+
+    pub fn name(&self) -> Option<String> {
+        self.name_token().map(|it| it.text().to_owned())
+    }
+
+    /// The lowercase access modifier: `public`, `private`, `package` or
+    /// `remote`.
+    pub fn access(&self) -> Option<String> {
+        let modifiers = child::<Modifiers>(&self.0)?;
+        token(modifiers.syntax(), ACCESS_KW).map(|it| it.text().to_ascii_lowercase())
+    }
+
+    pub fn is_static(&self) -> bool {
+        child::<Modifiers>(&self.0).is_some_and(|it| token(it.syntax(), STATIC_KW).is_some())
+    }
+
+    pub fn return_type(&self) -> Option<TypeRef> {
+        child(&self.0)
+    }
+
+    pub fn param_list(&self) -> Option<ParamList> {
+        child(&self.0)
+    }
+
+    pub fn params(&self) -> impl Iterator<Item = Param> {
+        self.param_list()
+            .into_iter()
+            .flat_map(|it| it.params().collect::<Vec<_>>())
+    }
+
+    pub fn attributes(&self) -> impl Iterator<Item = ScriptAttribute> {
+        children(&self.0)
+    }
+
+    pub fn attribute_value(&self, name: &str) -> Option<String> {
+        find_script_attribute(self.attributes(), name)?.value_text()
+    }
+
+    pub fn body(&self) -> Option<Block> {
+        child(&self.0)
+    }
+}
+
+ast_node!(Modifiers, MODIFIERS);
+
+ast_node!(ParamList, PARAM_LIST);
+
+impl ParamList {
+    pub fn params(&self) -> impl Iterator<Item = Param> {
+        children(&self.0)
+    }
+}
+
+ast_node!(Param, PARAM);
+
+impl Param {
+    pub fn name_token(&self) -> Option<SyntaxToken> {
+        token(&self.0, IDENT)
+    }
+
+    pub fn name(&self) -> Option<String> {
+        self.name_token().map(|it| it.text().to_owned())
+    }
+
+    pub fn is_required(&self) -> bool {
+        token(&self.0, REQUIRED_KW).is_some()
+    }
+
+    pub fn type_ref(&self) -> Option<TypeRef> {
+        child(&self.0)
+    }
+
+    pub fn default_value(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+
+    pub fn attributes(&self) -> impl Iterator<Item = ScriptAttribute> {
+        children(&self.0)
+    }
+
+    pub fn attribute_value(&self, name: &str) -> Option<String> {
+        find_script_attribute(self.attributes(), name)?.value_text()
+    }
+}
+
+ast_node!(PropertyDecl, PROPERTY_DECL);
+
+impl PropertyDecl {
+    /// The name of `property string name;`. For `property name="x";` see
+    /// [`PropertyDecl::attribute`].
+    pub fn name_token(&self) -> Option<SyntaxToken> {
+        token(&self.0, IDENT)
+    }
+
+    pub fn type_ref(&self) -> Option<TypeRef> {
+        child(&self.0)
+    }
+
+    pub fn attributes(&self) -> impl Iterator<Item = ScriptAttribute> {
+        children(&self.0)
+    }
+
+    pub fn attribute(&self, name: &str) -> Option<ScriptAttribute> {
+        find_script_attribute(self.attributes(), name)
+    }
+}
+
+ast_node!(ImportStmt, IMPORT_STMT);
+
+ast_node!(
+    /// A type in a signature: `string`, `models.User`, `User[]`.
+    TypeRef,
+    TYPE_REF
+);
+
+impl TypeRef {
+    pub fn text(&self) -> String {
+        self.0.text().to_string()
+    }
+}
+
+// endregion: declarations
+
+// region: statements
+
+ast_node!(Block, BLOCK);
+
+impl Block {
+    pub fn statements(&self) -> impl Iterator<Item = Stmt> {
+        children(&self.0)
+    }
+}
+
+ast_node!(
+    /// `var name = init`, in script or in `<cfset>`.
+    VarDecl,
+    VAR_DECL
+);
+
+impl VarDecl {
+    pub fn name_token(&self) -> Option<SyntaxToken> {
+        token(&self.0, IDENT)
+    }
+
+    pub fn initializer(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+}
+
+/// Any cfscript statement.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Stmt(SyntaxNode);
+
+impl AstNode for Stmt {
+    fn can_cast(kind: SyntaxKind) -> bool {
+        matches!(
+            kind,
+            BLOCK
+                | VAR_DECL
+                | EXPR_STMT
+                | IF_STMT
+                | FOR_STMT
+                | FOR_IN_STMT
+                | WHILE_STMT
+                | DO_WHILE_STMT
+                | SWITCH_STMT
+                | TRY_STMT
+                | RETURN_STMT
+                | BREAK_STMT
+                | CONTINUE_STMT
+                | SCRIPT_TAG_STMT
+                | EMPTY_STMT
+                | FUNCTION_DECL
+                | PROPERTY_DECL
+                | COMPONENT
+                | IMPORT_STMT
+        )
+    }
+
+    fn cast(syntax: SyntaxNode) -> Option<Self> {
+        Self::can_cast(syntax.kind()).then(|| Stmt(syntax))
+    }
+
+    fn syntax(&self) -> &SyntaxNode {
+        &self.0
+    }
+}
+
+ast_node!(
+    /// A tag written as a script statement: `lock scope="session" { }`.
+    ScriptTagStmt,
+    SCRIPT_TAG_STMT
+);
+
+impl ScriptTagStmt {
+    /// The lowercase tag name, without the `cf` prefix: `lock`.
+    pub fn name(&self) -> String {
+        token(&self.0, IDENT)
+            .map(|it| it.text().to_ascii_lowercase())
+            .unwrap_or_default()
+    }
+
+    pub fn attributes(&self) -> impl Iterator<Item = ScriptAttribute> {
+        children(&self.0)
+    }
+
+    pub fn attribute_value(&self, name: &str) -> Option<String> {
+        find_script_attribute(self.attributes(), name)?.value_text()
+    }
+
+    pub fn body(&self) -> Option<Block> {
+        child(&self.0)
+    }
+}
+
+// endregion: statements
+
+// region: expressions
+
+/// Any expression.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Expr {
+    Literal(Literal),
+    StringLit(StringLit),
+    HashExpr(HashExpr),
+    NameRef(NameRef),
+    MemberExpr(MemberExpr),
+    IndexExpr(IndexExpr),
+    CallExpr(CallExpr),
+    NewExpr(NewExpr),
+    StructLit(StructLit),
+    ArrayLit(ArrayLit),
+    ClosureExpr(ClosureExpr),
+    ArrowExpr(ArrowExpr),
+    ParenExpr(ParenExpr),
+    PrefixExpr(PrefixExpr),
+    PostfixExpr(PostfixExpr),
+    BinExpr(BinExpr),
+    TernaryExpr(TernaryExpr),
+    AssignExpr(AssignExpr),
+}
+
+impl AstNode for Expr {
+    fn can_cast(kind: SyntaxKind) -> bool {
+        matches!(
+            kind,
+            LITERAL
+                | STRING
+                | HASH_EXPR
+                | NAME_REF
+                | MEMBER_EXPR
+                | INDEX_EXPR
+                | CALL_EXPR
+                | NEW_EXPR
+                | STRUCT_LIT
+                | ARRAY_LIT
+                | CLOSURE_EXPR
+                | ARROW_EXPR
+                | PAREN_EXPR
+                | PREFIX_EXPR
+                | POSTFIX_EXPR
+                | BIN_EXPR
+                | TERNARY_EXPR
+                | ASSIGN_EXPR
+        )
+    }
+
+    fn cast(syntax: SyntaxNode) -> Option<Self> {
+        let expr = match syntax.kind() {
+            LITERAL => Expr::Literal(Literal(syntax)),
+            STRING => Expr::StringLit(StringLit(syntax)),
+            HASH_EXPR => Expr::HashExpr(HashExpr(syntax)),
+            NAME_REF => Expr::NameRef(NameRef(syntax)),
+            MEMBER_EXPR => Expr::MemberExpr(MemberExpr(syntax)),
+            INDEX_EXPR => Expr::IndexExpr(IndexExpr(syntax)),
+            CALL_EXPR => Expr::CallExpr(CallExpr(syntax)),
+            NEW_EXPR => Expr::NewExpr(NewExpr(syntax)),
+            STRUCT_LIT => Expr::StructLit(StructLit(syntax)),
+            ARRAY_LIT => Expr::ArrayLit(ArrayLit(syntax)),
+            CLOSURE_EXPR => Expr::ClosureExpr(ClosureExpr(syntax)),
+            ARROW_EXPR => Expr::ArrowExpr(ArrowExpr(syntax)),
+            PAREN_EXPR => Expr::ParenExpr(ParenExpr(syntax)),
+            PREFIX_EXPR => Expr::PrefixExpr(PrefixExpr(syntax)),
+            POSTFIX_EXPR => Expr::PostfixExpr(PostfixExpr(syntax)),
+            BIN_EXPR => Expr::BinExpr(BinExpr(syntax)),
+            TERNARY_EXPR => Expr::TernaryExpr(TernaryExpr(syntax)),
+            ASSIGN_EXPR => Expr::AssignExpr(AssignExpr(syntax)),
+            _ => return None,
+        };
+        Some(expr)
+    }
+
+    fn syntax(&self) -> &SyntaxNode {
+        match self {
+            Expr::Literal(it) => it.syntax(),
+            Expr::StringLit(it) => it.syntax(),
+            Expr::HashExpr(it) => it.syntax(),
+            Expr::NameRef(it) => it.syntax(),
+            Expr::MemberExpr(it) => it.syntax(),
+            Expr::IndexExpr(it) => it.syntax(),
+            Expr::CallExpr(it) => it.syntax(),
+            Expr::NewExpr(it) => it.syntax(),
+            Expr::StructLit(it) => it.syntax(),
+            Expr::ArrayLit(it) => it.syntax(),
+            Expr::ClosureExpr(it) => it.syntax(),
+            Expr::ArrowExpr(it) => it.syntax(),
+            Expr::ParenExpr(it) => it.syntax(),
+            Expr::PrefixExpr(it) => it.syntax(),
+            Expr::PostfixExpr(it) => it.syntax(),
+            Expr::BinExpr(it) => it.syntax(),
+            Expr::TernaryExpr(it) => it.syntax(),
+            Expr::AssignExpr(it) => it.syntax(),
+        }
+    }
+}
+
+ast_node!(Literal, LITERAL);
+
+ast_node!(
+    /// A string literal, possibly with `#expr#` interpolation.
+    StringLit,
+    STRING
+);
+
+impl StringLit {
+    /// The unescaped value, or `None` if the string is interpolated.
+    pub fn value(&self) -> Option<String> {
+        let quote = self.0.first_token()?.text().to_owned();
+        let mut value = String::new();
+        for element in self.0.children_with_tokens() {
+            match element {
+                rowan::NodeOrToken::Node(_) => return None,
+                rowan::NodeOrToken::Token(it) if it.kind() == STRING_TEXT => {
+                    let doubled = format!("{quote}{quote}");
+                    value.push_str(&it.text().replace(&doubled, &quote).replace("##", "#"));
+                }
+                rowan::NodeOrToken::Token(_) => (),
+            }
+        }
+        Some(value)
+    }
+
+    /// The range between the quotes.
+    pub fn contents_range(&self) -> TextRange {
+        let range = self.0.text_range();
+        let quotes: Vec<_> = self
+            .0
+            .children_with_tokens()
+            .filter(|it| it.kind() == QUOTE)
+            .map(|it| it.text_range())
+            .collect();
+        let start = quotes.first().map_or(range.start(), |it| it.end());
+        let end = match quotes.get(1) {
+            Some(it) => it.start(),
+            None => range.end(),
+        };
+        TextRange::new(start, end.max(start))
+    }
+
+    pub fn hash_exprs(&self) -> impl Iterator<Item = HashExpr> {
+        children(&self.0)
+    }
+}
+
+ast_node!(NameRef, NAME_REF);
+
+impl NameRef {
+    pub fn ident_token(&self) -> Option<SyntaxToken> {
+        self.0.first_token()
+    }
+
+    pub fn name(&self) -> String {
+        self.0.text().to_string()
+    }
+}
+
+ast_node!(
+    /// `receiver.name`, `receiver?.name` or `Component::name`.
+    MemberExpr,
+    MEMBER_EXPR
+);
+
+impl MemberExpr {
+    pub fn receiver(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+
+    pub fn name_token(&self) -> Option<SyntaxToken> {
+        token(&self.0, IDENT)
+    }
+}
+
+ast_node!(IndexExpr, INDEX_EXPR);
+
+impl IndexExpr {
+    pub fn base(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+
+    pub fn index(&self) -> Option<Expr> {
+        children(&self.0).nth(1)
+    }
+}
+
+ast_node!(CallExpr, CALL_EXPR);
+
+impl CallExpr {
+    pub fn callee(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+
+    pub fn arg_list(&self) -> Option<ArgList> {
+        child(&self.0)
+    }
+
+    pub fn args(&self) -> impl Iterator<Item = Arg> {
+        self.arg_list()
+            .into_iter()
+            .flat_map(|it| it.args().collect::<Vec<_>>())
+    }
+
+    /// The name of the called function, for `f()` and `x.f()`.
+    pub fn name_token(&self) -> Option<SyntaxToken> {
+        match self.callee()? {
+            Expr::NameRef(it) => it.ident_token(),
+            Expr::MemberExpr(it) => it.name_token(),
+            _ => None,
+        }
+    }
+}
+
+ast_node!(ArgList, ARG_LIST);
+
+/// A call argument.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Arg {
+    Positional(Expr),
+    Named(NamedArg),
+}
+
+impl ArgList {
+    pub fn args(&self) -> impl Iterator<Item = Arg> {
+        self.0.children().filter_map(|it| match it.kind() {
+            ARG => child(&it).map(Arg::Positional),
+            NAMED_ARG => Some(Arg::Named(NamedArg(it))),
+            _ => None,
+        })
+    }
+}
+
+ast_node!(
+    /// `name=value` or `name: value` in an argument list.
+    NamedArg,
+    NAMED_ARG
+);
+
+impl NamedArg {
+    pub fn name_token(&self) -> Option<SyntaxToken> {
+        token(&self.0, IDENT)
+    }
+
+    pub fn value(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+}
+
+ast_node!(NewExpr, NEW_EXPR);
+
+impl NewExpr {
+    pub fn path(&self) -> Option<ComponentPath> {
+        child(&self.0)
+    }
+
+    /// `new "models.User"()`.
+    pub fn path_string(&self) -> Option<StringLit> {
+        child(&self.0)
+    }
+
+    pub fn arg_list(&self) -> Option<ArgList> {
+        child(&self.0)
+    }
+}
+
+ast_node!(
+    /// A dotted component path: `models.user.User`.
+    ComponentPath,
+    COMPONENT_PATH
+);
+
+impl ComponentPath {
+    pub fn segments(&self) -> impl Iterator<Item = SyntaxToken> {
+        self.0
+            .children_with_tokens()
+            .filter_map(|it| it.into_token())
+            .filter(|it| it.kind() == IDENT)
+    }
+
+    pub fn text(&self) -> String {
+        self.segments()
+            .map(|it| it.text().to_owned())
+            .collect::<Vec<_>>()
+            .join(".")
+    }
+}
+
+ast_node!(StructLit, STRUCT_LIT);
+
+impl StructLit {
+    pub fn entries(&self) -> impl Iterator<Item = StructEntry> {
+        children(&self.0)
+    }
+}
+
+ast_node!(StructEntry, STRUCT_ENTRY);
+
+impl StructEntry {
+    pub fn key_token(&self) -> Option<SyntaxToken> {
+        token(&self.0, IDENT).or_else(|| token(&self.0, NUMBER))
+    }
+
+    pub fn value(&self) -> Option<Expr> {
+        let mut exprs = children::<Expr>(&self.0);
+        let first = exprs.next()?;
+        match (self.key_token(), first) {
+            (None, Expr::StringLit(_)) => exprs.next(),
+            (_, first) => Some(first),
+        }
+    }
+}
+
+ast_node!(ArrayLit, ARRAY_LIT);
+
+impl ArrayLit {
+    pub fn elements(&self) -> impl Iterator<Item = Expr> {
+        children(&self.0)
+    }
+}
+
+ast_node!(
+    /// `function(a) { }`.
+    ClosureExpr,
+    CLOSURE_EXPR
+);
+
+impl ClosureExpr {
+    pub fn param_list(&self) -> Option<ParamList> {
+        child(&self.0)
+    }
+
+    pub fn body(&self) -> Option<Block> {
+        child(&self.0)
+    }
+}
+
+ast_node!(
+    /// `(a) => a * 2`.
+    ArrowExpr,
+    ARROW_EXPR
+);
+
+impl ArrowExpr {
+    pub fn param_list(&self) -> Option<ParamList> {
+        child(&self.0)
+    }
+
+    pub fn body_block(&self) -> Option<Block> {
+        child(&self.0)
+    }
+
+    pub fn body_expr(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+}
+
+ast_node!(ParenExpr, PAREN_EXPR);
+
+impl ParenExpr {
+    pub fn expr(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+}
+
+ast_node!(PrefixExpr, PREFIX_EXPR);
+
+impl PrefixExpr {
+    pub fn op_token(&self) -> Option<SyntaxToken> {
+        self.0.first_token()
+    }
+
+    pub fn operand(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+}
+
+ast_node!(PostfixExpr, POSTFIX_EXPR);
+
+impl PostfixExpr {
+    pub fn operand(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+}
+
+ast_node!(BinExpr, BIN_EXPR);
+
+impl BinExpr {
+    pub fn lhs(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+
+    pub fn rhs(&self) -> Option<Expr> {
+        children(&self.0).nth(1)
+    }
+
+    /// The lowercase operator, with multi-word operators joined by a space:
+    /// `&`, `eq`, `does not contain`.
+    pub fn op(&self) -> String {
+        self.0
+            .children_with_tokens()
+            .filter_map(|it| it.into_token())
+            .filter(|it| !it.kind().is_trivia())
+            .map(|it| it.text().to_ascii_lowercase())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+ast_node!(TernaryExpr, TERNARY_EXPR);
+
+impl TernaryExpr {
+    pub fn condition(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+
+    pub fn then_expr(&self) -> Option<Expr> {
+        children(&self.0).nth(1)
+    }
+
+    pub fn else_expr(&self) -> Option<Expr> {
+        children(&self.0).nth(2)
+    }
+}
+
+ast_node!(
+    /// `target = value` and compound assignments like `target &= value`.
+    AssignExpr,
+    ASSIGN_EXPR
+);
+
+impl AssignExpr {
+    pub fn target(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+
+    pub fn value(&self) -> Option<Expr> {
+        children(&self.0).nth(1)
+    }
+
+    pub fn op_token(&self) -> Option<SyntaxToken> {
+        self.0
+            .children_with_tokens()
+            .filter_map(|it| it.into_token())
+            .find(|it| !it.kind().is_trivia())
+    }
+}
+
+// endregion: expressions
@@ -0,0 +1,255 @@
+//! Lexing primitives for the three CFML lexical modes.
+//!
+//! Which mode applies depends on the surrounding syntax (text, tag, script,
+//! string), so the parser drives the lexer one token at a time instead of
+//! tokenizing the file upfront.
+
+use crate::SyntaxKind::{self, *};
+
+/// Lexes a single cfscript token (trivia included) at the start of `text`.
+///
+/// Returns the token kind and its length in bytes. `text` must not be empty.
+pub(crate) fn script_token(text: &str) -> (SyntaxKind, usize) {
+    let bytes = text.as_bytes();
+    let first = text.chars().next().expect("script_token called at EOF");
+
+    if first.is_whitespace() {
+        let len = text
+            .char_indices()
+            .find(|(_, c)| !c.is_whitespace())
+            .map_or(text.len(), |(idx, _)| idx);
+        return (WHITESPACE, len);
+    }
+    if text.starts_with("//") {
+        return (LINE_COMMENT, text.find('\n').unwrap_or(text.len()));
+    }
+    if let Some(rest) = text.strip_prefix("/*") {
+        let len = rest.find("*/").map_or(text.len(), |idx| idx + 4);
+        return (BLOCK_COMMENT, len);
+    }
+    if text.starts_with("<!---") {
+        return (TAG_COMMENT, tag_comment_len(text).0);
+    }
+    if is_ident_start(first) {
+        return (IDENT, ident_len(text));
+    }
+    if first.is_ascii_digit() || (first == '.' && bytes.get(1).is_some_and(|b| b.is_ascii_digit()))
+    {
+        return (NUMBER, number_len(text));
+    }
+
+    const PUNCTUATION: &[(&str, SyntaxKind)] = &[
+        ("===", EQ3),
+        ("!==", NEQ2),
+        ("==", EQ2),
+        ("!=", NEQ),
+        ("<=", LTEQ),
+        (">=", GTEQ),
+        ("&&", AMP2),
+        ("||", PIPE2),
+        ("++", PLUS2),
+        ("--", MINUS2),
+        ("+=", PLUSEQ),
+        ("-=", MINUSEQ),
+        ("*=", STAREQ),
+        ("/=", SLASHEQ),
+        ("%=", PERCENTEQ),
+        ("&=", AMPEQ),
+        ("?.", QUESTION_DOT),
+        ("?:", ELVIS),
+        ("=>", FAT_ARROW),
+        ("::", COLON2),
+        ("</", L_ANGLE_SLASH),
+        ("(", L_PAREN),
+        (")", R_PAREN),
+        ("{", L_CURLY),
+        ("}", R_CURLY),
+        ("[", L_BRACK),
+        ("]", R_BRACK),
+        (",", COMMA),
+        (";", SEMICOLON),
+        (":", COLON),
+        (".", DOT),
+        ("?", QUESTION),
+        ("=", EQ),
+        ("<", LT),
+        (">", GT),
+        ("+", PLUS),
+        ("-", MINUS),
+        ("*", STAR),
+        ("/", SLASH),
+        ("\\", BACKSLASH),
+        ("%", PERCENT),
+        ("^", CARET),
+        ("&", AMP),
+        ("!", BANG),
+        ("#", HASH),
+        ("\"", QUOTE),
+        ("'", QUOTE),
+    ];
+    for &(punct, kind) in PUNCTUATION {
+        if text.starts_with(punct) {
+            return (kind, punct.len());
+        }
+    }
+    (ERROR_TOKEN, first.len_utf8())
+}
+
+/// Length of a `<!--- ... --->` comment at the start of `text`, and whether it
+/// was terminated. CFML comments nest.
+pub(crate) fn tag_comment_len(text: &str) -> (usize, bool) {
+    debug_assert!(text.starts_with("<!---"));
+    let mut depth = 0usize;
+    let mut idx = 0;
+    while idx < text.len() {
+        let rest = &text[idx..];
+        if rest.starts_with("<!---") {
+            depth += 1;
+            idx += 5;
+        } else if rest.starts_with("--->") {
+            depth -= 1;
+            idx += 4;
+            if depth == 0 {
+                return (idx, true);
+            }
+        } else {
+            idx += rest.chars().next().map_or(1, char::len_utf8);
+        }
+    }
+    (text.len(), false)
+}
+
+pub(crate) fn is_ident_start(c: char) -> bool {
+    c.is_alphabetic() || c == '_' || c == '$'
+}
+
+pub(crate) fn is_ident_continue(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+pub(crate) fn ident_len(text: &str) -> usize {
+    text.char_indices()
+        .find(|&(_, c)| !is_ident_continue(c))
+        .map_or(text.len(), |(idx, _)| idx)
+}
+
+fn number_len(text: &str) -> usize {
+    let bytes = text.as_bytes();
+    let mut idx = 0;
+    let digits = |idx: &mut usize| {
+        while bytes.get(*idx).is_some_and(u8::is_ascii_digit) {
+            *idx += 1;
+        }
+    };
+    digits(&mut idx);
+    if bytes.get(idx) == Some(&b'.') && bytes.get(idx + 1).is_some_and(u8::is_ascii_digit) {
+        idx += 1;
+        digits(&mut idx);
+    }
+    if matches!(bytes.get(idx), Some(b'e' | b'E')) {
+        let mut exp = idx + 1;
+        if matches!(bytes.get(exp), Some(b'+' | b'-')) {
+            exp += 1;
+        }
+        if bytes.get(exp).is_some_and(u8::is_ascii_digit) {
+            idx = exp;
+            digits(&mut idx);
+        }
+    }
+    idx
+}
+
+/// Length of a tag name at the start of `text`: `cfset`, `cf_panel`,
+/// `ui:button`.
+pub(crate) fn tag_name_len(text: &str) -> usize {
+    text.char_indices()
+        .find(|&(_, c)| !(is_ident_continue(c) || c == ':' || c == '-' || c == '.'))
+        .map_or(text.len(), |(idx, _)| idx)
+}
+
+/// Length of an attribute name at the start of `text`.
+pub(crate) fn attr_name_len(text: &str) -> usize {
+    text.char_indices()
+        .find(|&(_, c)| c.is_whitespace() || matches!(c, '=' | '>' | '/' | '"' | '\'' | '<' | '#'))
+        .map_or(text.len(), |(idx, _)| idx)
+}
+
+/// Length of an unquoted attribute value at the start of `text`.
+pub(crate) fn attr_value_len(text: &str) -> usize {
+    let mut end = text
+        .char_indices()
+        .find(|&(_, c)| c.is_whitespace() || c == '>')
+        .map_or(text.len(), |(idx, _)| idx);
+    if text[..end].ends_with('/') && text[end..].starts_with('>') {
+        end -= 1;
+    }
+    end
+}
+
+/// Returns the length of the leading whitespace of `text`.
+pub(crate) fn whitespace_len(text: &str) -> usize {
+    text.char_indices()
+        .find(|(_, c)| !c.is_whitespace())
+        .map_or(text.len(), |(idx, _)| idx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(mut text: &str) -> Vec<(SyntaxKind, &str)> {
+        let mut res = Vec::new();
+        while !text.is_empty() {
+            let (kind, len) = script_token(text);
+            res.push((kind, &text[..len]));
+            text = &text[len..];
+        }
+        res
+    }
+
+    #[test]
+    fn lexes_script_tokens() {
+        assert_eq!(
+            tokens("local.x += 1.5e3;"),
+            vec![
+                (IDENT, "local"),
+                (DOT, "."),
+                (IDENT, "x"),
+                (WHITESPACE, " "),
+                (PLUSEQ, "+="),
+                (WHITESPACE, " "),
+                (NUMBER, "1.5e3"),
+                (SEMICOLON, ";"),
+            ]
+        );
+        assert_eq!(
+            tokens("a?.b ?: c // hi"),
+            vec![
+                (IDENT, "a"),
+                (QUESTION_DOT, "?."),
+                (IDENT, "b"),
+                (WHITESPACE, " "),
+                (ELVIS, "?:"),
+                (WHITESPACE, " "),
+                (IDENT, "c"),
+                (WHITESPACE, " "),
+                (LINE_COMMENT, "// hi"),
+            ]
+        );
+    }
+
+    #[test]
+    fn tag_comments_nest() {
+        let text = "<!--- a <!--- b ---> c ---> rest";
+        assert_eq!(tag_comment_len(text), (text.len() - " rest".len(), true));
+        assert_eq!(tag_comment_len("<!--- open"), (10, false));
+    }
+
+    #[test]
+    fn lexes_tag_parts() {
+        assert_eq!(tag_name_len("ui:button label"), "ui:button".len());
+        assert_eq!(attr_name_len("name=\"x\""), 4);
+        assert_eq!(attr_value_len("10/>"), 2);
+        assert_eq!(attr_value_len("10 to=1"), 2);
+    }
+}
@@ -0,0 +1,96 @@
+//! Lossless syntax trees for CFML templates and components.
+//!
+//! Parsing never fails: [`SourceFile::parse`] always produces a tree covering
+//! every byte of the input, with syntax errors collected on the side.
+
+pub mod ast;
+mod lexer;
+mod parser;
+mod syntax_kind;
+pub mod tags;
+
+use std::fmt;
+use std::sync::Arc;
+
+pub use rowan::{Direction, NodeOrToken, TextRange, TextSize, TokenAtOffset, WalkEvent};
+
+pub use crate::{
+    ast::AstNode,
+    syntax_kind::{
+        CfmlLanguage, SyntaxElement, SyntaxKind, SyntaxNode, SyntaxNodeChildren, SyntaxToken,
+    },
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SyntaxError {
+    message: String,
+    range: TextRange,
+}
+
+impl SyntaxError {
+    pub fn new(message: impl Into<String>, range: TextRange) -> Self {
+        SyntaxError {
+            message: message.into(),
+            range,
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn range(&self) -> TextRange {
+        self.range
+    }
+}
+
+impl fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at {:?}", self.message, self.range)
+    }
+}
+
+/// The result of parsing a file: the green tree and its syntax errors.
+///
+/// Cheap to clone.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Parse {
+    green: rowan::GreenNode,
+    errors: Arc<[SyntaxError]>,
+}
+
+impl Parse {
+    pub fn syntax_node(&self) -> SyntaxNode {
+        SyntaxNode::new_root(self.green.clone())
+    }
+
+    pub fn tree(&self) -> ast::SourceFile {
+        ast::SourceFile::cast(self.syntax_node()).unwrap()
+    }
+
+    pub fn errors(&self) -> &[SyntaxError] {
+        &self.errors
+    }
+
+    /// Renders the tree and its errors, one element per line. Used in tests.
+    pub fn debug_dump(&self) -> String {
+        let mut buf = format!("{:#?}", self.syntax_node());
+        for error in self.errors.iter() {
+            buf.push_str(&format!("error {:?}: {}\n", error.range(), error.message()));
+        }
+        buf
+    }
+}
+
+impl ast::SourceFile {
+    pub fn parse(text: &str) -> Parse {
+        let (green, errors) = parser::parse(text);
+        Parse {
+            green,
+            errors: errors.into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
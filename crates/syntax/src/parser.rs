@@ -0,0 +1,246 @@
+//! A hand-written recursive descent parser for CFML producing a lossless
+//! rowan tree.
+//!
+//! The parser never fails: syntax errors are recorded next to the tree and
+//! the offending input is wrapped in `ERROR` nodes, so every byte of the
+//! source ends up in the tree.
+
+mod expressions;
+mod statements;
+mod template;
+
+use rowan::{Checkpoint, GreenNode, GreenNodeBuilder, TextRange, TextSize};
+
+use crate::{
+    lexer, SyntaxError,
+    SyntaxKind::{self, *},
+};
+
+pub(crate) fn parse(text: &str) -> (GreenNode, Vec<SyntaxError>) {
+    let mut p = Parser::new(text);
+    p.builder.start_node(SOURCE_FILE.into());
+    if statements::is_script_file(text) {
+        p.script_mode = true;
+        statements::script_file(&mut p);
+    } else {
+        template::template_file(&mut p);
+    }
+    p.builder.finish_node();
+    (p.builder.finish(), p.errors)
+}
+
+pub(crate) struct Parser<'t> {
+    text: &'t str,
+    pos: usize,
+    builder: GreenNodeBuilder<'static>,
+    errors: Vec<SyntaxError>,
+    /// Whether whitespace and comments are trivia (script) or text (template).
+    script_mode: bool,
+    /// Inside a tag expression like `<cfif a gt b>`, where `>` ends the tag.
+    in_tag_expr: bool,
+    /// Nesting of `cfoutput`-like tags, in which `#` in text starts an
+    /// expression.
+    output_depth: u32,
+    /// Lowercase names of the tags whose bodies are being parsed.
+    open_tags: Vec<String>,
+    /// Lowercase prefixes declared with `<cfimport prefix="ui">`.
+    tag_prefixes: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Token<'t> {
+    pub(crate) kind: SyntaxKind,
+    pub(crate) text: &'t str,
+    pub(crate) start: usize,
+}
+
+impl<'t> Parser<'t> {
+    fn new(text: &'t str) -> Self {
+        Parser {
+            text,
+            pos: 0,
+            builder: GreenNodeBuilder::new(),
+            errors: Vec::new(),
+            script_mode: false,
+            in_tag_expr: false,
+            output_depth: 0,
+            open_tags: Vec::new(),
+            tag_prefixes: Vec::new(),
+        }
+    }
+
+    fn rest(&self) -> &'t str {
+        &self.text[self.pos..]
+    }
+
+    // region: script tokens
+
+    /// Iterates over the non-trivia script tokens ahead of the cursor, ending
+    /// with a single `EOF` token.
+    fn tokens(&self) -> impl Iterator<Item = Token<'t>> + '_ {
+        let mut pos = self.pos;
+        let mut done = false;
+        std::iter::from_fn(move || loop {
+            if done {
+                return None;
+            }
+            let rest = &self.text[pos..];
+            if rest.is_empty() {
+                done = true;
+                return Some(Token {
+                    kind: EOF,
+                    text: "",
+                    start: pos,
+                });
+            }
+            let (kind, len) = lexer::script_token(rest);
+            let start = pos;
+            pos += len;
+            if !kind.is_trivia() {
+                return Some(Token {
+                    kind,
+                    text: &rest[..len],
+                    start,
+                });
+            }
+        })
+    }
+
+    /// Returns the `n`th non-trivia script token ahead of the cursor.
+    fn nth_token(&self, n: usize) -> Token<'t> {
+        self.tokens().nth(n).unwrap_or(Token {
+            kind: EOF,
+            text: "",
+            start: self.text.len(),
+        })
+    }
+
+    fn nth(&self, n: usize) -> SyntaxKind {
+        self.nth_token(n).kind
+    }
+
+    fn current(&self) -> SyntaxKind {
+        self.nth(0)
+    }
+
+    fn at(&self, kind: SyntaxKind) -> bool {
+        self.current() == kind
+    }
+
+    /// Whether the `n`th token is the identifier `kw`, case-insensitively.
+    fn nth_at_kw(&self, n: usize, kw: &str) -> bool {
+        let token = self.nth_token(n);
+        token.kind == IDENT && token.text.eq_ignore_ascii_case(kw)
+    }
+
+    fn at_kw(&self, kw: &str) -> bool {
+        self.nth_at_kw(0, kw)
+    }
+
+    /// Emits leading script trivia into the currently open node.
+    fn eat_trivia(&mut self) {
+        while !self.rest().is_empty() {
+            let (kind, len) = lexer::script_token(self.rest());
+            if !kind.is_trivia() {
+                break;
+            }
+            if kind == BLOCK_COMMENT && !self.rest()[..len].ends_with("*/") {
+                self.error_at(self.pos, self.pos + len, "unterminated block comment");
+            }
+            if kind == TAG_COMMENT && !lexer::tag_comment_len(self.rest()).1 {
+                self.error_at(self.pos, self.pos + len, "unterminated comment");
+            }
+            self.token(kind, len);
+        }
+    }
+
+    fn bump(&mut self) {
+        let kind = self.current();
+        self.bump_remap(kind);
+    }
+
+    /// Consumes the current script token, giving it `kind`.
+    fn bump_remap(&mut self, kind: SyntaxKind) {
+        self.eat_trivia();
+        let token = self.nth_token(0);
+        if token.kind == EOF {
+            return;
+        }
+        self.token(kind, token.text.len());
+    }
+
+    fn eat(&mut self, kind: SyntaxKind) -> bool {
+        if self.at(kind) {
+            self.bump();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, kind: SyntaxKind, what: &str) -> bool {
+        if self.eat(kind) {
+            return true;
+        }
+        self.error(format!("expected {what}"));
+        false
+    }
+
+    /// Consumes the current token wrapped in an `ERROR` node.
+    fn err_and_bump(&mut self, message: &str) {
+        self.error(message);
+        self.start(ERROR);
+        self.bump();
+        self.finish();
+    }
+
+    // endregion: script tokens
+
+    // region: tree building
+
+    fn token(&mut self, kind: SyntaxKind, len: usize) {
+        let text = &self.text[self.pos..self.pos + len];
+        self.builder.token(kind.into(), text);
+        self.pos += len;
+    }
+
+    fn start(&mut self, kind: SyntaxKind) {
+        if self.script_mode {
+            self.eat_trivia();
+        }
+        self.builder.start_node(kind.into());
+    }
+
+    fn finish(&mut self) {
+        self.builder.finish_node();
+    }
+
+    fn checkpoint(&mut self) -> Checkpoint {
+        if self.script_mode {
+            self.eat_trivia();
+        }
+        self.builder.checkpoint()
+    }
+
+    fn start_at(&mut self, checkpoint: Checkpoint, kind: SyntaxKind) {
+        self.builder.start_node_at(checkpoint, kind.into());
+    }
+
+    // endregion: tree building
+
+    // region: errors
+
+    /// Records an error at the next script token.
+    fn error(&mut self, message: impl Into<String>) {
+        let token = self.nth_token(0);
+        let end = token.start + token.text.len();
+        self.error_at(token.start, end, message);
+    }
+
+    fn error_at(&mut self, start: usize, end: usize, message: impl Into<String>) {
+        let range = TextRange::new(TextSize::from(start as u32), TextSize::from(end as u32));
+        self.errors.push(SyntaxError::new(message, range));
+    }
+
+    // endregion: errors
+}
@@ -0,0 +1,468 @@
+//! cfscript expressions, shared by script bodies, tag expressions and `#`
+//! interpolation.
+
+use super::{statements, Parser};
+use crate::SyntaxKind::{self, *};
+
+/// Parses an expression, assignments included. Returns `false` without
+/// consuming anything when the input does not start an expression.
+pub(super) fn expr(p: &mut Parser) -> bool {
+    assignment(p, true)
+}
+
+/// Parses an expression in a position where `=` is not an assignment, like
+/// argument lists, attribute values and defaults.
+pub(super) fn expr_no_assign(p: &mut Parser) -> bool {
+    assignment(p, false)
+}
+
+/// Whether the cursor is at the `>` or `/>` ending a start tag.
+pub(super) fn at_tag_close(p: &Parser) -> bool {
+    let token = p.nth_token(0);
+    match token.kind {
+        GT | EOF => true,
+        SLASH => p.text[token.start + 1..].starts_with('>'),
+        _ => false,
+    }
+}
+
+fn assignment(p: &mut Parser, allow_assign: bool) -> bool {
+    let checkpoint = p.checkpoint();
+    if !ternary(p) {
+        return false;
+    }
+    if allow_assign
+        && matches!(
+            p.current(),
+            EQ | PLUSEQ | MINUSEQ | STAREQ | SLASHEQ | PERCENTEQ | AMPEQ
+        )
+    {
+        p.start_at(checkpoint, ASSIGN_EXPR);
+        p.bump();
+        if !assignment(p, true) {
+            p.error("expected an expression");
+        }
+        p.finish();
+    }
+    true
+}
+
+fn ternary(p: &mut Parser) -> bool {
+    let checkpoint = p.checkpoint();
+    if !binary(p, 0) {
+        return false;
+    }
+    if p.at(QUESTION) {
+        p.start_at(checkpoint, TERNARY_EXPR);
+        p.bump();
+        if !expr(p) {
+            p.error("expected an expression");
+        }
+        p.expect(COLON, "`:`");
+        if !expr(p) {
+            p.error("expected an expression");
+        }
+        p.finish();
+    }
+    true
+}
+
+const PREFIX_NOT_BP: u8 = 7;
+const UNARY_BP: u8 = 14;
+
+/// Returns the binding power of the binary operator at the cursor and the
+/// number of tokens it is spelled with (`does not contain` takes three).
+fn binary_op(p: &Parser) -> Option<(u8, usize)> {
+    let token = p.nth_token(0);
+    let op = match token.kind {
+        GT if p.in_tag_expr => return None,
+        SLASH if p.in_tag_expr && p.text[token.start + 1..].starts_with('>') => return None,
+        ELVIS => (1, 1),
+        PIPE2 => (5, 1),
+        AMP2 => (6, 1),
+        EQ2 | EQ3 | NEQ | NEQ2 | LT | LTEQ | GT | GTEQ => (8, 1),
+        AMP => (9, 1),
+        PLUS | MINUS => (10, 1),
+        PERCENT => (11, 1),
+        STAR | SLASH | BACKSLASH => (12, 1),
+        CARET => (13, 1),
+        IDENT => match token.text.to_ascii_lowercase().as_str() {
+            "imp" => (2, 1),
+            "eqv" => (3, 1),
+            "xor" => (4, 1),
+            "or" => (5, 1),
+            "and" => (6, 1),
+            "is" if p.nth_at_kw(1, "not") => (8, 2),
+            "does" if p.nth_at_kw(1, "not") && p.nth_at_kw(2, "contain") => (8, 3),
+            "eq" | "equal" | "neq" | "is" | "gt" | "gte" | "ge" | "lt" | "lte" | "le"
+            | "contains" | "ct" => (8, 1),
+            "mod" => (11, 1),
+            _ => return None,
+        },
+        _ => return None,
+    };
+    Some(op)
+}
+
+fn binary(p: &mut Parser, min_bp: u8) -> bool {
+    let checkpoint = p.checkpoint();
+    if !unary(p) {
+        return false;
+    }
+    while let Some((bp, n_tokens)) = binary_op(p) {
+        if bp <= min_bp {
+            break;
+        }
+        p.start_at(checkpoint, BIN_EXPR);
+        for _ in 0..n_tokens {
+            let kind = match p.current() {
+                IDENT => WORD_OP,
+                kind => kind,
+            };
+            p.bump_remap(kind);
+        }
+        if !binary(p, bp) {
+            p.error("expected an expression");
+        }
+        p.finish();
+    }
+    true
+}
+
+fn unary(p: &mut Parser) -> bool {
+    let (kind, operand_bp) = match p.current() {
+        BANG => (BANG, PREFIX_NOT_BP),
+        IDENT if p.at_kw("not") => (WORD_OP, PREFIX_NOT_BP),
+        MINUS | PLUS | PLUS2 | MINUS2 => (p.current(), UNARY_BP - 1),
+        _ => return postfix(p),
+    };
+    p.start(PREFIX_EXPR);
+    p.bump_remap(kind);
+    if !binary(p, operand_bp) {
+        p.error("expected an expression");
+    }
+    p.finish();
+    true
+}
+
+fn postfix(p: &mut Parser) -> bool {
+    let checkpoint = p.checkpoint();
+    if !primary(p) {
+        return false;
+    }
+    loop {
+        match p.current() {
+            DOT | QUESTION_DOT | COLON2 => {
+                p.start_at(checkpoint, MEMBER_EXPR);
+                p.bump();
+                if !p.eat(IDENT) {
+                    p.error("expected a member name");
+                }
+                p.finish();
+            }
+            L_BRACK => {
+                p.start_at(checkpoint, INDEX_EXPR);
+                p.bump();
+                if !expr(p) {
+                    p.error("expected an expression");
+                }
+                p.expect(R_BRACK, "`]`");
+                p.finish();
+            }
+            L_PAREN => {
+                p.start_at(checkpoint, CALL_EXPR);
+                arg_list(p);
+                p.finish();
+            }
+            PLUS2 | MINUS2 => {
+                p.start_at(checkpoint, POSTFIX_EXPR);
+                p.bump();
+                p.finish();
+            }
+            _ => return true,
+        }
+    }
+}
+
+fn primary(p: &mut Parser) -> bool {
+    let token = p.nth_token(0);
+    match token.kind {
+        NUMBER => {
+            p.start(LITERAL);
+            p.bump();
+            p.finish();
+        }
+        QUOTE => string(p),
+        HASH => hash_expr(p),
+        L_PAREN if at_arrow_params(p) => arrow(p),
+        L_PAREN => {
+            p.start(PAREN_EXPR);
+            p.bump();
+            if !expr(p) {
+                p.error("expected an expression");
+            }
+            p.expect(R_PAREN, "`)`");
+            p.finish();
+        }
+        L_BRACK => array_lit(p),
+        L_CURLY => struct_lit(p),
+        IDENT => match SyntaxKind::from_keyword(token.text) {
+            Some(kw @ (TRUE_KW | FALSE_KW | NULL_KW)) => {
+                p.start(LITERAL);
+                p.bump_remap(kw);
+                p.finish();
+            }
+            Some(NEW_KW) if matches!(p.nth(1), IDENT | QUOTE) => new_expr(p),
+            Some(FUNCTION_KW) if p.nth(1) == L_PAREN => closure(p),
+            _ if p.nth(1) == FAT_ARROW => arrow(p),
+            _ => {
+                p.start(NAME_REF);
+                p.bump();
+                p.finish();
+            }
+        },
+        _ => return false,
+    }
+    true
+}
+
+/// Whether the parenthesis at the cursor opens the parameter list of an
+/// arrow function: `(a, b) => a + b`.
+fn at_arrow_params(p: &Parser) -> bool {
+    let mut depth = 0;
+    for (n, token) in p.tokens().enumerate().take(64) {
+        match token.kind {
+            L_PAREN => depth += 1,
+            R_PAREN => {
+                depth -= 1;
+                if depth == 0 {
+                    return p.nth(n + 1) == FAT_ARROW;
+                }
+            }
+            SEMICOLON | L_CURLY | R_CURLY | EOF => return false,
+            _ => (),
+        }
+    }
+    false
+}
+
+fn arrow(p: &mut Parser) {
+    p.start(ARROW_EXPR);
+    if p.at(L_PAREN) {
+        statements::param_list(p);
+    } else {
+        p.start(PARAM_LIST);
+        p.start(PARAM);
+        p.bump();
+        p.finish();
+        p.finish();
+    }
+    p.expect(FAT_ARROW, "`=>`");
+    if p.at(L_CURLY) {
+        statements::block(p);
+    } else if !expr(p) {
+        p.error("expected an expression");
+    }
+    p.finish();
+}
+
+fn closure(p: &mut Parser) {
+    p.start(CLOSURE_EXPR);
+    p.bump_remap(FUNCTION_KW);
+    statements::param_list(p);
+    statements::script_attributes(p, true);
+    if p.at(L_CURLY) {
+        statements::block(p);
+    } else {
+        p.error("expected a function body");
+    }
+    p.finish();
+}
+
+fn new_expr(p: &mut Parser) {
+    p.start(NEW_EXPR);
+    p.bump_remap(NEW_KW);
+    if p.at(QUOTE) {
+        string(p);
+    } else {
+        component_path(p);
+    }
+    if p.at(L_PAREN) {
+        arg_list(p);
+    }
+    p.finish();
+}
+
+/// Parses a dotted component path: `models.user.User`.
+pub(super) fn component_path(p: &mut Parser) {
+    p.start(COMPONENT_PATH);
+    p.expect(IDENT, "a component name");
+    while p.at(DOT) && p.nth(1) == IDENT {
+        p.bump();
+        p.bump();
+    }
+    p.finish();
+}
+
+pub(super) fn arg_list(p: &mut Parser) {
+    p.start(ARG_LIST);
+    p.bump();
+    while !matches!(p.current(), R_PAREN | EOF) {
+        if !arg(p) {
+            if matches!(
+                p.current(),
+                SEMICOLON | L_CURLY | R_CURLY | L_ANGLE_SLASH | HASH
+            ) {
+                break;
+            }
+            p.err_and_bump("expected an argument");
+            continue;
+        }
+        if !p.at(R_PAREN) && !p.eat(COMMA) {
+            p.error("expected `,` or `)`");
+            break;
+        }
+    }
+    p.expect(R_PAREN, "`)`");
+    p.finish();
+}
+
+fn arg(p: &mut Parser) -> bool {
+    if p.at(IDENT) && matches!(p.nth(1), EQ | COLON) {
+        p.start(NAMED_ARG);
+        p.bump();
+        p.bump();
+        if !expr_no_assign(p) {
+            p.error("expected an expression");
+        }
+        p.finish();
+        return true;
+    }
+    let checkpoint = p.checkpoint();
+    if !expr_no_assign(p) {
+        return false;
+    }
+    p.start_at(checkpoint, ARG);
+    p.finish();
+    true
+}
+
+fn array_lit(p: &mut Parser) {
+    p.start(ARRAY_LIT);
+    p.bump();
+    while !matches!(p.current(), R_BRACK | EOF) {
+        if !expr(p) {
+            if matches!(p.current(), SEMICOLON | L_CURLY | R_CURLY | L_ANGLE_SLASH) {
+                break;
+            }
+            p.err_and_bump("expected an array element");
+            continue;
+        }
+        if !p.at(R_BRACK) && !p.eat(COMMA) {
+            p.error("expected `,` or `]`");
+            break;
+        }
+    }
+    p.expect(R_BRACK, "`]`");
+    p.finish();
+}
+
+fn struct_lit(p: &mut Parser) {
+    p.start(STRUCT_LIT);
+    p.bump();
+    while !matches!(p.current(), R_CURLY | EOF) {
+        if !matches!(p.current(), IDENT | QUOTE | NUMBER) {
+            if matches!(p.current(), SEMICOLON | L_ANGLE_SLASH) {
+                break;
+            }
+            p.err_and_bump("expected a struct key");
+            continue;
+        }
+        p.start(STRUCT_ENTRY);
+        if p.at(QUOTE) {
+            string(p);
+        } else {
+            p.bump_remap(IDENT);
+        }
+        if p.at(COLON) || p.at(EQ) {
+            p.bump();
+            if !expr_no_assign(p) {
+                p.error("expected an expression");
+            }
+        } else {
+            p.error("expected `:` or `=`");
+        }
+        p.finish();
+        if !p.at(R_CURLY) && !p.eat(COMMA) {
+            p.error("expected `,` or `}`");
+            break;
+        }
+    }
+    p.expect(R_CURLY, "`}`");
+    p.finish();
+}
+
+/// Parses a string literal with `#expr#` interpolation. Works both in script
+/// and in tag attribute position.
+pub(super) fn string(p: &mut Parser) {
+    p.start(STRING);
+    let start = p.pos;
+    let quote = if p.rest().starts_with('\'') {
+        '\''
+    } else {
+        '"'
+    };
+    p.token(QUOTE, 1);
+    let script_mode = std::mem::replace(&mut p.script_mode, false);
+    loop {
+        let rest = p.rest();
+        if rest.is_empty() {
+            p.error_at(start, start + 1, "unterminated string");
+            break;
+        }
+        if rest.starts_with(quote) && !rest[1..].starts_with(quote) {
+            p.token(QUOTE, 1);
+            break;
+        }
+        if rest.starts_with('#') && !rest.starts_with("##") {
+            hash_expr(p);
+            continue;
+        }
+        p.token(STRING_TEXT, string_text_len(rest, quote));
+    }
+    p.script_mode = script_mode;
+    p.finish();
+}
+
+/// Length of the literal text at the start of `rest`, including doubled
+/// quote and `##` escapes.
+fn string_text_len(rest: &str, quote: char) -> usize {
+    let mut chars = rest.char_indices().peekable();
+    while let Some((idx, c)) = chars.next() {
+        if c == quote || c == '#' {
+            if chars.peek().map(|&(_, next)| next) == Some(c) {
+                chars.next();
+                continue;
+            }
+            return idx;
+        }
+    }
+    rest.len()
+}
+
+/// Parses `#expr#`. The cursor must be at the opening `#`.
+pub(super) fn hash_expr(p: &mut Parser) {
+    p.start(HASH_EXPR);
+    let start = p.pos;
+    p.token(HASH, 1);
+    let saved = (p.script_mode, p.in_tag_expr);
+    p.script_mode = true;
+    p.in_tag_expr = false;
+    if !expr(p) {
+        p.error_at(start, start + 1, "stray `#`, use `##` for a literal `#`");
+    } else if !p.eat(HASH) {
+        p.error_at(start, start + 1, "unterminated `#` expression");
+    }
+    (p.script_mode, p.in_tag_expr) = saved;
+    p.finish();
+}
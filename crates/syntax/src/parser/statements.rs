@@ -0,0 +1,542 @@
+//! cfscript statements and declarations.
+
+use super::{expressions, Parser};
+use crate::{
+    lexer, tags,
+    SyntaxKind::{self, *},
+};
+
+/// Whether `text` is a script component rather than a template: its first
+/// non-trivia token starts a `component` or `interface` declaration.
+pub(super) fn is_script_file(text: &str) -> bool {
+    let mut rest = text;
+    while !rest.is_empty() {
+        let (kind, len) = lexer::script_token(rest);
+        if !kind.is_trivia() {
+            let word = &rest[..len];
+            return kind == IDENT
+                && ["component", "interface", "import", "abstract", "final"]
+                    .iter()
+                    .any(|kw| word.eq_ignore_ascii_case(kw));
+        }
+        rest = &rest[len..];
+    }
+    false
+}
+
+pub(super) fn script_file(p: &mut Parser) {
+    while !p.at(EOF) {
+        statement(p);
+    }
+    p.eat_trivia();
+}
+
+pub(super) fn statement(p: &mut Parser) {
+    let token = p.nth_token(0);
+    if token.kind != IDENT {
+        match token.kind {
+            L_CURLY => block(p),
+            SEMICOLON => {
+                p.start(EMPTY_STMT);
+                p.bump();
+                p.finish();
+            }
+            _ => expr_stmt(p),
+        }
+        return;
+    }
+
+    let next = p.nth(1);
+    match token.text.to_ascii_lowercase().as_str() {
+        "var" if next == IDENT => {
+            p.start(VAR_DECL);
+            var_decl_inner(p);
+            p.eat(SEMICOLON);
+            p.finish();
+        }
+        "if" if next == L_PAREN => if_stmt(p),
+        "for" if next == L_PAREN => for_stmt(p),
+        "while" if next == L_PAREN => while_stmt(p),
+        "do" if next == L_CURLY => do_while_stmt(p),
+        "switch" if next == L_PAREN => switch_stmt(p),
+        "try" if next == L_CURLY => try_stmt(p),
+        "return" if !at_assignment_target(next) => return_stmt(p),
+        "break" if !at_assignment_target(next) => keyword_stmt(p, BREAK_STMT, BREAK_KW),
+        "continue" if !at_assignment_target(next) => keyword_stmt(p, CONTINUE_STMT, CONTINUE_KW),
+        "import" if matches!(next, IDENT | QUOTE) => import_stmt(p),
+        "component" | "interface" if matches!(next, IDENT | L_CURLY) => component(p),
+        "abstract" | "final" if p.nth_at_kw(1, "component") => component(p),
+        "property" if matches!(next, IDENT) => property_decl(p),
+        _ if at_function_decl(p) => function_decl(p),
+        _ if at_script_tag_stmt(p) => script_tag_stmt(p),
+        _ => expr_stmt(p),
+    }
+}
+
+/// Whether a keyword followed by `next` is actually used as a variable name,
+/// as in `return = 1`.
+fn at_assignment_target(next: SyntaxKind) -> bool {
+    matches!(next, EQ | DOT | L_BRACK | L_PAREN)
+}
+
+pub(super) fn block(p: &mut Parser) {
+    p.start(BLOCK);
+    p.bump();
+    while !matches!(p.current(), R_CURLY | EOF | L_ANGLE_SLASH) {
+        statement(p);
+    }
+    p.expect(R_CURLY, "`}`");
+    p.finish();
+}
+
+fn expr_stmt(p: &mut Parser) {
+    let checkpoint = p.checkpoint();
+    if !expressions::expr(p) {
+        p.err_and_bump("expected a statement");
+        return;
+    }
+    p.start_at(checkpoint, EXPR_STMT);
+    p.eat(SEMICOLON);
+    p.finish();
+}
+
+/// Parses `var name = init` without the terminating semicolon. The cursor
+/// must be at `var`.
+fn var_decl_inner(p: &mut Parser) {
+    p.bump_remap(VAR_KW);
+    p.bump();
+    if p.at(EQ) {
+        p.bump();
+        if !expressions::expr(p) {
+            p.error("expected an expression");
+        }
+    }
+}
+
+/// Parses the `var x = 1` body of `<cfset var x = 1>`.
+pub(super) fn var_decl_body(p: &mut Parser) {
+    p.start(VAR_DECL);
+    var_decl_inner(p);
+    p.finish();
+}
+
+fn condition(p: &mut Parser) {
+    p.expect(L_PAREN, "`(`");
+    if !expressions::expr(p) {
+        p.error("expected a condition");
+    }
+    p.expect(R_PAREN, "`)`");
+}
+
+fn body_statement(p: &mut Parser) {
+    if matches!(p.current(), EOF | R_CURLY | L_ANGLE_SLASH) {
+        p.error("expected a statement");
+    } else {
+        statement(p);
+    }
+}
+
+fn if_stmt(p: &mut Parser) {
+    p.start(IF_STMT);
+    p.bump_remap(IF_KW);
+    condition(p);
+    body_statement(p);
+    if p.at_kw("else") {
+        p.start(ELSE_BRANCH);
+        p.bump_remap(ELSE_KW);
+        body_statement(p);
+        p.finish();
+    }
+    p.finish();
+}
+
+fn for_stmt(p: &mut Parser) {
+    let is_for_in = {
+        let mut n = 2;
+        if p.nth_at_kw(n, "var") {
+            n += 1;
+        }
+        if p.nth(n) == IDENT {
+            n += 1;
+            while p.nth(n) == DOT && p.nth(n + 1) == IDENT {
+                n += 2;
+            }
+            p.nth_at_kw(n, "in")
+        } else {
+            false
+        }
+    };
+
+    if is_for_in {
+        p.start(FOR_IN_STMT);
+        p.bump_remap(FOR_KW);
+        p.bump();
+        if p.at_kw("var") {
+            p.start(VAR_DECL);
+            p.bump_remap(VAR_KW);
+            p.bump();
+            p.finish();
+        } else {
+            expressions::expr_no_assign(p);
+        }
+        p.bump_remap(IN_KW);
+        if !expressions::expr(p) {
+            p.error("expected an expression");
+        }
+        p.expect(R_PAREN, "`)`");
+    } else {
+        p.start(FOR_STMT);
+        p.bump_remap(FOR_KW);
+        p.bump();
+        if p.at_kw("var") && p.nth(1) == IDENT {
+            var_decl_body(p);
+        } else {
+            expressions::expr(p);
+        }
+        p.expect(SEMICOLON, "`;`");
+        expressions::expr(p);
+        p.expect(SEMICOLON, "`;`");
+        expressions::expr(p);
+        p.expect(R_PAREN, "`)`");
+    }
+    body_statement(p);
+    p.finish();
+}
+
+fn while_stmt(p: &mut Parser) {
+    p.start(WHILE_STMT);
+    p.bump_remap(WHILE_KW);
+    condition(p);
+    body_statement(p);
+    p.finish();
+}
+
+fn do_while_stmt(p: &mut Parser) {
+    p.start(DO_WHILE_STMT);
+    p.bump_remap(DO_KW);
+    block(p);
+    if p.at_kw("while") {
+        p.bump_remap(WHILE_KW);
+        condition(p);
+    } else {
+        p.error("expected `while`");
+    }
+    p.eat(SEMICOLON);
+    p.finish();
+}
+
+fn switch_stmt(p: &mut Parser) {
+    p.start(SWITCH_STMT);
+    p.bump_remap(SWITCH_KW);
+    condition(p);
+    if p.expect(L_CURLY, "`{`") {
+        while !matches!(p.current(), R_CURLY | EOF | L_ANGLE_SLASH) {
+            if p.at_kw("case") {
+                p.start(CASE_CLAUSE);
+                p.bump_remap(CASE_KW);
+                if !expressions::expr_no_assign(p) {
+                    p.error("expected a case value");
+                }
+            } else if p.at_kw("default") && p.nth(1) == COLON {
+                p.start(CASE_CLAUSE);
+                p.bump_remap(DEFAULT_KW);
+            } else {
+                p.err_and_bump("expected `case` or `default`");
+                continue;
+            }
+            p.expect(COLON, "`:`");
+            while !matches!(p.current(), R_CURLY | EOF | L_ANGLE_SLASH)
+                && !p.at_kw("case")
+                && (!p.at_kw("default") || p.nth(1) != COLON)
+            {
+                statement(p);
+            }
+            p.finish();
+        }
+        p.expect(R_CURLY, "`}`");
+    }
+    p.finish();
+}
+
+fn try_stmt(p: &mut Parser) {
+    p.start(TRY_STMT);
+    p.bump_remap(TRY_KW);
+    block(p);
+    while p.at_kw("catch") {
+        p.start(CATCH_CLAUSE);
+        p.bump_remap(CATCH_KW);
+        if p.expect(L_PAREN, "`(`") {
+            if p.at(QUOTE) {
+                expressions::string(p);
+            } else if p.at(IDENT) && matches!(p.nth(1), IDENT | DOT) {
+                type_ref(p);
+            }
+            p.expect(IDENT, "an exception variable");
+            p.expect(R_PAREN, "`)`");
+        }
+        if p.at(L_CURLY) {
+            block(p);
+        } else {
+            p.error("expected `{`");
+        }
+        p.finish();
+    }
+    if p.at_kw("finally") {
+        p.start(FINALLY_CLAUSE);
+        p.bump_remap(FINALLY_KW);
+        if p.at(L_CURLY) {
+            block(p);
+        } else {
+            p.error("expected `{`");
+        }
+        p.finish();
+    }
+    p.finish();
+}
+
+fn return_stmt(p: &mut Parser) {
+    p.start(RETURN_STMT);
+    p.bump_remap(RETURN_KW);
+    if !matches!(p.current(), SEMICOLON | R_CURLY | EOF | L_ANGLE_SLASH) {
+        expressions::expr(p);
+    }
+    p.eat(SEMICOLON);
+    p.finish();
+}
+
+fn keyword_stmt(p: &mut Parser, node: SyntaxKind, kw: SyntaxKind) {
+    p.start(node);
+    p.bump_remap(kw);
+    p.eat(SEMICOLON);
+    p.finish();
+}
+
+fn import_stmt(p: &mut Parser) {
+    p.start(IMPORT_STMT);
+    p.bump_remap(IMPORT_KW);
+    if p.at(QUOTE) {
+        expressions::string(p);
+    } else {
+        expressions::component_path(p);
+        if p.at(DOT) && p.nth(1) == STAR {
+            p.bump();
+            p.bump();
+        }
+    }
+    p.eat(SEMICOLON);
+    p.finish();
+}
+
+fn component(p: &mut Parser) {
+    p.start(COMPONENT);
+    while let Some(kw @ (ABSTRACT_KW | FINAL_KW)) = keyword_at(p) {
+        p.bump_remap(kw);
+    }
+    match keyword_at(p) {
+        Some(kw @ (COMPONENT_KW | INTERFACE_KW)) => p.bump_remap(kw),
+        _ => p.error("expected `component`"),
+    }
+    script_attributes(p, true);
+    if p.at(L_CURLY) {
+        block(p);
+    } else {
+        p.error("expected `{`");
+    }
+    p.finish();
+}
+
+fn keyword_at(p: &Parser) -> Option<SyntaxKind> {
+    let token = p.nth_token(0);
+    if token.kind != IDENT {
+        return None;
+    }
+    SyntaxKind::from_keyword(token.text)
+}
+
+/// Whether the cursor is at a function declaration, possibly preceded by
+/// modifiers and a return type: `public static User[] function find(`.
+fn at_function_decl(p: &Parser) -> bool {
+    let mut tokens = p.tokens().take(16).peekable();
+    while let Some(token) = tokens.next() {
+        match token.kind {
+            IDENT if token.text.eq_ignore_ascii_case("function") => {
+                return tokens.next().map(|it| it.kind) == Some(IDENT)
+                    && tokens.next().map(|it| it.kind) == Some(L_PAREN);
+            }
+            IDENT | DOT | L_BRACK | R_BRACK => (),
+            _ => return false,
+        }
+    }
+    false
+}
+
+fn function_decl(p: &mut Parser) {
+    p.start(FUNCTION_DECL);
+    if matches!(
+        keyword_at(p),
+        Some(ACCESS_KW | STATIC_KW | ABSTRACT_KW | FINAL_KW)
+    ) {
+        p.start(MODIFIERS);
+        while let Some(kw @ (ACCESS_KW | STATIC_KW | ABSTRACT_KW | FINAL_KW)) = keyword_at(p) {
+            p.bump_remap(kw);
+        }
+        p.finish();
+    }
+    if !p.at_kw("function") {
+        type_ref(p);
+    }
+    p.bump_remap(FUNCTION_KW);
+    p.expect(IDENT, "a function name");
+    param_list(p);
+    script_attributes(p, true);
+    if p.at(L_CURLY) {
+        block(p);
+    } else if !p.eat(SEMICOLON) {
+        p.error("expected a function body");
+    }
+    p.finish();
+}
+
+/// Parses a type: `string`, `models.User`, `User[]`.
+fn type_ref(p: &mut Parser) {
+    p.start(TYPE_REF);
+    p.bump_remap(IDENT);
+    while p.at(DOT) && p.nth(1) == IDENT {
+        p.bump();
+        p.bump();
+    }
+    if p.at(L_BRACK) && p.nth(1) == R_BRACK {
+        p.bump();
+        p.bump();
+    }
+    p.finish();
+}
+
+pub(super) fn param_list(p: &mut Parser) {
+    p.start(PARAM_LIST);
+    p.expect(L_PAREN, "`(`");
+    while !matches!(p.current(), R_PAREN | EOF | L_CURLY | SEMICOLON) {
+        if !p.at(IDENT) {
+            p.err_and_bump("expected a parameter");
+            continue;
+        }
+        param(p);
+        if !p.at(R_PAREN) && !p.eat(COMMA) {
+            p.error("expected `,` or `)`");
+            break;
+        }
+    }
+    p.expect(R_PAREN, "`)`");
+    p.finish();
+}
+
+fn param(p: &mut Parser) {
+    p.start(PARAM);
+    if p.at_kw("required") && p.nth(1) == IDENT {
+        p.bump_remap(REQUIRED_KW);
+    }
+    if at_typed_param(p) {
+        type_ref(p);
+    }
+    p.expect(IDENT, "a parameter name");
+    if p.at(EQ) {
+        p.bump();
+        if !expressions::expr_no_assign(p) {
+            p.error("expected a default value");
+        }
+    }
+    script_attributes(p, false);
+    p.finish();
+}
+
+/// Whether the parameter at the cursor starts with a type.
+fn at_typed_param(p: &Parser) -> bool {
+    let mut n = 0;
+    if p.nth(n) != IDENT {
+        return false;
+    }
+    n += 1;
+    while p.nth(n) == DOT && p.nth(n + 1) == IDENT {
+        n += 2;
+    }
+    if p.nth(n) == L_BRACK && p.nth(n + 1) == R_BRACK {
+        n += 2;
+    }
+    p.nth(n) == IDENT
+}
+
+/// Parses `name=value` metadata on declarations: `output=false`,
+/// `extends="Base"`. When `allow_bare` is set, valueless names are accepted
+/// too, as in `function foo() inject {`.
+pub(super) fn script_attributes(p: &mut Parser, allow_bare: bool) {
+    while p.at(IDENT) {
+        let has_value = p.nth(1) == EQ;
+        if !has_value && !allow_bare {
+            break;
+        }
+        p.start(SCRIPT_ATTRIBUTE);
+        p.bump();
+        if has_value {
+            p.bump();
+            if !expressions::expr_no_assign(p) {
+                p.error("expected an attribute value");
+            }
+        }
+        p.finish();
+    }
+}
+
+fn property_decl(p: &mut Parser) {
+    p.start(PROPERTY_DECL);
+    p.bump_remap(PROPERTY_KW);
+    let mut has_name = false;
+    while !matches!(
+        p.current(),
+        SEMICOLON | EOF | R_CURLY | L_CURLY | L_ANGLE_SLASH
+    ) {
+        if p.at(IDENT) && p.nth(1) == EQ {
+            script_attributes(p, false);
+        } else if p.at(IDENT) && !has_name {
+            if at_typed_param(p) {
+                type_ref(p);
+            }
+            p.bump();
+            has_name = true;
+        } else if p.at(IDENT) {
+            p.start(SCRIPT_ATTRIBUTE);
+            p.bump();
+            p.finish();
+        } else {
+            p.err_and_bump("expected a property attribute");
+        }
+    }
+    p.eat(SEMICOLON);
+    p.finish();
+}
+
+/// Whether the cursor is at a tag written as a script statement.
+fn at_script_tag_stmt(p: &Parser) -> bool {
+    let token = p.nth_token(0);
+    if !tags::is_script_statement(&token.text.to_ascii_lowercase()) {
+        return false;
+    }
+    match p.nth(1) {
+        QUOTE | L_CURLY | SEMICOLON => true,
+        IDENT => p.nth(2) == EQ,
+        _ => false,
+    }
+}
+
+fn script_tag_stmt(p: &mut Parser) {
+    p.start(SCRIPT_TAG_STMT);
+    p.bump();
+    if p.at(QUOTE) {
+        expressions::expr_no_assign(p);
+    }
+    script_attributes(p, false);
+    if p.at(L_CURLY) {
+        block(p);
+    }
+    p.eat(SEMICOLON);
+    p.finish();
+}
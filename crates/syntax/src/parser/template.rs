@@ -0,0 +1,378 @@
+//! Template grammar: HTML text interleaved with CFML tags, comments and, in
+//! output tags, `#expr#` islands.
+
+use super::{expressions, statements, Parser};
+use crate::{
+    lexer,
+    tags::{self, TagBody},
+    SyntaxKind::*,
+};
+
+pub(super) fn template_file(p: &mut Parser) {
+    content(p);
+}
+
+/// Parses text and tags until the end of input, or until an end tag of one
+/// of the open tags (or a `cfif` branch) that the caller has to handle.
+fn content(p: &mut Parser) {
+    loop {
+        let rest = p.rest();
+        if rest.is_empty() {
+            return;
+        }
+        if rest.starts_with("<!---") {
+            comment(p);
+            continue;
+        }
+        if let Some(name) = end_tag_name(p, rest) {
+            if p.open_tags.contains(&name) {
+                return;
+            }
+            stray_end_tag(p, &name);
+            continue;
+        }
+        if let Some(name) = start_tag_name(p, rest) {
+            if tags::is_if_branch(&name) && p.open_tags.last().map(String::as_str) == Some("cfif") {
+                return;
+            }
+            tag(p, name);
+            continue;
+        }
+        if p.output_depth > 0 && rest.starts_with('#') && !rest.starts_with("##") {
+            expressions::hash_expr(p);
+            continue;
+        }
+        let len = text_len(p, rest);
+        p.token(TEXT, len);
+    }
+}
+
+/// Length of the text run at the start of `rest`, up to the next CFML
+/// construct.
+fn text_len(p: &Parser, rest: &str) -> usize {
+    let mut idx = if rest.starts_with("##") {
+        2
+    } else {
+        rest.chars().next().map_or(0, char::len_utf8)
+    };
+    while idx < rest.len() {
+        let next = match rest[idx..].find(['<', '#']) {
+            Some(offset) => idx + offset,
+            None => return rest.len(),
+        };
+        let tail = &rest[next..];
+        if tail.starts_with('<') {
+            if tail.starts_with("<!---")
+                || start_tag_name(p, tail).is_some()
+                || end_tag_name(p, tail).is_some()
+            {
+                return next;
+            }
+        } else if p.output_depth > 0 {
+            if !tail.starts_with("##") {
+                return next;
+            }
+            idx = next + 2;
+            continue;
+        }
+        idx = next + 1;
+    }
+    rest.len()
+}
+
+fn comment(p: &mut Parser) {
+    let (len, terminated) = lexer::tag_comment_len(p.rest());
+    if !terminated {
+        p.error_at(p.pos, p.pos + len.min(5), "unterminated comment");
+    }
+    p.token(TAG_COMMENT, len);
+}
+
+/// The lowercase name of the CFML start tag at the start of `rest`, if any.
+fn start_tag_name(p: &Parser, rest: &str) -> Option<String> {
+    let after = rest.strip_prefix('<')?;
+    tag_name(p, after)
+}
+
+/// The lowercase name of the CFML end tag at the start of `rest`, if any.
+fn end_tag_name(p: &Parser, rest: &str) -> Option<String> {
+    let after = rest.strip_prefix("</")?;
+    tag_name(p, after)
+}
+
+fn tag_name(p: &Parser, text: &str) -> Option<String> {
+    let name = text[..lexer::tag_name_len(text)].to_ascii_lowercase();
+    let is_cfml = match name.split_once(':') {
+        Some((prefix, local)) => !local.is_empty() && p.tag_prefixes.iter().any(|it| it == prefix),
+        None => {
+            name.len() > 2
+                && name.starts_with("cf")
+                && name[2..].starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        }
+    };
+    is_cfml.then_some(name)
+}
+
+fn tag(p: &mut Parser, name: String) {
+    p.start(TAG);
+    let name_start = p.pos + 1;
+    let name_end = name_start + name.len();
+    let self_closed = start_tag(p, &name);
+
+    if tags::is_if_branch(&name) {
+        p.error_at(
+            name_start,
+            name_end,
+            format!("`<{name}>` outside of `<cfif>`"),
+        );
+        p.finish();
+        return;
+    }
+    if name == "cfimport" {
+        declare_prefix(p, name_start);
+    }
+
+    let body = tags::body(&name);
+    let has_body = !self_closed
+        && match body {
+            TagBody::Empty => false,
+            TagBody::Required | TagBody::Script => true,
+            TagBody::Optional => has_end_tag_ahead(p.rest(), &name),
+        };
+    if has_body {
+        if body == TagBody::Script {
+            script_body(p);
+        } else {
+            tag_body(p, &name);
+        }
+        end_tag(p, &name, name_start, name_end);
+    }
+    p.finish();
+}
+
+fn tag_body(p: &mut Parser, name: &str) {
+    let is_output = tags::is_output(name);
+    p.open_tags.push(name.to_owned());
+    if is_output {
+        p.output_depth += 1;
+    }
+
+    content(p);
+    if name == "cfif" {
+        while let Some(branch) = start_tag_name(p, p.rest()).filter(|it| tags::is_if_branch(it)) {
+            p.start(TAG);
+            start_tag(p, &branch);
+            content(p);
+            p.finish();
+        }
+    }
+
+    if is_output {
+        p.output_depth -= 1;
+    }
+    p.open_tags.pop();
+}
+
+fn script_body(p: &mut Parser) {
+    p.start(SCRIPT);
+    let script_mode = std::mem::replace(&mut p.script_mode, true);
+    while !matches!(p.current(), EOF | L_ANGLE_SLASH) {
+        statements::statement(p);
+    }
+    p.eat_trivia();
+    p.script_mode = script_mode;
+    p.finish();
+}
+
+/// Parses a start tag, returning whether it was self-closed with `/>`.
+fn start_tag(p: &mut Parser, name: &str) -> bool {
+    p.start(START_TAG);
+    p.token(L_ANGLE, 1);
+    p.token(TAG_NAME, name.len());
+    if tags::takes_expression(name) {
+        tag_expression(p, name);
+    } else {
+        attributes(p);
+    }
+    whitespace(p);
+
+    let self_closed = close_tag(p);
+    p.finish();
+    self_closed
+}
+
+fn close_tag(p: &mut Parser) -> bool {
+    loop {
+        let rest = p.rest();
+        if rest.starts_with("/>") {
+            p.token(SLASH_R_ANGLE, 2);
+            return true;
+        }
+        if rest.starts_with('>') {
+            p.token(R_ANGLE, 1);
+            return false;
+        }
+        if rest.is_empty() || rest.starts_with('<') {
+            p.error_at(p.pos, p.pos, "expected `>`");
+            return false;
+        }
+        let junk = rest.find(['>', '<']).unwrap_or(rest.len());
+        let junk = if rest[..junk].ends_with('/') && rest[junk..].starts_with('>') {
+            junk - 1
+        } else {
+            junk
+        };
+        p.error_at(p.pos, p.pos + junk, "unexpected input in tag");
+        p.start(ERROR);
+        p.token(ERROR_TOKEN, junk);
+        p.finish();
+    }
+}
+
+fn tag_expression(p: &mut Parser, name: &str) {
+    let saved = (p.script_mode, p.in_tag_expr);
+    p.script_mode = true;
+    p.in_tag_expr = true;
+
+    let at_close = expressions::at_tag_close(p);
+    match name {
+        "cfset" if p.at_kw("var") && p.nth(1) == IDENT => statements::var_decl_body(p),
+        "cfreturn" if at_close => (),
+        _ if at_close => p.error(format!("expected an expression in `<{name}>`")),
+        _ => {
+            expressions::expr(p);
+        }
+    }
+
+    (p.script_mode, p.in_tag_expr) = saved;
+}
+
+fn whitespace(p: &mut Parser) {
+    let len = lexer::whitespace_len(p.rest());
+    if len > 0 {
+        p.token(WHITESPACE, len);
+    }
+}
+
+fn attributes(p: &mut Parser) {
+    loop {
+        whitespace(p);
+        let rest = p.rest();
+        if rest.is_empty() || rest.starts_with('>') || rest.starts_with("/>") {
+            return;
+        }
+        if rest.starts_with("<!---") {
+            comment(p);
+            continue;
+        }
+        if rest.starts_with('<') {
+            return;
+        }
+        let name_len = lexer::attr_name_len(rest);
+        if name_len == 0 {
+            let len = rest.chars().next().map_or(1, char::len_utf8);
+            p.error_at(p.pos, p.pos + len, "expected an attribute");
+            p.start(ERROR);
+            if rest.starts_with(['"', '\'']) {
+                expressions::string(p);
+            } else if rest.starts_with('#') {
+                expressions::hash_expr(p);
+            } else {
+                p.token(ERROR_TOKEN, len);
+            }
+            p.finish();
+            continue;
+        }
+
+        p.start(ATTRIBUTE);
+        p.token(ATTR_NAME, name_len);
+        let ws = lexer::whitespace_len(p.rest());
+        if p.rest()[ws..].starts_with('=') {
+            whitespace(p);
+            p.token(EQ, 1);
+            whitespace(p);
+            attribute_value(p);
+        }
+        p.finish();
+    }
+}
+
+fn attribute_value(p: &mut Parser) {
+    let rest = p.rest();
+    if rest.starts_with(['"', '\'']) {
+        expressions::string(p);
+    } else if rest.starts_with('#') {
+        expressions::hash_expr(p);
+    } else {
+        let len = lexer::attr_value_len(rest);
+        if len == 0 {
+            p.error_at(p.pos, p.pos, "expected an attribute value");
+        } else {
+            p.token(ATTR_VALUE, len);
+        }
+    }
+}
+
+fn end_tag(p: &mut Parser, name: &str, name_start: usize, name_end: usize) {
+    if end_tag_name(p, p.rest()).as_deref() != Some(name) {
+        p.error_at(name_start, name_end, format!("unclosed `<{name}>`"));
+        return;
+    }
+    end_tag_inner(p, name);
+}
+
+fn end_tag_inner(p: &mut Parser, name: &str) {
+    p.start(END_TAG);
+    p.token(L_ANGLE_SLASH, 2);
+    p.token(TAG_NAME, name.len());
+    whitespace(p);
+    if p.rest().starts_with('>') {
+        p.token(R_ANGLE, 1);
+    } else {
+        p.error_at(p.pos, p.pos, "expected `>`");
+    }
+    p.finish();
+}
+
+fn stray_end_tag(p: &mut Parser, name: &str) {
+    let start = p.pos;
+    p.error_at(
+        start,
+        start + 2 + name.len(),
+        format!("unmatched `</{name}>`"),
+    );
+    p.start(ERROR);
+    end_tag_inner(p, name);
+    p.finish();
+}
+
+/// Whether a matching end tag for `name` appears somewhere in `rest`. Used
+/// to decide whether tags with optional bodies have one.
+fn has_end_tag_ahead(rest: &str, name: &str) -> bool {
+    rest.match_indices("</").any(|(idx, _)| {
+        let after = &rest[idx + 2..];
+        after.len() >= name.len()
+            && after.as_bytes()[..name.len()].eq_ignore_ascii_case(name.as_bytes())
+            && lexer::tag_name_len(after) == name.len()
+    })
+}
+
+/// Registers the prefix of a `<cfimport prefix="ui" taglib="...">` so that
+/// `<ui:button>` parses as a tag.
+fn declare_prefix(p: &mut Parser, name_start: usize) {
+    let start_tag = p.text[name_start..p.pos].to_ascii_lowercase();
+    let Some(idx) = start_tag.find("prefix") else {
+        return;
+    };
+    let value = start_tag[idx + "prefix".len()..].trim_start();
+    let Some(value) = value.strip_prefix('=') else {
+        return;
+    };
+    let value = value.trim_start().trim_start_matches(['"', '\'']);
+    let len = value
+        .find(|c: char| !lexer::is_ident_continue(c))
+        .unwrap_or(value.len());
+    if len > 0 {
+        p.tag_prefixes.push(value[..len].to_owned());
+    }
+}
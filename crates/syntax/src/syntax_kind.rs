@@ -0,0 +1,332 @@
+//! The set of token and node kinds produced by the CFML parser.
+//!
+//! CFML mixes three languages in one file: HTML text, CFML tags and cfscript.
+//! All of them share this single kind space so that a template and a script
+//! component end up in the same lossless tree.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[allow(non_camel_case_types)]
+#[repr(u16)]
+pub enum SyntaxKind {
+    // Trivia
+    WHITESPACE = 0,
+    /// `// ...` in script.
+    LINE_COMMENT,
+    /// `/* ... */` in script, including `/** ... */` docblocks.
+    BLOCK_COMMENT,
+    /// `<!--- ... --->`, valid in tag and text context.
+    TAG_COMMENT,
+
+    // Template text
+    /// Plain HTML (or SQL inside `cfquery`) text between CFML constructs.
+    TEXT,
+
+    // Tag punctuation
+    /// `<` starting a CFML tag.
+    L_ANGLE,
+    /// `</` starting a CFML end tag.
+    L_ANGLE_SLASH,
+    /// `>` closing a start or end tag.
+    R_ANGLE,
+    /// `/>` closing a self-closed tag.
+    SLASH_R_ANGLE,
+    /// `cfset`, `cf_panel`, `ui:button`.
+    TAG_NAME,
+    /// An attribute name inside a start tag.
+    ATTR_NAME,
+    /// An unquoted attribute value: `<cfloop from=1 to=10>`.
+    ATTR_VALUE,
+
+    // Literals
+    IDENT,
+    NUMBER,
+    /// `"` or `'` delimiting a string literal.
+    QUOTE,
+    /// Literal characters of a string, including `""` and `##` escapes.
+    STRING_TEXT,
+    /// `#` delimiting an interpolated expression.
+    HASH,
+
+    // Punctuation
+    L_PAREN,
+    R_PAREN,
+    L_CURLY,
+    R_CURLY,
+    L_BRACK,
+    R_BRACK,
+    COMMA,
+    SEMICOLON,
+    COLON,
+    COLON2,
+    DOT,
+    QUESTION,
+    QUESTION_DOT,
+    ELVIS,
+    FAT_ARROW,
+    EQ,
+    EQ2,
+    EQ3,
+    NEQ,
+    NEQ2,
+    LT,
+    LTEQ,
+    GT,
+    GTEQ,
+    PLUS,
+    PLUS2,
+    PLUSEQ,
+    MINUS,
+    MINUS2,
+    MINUSEQ,
+    STAR,
+    STAREQ,
+    SLASH,
+    SLASHEQ,
+    BACKSLASH,
+    PERCENT,
+    PERCENTEQ,
+    CARET,
+    AMP,
+    AMP2,
+    AMPEQ,
+    PIPE2,
+    BANG,
+
+    // Keywords. The lexer only produces `IDENT`; the parser remaps identifiers
+    // to these kinds where they are used as keywords, since CFML keywords are
+    // not reserved words.
+    VAR_KW,
+    FUNCTION_KW,
+    RETURN_KW,
+    IF_KW,
+    ELSE_KW,
+    FOR_KW,
+    IN_KW,
+    WHILE_KW,
+    DO_KW,
+    SWITCH_KW,
+    CASE_KW,
+    DEFAULT_KW,
+    BREAK_KW,
+    CONTINUE_KW,
+    TRY_KW,
+    CATCH_KW,
+    FINALLY_KW,
+    NEW_KW,
+    COMPONENT_KW,
+    INTERFACE_KW,
+    PROPERTY_KW,
+    IMPORT_KW,
+    REQUIRED_KW,
+    STATIC_KW,
+    ABSTRACT_KW,
+    FINAL_KW,
+    ACCESS_KW,
+    TRUE_KW,
+    FALSE_KW,
+    NULL_KW,
+    /// Word operators: `and`, `or`, `not`, `eq`, `gt`, `contains`, `mod`, ...
+    WORD_OP,
+
+    ERROR_TOKEN,
+    /// Never produced in the tree; returned by the parser at end of input.
+    EOF,
+
+    // Nodes
+    SOURCE_FILE,
+    ERROR,
+
+    // Tags
+    /// A CFML tag element: its start tag, body and end tag.
+    TAG,
+    START_TAG,
+    END_TAG,
+    ATTRIBUTE,
+    /// The cfscript body of a `<cfscript>` tag.
+    SCRIPT,
+    /// `#expr#` in text, strings and attribute values.
+    HASH_EXPR,
+
+    // Declarations
+    COMPONENT,
+    /// `extends="Base" output=false` pairs on script declarations.
+    SCRIPT_ATTRIBUTE,
+    FUNCTION_DECL,
+    PARAM_LIST,
+    PARAM,
+    PROPERTY_DECL,
+    IMPORT_STMT,
+    /// A type in a signature: `string`, `path.to.Component`, `User[]`.
+    TYPE_REF,
+    /// `public static`, `private` on functions.
+    MODIFIERS,
+
+    // Statements
+    BLOCK,
+    VAR_DECL,
+    EXPR_STMT,
+    IF_STMT,
+    ELSE_BRANCH,
+    FOR_STMT,
+    FOR_IN_STMT,
+    WHILE_STMT,
+    DO_WHILE_STMT,
+    SWITCH_STMT,
+    CASE_CLAUSE,
+    TRY_STMT,
+    CATCH_CLAUSE,
+    FINALLY_CLAUSE,
+    RETURN_STMT,
+    BREAK_STMT,
+    CONTINUE_STMT,
+    /// Tag-in-script statements: `lock scope="session" { }`, `include "x";`.
+    SCRIPT_TAG_STMT,
+    EMPTY_STMT,
+
+    // Expressions
+    LITERAL,
+    STRING,
+    NAME_REF,
+    MEMBER_EXPR,
+    INDEX_EXPR,
+    CALL_EXPR,
+    ARG_LIST,
+    ARG,
+    NAMED_ARG,
+    NEW_EXPR,
+    /// A dotted component path: `models.user.User`.
+    COMPONENT_PATH,
+    STRUCT_LIT,
+    STRUCT_ENTRY,
+    ARRAY_LIT,
+    CLOSURE_EXPR,
+    ARROW_EXPR,
+    PAREN_EXPR,
+    PREFIX_EXPR,
+    POSTFIX_EXPR,
+    BIN_EXPR,
+    TERNARY_EXPR,
+    ASSIGN_EXPR,
+
+    #[doc(hidden)]
+    __LAST,
+}
+
+use SyntaxKind::*;
+
+impl SyntaxKind {
+    pub fn is_trivia(self) -> bool {
+        matches!(
+            self,
+            WHITESPACE | LINE_COMMENT | BLOCK_COMMENT | TAG_COMMENT
+        )
+    }
+
+    pub fn is_comment(self) -> bool {
+        matches!(self, LINE_COMMENT | BLOCK_COMMENT | TAG_COMMENT)
+    }
+
+    pub fn is_keyword(self) -> bool {
+        (VAR_KW as u16..=WORD_OP as u16).contains(&(self as u16))
+    }
+
+    pub fn is_punct(self) -> bool {
+        (L_PAREN as u16..=BANG as u16).contains(&(self as u16))
+    }
+
+    /// Returns the keyword kind for `ident` when it is used in keyword position.
+    ///
+    /// CFML is case-insensitive, so `FUNCTION` and `Function` are keywords too.
+    pub fn from_keyword(ident: &str) -> Option<SyntaxKind> {
+        let kind = match ident.to_ascii_lowercase().as_str() {
+            "var" => VAR_KW,
+            "function" => FUNCTION_KW,
+            "return" => RETURN_KW,
+            "if" => IF_KW,
+            "else" => ELSE_KW,
+            "for" => FOR_KW,
+            "in" => IN_KW,
+            "while" => WHILE_KW,
+            "do" => DO_KW,
+            "switch" => SWITCH_KW,
+            "case" => CASE_KW,
+            "default" => DEFAULT_KW,
+            "break" => BREAK_KW,
+            "continue" => CONTINUE_KW,
+            "try" => TRY_KW,
+            "catch" => CATCH_KW,
+            "finally" => FINALLY_KW,
+            "new" => NEW_KW,
+            "component" => COMPONENT_KW,
+            "interface" => INTERFACE_KW,
+            "property" => PROPERTY_KW,
+            "import" => IMPORT_KW,
+            "required" => REQUIRED_KW,
+            "static" => STATIC_KW,
+            "abstract" => ABSTRACT_KW,
+            "final" => FINAL_KW,
+            "public" | "private" | "package" | "remote" => ACCESS_KW,
+            "true" => TRUE_KW,
+            "false" => FALSE_KW,
+            "null" => NULL_KW,
+            _ => return None,
+        };
+        Some(kind)
+    }
+}
+
+impl From<u16> for SyntaxKind {
+    fn from(raw: u16) -> SyntaxKind {
+        assert!(raw < __LAST as u16);
+        // SAFETY: `SyntaxKind` is `repr(u16)` and `raw` is in range.
+        unsafe { std::mem::transmute::<u16, SyntaxKind>(raw) }
+    }
+}
+
+impl From<SyntaxKind> for rowan::SyntaxKind {
+    fn from(kind: SyntaxKind) -> Self {
+        Self(kind as u16)
+    }
+}
+
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub enum CfmlLanguage {}
+
+impl rowan::Language for CfmlLanguage {
+    type Kind = SyntaxKind;
+
+    fn kind_from_raw(raw: rowan::SyntaxKind) -> SyntaxKind {
+        SyntaxKind::from(raw.0)
+    }
+
+    fn kind_to_raw(kind: SyntaxKind) -> rowan::SyntaxKind {
+        kind.into()
+    }
+}
+
+pub type SyntaxNode = rowan::SyntaxNode<CfmlLanguage>;
+pub type SyntaxToken = rowan::SyntaxToken<CfmlLanguage>;
+pub type SyntaxElement = rowan::SyntaxElement<CfmlLanguage>;
+pub type SyntaxNodeChildren = rowan::SyntaxNodeChildren<CfmlLanguage>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keywords_are_case_insensitive() {
+        assert_eq!(SyntaxKind::from_keyword("Function"), Some(FUNCTION_KW));
+        assert_eq!(SyntaxKind::from_keyword("PRIVATE"), Some(ACCESS_KW));
+        assert_eq!(SyntaxKind::from_keyword("foo"), None);
+    }
+
+    #[test]
+    fn kind_round_trips_through_raw() {
+        for kind in [WHITESPACE, TAG, ASSIGN_EXPR, WORD_OP] {
+            assert_eq!(SyntaxKind::from(kind as u16), kind);
+        }
+        assert!(FUNCTION_KW.is_keyword());
+        assert!(!IDENT.is_keyword());
+        assert!(TAG_COMMENT.is_trivia());
+    }
+}
@@ -0,0 +1,172 @@
+//! Structural knowledge about CFML tags that the parser needs: which tags
+//! have bodies, which take a cfscript expression instead of attributes, and
+//! in which ones `#` in text starts an expression.
+//!
+//! Tag names are compared in lowercase.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TagBody {
+    /// The tag never has a body or an end tag: `<cfset>`, `<cfargument>`.
+    Empty,
+    /// The tag always has a body and requires an end tag: `<cfif>`.
+    Required,
+    /// The tag may be used with or without a body: `<cfhttp>`, custom tags.
+    Optional,
+    /// The body is cfscript: `<cfscript>`.
+    Script,
+}
+
+const EMPTY_TAGS: &[&str] = &[
+    "cfabort",
+    "cfargument",
+    "cfbreak",
+    "cfcontent",
+    "cfcontinue",
+    "cfcookie",
+    "cfdbinfo",
+    "cfdirectory",
+    "cfdump",
+    "cfelse",
+    "cfelseif",
+    "cfexit",
+    "cffile",
+    "cfflush",
+    "cfheader",
+    "cfhttpparam",
+    "cfimage",
+    "cfimport",
+    "cfinclude",
+    "cfinvokeargument",
+    "cflocation",
+    "cflog",
+    "cflogout",
+    "cfmailparam",
+    "cfobject",
+    "cfparam",
+    "cfprocparam",
+    "cfprocresult",
+    "cfproperty",
+    "cfqueryparam",
+    "cfrethrow",
+    "cfreturn",
+    "cfschedule",
+    "cfset",
+    "cfsetting",
+    "cfthrow",
+    "cfwddx",
+    "cfzipparam",
+];
+
+const REQUIRED_BODY_TAGS: &[&str] = &[
+    "cfcase",
+    "cfcatch",
+    "cfcomponent",
+    "cfdefaultcase",
+    "cffinally",
+    "cffunction",
+    "cfif",
+    "cfinterface",
+    "cflock",
+    "cfloop",
+    "cfmail",
+    "cfoutput",
+    "cfquery",
+    "cfsavecontent",
+    "cfsilent",
+    "cfswitch",
+    "cftimer",
+    "cftry",
+    "cfwhile",
+];
+
+/// Tags whose start tag holds a cfscript expression instead of attributes.
+const EXPRESSION_TAGS: &[&str] = &["cfset", "cfif", "cfelseif", "cfreturn"];
+
+/// Tags in which `#expr#` in text is evaluated.
+const OUTPUT_TAGS: &[&str] = &["cfoutput", "cfquery", "cfmail"];
+
+/// Tags that can be written as statements in cfscript, without their `cf`
+/// prefix:
+/// `lock scope="session" type="exclusive" { }`, `include "header.cfm";`.
+const SCRIPT_TAGS: &[&str] = &[
+    "abort",
+    "content",
+    "cookie",
+    "directory",
+    "dump",
+    "execute",
+    "exit",
+    "file",
+    "flush",
+    "header",
+    "http",
+    "include",
+    "invoke",
+    "location",
+    "lock",
+    "log",
+    "loop",
+    "mail",
+    "module",
+    "output",
+    "param",
+    "query",
+    "rethrow",
+    "savecontent",
+    "setting",
+    "silent",
+    "storedproc",
+    "thread",
+    "throw",
+    "timer",
+    "transaction",
+    "zip",
+];
+
+pub fn body(name: &str) -> TagBody {
+    if name == "cfscript" {
+        TagBody::Script
+    } else if EMPTY_TAGS.contains(&name) {
+        TagBody::Empty
+    } else if REQUIRED_BODY_TAGS.contains(&name) {
+        TagBody::Required
+    } else {
+        TagBody::Optional
+    }
+}
+
+pub fn takes_expression(name: &str) -> bool {
+    EXPRESSION_TAGS.contains(&name)
+}
+
+pub fn is_output(name: &str) -> bool {
+    OUTPUT_TAGS.contains(&name)
+}
+
+/// Whether the tag `cf{name}` can be written as a cfscript statement.
+pub fn is_script_statement(name: &str) -> bool {
+    SCRIPT_TAGS.contains(&name)
+}
+
+/// `cfelseif` and `cfelse` split the body of the enclosing `cfif`.
+pub fn is_if_branch(name: &str) -> bool {
+    matches!(name, "cfelseif" | "cfelse")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_tag_bodies() {
+        assert_eq!(body("cfset"), TagBody::Empty);
+        assert_eq!(body("cfif"), TagBody::Required);
+        assert_eq!(body("cf_panel"), TagBody::Optional);
+        assert_eq!(body("cfscript"), TagBody::Script);
+        assert!(takes_expression("cfreturn"));
+        assert!(!takes_expression("cfargument"));
+        assert!(is_output("cfquery"));
+        assert!(is_script_statement("lock"));
+        assert!(!is_script_statement("cflock"));
+    }
+}
@@ -0,0 +1,240 @@
+use crate::{
+    ast::{self, AstNode, SourceFile},
+    Parse, SyntaxKind,
+};
+
+fn parse(text: &str) -> Parse {
+    let parse = SourceFile::parse(text);
+    assert_eq!(
+        parse.syntax_node().to_string(),
+        text,
+        "tree must be lossless"
+    );
+    parse
+}
+
+fn errors(text: &str) -> Vec<String> {
+    parse(text)
+        .errors()
+        .iter()
+        .map(|it| format!("{:?} {}", it.range(), it.message()))
+        .collect()
+}
+
+#[test]
+fn parses_template_tags() {
+    let parse = parse(
+        r##"<cfset var x = 1><cfif x gt 2>a<cfelseif y>b<cfelse>c</cfif><cfoutput>#x#</cfoutput>"##,
+    );
+    assert!(parse.errors().is_empty(), "{:?}", parse.errors());
+    let tags: Vec<_> = parse.tree().tags().map(|it| it.name()).collect();
+    assert_eq!(tags, ["cfset", "cfif", "cfoutput"]);
+
+    let cfif = parse.tree().tags().nth(1).unwrap();
+    let branches: Vec<_> = cfif.child_tags().map(|it| it.name()).collect();
+    assert_eq!(branches, ["cfelseif", "cfelse"]);
+    assert!(cfif.end_tag().is_some());
+    assert!(matches!(cfif.expression(), Some(ast::Expr::BinExpr(_))));
+
+    let cfset = parse.tree().tags().next().unwrap();
+    assert_eq!(cfset.var_decl().unwrap().name_token().unwrap().text(), "x");
+}
+
+#[test]
+fn parses_attributes() {
+    let parse = parse(r##"<cfloop from=1 to="#n#" index='i' query=#q#></cfloop>"##);
+    assert!(parse.errors().is_empty(), "{:?}", parse.errors());
+    let tag = parse.tree().tags().next().unwrap();
+    assert_eq!(tag.attribute_value("FROM").as_deref(), Some("1"));
+    assert_eq!(tag.attribute_value("to"), None);
+    assert_eq!(tag.attribute_value("index").as_deref(), Some("i"));
+    assert!(matches!(
+        tag.attribute("query").unwrap().value(),
+        Some(ast::AttributeValue::Hash(_))
+    ));
+}
+
+#[test]
+fn hashes_are_text_outside_output_tags() {
+    let parse = parse("<p style=\"color: #fff\">#x#</p>");
+    assert!(parse.errors().is_empty());
+    assert!(parse
+        .syntax_node()
+        .descendants()
+        .all(|it| it.kind() != SyntaxKind::HASH_EXPR));
+}
+
+#[test]
+fn parses_script_component() {
+    let parse = parse(
+        r##"
+/** A user. */
+component extends="base.Model" accessors=true {
+    property name="id" type="numeric";
+    property string email;
+
+    public string function greet(required string name, numeric age = 1) output=false {
+        var msg = "Hello #arguments.name#!";
+        local.total = add(a=1, b: 2) & msg;
+        for (var i = 1; i <= 10; i++) { total += i; }
+        for (key in data) {}
+        lock scope="session" type="exclusive" timeout=10 { session.count++; }
+        var fn = (a, b) => a + b;
+        var user = new models.User(name = "x");
+        try { x = [1, {a: 1, "b" = 2}]; } catch (any e) { rethrow; }
+        switch (x) { case 1: break; default: y = 2; }
+        return msg;
+    }
+}
+"##,
+    );
+    assert!(parse.errors().is_empty(), "{:?}", parse.errors());
+    let component = parse.tree().component().unwrap();
+    assert!(!component.is_interface());
+    assert_eq!(
+        component.attribute_value("extends").as_deref(),
+        Some("base.Model")
+    );
+    assert_eq!(
+        component.attribute_value("accessors").as_deref(),
+        Some("true")
+    );
+
+    let body = component.body().unwrap();
+    let function = body
+        .statements()
+        .find_map(|it| ast::FunctionDecl::cast(it.syntax().clone()))
+        .unwrap();
+    assert_eq!(function.name().as_deref(), Some("greet"));
+    assert_eq!(function.access().as_deref(), Some("public"));
+    assert_eq!(function.return_type().unwrap().text(), "string");
+    assert_eq!(function.attribute_value("output").as_deref(), Some("false"));
+
+    let params: Vec<_> = function
+        .params()
+        .map(|it| {
+            (
+                it.name().unwrap(),
+                it.is_required(),
+                it.type_ref().map(|t| t.text()),
+            )
+        })
+        .collect();
+    assert_eq!(
+        params,
+        [
+            ("name".to_owned(), true, Some("string".to_owned())),
+            ("age".to_owned(), false, Some("numeric".to_owned())),
+        ]
+    );
+
+    let new_expr = parse
+        .syntax_node()
+        .descendants()
+        .find_map(ast::NewExpr::cast)
+        .unwrap();
+    assert_eq!(new_expr.path().unwrap().text(), "models.User");
+}
+
+#[test]
+fn parses_script_in_cfscript_tag() {
+    let parse = parse("<cfscript>\n x = 1;\n if (x) { writeOutput(x); }\n</cfscript>");
+    assert!(parse.errors().is_empty(), "{:?}", parse.errors());
+    let tag = parse.tree().tags().next().unwrap();
+    assert_eq!(tag.script().unwrap().statements().count(), 2);
+}
+
+#[test]
+fn parses_operator_precedence() {
+    let parse = parse("<cfset x = not a eq b and c & d + e * f>");
+    let expr = parse.tree().tags().next().unwrap().expression().unwrap();
+    let ast::Expr::AssignExpr(assign) = expr else {
+        panic!()
+    };
+    let ast::Expr::BinExpr(and) = assign.value().unwrap() else {
+        panic!()
+    };
+    assert_eq!(and.op(), "and");
+    assert!(matches!(and.lhs(), Some(ast::Expr::PrefixExpr(_))));
+    let ast::Expr::BinExpr(concat) = and.rhs().unwrap() else {
+        panic!()
+    };
+    assert_eq!(concat.op(), "&");
+}
+
+#[test]
+fn parses_multi_word_operators() {
+    let parse = parse("<cfif a is not b or c does not contain d></cfif>");
+    assert!(parse.errors().is_empty(), "{:?}", parse.errors());
+    let ops: Vec<_> = parse
+        .syntax_node()
+        .descendants()
+        .filter_map(ast::BinExpr::cast)
+        .map(|it| it.op())
+        .collect();
+    assert_eq!(ops, ["or", "is not", "does not contain"]);
+}
+
+#[test]
+fn parses_custom_tags() {
+    let parse = parse(
+        r#"<cf_panel title="x"/><cfimport prefix="ui" taglib="/tags"><ui:button>ok</ui:button><x:y>"#,
+    );
+    assert!(parse.errors().is_empty(), "{:?}", parse.errors());
+    let tags: Vec<_> = parse.tree().tags().map(|it| it.name()).collect();
+    assert_eq!(tags, ["cf_panel", "cfimport", "ui:button"]);
+}
+
+#[test]
+fn reports_tag_errors() {
+    assert_eq!(
+        errors("<cfoutput><cfif x>a</cfoutput></cfif>"),
+        ["11..15 unclosed `<cfif>`", "30..36 unmatched `</cfif>`"]
+    );
+    assert_eq!(errors("<cfelse>"), ["1..7 `<cfelse>` outside of `<cfif>`"]);
+}
+
+#[test]
+fn reports_expression_errors() {
+    assert_eq!(
+        errors("<cfoutput>#</cfoutput>"),
+        ["10..11 stray `#`, use `##` for a literal `#`"]
+    );
+    assert_eq!(
+        errors("<cfoutput>#x y</cfoutput>"),
+        ["10..11 unterminated `#` expression"]
+    );
+    assert_eq!(
+        errors("<cfset x = \"abc>"),
+        ["11..12 unterminated string", "16..16 expected `>`"]
+    );
+    assert_eq!(
+        errors("<cfif ></cfif>"),
+        ["6..7 expected an expression in `<cfif>`"]
+    );
+}
+
+#[test]
+fn recovers_from_script_errors() {
+    let parse = parse("component { function a() { x = ; } function b() {} }");
+    assert_eq!(parse.errors().len(), 1, "{:?}", parse.errors());
+    let functions = parse
+        .syntax_node()
+        .descendants()
+        .filter_map(ast::FunctionDecl::cast)
+        .count();
+    assert_eq!(functions, 2);
+}
+
+#[test]
+fn detects_script_components() {
+    assert!(parse("// c\ncomponent {}").tree().component().is_some());
+    assert!(parse("<cfcomponent></cfcomponent>")
+        .tree()
+        .component()
+        .is_none());
+    assert!(parse("import models.*;\ncomponent {}")
+        .tree()
+        .component()
+        .is_some());
+}
@@ -16,6 +16,8 @@ impl FileId {
     }
 }
 
+impl nohash_hasher::IsEnabled for FileId {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
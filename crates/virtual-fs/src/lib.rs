@@ -1,4 +1,5 @@
 mod anchored_path;
+pub use anchored_path::{AnchoredPath, AnchoredPathBuf};
 mod file_id;
 pub use file_id::FileId;
 mod path_interner;